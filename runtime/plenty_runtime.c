@@ -13,98 +13,480 @@
 // program; the real `main` lives here and just forwards to it. That
 // keeps the Cranelift entry point typed as `() -> i32` and avoids
 // distinguishing the platform's `main(argc, argv)` shape from the IR
-// builder's tidy zero-arg signature.
+// builder's tidy zero-arg signature. `main` keeps `argc`/`argv` where
+// `:argc` and `:arg` can reach them.
+
+// `open_memstream`, which `:to-str` captures rendered text through.
+#define _POSIX_C_SOURCE 200809L
 
 #include <stdio.h>
 #include <stdint.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/stat.h>
 
 extern int32_t plenty_main(void);
 
+static int plenty_arg_count = 0;
+static char **plenty_arg_values = NULL;
+
 int main(int argc, char **argv) {
-    (void)argc;
-    (void)argv;
+    plenty_arg_count = argc;
+    plenty_arg_values = argv;
     return (int)plenty_main();
 }
 
-void plenty_print_i8 (int8_t  n) { printf("%di8",  (int)n); }
-void plenty_print_i16(int16_t n) { printf("%di16", (int)n); }
-void plenty_print_i32(int32_t n) { printf("%di32", n); }
-void plenty_print_i64(int64_t n) { printf("%lldi64", (long long)n); }
-void plenty_print_u8 (uint8_t  n) { printf("%uu8",  (unsigned)n); }
-void plenty_print_u16(uint16_t n) { printf("%uu16", (unsigned)n); }
-void plenty_print_u32(uint32_t n) { printf("%uu32", n); }
-void plenty_print_u64(uint64_t n) { printf("%lluu64", (unsigned long long)n); }
-void plenty_print_bool(int8_t b) { fputs(b ? "true" : "false", stdout); }
+// Where the print helpers write: stdout, except while `:to-str` is
+// capturing a rendering into a string (see `plenty_capture_begin`).
+static FILE *plenty_capture = NULL;
+
+static FILE *plenty_out(void) {
+    return plenty_capture ? plenty_capture : stdout;
+}
+
+void plenty_print_i8 (int8_t  n) { fprintf(plenty_out(), "%di8",  (int)n); }
+void plenty_print_i16(int16_t n) { fprintf(plenty_out(), "%di16", (int)n); }
+void plenty_print_i32(int32_t n) { fprintf(plenty_out(), "%di32", n); }
+void plenty_print_i64(int64_t n) { fprintf(plenty_out(), "%lldi64", (long long)n); }
+void plenty_print_u8 (uint8_t  n) { fprintf(plenty_out(), "%uu8",  (unsigned)n); }
+void plenty_print_u16(uint16_t n) { fprintf(plenty_out(), "%uu16", (unsigned)n); }
+void plenty_print_u32(uint32_t n) { fprintf(plenty_out(), "%uu32", n); }
+void plenty_print_u64(uint64_t n) { fprintf(plenty_out(), "%lluu64", (unsigned long long)n); }
+void plenty_print_bool(int8_t b) { fputs(b ? "true" : "false", plenty_out()); }
+
+void plenty_print_open_bracket(void)  { fputc('[',  plenty_out()); }
+void plenty_print_close_bracket(void) { fputs("]\n", plenty_out()); }
+void plenty_print_space(void)         { fputc(' ',  plenty_out()); }
+
+// String runtime — c.4. A string is a pointer to a `plenty_str`: its
+// byte length followed by the bytes themselves and one trailing nul.
+// The length is authoritative — a string may contain interior nuls —
+// and the terminator only makes the bytes safe to hand to C code that
+// expects one. The compiler emits one static-data symbol in this
+// layout per source string literal, and runtime concatenation mallocs
+// a fresh one. The heap is append-only (no free) to mirror the
+// interpreter's `Heap` (DESIGN.md §12.1) — a real allocator and
+// reclamation are a later concern.
+typedef struct {
+    int64_t len;
+    char bytes[];
+} plenty_str;
+
+// Allocate an uninitialised string of `len` bytes with its terminator
+// already written. Never freed, like every runtime string.
+static plenty_str *plenty_str_alloc(int64_t len) {
+    plenty_str *out = (plenty_str *)malloc(sizeof(plenty_str) + (size_t)len + 1);
+    if (!out) {
+        fputs("error: out of memory\n", stderr);
+        exit(1);
+    }
+    out->len = len;
+    out->bytes[len] = '\0';
+    return out;
+}
+
+// The chars Rust's `{:?}` writes as `\u{...}`: the non-printable ones
+// and the grapheme extenders (combining marks and the like), as sorted,
+// inclusive ranges. Generated from the Rust standard library's tables,
+// which `Vm::render` uses, so `.` and `:to-str` render every char the
+// way the interpreter does. Surrogates fall inside a range but never
+// reach here.
+static const uint32_t plenty_debug_escaped[][2] = {
+    {0x1, 0x8}, {0xb, 0xc}, {0xe, 0x1f}, {0x7f, 0xa0}, {0xad, 0xad},
+    {0x300, 0x36f}, {0x378, 0x379}, {0x380, 0x383}, {0x38b, 0x38b},
+    {0x38d, 0x38d}, {0x3a2, 0x3a2}, {0x483, 0x489}, {0x530, 0x530},
+    {0x557, 0x558}, {0x58b, 0x58c}, {0x590, 0x5bd}, {0x5bf, 0x5bf},
+    {0x5c1, 0x5c2}, {0x5c4, 0x5c5}, {0x5c7, 0x5cf}, {0x5eb, 0x5ee},
+    {0x5f5, 0x605}, {0x610, 0x61a}, {0x61c, 0x61c}, {0x64b, 0x65f},
+    {0x670, 0x670}, {0x6d6, 0x6dd}, {0x6df, 0x6e4}, {0x6e7, 0x6e8},
+    {0x6ea, 0x6ed}, {0x70e, 0x70f}, {0x711, 0x711}, {0x730, 0x74c},
+    {0x7a6, 0x7b0}, {0x7b2, 0x7bf}, {0x7eb, 0x7f3}, {0x7fb, 0x7fd},
+    {0x816, 0x819}, {0x81b, 0x823}, {0x825, 0x827}, {0x829, 0x82f},
+    {0x83f, 0x83f}, {0x859, 0x85d}, {0x85f, 0x85f}, {0x86b, 0x86f},
+    {0x890, 0x89f}, {0x8ca, 0x902}, {0x93a, 0x93a}, {0x93c, 0x93c},
+    {0x941, 0x948}, {0x94d, 0x94d}, {0x951, 0x957}, {0x962, 0x963},
+    {0x981, 0x981}, {0x984, 0x984}, {0x98d, 0x98e}, {0x991, 0x992},
+    {0x9a9, 0x9a9}, {0x9b1, 0x9b1}, {0x9b3, 0x9b5}, {0x9ba, 0x9bc},
+    {0x9be, 0x9be}, {0x9c1, 0x9c6}, {0x9c9, 0x9ca}, {0x9cd, 0x9cd},
+    {0x9cf, 0x9db}, {0x9de, 0x9de}, {0x9e2, 0x9e5}, {0x9fe, 0xa02},
+    {0xa04, 0xa04}, {0xa0b, 0xa0e}, {0xa11, 0xa12}, {0xa29, 0xa29},
+    {0xa31, 0xa31}, {0xa34, 0xa34}, {0xa37, 0xa37}, {0xa3a, 0xa3d},
+    {0xa41, 0xa58}, {0xa5d, 0xa5d}, {0xa5f, 0xa65}, {0xa70, 0xa71},
+    {0xa75, 0xa75}, {0xa77, 0xa82}, {0xa84, 0xa84}, {0xa8e, 0xa8e},
+    {0xa92, 0xa92}, {0xaa9, 0xaa9}, {0xab1, 0xab1}, {0xab4, 0xab4},
+    {0xaba, 0xabc}, {0xac1, 0xac8}, {0xaca, 0xaca}, {0xacd, 0xacf},
+    {0xad1, 0xadf}, {0xae2, 0xae5}, {0xaf2, 0xaf8}, {0xafa, 0xb01},
+    {0xb04, 0xb04}, {0xb0d, 0xb0e}, {0xb11, 0xb12}, {0xb29, 0xb29},
+    {0xb31, 0xb31}, {0xb34, 0xb34}, {0xb3a, 0xb3c}, {0xb3e, 0xb3f},
+    {0xb41, 0xb46}, {0xb49, 0xb4a}, {0xb4d, 0xb5b}, {0xb5e, 0xb5e},
+    {0xb62, 0xb65}, {0xb78, 0xb82}, {0xb84, 0xb84}, {0xb8b, 0xb8d},
+    {0xb91, 0xb91}, {0xb96, 0xb98}, {0xb9b, 0xb9b}, {0xb9d, 0xb9d},
+    {0xba0, 0xba2}, {0xba5, 0xba7}, {0xbab, 0xbad}, {0xbba, 0xbbe},
+    {0xbc0, 0xbc0}, {0xbc3, 0xbc5}, {0xbc9, 0xbc9}, {0xbcd, 0xbcf},
+    {0xbd1, 0xbe5}, {0xbfb, 0xc00}, {0xc04, 0xc04}, {0xc0d, 0xc0d},
+    {0xc11, 0xc11}, {0xc29, 0xc29}, {0xc3a, 0xc3c}, {0xc3e, 0xc40},
+    {0xc45, 0xc57}, {0xc5b, 0xc5b}, {0xc5e, 0xc5f}, {0xc62, 0xc65},
+    {0xc70, 0xc76}, {0xc81, 0xc81}, {0xc8d, 0xc8d}, {0xc91, 0xc91},
+    {0xca9, 0xca9}, {0xcb4, 0xcb4}, {0xcba, 0xcbc}, {0xcbf, 0xcc0},
+    {0xcc2, 0xcc2}, {0xcc5, 0xcdb}, {0xcdf, 0xcdf}, {0xce2, 0xce5},
+    {0xcf0, 0xcf0}, {0xcf4, 0xd01}, {0xd0d, 0xd0d}, {0xd11, 0xd11},
+    {0xd3b, 0xd3c}, {0xd3e, 0xd3e}, {0xd41, 0xd45}, {0xd49, 0xd49},
+    {0xd4d, 0xd4d}, {0xd50, 0xd53}, {0xd57, 0xd57}, {0xd62, 0xd65},
+    {0xd80, 0xd81}, {0xd84, 0xd84}, {0xd97, 0xd99}, {0xdb2, 0xdb2},
+    {0xdbc, 0xdbc}, {0xdbe, 0xdbf}, {0xdc7, 0xdcf}, {0xdd2, 0xdd7},
+    {0xddf, 0xde5}, {0xdf0, 0xdf1}, {0xdf5, 0xe00}, {0xe31, 0xe31},
+    {0xe34, 0xe3e}, {0xe47, 0xe4e}, {0xe5c, 0xe80}, {0xe83, 0xe83},
+    {0xe85, 0xe85}, {0xe8b, 0xe8b}, {0xea4, 0xea4}, {0xea6, 0xea6},
+    {0xeb1, 0xeb1}, {0xeb4, 0xebc}, {0xebe, 0xebf}, {0xec5, 0xec5},
+    {0xec7, 0xecf}, {0xeda, 0xedb}, {0xee0, 0xeff}, {0xf18, 0xf19},
+    {0xf35, 0xf35}, {0xf37, 0xf37}, {0xf39, 0xf39}, {0xf48, 0xf48},
+    {0xf6d, 0xf7e}, {0xf80, 0xf84}, {0xf86, 0xf87}, {0xf8d, 0xfbd},
+    {0xfc6, 0xfc6}, {0xfcd, 0xfcd}, {0xfdb, 0xfff}, {0x102d, 0x1030},
+    {0x1032, 0x1037}, {0x1039, 0x103a}, {0x103d, 0x103e}, {0x1058, 0x1059},
+    {0x105e, 0x1060}, {0x1071, 0x1074}, {0x1082, 0x1082}, {0x1085, 0x1086},
+    {0x108d, 0x108d}, {0x109d, 0x109d}, {0x10c6, 0x10c6}, {0x10c8, 0x10cc},
+    {0x10ce, 0x10cf}, {0x1249, 0x1249}, {0x124e, 0x124f}, {0x1257, 0x1257},
+    {0x1259, 0x1259}, {0x125e, 0x125f}, {0x1289, 0x1289}, {0x128e, 0x128f},
+    {0x12b1, 0x12b1}, {0x12b6, 0x12b7}, {0x12bf, 0x12bf}, {0x12c1, 0x12c1},
+    {0x12c6, 0x12c7}, {0x12d7, 0x12d7}, {0x1311, 0x1311}, {0x1316, 0x1317},
+    {0x135b, 0x135f}, {0x137d, 0x137f}, {0x139a, 0x139f}, {0x13f6, 0x13f7},
+    {0x13fe, 0x13ff}, {0x1680, 0x1680}, {0x169d, 0x169f}, {0x16f9, 0x16ff},
+    {0x1712, 0x171e}, {0x1732, 0x1734}, {0x1737, 0x173f}, {0x1752, 0x175f},
+    {0x176d, 0x176d}, {0x1771, 0x177f}, {0x17b4, 0x17b5}, {0x17b7, 0x17bd},
+    {0x17c6, 0x17c6}, {0x17c9, 0x17d3}, {0x17dd, 0x17df}, {0x17ea, 0x17ef},
+    {0x17fa, 0x17ff}, {0x180b, 0x180f}, {0x181a, 0x181f}, {0x1879, 0x187f},
+    {0x1885, 0x1886}, {0x18a9, 0x18a9}, {0x18ab, 0x18af}, {0x18f6, 0x18ff},
+    {0x191f, 0x1922}, {0x1927, 0x1928}, {0x192c, 0x192f}, {0x1932, 0x1932},
+    {0x1939, 0x193f}, {0x1941, 0x1943}, {0x196e, 0x196f}, {0x1975, 0x197f},
+    {0x19ac, 0x19af}, {0x19ca, 0x19cf}, {0x19db, 0x19dd}, {0x1a17, 0x1a18},
+    {0x1a1b, 0x1a1d}, {0x1a56, 0x1a56}, {0x1a58, 0x1a60}, {0x1a62, 0x1a62},
+    {0x1a65, 0x1a6c}, {0x1a73, 0x1a7f}, {0x1a8a, 0x1a8f}, {0x1a9a, 0x1a9f},
+    {0x1aae, 0x1b03}, {0x1b34, 0x1b3d}, {0x1b42, 0x1b44}, {0x1b4d, 0x1b4d},
+    {0x1b6b, 0x1b73}, {0x1b80, 0x1b81}, {0x1ba2, 0x1ba5}, {0x1ba8, 0x1bad},
+    {0x1be6, 0x1be6}, {0x1be8, 0x1be9}, {0x1bed, 0x1bed}, {0x1bef, 0x1bfb},
+    {0x1c2c, 0x1c33}, {0x1c36, 0x1c3a}, {0x1c4a, 0x1c4c}, {0x1c8b, 0x1c8f},
+    {0x1cbb, 0x1cbc}, {0x1cc8, 0x1cd2}, {0x1cd4, 0x1ce0}, {0x1ce2, 0x1ce8},
+    {0x1ced, 0x1ced}, {0x1cf4, 0x1cf4}, {0x1cf8, 0x1cf9}, {0x1cfb, 0x1cff},
+    {0x1dc0, 0x1dff}, {0x1f16, 0x1f17}, {0x1f1e, 0x1f1f}, {0x1f46, 0x1f47},
+    {0x1f4e, 0x1f4f}, {0x1f58, 0x1f58}, {0x1f5a, 0x1f5a}, {0x1f5c, 0x1f5c},
+    {0x1f5e, 0x1f5e}, {0x1f7e, 0x1f7f}, {0x1fb5, 0x1fb5}, {0x1fc5, 0x1fc5},
+    {0x1fd4, 0x1fd5}, {0x1fdc, 0x1fdc}, {0x1ff0, 0x1ff1}, {0x1ff5, 0x1ff5},
+    {0x1fff, 0x200f}, {0x2028, 0x202f}, {0x205f, 0x206f}, {0x2072, 0x2073},
+    {0x208f, 0x208f}, {0x209d, 0x209f}, {0x20c2, 0x20ff}, {0x218c, 0x218f},
+    {0x242a, 0x243f}, {0x244b, 0x245f}, {0x2b74, 0x2b75}, {0x2cef, 0x2cf1},
+    {0x2cf4, 0x2cf8}, {0x2d26, 0x2d26}, {0x2d28, 0x2d2c}, {0x2d2e, 0x2d2f},
+    {0x2d68, 0x2d6e}, {0x2d71, 0x2d7f}, {0x2d97, 0x2d9f}, {0x2da7, 0x2da7},
+    {0x2daf, 0x2daf}, {0x2db7, 0x2db7}, {0x2dbf, 0x2dbf}, {0x2dc7, 0x2dc7},
+    {0x2dcf, 0x2dcf}, {0x2dd7, 0x2dd7}, {0x2ddf, 0x2dff}, {0x2e5e, 0x2e7f},
+    {0x2e9a, 0x2e9a}, {0x2ef4, 0x2eff}, {0x2fd6, 0x2fef}, {0x3000, 0x3000},
+    {0x302a, 0x302f}, {0x3040, 0x3040}, {0x3097, 0x309a}, {0x3100, 0x3104},
+    {0x3130, 0x3130}, {0x318f, 0x318f}, {0x31e6, 0x31ee}, {0x321f, 0x321f},
+    {0xa48d, 0xa48f}, {0xa4c7, 0xa4cf}, {0xa62c, 0xa63f}, {0xa66f, 0xa672},
+    {0xa674, 0xa67d}, {0xa69e, 0xa69f}, {0xa6f0, 0xa6f1}, {0xa6f8, 0xa6ff},
+    {0xa7dd, 0xa7f0}, {0xa802, 0xa802}, {0xa806, 0xa806}, {0xa80b, 0xa80b},
+    {0xa825, 0xa826}, {0xa82c, 0xa82f}, {0xa83a, 0xa83f}, {0xa878, 0xa87f},
+    {0xa8c4, 0xa8cd}, {0xa8da, 0xa8f1}, {0xa8ff, 0xa8ff}, {0xa926, 0xa92d},
+    {0xa947, 0xa951}, {0xa953, 0xa95e}, {0xa97d, 0xa982}, {0xa9b3, 0xa9b3},
+    {0xa9b6, 0xa9b9}, {0xa9bc, 0xa9bd}, {0xa9c0, 0xa9c0}, {0xa9ce, 0xa9ce},
+    {0xa9da, 0xa9dd}, {0xa9e5, 0xa9e5}, {0xa9ff, 0xa9ff}, {0xaa29, 0xaa2e},
+    {0xaa31, 0xaa32}, {0xaa35, 0xaa3f}, {0xaa43, 0xaa43}, {0xaa4c, 0xaa4c},
+    {0xaa4e, 0xaa4f}, {0xaa5a, 0xaa5b}, {0xaa7c, 0xaa7c}, {0xaab0, 0xaab0},
+    {0xaab2, 0xaab4}, {0xaab7, 0xaab8}, {0xaabe, 0xaabf}, {0xaac1, 0xaac1},
+    {0xaac3, 0xaada}, {0xaaec, 0xaaed}, {0xaaf6, 0xab00}, {0xab07, 0xab08},
+    {0xab0f, 0xab10}, {0xab17, 0xab1f}, {0xab27, 0xab27}, {0xab2f, 0xab2f},
+    {0xab6c, 0xab6f}, {0xabe5, 0xabe5}, {0xabe8, 0xabe8}, {0xabed, 0xabef},
+    {0xabfa, 0xabff}, {0xd7a4, 0xd7af}, {0xd7c7, 0xd7ca}, {0xd7fc, 0xf8ff},
+    {0xfa6e, 0xfa6f}, {0xfada, 0xfaff}, {0xfb07, 0xfb12}, {0xfb18, 0xfb1c},
+    {0xfb1e, 0xfb1e}, {0xfb37, 0xfb37}, {0xfb3d, 0xfb3d}, {0xfb3f, 0xfb3f},
+    {0xfb42, 0xfb42}, {0xfb45, 0xfb45}, {0xfdd0, 0xfdef}, {0xfe00, 0xfe0f},
+    {0xfe1a, 0xfe2f}, {0xfe53, 0xfe53}, {0xfe67, 0xfe67}, {0xfe6c, 0xfe6f},
+    {0xfe75, 0xfe75}, {0xfefd, 0xff00}, {0xff9e, 0xff9f}, {0xffbf, 0xffc1},
+    {0xffc8, 0xffc9}, {0xffd0, 0xffd1}, {0xffd8, 0xffd9}, {0xffdd, 0xffdf},
+    {0xffe7, 0xffe7}, {0xffef, 0xfffb}, {0xfffe, 0xffff}, {0x1000c, 0x1000c},
+    {0x10027, 0x10027}, {0x1003b, 0x1003b}, {0x1003e, 0x1003e},
+    {0x1004e, 0x1004f}, {0x1005e, 0x1007f}, {0x100fb, 0x100ff},
+    {0x10103, 0x10106}, {0x10134, 0x10136}, {0x1018f, 0x1018f},
+    {0x1019d, 0x1019f}, {0x101a1, 0x101cf}, {0x101fd, 0x1027f},
+    {0x1029d, 0x1029f}, {0x102d1, 0x102e0}, {0x102fc, 0x102ff},
+    {0x10324, 0x1032c}, {0x1034b, 0x1034f}, {0x10376, 0x1037f},
+    {0x1039e, 0x1039e}, {0x103c4, 0x103c7}, {0x103d6, 0x103ff},
+    {0x1049e, 0x1049f}, {0x104aa, 0x104af}, {0x104d4, 0x104d7},
+    {0x104fc, 0x104ff}, {0x10528, 0x1052f}, {0x10564, 0x1056e},
+    {0x1057b, 0x1057b}, {0x1058b, 0x1058b}, {0x10593, 0x10593},
+    {0x10596, 0x10596}, {0x105a2, 0x105a2}, {0x105b2, 0x105b2},
+    {0x105ba, 0x105ba}, {0x105bd, 0x105bf}, {0x105f4, 0x105ff},
+    {0x10737, 0x1073f}, {0x10756, 0x1075f}, {0x10768, 0x1077f},
+    {0x10786, 0x10786}, {0x107b1, 0x107b1}, {0x107bb, 0x107ff},
+    {0x10806, 0x10807}, {0x10809, 0x10809}, {0x10836, 0x10836},
+    {0x10839, 0x1083b}, {0x1083d, 0x1083e}, {0x10856, 0x10856},
+    {0x1089f, 0x108a6}, {0x108b0, 0x108df}, {0x108f3, 0x108f3},
+    {0x108f6, 0x108fa}, {0x1091c, 0x1091e}, {0x1093a, 0x1093e},
+    {0x1095a, 0x1097f}, {0x109b8, 0x109bb}, {0x109d0, 0x109d1},
+    {0x10a01, 0x10a0f}, {0x10a14, 0x10a14}, {0x10a18, 0x10a18},
+    {0x10a36, 0x10a3f}, {0x10a49, 0x10a4f}, {0x10a59, 0x10a5f},
+    {0x10aa0, 0x10abf}, {0x10ae5, 0x10aea}, {0x10af7, 0x10aff},
+    {0x10b36, 0x10b38}, {0x10b56, 0x10b57}, {0x10b73, 0x10b77},
+    {0x10b92, 0x10b98}, {0x10b9d, 0x10ba8}, {0x10bb0, 0x10bff},
+    {0x10c49, 0x10c7f}, {0x10cb3, 0x10cbf}, {0x10cf3, 0x10cf9},
+    {0x10d24, 0x10d2f}, {0x10d3a, 0x10d3f}, {0x10d66, 0x10d6d},
+    {0x10d86, 0x10d8d}, {0x10d90, 0x10e5f}, {0x10e7f, 0x10e7f},
+    {0x10eaa, 0x10eac}, {0x10eae, 0x10eaf}, {0x10eb2, 0x10ec1},
+    {0x10ec8, 0x10ecf}, {0x10ed9, 0x10eff}, {0x10f28, 0x10f2f},
+    {0x10f46, 0x10f50}, {0x10f5a, 0x10f6f}, {0x10f82, 0x10f85},
+    {0x10f8a, 0x10faf}, {0x10fcc, 0x10fdf}, {0x10ff7, 0x10fff},
+    {0x11001, 0x11001}, {0x11038, 0x11046}, {0x1104e, 0x11051},
+    {0x11070, 0x11070}, {0x11073, 0x11074}, {0x11076, 0x11081},
+    {0x110b3, 0x110b6}, {0x110b9, 0x110ba}, {0x110bd, 0x110bd},
+    {0x110c2, 0x110cf}, {0x110e9, 0x110ef}, {0x110fa, 0x11102},
+    {0x11127, 0x1112b}, {0x1112d, 0x11135}, {0x11148, 0x1114f},
+    {0x11173, 0x11173}, {0x11177, 0x11181}, {0x111b6, 0x111be},
+    {0x111c0, 0x111c0}, {0x111c9, 0x111cc}, {0x111cf, 0x111cf},
+    {0x111e0, 0x111e0}, {0x111f5, 0x111ff}, {0x11212, 0x11212},
+    {0x1122f, 0x11231}, {0x11234, 0x11237}, {0x1123e, 0x1123e},
+    {0x11241, 0x1127f}, {0x11287, 0x11287}, {0x11289, 0x11289},
+    {0x1128e, 0x1128e}, {0x1129e, 0x1129e}, {0x112aa, 0x112af},
+    {0x112df, 0x112df}, {0x112e3, 0x112ef}, {0x112fa, 0x11301},
+    {0x11304, 0x11304}, {0x1130d, 0x1130e}, {0x11311, 0x11312},
+    {0x11329, 0x11329}, {0x11331, 0x11331}, {0x11334, 0x11334},
+    {0x1133a, 0x1133c}, {0x1133e, 0x1133e}, {0x11340, 0x11340},
+    {0x11345, 0x11346}, {0x11349, 0x1134a}, {0x1134d, 0x1134f},
+    {0x11351, 0x1135c}, {0x11364, 0x1137f}, {0x1138a, 0x1138a},
+    {0x1138c, 0x1138d}, {0x1138f, 0x1138f}, {0x113b6, 0x113b6},
+    {0x113b8, 0x113b8}, {0x113bb, 0x113c9}, {0x113cb, 0x113cb},
+    {0x113ce, 0x113d0}, {0x113d2, 0x113d2}, {0x113d6, 0x113d6},
+    {0x113d9, 0x113ff}, {0x11438, 0x1143f}, {0x11442, 0x11444},
+    {0x11446, 0x11446}, {0x1145c, 0x1145c}, {0x1145e, 0x1145e},
+    {0x11462, 0x1147f}, {0x114b0, 0x114b0}, {0x114b3, 0x114b8},
+    {0x114ba, 0x114ba}, {0x114bd, 0x114bd}, {0x114bf, 0x114c0},
+    {0x114c2, 0x114c3}, {0x114c8, 0x114cf}, {0x114da, 0x1157f},
+    {0x115af, 0x115af}, {0x115b2, 0x115b7}, {0x115bc, 0x115bd},
+    {0x115bf, 0x115c0}, {0x115dc, 0x115ff}, {0x11633, 0x1163a},
+    {0x1163d, 0x1163d}, {0x1163f, 0x11640}, {0x11645, 0x1164f},
+    {0x1165a, 0x1165f}, {0x1166d, 0x1167f}, {0x116ab, 0x116ab},
+    {0x116ad, 0x116ad}, {0x116b0, 0x116b7}, {0x116ba, 0x116bf},
+    {0x116ca, 0x116cf}, {0x116e4, 0x116ff}, {0x1171b, 0x1171d},
+    {0x1171f, 0x1171f}, {0x11722, 0x11725}, {0x11727, 0x1172f},
+    {0x11747, 0x117ff}, {0x1182f, 0x11837}, {0x11839, 0x1183a},
+    {0x1183c, 0x1189f}, {0x118f3, 0x118fe}, {0x11907, 0x11908},
+    {0x1190a, 0x1190b}, {0x11914, 0x11914}, {0x11917, 0x11917},
+    {0x11930, 0x11930}, {0x11936, 0x11936}, {0x11939, 0x1193e},
+    {0x11943, 0x11943}, {0x11947, 0x1194f}, {0x1195a, 0x1199f},
+    {0x119a8, 0x119a9}, {0x119d4, 0x119db}, {0x119e0, 0x119e0},
+    {0x119e5, 0x119ff}, {0x11a01, 0x11a0a}, {0x11a33, 0x11a38},
+    {0x11a3b, 0x11a3e}, {0x11a47, 0x11a4f}, {0x11a51, 0x11a56},
+    {0x11a59, 0x11a5b}, {0x11a8a, 0x11a96}, {0x11a98, 0x11a99},
+    {0x11aa3, 0x11aaf}, {0x11af9, 0x11aff}, {0x11b0a, 0x11b60},
+    {0x11b62, 0x11b64}, {0x11b66, 0x11b66}, {0x11b68, 0x11bbf},
+    {0x11be2, 0x11bef}, {0x11bfa, 0x11bff}, {0x11c09, 0x11c09},
+    {0x11c30, 0x11c3d}, {0x11c3f, 0x11c3f}, {0x11c46, 0x11c4f},
+    {0x11c6d, 0x11c6f}, {0x11c90, 0x11ca8}, {0x11caa, 0x11cb0},
+    {0x11cb2, 0x11cb3}, {0x11cb5, 0x11cff}, {0x11d07, 0x11d07},
+    {0x11d0a, 0x11d0a}, {0x11d31, 0x11d45}, {0x11d47, 0x11d4f},
+    {0x11d5a, 0x11d5f}, {0x11d66, 0x11d66}, {0x11d69, 0x11d69},
+    {0x11d8f, 0x11d92}, {0x11d95, 0x11d95}, {0x11d97, 0x11d97},
+    {0x11d99, 0x11d9f}, {0x11daa, 0x11daf}, {0x11ddc, 0x11ddf},
+    {0x11dea, 0x11edf}, {0x11ef3, 0x11ef4}, {0x11ef9, 0x11f01},
+    {0x11f11, 0x11f11}, {0x11f36, 0x11f3d}, {0x11f40, 0x11f42},
+    {0x11f5a, 0x11faf}, {0x11fb1, 0x11fbf}, {0x11ff2, 0x11ffe},
+    {0x1239a, 0x123ff}, {0x1246f, 0x1246f}, {0x12475, 0x1247f},
+    {0x12544, 0x12f8f}, {0x12ff3, 0x12fff}, {0x13430, 0x13440},
+    {0x13447, 0x1345f}, {0x143fb, 0x143ff}, {0x14647, 0x160ff},
+    {0x1611e, 0x16129}, {0x1612d, 0x1612f}, {0x1613a, 0x167ff},
+    {0x16a39, 0x16a3f}, {0x16a5f, 0x16a5f}, {0x16a6a, 0x16a6d},
+    {0x16abf, 0x16abf}, {0x16aca, 0x16acf}, {0x16aee, 0x16af4},
+    {0x16af6, 0x16aff}, {0x16b30, 0x16b36}, {0x16b46, 0x16b4f},
+    {0x16b5a, 0x16b5a}, {0x16b62, 0x16b62}, {0x16b78, 0x16b7c},
+    {0x16b90, 0x16d3f}, {0x16d7a, 0x16e3f}, {0x16e9b, 0x16e9f},
+    {0x16eb9, 0x16eba}, {0x16ed4, 0x16eff}, {0x16f4b, 0x16f4f},
+    {0x16f88, 0x16f92}, {0x16fa0, 0x16fdf}, {0x16fe4, 0x16ff1},
+    {0x16ff7, 0x16fff}, {0x18cd6, 0x18cfe}, {0x18d1f, 0x18d7f},
+    {0x18df3, 0x1afef}, {0x1aff4, 0x1aff4}, {0x1affc, 0x1affc},
+    {0x1afff, 0x1afff}, {0x1b123, 0x1b131}, {0x1b133, 0x1b14f},
+    {0x1b153, 0x1b154}, {0x1b156, 0x1b163}, {0x1b168, 0x1b16f},
+    {0x1b2fc, 0x1bbff}, {0x1bc6b, 0x1bc6f}, {0x1bc7d, 0x1bc7f},
+    {0x1bc89, 0x1bc8f}, {0x1bc9a, 0x1bc9b}, {0x1bc9d, 0x1bc9e},
+    {0x1bca0, 0x1cbff}, {0x1ccfd, 0x1ccff}, {0x1ceb4, 0x1ceb9},
+    {0x1ced1, 0x1cedf}, {0x1cef1, 0x1cf4f}, {0x1cfc4, 0x1cfff},
+    {0x1d0f6, 0x1d0ff}, {0x1d127, 0x1d128}, {0x1d165, 0x1d169},
+    {0x1d16d, 0x1d182}, {0x1d185, 0x1d18b}, {0x1d1aa, 0x1d1ad},
+    {0x1d1eb, 0x1d1ff}, {0x1d242, 0x1d244}, {0x1d246, 0x1d2bf},
+    {0x1d2d4, 0x1d2df}, {0x1d2f4, 0x1d2ff}, {0x1d357, 0x1d35f},
+    {0x1d379, 0x1d3ff}, {0x1d455, 0x1d455}, {0x1d49d, 0x1d49d},
+    {0x1d4a0, 0x1d4a1}, {0x1d4a3, 0x1d4a4}, {0x1d4a7, 0x1d4a8},
+    {0x1d4ad, 0x1d4ad}, {0x1d4ba, 0x1d4ba}, {0x1d4bc, 0x1d4bc},
+    {0x1d4c4, 0x1d4c4}, {0x1d506, 0x1d506}, {0x1d50b, 0x1d50c},
+    {0x1d515, 0x1d515}, {0x1d51d, 0x1d51d}, {0x1d53a, 0x1d53a},
+    {0x1d53f, 0x1d53f}, {0x1d545, 0x1d545}, {0x1d547, 0x1d549},
+    {0x1d551, 0x1d551}, {0x1d6a6, 0x1d6a7}, {0x1d7cc, 0x1d7cd},
+    {0x1da00, 0x1da36}, {0x1da3b, 0x1da6c}, {0x1da75, 0x1da75},
+    {0x1da84, 0x1da84}, {0x1da8c, 0x1deff}, {0x1df1f, 0x1df24},
+    {0x1df2b, 0x1e02f}, {0x1e06e, 0x1e0ff}, {0x1e12d, 0x1e136},
+    {0x1e13e, 0x1e13f}, {0x1e14a, 0x1e14d}, {0x1e150, 0x1e28f},
+    {0x1e2ae, 0x1e2bf}, {0x1e2ec, 0x1e2ef}, {0x1e2fa, 0x1e2fe},
+    {0x1e300, 0x1e4cf}, {0x1e4ec, 0x1e4ef}, {0x1e4fa, 0x1e5cf},
+    {0x1e5ee, 0x1e5ef}, {0x1e5fb, 0x1e5fe}, {0x1e600, 0x1e6bf},
+    {0x1e6df, 0x1e6df}, {0x1e6e3, 0x1e6e3}, {0x1e6e6, 0x1e6e6},
+    {0x1e6ee, 0x1e6ef}, {0x1e6f5, 0x1e6fd}, {0x1e700, 0x1e7df},
+    {0x1e7e7, 0x1e7e7}, {0x1e7ec, 0x1e7ec}, {0x1e7ef, 0x1e7ef},
+    {0x1e7ff, 0x1e7ff}, {0x1e8c5, 0x1e8c6}, {0x1e8d0, 0x1e8ff},
+    {0x1e944, 0x1e94a}, {0x1e94c, 0x1e94f}, {0x1e95a, 0x1e95d},
+    {0x1e960, 0x1ec70}, {0x1ecb5, 0x1ed00}, {0x1ed3e, 0x1edff},
+    {0x1ee04, 0x1ee04}, {0x1ee20, 0x1ee20}, {0x1ee23, 0x1ee23},
+    {0x1ee25, 0x1ee26}, {0x1ee28, 0x1ee28}, {0x1ee33, 0x1ee33},
+    {0x1ee38, 0x1ee38}, {0x1ee3a, 0x1ee3a}, {0x1ee3c, 0x1ee41},
+    {0x1ee43, 0x1ee46}, {0x1ee48, 0x1ee48}, {0x1ee4a, 0x1ee4a},
+    {0x1ee4c, 0x1ee4c}, {0x1ee50, 0x1ee50}, {0x1ee53, 0x1ee53},
+    {0x1ee55, 0x1ee56}, {0x1ee58, 0x1ee58}, {0x1ee5a, 0x1ee5a},
+    {0x1ee5c, 0x1ee5c}, {0x1ee5e, 0x1ee5e}, {0x1ee60, 0x1ee60},
+    {0x1ee63, 0x1ee63}, {0x1ee65, 0x1ee66}, {0x1ee6b, 0x1ee6b},
+    {0x1ee73, 0x1ee73}, {0x1ee78, 0x1ee78}, {0x1ee7d, 0x1ee7d},
+    {0x1ee7f, 0x1ee7f}, {0x1ee8a, 0x1ee8a}, {0x1ee9c, 0x1eea0},
+    {0x1eea4, 0x1eea4}, {0x1eeaa, 0x1eeaa}, {0x1eebc, 0x1eeef},
+    {0x1eef2, 0x1efff}, {0x1f02c, 0x1f02f}, {0x1f094, 0x1f09f},
+    {0x1f0af, 0x1f0b0}, {0x1f0c0, 0x1f0c0}, {0x1f0d0, 0x1f0d0},
+    {0x1f0f6, 0x1f0ff}, {0x1f1ae, 0x1f1e5}, {0x1f203, 0x1f20f},
+    {0x1f23c, 0x1f23f}, {0x1f249, 0x1f24f}, {0x1f252, 0x1f25f},
+    {0x1f266, 0x1f2ff}, {0x1f6d9, 0x1f6db}, {0x1f6ed, 0x1f6ef},
+    {0x1f6fd, 0x1f6ff}, {0x1f7da, 0x1f7df}, {0x1f7ec, 0x1f7ef},
+    {0x1f7f1, 0x1f7ff}, {0x1f80c, 0x1f80f}, {0x1f848, 0x1f84f},
+    {0x1f85a, 0x1f85f}, {0x1f888, 0x1f88f}, {0x1f8ae, 0x1f8af},
+    {0x1f8bc, 0x1f8bf}, {0x1f8c2, 0x1f8cf}, {0x1f8d9, 0x1f8ff},
+    {0x1fa58, 0x1fa5f}, {0x1fa6e, 0x1fa6f}, {0x1fa7d, 0x1fa7f},
+    {0x1fa8b, 0x1fa8d}, {0x1fac7, 0x1fac7}, {0x1fac9, 0x1facc},
+    {0x1fadd, 0x1fade}, {0x1faeb, 0x1faee}, {0x1faf9, 0x1faff},
+    {0x1fb93, 0x1fb93}, {0x1fbfb, 0x1ffff}, {0x2a6e0, 0x2a6ff},
+    {0x2b81e, 0x2b81f}, {0x2ceae, 0x2ceaf}, {0x2ebe1, 0x2ebef},
+    {0x2ee5e, 0x2f7ff}, {0x2fa1e, 0x2ffff}, {0x3134b, 0x3134f},
+    {0x3347a, 0x10ffff},
+};
 
-void plenty_print_open_bracket(void)  { fputc('[',  stdout); }
-void plenty_print_close_bracket(void) { fputs("]\n", stdout); }
-void plenty_print_space(void)         { fputc(' ',  stdout); }
+static int plenty_debug_escapes(uint32_t c) {
+    size_t lo = 0;
+    size_t hi = sizeof plenty_debug_escaped / sizeof plenty_debug_escaped[0];
+    while (lo < hi) {
+        size_t mid = lo + (hi - lo) / 2;
+        if (c < plenty_debug_escaped[mid][0]) {
+            hi = mid;
+        } else if (c > plenty_debug_escaped[mid][1]) {
+            lo = mid + 1;
+        } else {
+            return 1;
+        }
+    }
+    return 0;
+}
 
-// String runtime — c.4. Strings are nul-terminated `const char *`s; the
-// compiler emits one static-data symbol per source string literal, and
-// runtime concatenation mallocs a fresh buffer. The heap is append-only
-// (no free) to mirror the interpreter's `Heap` (DESIGN.md §12.1) — a
-// real allocator and reclamation are a later concern.
+// Decode the char whose UTF-8 encoding starts at byte `i` of `s`, storing
+// its byte length in `len`. Strings always hold valid UTF-8; the width
+// is still capped at the string's end so a bad lead byte cannot read
+// past it.
+static uint32_t plenty_decode_char(const plenty_str *s, int64_t i, int64_t *len) {
+    unsigned char b = (unsigned char)s->bytes[i];
+    int64_t n = b < 0x80 ? 1 : b < 0xE0 ? 2 : b < 0xF0 ? 3 : 4;
+    if (n > s->len - i) {
+        n = s->len - i;
+    }
+    uint32_t c = n == 1 ? b : n == 2 ? (b & 0x1F) : n == 3 ? (b & 0x0F) : (b & 0x07);
+    for (int64_t k = 1; k < n; k++) {
+        c = (c << 6) | ((unsigned char)s->bytes[i + k] & 0x3F);
+    }
+    *len = n;
+    return c;
+}
 
 // Print a string with the same escaping the interpreter's `Vm::render`
 // uses (Rust's `{:?}` for `&str`): wrapped in double quotes, with `\`,
-// `"`, and the common control chars (`\t`, `\n`, `\r`) backslash-escaped.
-// Non-printable bytes outside that set are emitted as `\u{XX}` in
-// lowercase hex — enough to match Rust for any ASCII string. Full
-// Unicode-debug parity (escaping non-printable codepoints by name)
-// would need a much larger table; AOT tests stay within ASCII for now.
-void plenty_print_str(const char *s) {
-    fputc('"', stdout);
-    for (const unsigned char *p = (const unsigned char *)s; *p; p++) {
-        unsigned char c = *p;
+// `"`, `\0` and the common control chars (`\t`, `\n`, `\r`)
+// backslash-escaped, every char `plenty_debug_escapes` names emitted as
+// `\u{...}` in lowercase hex, and everything else passed through.
+void plenty_print_str(const plenty_str *s) {
+    const unsigned char *p = (const unsigned char *)s->bytes;
+    fputc('"', plenty_out());
+    int64_t width;
+    for (int64_t i = 0; i < s->len; i += width) {
+        uint32_t c = plenty_decode_char(s, i, &width);
         switch (c) {
-            case '"':  fputs("\\\"", stdout); break;
-            case '\\': fputs("\\\\", stdout); break;
-            case '\t': fputs("\\t",  stdout); break;
-            case '\n': fputs("\\n",  stdout); break;
-            case '\r': fputs("\\r",  stdout); break;
-            case '\0':                        break;  // unreachable: terminator
+            case '"':  fputs("\\\"", plenty_out()); break;
+            case '\\': fputs("\\\\", plenty_out()); break;
+            case '\t': fputs("\\t",  plenty_out()); break;
+            case '\n': fputs("\\n",  plenty_out()); break;
+            case '\r': fputs("\\r",  plenty_out()); break;
+            case '\0': fputs("\\0",  plenty_out()); break;
             default:
-                if (c >= 0x20 && c <= 0x7e) {
-                    fputc((int)c, stdout);
+                if (plenty_debug_escapes(c)) {
+                    fprintf(plenty_out(), "\\u{%x}", (unsigned)c);
                 } else {
-                    fprintf(stdout, "\\u{%x}", (unsigned)c);
+                    fwrite(p + i, 1, (size_t)width, plenty_out());
                 }
                 break;
         }
     }
-    fputc('"', stdout);
+    fputc('"', plenty_out());
 }
 
-// Concatenate two strings into a fresh malloc'd buffer with a trailing
-// nul. The returned pointer is owned by the program and intentionally
-// leaked — mirrors the interpreter's `Heap` which never reclaims.
-const char *plenty_concat(const char *a, const char *b) {
-    size_t la = strlen(a);
-    size_t lb = strlen(b);
-    char *out = (char *)malloc(la + lb + 1);
-    if (!out) {
-        fputs("plenty: out of memory in plenty_concat\n", stderr);
-        abort();
+// `:to-str` renders a value by running the same print helpers `.` uses
+// with their output redirected into a memory stream, so the text is the
+// `.` rendering by construction. Renderings never nest: the helpers call
+// no user code.
+static char *plenty_capture_buf = NULL;
+static size_t plenty_capture_len = 0;
+
+void plenty_capture_begin(void) {
+    plenty_capture = open_memstream(&plenty_capture_buf, &plenty_capture_len);
+    if (!plenty_capture) {
+        fputs("error: out of memory\n", stderr);
+        exit(1);
     }
-    memcpy(out, a, la);
-    memcpy(out + la, b, lb);
-    out[la + lb] = '\0';
+}
+
+const plenty_str *plenty_capture_end(void) {
+    fclose(plenty_capture);
+    plenty_capture = NULL;
+    plenty_str *out = plenty_str_alloc((int64_t)plenty_capture_len);
+    memcpy(out->bytes, plenty_capture_buf, plenty_capture_len);
+    free(plenty_capture_buf);
+    plenty_capture_buf = NULL;
+    return out;
+}
+
+// Decimal text of an integer with no width suffix — `:to-digits`. The
+// compiled code widens narrower integers to 64 bits first.
+const plenty_str *plenty_digits_i64(int64_t n) {
+    char buf[24];
+    int len = snprintf(buf, sizeof buf, "%lld", (long long)n);
+    plenty_str *out = plenty_str_alloc(len);
+    memcpy(out->bytes, buf, (size_t)len);
+    return out;
+}
+
+const plenty_str *plenty_digits_u64(uint64_t n) {
+    char buf[24];
+    int len = snprintf(buf, sizeof buf, "%llu", (unsigned long long)n);
+    plenty_str *out = plenty_str_alloc(len);
+    memcpy(out->bytes, buf, (size_t)len);
+    return out;
+}
+
+// Concatenate two strings into a fresh buffer. The returned pointer is
+// owned by the program and intentionally leaked — mirrors the
+// interpreter's `Heap` which never reclaims.
+const plenty_str *plenty_concat(const plenty_str *a, const plenty_str *b) {
+    plenty_str *out = plenty_str_alloc(a->len + b->len);
+    memcpy(out->bytes, a->bytes, (size_t)a->len);
+    memcpy(out->bytes + a->len, b->bytes, (size_t)b->len);
     return out;
 }
 
 // Byte-for-byte string equality. Returned as `int8_t` (Plenty's Bool
 // representation) so the caller can push it onto the value stack
 // without further conversion.
-int8_t plenty_str_eq(const char *a, const char *b) {
-    return (int8_t)(strcmp(a, b) == 0);
+int8_t plenty_str_eq(const plenty_str *a, const plenty_str *b) {
+    return (int8_t)(a->len == b->len && memcmp(a->bytes, b->bytes, (size_t)a->len) == 0);
 }
 
 // Trap helpers — c.5.5. Compiled programs branch here when an
-// arithmetic operation would have errored in the interpreter. Both
-// helpers print the same stderr line the interpreter's `main.rs`
+// arithmetic operation would have errored in the interpreter. Each
+// helper prints the same stderr line the interpreter's `main.rs`
 // would have (`error: <msg>`) and `exit(1)`. They never return; the
 // caller in generated code follows the call with a CLIF `trap` to
 // satisfy the verifier's block-terminator requirement.
@@ -118,19 +500,73 @@ _Noreturn void plenty_trap_div_zero(void) {
     exit(1);
 }
 
+_Noreturn void plenty_trap_negative_exponent(void) {
+    fputs("error: negative exponent\n", stderr);
+    exit(1);
+}
+
 // I/O and substring helpers — the small surface added so AOT-compiled
 // programs can act as filters over newline-delimited stdin. See
 // DESIGN.md §8 "Built-in words summary" for the user-facing words.
 
+// The UTF-8 sequence at byte `i` of `b`, judged as strictly as
+// `String::from_utf8`: no overlong forms, no surrogates, nothing past
+// U+10FFFF, no cut-off sequences. Returns the sequence's width when it is
+// valid, or minus the length of its longest valid prefix (at least one
+// byte) when it is not, which is how far `String::from_utf8_lossy` skips
+// for each U+FFFD.
+static int64_t plenty_utf8_step(const unsigned char *b, int64_t len, int64_t i) {
+    unsigned char lead = b[i];
+    int64_t width;
+    unsigned char lo = 0x80, hi = 0xbf;
+    if (lead < 0x80) {
+        return 1;
+    } else if (lead >= 0xc2 && lead <= 0xdf) {
+        width = 2;
+    } else if (lead >= 0xe0 && lead <= 0xef) {
+        width = 3;
+        if (lead == 0xe0) lo = 0xa0;
+        if (lead == 0xed) hi = 0x9f;
+    } else if (lead >= 0xf0 && lead <= 0xf4) {
+        width = 4;
+        if (lead == 0xf0) lo = 0x90;
+        if (lead == 0xf4) hi = 0x8f;
+    } else {
+        return -1;
+    }
+    if (i + 1 >= len || b[i + 1] < lo || b[i + 1] > hi) {
+        return -1;
+    }
+    for (int64_t k = 2; k < width; k++) {
+        if (i + k >= len || (b[i + k] & 0xc0) != 0x80) {
+            return -k;
+        }
+    }
+    return width;
+}
+
+// `:utf8-decode`'s check, and `:readline`'s.
+int8_t plenty_utf8_valid(const plenty_str *s) {
+    const unsigned char *b = (const unsigned char *)s->bytes;
+    for (int64_t i = 0; i < s->len;) {
+        int64_t step = plenty_utf8_step(b, s->len, i);
+        if (step < 0) {
+            return 0;
+        }
+        i += step;
+    }
+    return 1;
+}
+
 // Read one newline-terminated line from stdin, strip the trailing
-// `\n` (and a preceding `\r` if present), return a malloc'd
-// nul-terminated buffer with the line content. Returns NULL on EOF.
+// `\n` (and a preceding `\r` if present), and return the line content
+// as a fresh string. Returns NULL on EOF. The length comes from
+// `getline`, so a line with an embedded nul byte survives intact.
 //
-// The returned buffer is intentionally leaked — the runtime heap is
+// The returned string is intentionally leaked — the runtime heap is
 // append-only (DESIGN.md §12.1), mirroring the interpreter's `Heap`.
-// `getline` allocates the buffer for us via `malloc`/`realloc`, which
-// is the same allocator `free` would call; we just don't.
-const char *plenty_readline(void) {
+// This is `:readline-bytes`; `plenty_readline` checks the line is text.
+const plenty_str *plenty_readline_bytes(void) {
     char *line = NULL;
     size_t cap = 0;
     ssize_t n = getline(&line, &cap, stdin);
@@ -139,26 +575,689 @@ const char *plenty_readline(void) {
         return NULL;
     }
     if (n > 0 && line[n - 1] == '\n') {
-        line[--n] = '\0';
+        n--;
         if (n > 0 && line[n - 1] == '\r') {
-            line[--n] = '\0';
+            n--;
         }
     }
-    return line;
+    plenty_str *out = plenty_str_alloc((int64_t)n);
+    memcpy(out->bytes, line, (size_t)n);
+    free(line);
+    return out;
 }
 
-// Byte-level substring test. `strstr` returns the address of the first
-// occurrence of `needle` inside `haystack`, or NULL if absent; we map
-// that to a Plenty `Bool` (`int8_t`).
-int8_t plenty_contains(const char *haystack, const char *needle) {
-    return (int8_t)(strstr(haystack, needle) != NULL);
+// `:readline`: a line that is not UTF-8 ends the program with the
+// interpreter's error rather than becoming a `Str`.
+const plenty_str *plenty_readline(void) {
+    const plenty_str *out = plenty_readline_bytes();
+    if (out != NULL && !plenty_utf8_valid(out)) {
+        fputs("error: stream did not contain valid UTF-8\n", stderr);
+        exit(1);
+    }
+    return out;
+}
+
+// Byte-level substring test, mapped to a Plenty `Bool` (`int8_t`).
+// A plain scan rather than `strstr`, which would stop at the first
+// interior nul of either string.
+int8_t plenty_contains(const plenty_str *haystack, const plenty_str *needle) {
+    for (int64_t i = 0; i + needle->len <= haystack->len; i++) {
+        if (memcmp(haystack->bytes + i, needle->bytes, (size_t)needle->len) == 0) {
+            return 1;
+        }
+    }
+    return 0;
 }
 
 // Bare-text output. Writes the bytes verbatim followed by a single
 // `\n` — no quoting, no escaping, no surrounding brackets.
 // `plenty_print_str` (used by `.`) is the introspection-friendly
 // version that escapes; this one is for programs that emit data.
-void plenty_println(const char *s) {
-    fputs(s, stdout);
+void plenty_println(const plenty_str *s) {
+    fwrite(s->bytes, 1, (size_t)s->len, stdout);
     fputc('\n', stdout);
 }
+
+// `:eprintln`: `plenty_println` for diagnostics.
+void plenty_eprintln(const plenty_str *s) {
+    fwrite(s->bytes, 1, (size_t)s->len, stderr);
+    fputc('\n', stderr);
+}
+
+// `:eprint` runs the print helpers between these two, so its rendering
+// is `:print`'s with stderr as the target.
+void plenty_stderr_begin(void) {
+    plenty_capture = stderr;
+}
+
+void plenty_stderr_end(void) {
+    plenty_capture = NULL;
+}
+
+// `:exit`: `exit` rather than a return from `plenty_main`, since the word
+// may run at any call depth; both flush stdout the same way.
+_Noreturn void plenty_exit(int32_t code) {
+    exit(code);
+}
+
+// Verbatim output with no newline. Prints the constant label the
+// compiler emits for a function reference (`<fn ( Str -> Bool )>`),
+// which has no runtime value worth showing.
+void plenty_print_raw(const char *s) {
+    fputs(s, plenty_out());
+}
+
+// Allocation for closure environments: a code address followed by the
+// captured values. Like runtime strings, environments are never freed
+// (the interpreter's heap is append-only too, DESIGN.md §12.1).
+void *plenty_alloc(int64_t size) {
+    void *p = malloc((size_t)size);
+    if (p == NULL) {
+        fputs("error: out of memory\n", stderr);
+        exit(1);
+    }
+    return p;
+}
+
+// String words. Indices count chars (Unicode scalar values) rather
+// than bytes, as in the interpreter; a byte starts a char unless it is a
+// UTF-8 continuation byte (0b10xxxxxx).
+static int is_char_start(char b) {
+    return ((unsigned char)b & 0xC0) != 0x80;
+}
+
+// Copy `len` bytes starting at `from` into a fresh string.
+static const plenty_str *plenty_str_from(const char *from, int64_t len) {
+    plenty_str *out = plenty_str_alloc(len);
+    memcpy(out->bytes, from, (size_t)len);
+    return out;
+}
+
+// Byte offset of the first occurrence of `needle` in `s` at or after
+// byte `from`, or -1. An empty needle matches at `from`.
+static int64_t plenty_str_search(const plenty_str *s, const plenty_str *needle, int64_t from) {
+    for (int64_t i = from; i + needle->len <= s->len; i++) {
+        if (memcmp(s->bytes + i, needle->bytes, (size_t)needle->len) == 0) {
+            return i;
+        }
+    }
+    return -1;
+}
+
+// Number of chars in the first `bytes` bytes of `s`.
+static int64_t plenty_char_count(const plenty_str *s, int64_t bytes) {
+    int64_t n = 0;
+    for (int64_t i = 0; i < bytes; i++) {
+        n += is_char_start(s->bytes[i]);
+    }
+    return n;
+}
+
+// Byte offset where the `chars`-th char of `s` starts (`s->len` for the
+// char count itself). The caller has bounds-checked `chars`.
+static int64_t plenty_byte_offset(const plenty_str *s, int64_t chars) {
+    int64_t i = 0;
+    for (int64_t seen = 0; i < s->len; i++) {
+        if (is_char_start(s->bytes[i]) && seen++ == chars) {
+            return i;
+        }
+    }
+    return i;
+}
+
+int64_t plenty_str_len(const plenty_str *s) {
+    return plenty_char_count(s, s->len);
+}
+
+const plenty_str *plenty_str_slice(const plenty_str *s, int64_t start, int64_t end) {
+    int64_t len = plenty_str_len(s);
+    if (start < 0 || start > end || end > len) {
+        fprintf(stderr,
+                "error: `:slice` range %lld..%lld is out of bounds for a string of %lld chars\n",
+                (long long)start, (long long)end, (long long)len);
+        exit(1);
+    }
+    int64_t from = plenty_byte_offset(s, start);
+    int64_t to = plenty_byte_offset(s, end);
+    return plenty_str_from(s->bytes + from, to - from);
+}
+
+// `Bytes` shares the `plenty_str` layout, so these count and cut bytes
+// where `:len` and `:slice` count and cut chars.
+int64_t plenty_bytes_len(const plenty_str *s) {
+    return s->len;
+}
+
+const plenty_str *plenty_bytes_slice(const plenty_str *s, int64_t start, int64_t end) {
+    if (start < 0 || start > end || end > s->len) {
+        fprintf(stderr,
+                "error: `:bytes-slice` range %lld..%lld is out of bounds for %lld bytes\n",
+                (long long)start, (long long)end, (long long)s->len);
+        exit(1);
+    }
+    return plenty_str_from(s->bytes + start, end - start);
+}
+
+// `:split` has three results, so it returns them in a fresh block the
+// compiled code loads from: two strings and the found flag.
+typedef struct {
+    const plenty_str *before;
+    const plenty_str *after;
+    int8_t found;
+} plenty_split;
+
+const plenty_split *plenty_str_split(const plenty_str *s, const plenty_str *sep) {
+    if (sep->len == 0) {
+        fputs("error: `:split` separator must not be empty\n", stderr);
+        exit(1);
+    }
+    plenty_split *out = (plenty_split *)plenty_alloc((int64_t)sizeof(plenty_split));
+    int64_t at = plenty_str_search(s, sep, 0);
+    if (at < 0) {
+        out->before = s;
+        out->after = plenty_str_from("", 0);
+        out->found = 0;
+    } else {
+        out->before = plenty_str_from(s->bytes, at);
+        out->after = plenty_str_from(s->bytes + at + sep->len, s->len - at - sep->len);
+        out->found = 1;
+    }
+    return out;
+}
+
+// The ASCII whitespace set of Rust's `char::is_ascii_whitespace`.
+static int is_ascii_space(char c) {
+    return c == ' ' || c == '\t' || c == '\n' || c == '\f' || c == '\r';
+}
+
+const plenty_str *plenty_str_trim(const plenty_str *s) {
+    int64_t from = 0;
+    int64_t to = s->len;
+    while (from < to && is_ascii_space(s->bytes[from])) {
+        from++;
+    }
+    while (to > from && is_ascii_space(s->bytes[to - 1])) {
+        to--;
+    }
+    return plenty_str_from(s->bytes + from, to - from);
+}
+
+int8_t plenty_str_starts_with(const plenty_str *s, const plenty_str *prefix) {
+    return prefix->len <= s->len
+        && memcmp(s->bytes, prefix->bytes, (size_t)prefix->len) == 0;
+}
+
+int8_t plenty_str_ends_with(const plenty_str *s, const plenty_str *suffix) {
+    return suffix->len <= s->len
+        && memcmp(s->bytes + s->len - suffix->len, suffix->bytes, (size_t)suffix->len) == 0;
+}
+
+int64_t plenty_str_find(const plenty_str *s, const plenty_str *needle) {
+    int64_t at = plenty_str_search(s, needle, 0);
+    return at < 0 ? -1 : plenty_char_count(s, at);
+}
+
+// Replace every non-overlapping occurrence of `from`, scanning left to
+// right: one pass to size the result, one to fill it.
+const plenty_str *plenty_str_replace(const plenty_str *s, const plenty_str *from,
+                                     const plenty_str *to) {
+    if (from->len == 0) {
+        fputs("error: `:replace` pattern must not be empty\n", stderr);
+        exit(1);
+    }
+    int64_t len = s->len;
+    for (int64_t i = 0, at; (at = plenty_str_search(s, from, i)) >= 0; i = at + from->len) {
+        len += to->len - from->len;
+    }
+    plenty_str *out = plenty_str_alloc(len);
+    int64_t w = 0;
+    int64_t i = 0;
+    for (int64_t at; (at = plenty_str_search(s, from, i)) >= 0; i = at + from->len) {
+        memcpy(out->bytes + w, s->bytes + i, (size_t)(at - i));
+        w += at - i;
+        memcpy(out->bytes + w, to->bytes, (size_t)to->len);
+        w += to->len;
+    }
+    memcpy(out->bytes + w, s->bytes + i, (size_t)(s->len - i));
+    return out;
+}
+
+const plenty_str *plenty_str_upper(const plenty_str *s) {
+    plenty_str *out = plenty_str_alloc(s->len);
+    for (int64_t i = 0; i < s->len; i++) {
+        char c = s->bytes[i];
+        out->bytes[i] = (c >= 'a' && c <= 'z') ? (char)(c - 'a' + 'A') : c;
+    }
+    return out;
+}
+
+const plenty_str *plenty_str_lower(const plenty_str *s) {
+    plenty_str *out = plenty_str_alloc(s->len);
+    for (int64_t i = 0; i < s->len; i++) {
+        char c = s->bytes[i];
+        out->bytes[i] = (c >= 'A' && c <= 'Z') ? (char)(c - 'A' + 'a') : c;
+    }
+    return out;
+}
+
+const plenty_str *plenty_str_repeat(const plenty_str *s, int64_t n) {
+    if (n < 0) {
+        fprintf(stderr, "error: `:repeat` count must not be negative, got %lld\n", (long long)n);
+        exit(1);
+    }
+    if (s->len != 0 && n > (INT64_MAX - 1 - (int64_t)sizeof(plenty_str)) / s->len) {
+        fputs("error: out of memory\n", stderr);
+        exit(1);
+    }
+    plenty_str *out = plenty_str_alloc(s->len * n);
+    for (int64_t i = 0; s->len != 0 && i < n; i++) {
+        memcpy(out->bytes + i * s->len, s->bytes, (size_t)s->len);
+    }
+    return out;
+}
+
+// `:parse-T` has two results, returned in a fresh block like `:split`'s:
+// the value (sign-extended or zero-extended to 64 bits, truncated to the
+// target width by the compiled code) and the success flag.
+typedef struct {
+    int64_t value;
+    int8_t ok;
+} plenty_parsed;
+
+// Parse decimal text as an integer of `bits` bits, signed or not, with
+// exactly the rules of Rust's `str::parse`: an optional `+`, or `-` for a
+// signed target, then one or more ASCII digits and nothing else. Failure
+// yields value 0.
+const plenty_parsed *plenty_parse_int(const plenty_str *s, int8_t is_signed, int64_t bits) {
+    plenty_parsed *out = (plenty_parsed *)plenty_alloc((int64_t)sizeof(plenty_parsed));
+    out->value = 0;
+    out->ok = 0;
+    int64_t i = 0;
+    int negative = 0;
+    if (s->len > 0 && (s->bytes[0] == '+' || (is_signed && s->bytes[0] == '-'))) {
+        negative = s->bytes[0] == '-';
+        i = 1;
+    }
+    if (i == s->len) {
+        return out;
+    }
+    // The largest magnitude the target holds in this direction.
+    uint64_t limit = is_signed
+        ? (UINT64_C(1) << (bits - 1)) - (negative ? 0 : 1)
+        : (bits == 64 ? UINT64_MAX : (UINT64_C(1) << bits) - 1);
+    uint64_t magnitude = 0;
+    for (; i < s->len; i++) {
+        char c = s->bytes[i];
+        if (c < '0' || c > '9') {
+            return out;
+        }
+        uint64_t digit = (uint64_t)(c - '0');
+        if (magnitude > (limit - digit) / 10) {
+            return out;
+        }
+        magnitude = magnitude * 10 + digit;
+    }
+    out->value = negative ? (int64_t)(0 - magnitude) : (int64_t)magnitude;
+    out->ok = 1;
+    return out;
+}
+
+// Raw string output where the print helpers write — `:format`'s literal
+// text and its `{}` strings, which are not quoted.
+void plenty_write_str(const plenty_str *s) {
+    fwrite(s->bytes, 1, (size_t)s->len, plenty_out());
+}
+
+// `:format`'s `{:x}`: the compiled code zero-extends the integer's bits.
+void plenty_print_hex(uint64_t n) {
+    fprintf(plenty_out(), "%llx", (unsigned long long)n);
+}
+
+// Chars. A `Char` is passed as its Unicode scalar value in a `uint32_t`;
+// strings hold chars UTF-8 encoded.
+
+// UTF-8 encode `c` into `out`, returning the byte count.
+static int plenty_encode_char(uint32_t c, char out[4]) {
+    if (c < 0x80) {
+        out[0] = (char)c;
+        return 1;
+    }
+    if (c < 0x800) {
+        out[0] = (char)(0xC0 | (c >> 6));
+        out[1] = (char)(0x80 | (c & 0x3F));
+        return 2;
+    }
+    if (c < 0x10000) {
+        out[0] = (char)(0xE0 | (c >> 12));
+        out[1] = (char)(0x80 | ((c >> 6) & 0x3F));
+        out[2] = (char)(0x80 | (c & 0x3F));
+        return 3;
+    }
+    out[0] = (char)(0xF0 | (c >> 18));
+    out[1] = (char)(0x80 | ((c >> 12) & 0x3F));
+    out[2] = (char)(0x80 | ((c >> 6) & 0x3F));
+    out[3] = (char)(0x80 | (c & 0x3F));
+    return 4;
+}
+
+// A char as `.` shows it: quoted and escaped like Rust's `{:?}`, which
+// escapes `'` but not `"`.
+void plenty_print_char(uint32_t c) {
+    FILE *out = plenty_out();
+    fputc('\'', out);
+    switch (c) {
+        case '\'': fputs("\\'",  out); break;
+        case '\\': fputs("\\\\", out); break;
+        case '\t': fputs("\\t",  out); break;
+        case '\n': fputs("\\n",  out); break;
+        case '\r': fputs("\\r",  out); break;
+        case '\0': fputs("\\0",  out); break;
+        default:
+            if (plenty_debug_escapes(c)) {
+                fprintf(out, "\\u{%x}", (unsigned)c);
+            } else {
+                char bytes[4];
+                fwrite(bytes, 1, (size_t)plenty_encode_char(c, bytes), out);
+            }
+            break;
+    }
+    fputc('\'', out);
+}
+
+// A char written raw, for `:format`'s `{}`.
+void plenty_write_char(uint32_t c) {
+    char bytes[4];
+    fwrite(bytes, 1, (size_t)plenty_encode_char(c, bytes), plenty_out());
+}
+
+// `:next-char` decodes the char at byte offset `i` in place and returns
+// it packed with its byte width, `(c << 8) | width`, so a walk over a
+// string allocates nothing; a width of 0 means `i` is the end.
+uint64_t plenty_next_char(const plenty_str *s, int64_t i) {
+    if (i < 0 || i > s->len || (i < s->len && ((unsigned char)s->bytes[i] & 0xC0) == 0x80)) {
+        fprintf(stderr,
+                "error: `:next-char` offset %lld is not a char boundary of a string of %lld bytes\n",
+                (long long)i, (long long)s->len);
+        exit(1);
+    }
+    if (i == s->len) {
+        return 0;
+    }
+    int64_t len;
+    uint32_t c = plenty_decode_char(s, i, &len);
+    return ((uint64_t)c << 8) | (uint64_t)len;
+}
+
+uint32_t plenty_char_at(const plenty_str *s, int64_t i) {
+    int64_t len = plenty_str_len(s);
+    if (i < 0 || i >= len) {
+        fprintf(stderr,
+                "error: `:char-at` index %lld is out of bounds for a string of %lld chars\n",
+                (long long)i, (long long)len);
+        exit(1);
+    }
+    int64_t width;
+    return plenty_decode_char(s, plenty_byte_offset(s, i), &width);
+}
+
+const plenty_str *plenty_from_char(uint32_t c) {
+    char bytes[4];
+    return plenty_str_from(bytes, plenty_encode_char(c, bytes));
+}
+
+// `:as-char` on an integer that is not a Unicode scalar value. The
+// compiled code widens the integer to 64 bits by its signedness.
+_Noreturn void plenty_trap_not_char(int64_t n, int8_t is_signed) {
+    if (is_signed) {
+        fprintf(stderr, "error: `:as-char` %lld is not a Unicode scalar value\n", (long long)n);
+    } else {
+        fprintf(stderr, "error: `:as-char` %llu is not a Unicode scalar value\n",
+                (unsigned long long)(uint64_t)n);
+    }
+    exit(1);
+}
+
+// A byte string as `.` shows it, matching Rust's `escape_ascii`:
+// printable ASCII as is, the usual escapes, `\xHH` for the rest.
+void plenty_print_bytes(const plenty_str *s) {
+    FILE *out = plenty_out();
+    fputs("b\"", out);
+    for (int64_t i = 0; i < s->len; i++) {
+        unsigned char b = (unsigned char)s->bytes[i];
+        switch (b) {
+            case '\t': fputs("\\t",  out); break;
+            case '\r': fputs("\\r",  out); break;
+            case '\n': fputs("\\n",  out); break;
+            case '\\': fputs("\\\\", out); break;
+            case '\'': fputs("\\'",  out); break;
+            case '"':  fputs("\\\"", out); break;
+            default:
+                if (b >= 0x20 && b <= 0x7e) {
+                    fputc(b, out);
+                } else {
+                    fprintf(out, "\\x%02x", (unsigned)b);
+                }
+                break;
+        }
+    }
+    fputc('"', out);
+}
+
+// Everything left in `in`, read in growing chunks, as a fresh string.
+static const plenty_str *plenty_read_stream(FILE *in) {
+    size_t cap = 4096;
+    size_t len = 0;
+    char *buf = malloc(cap);
+    if (buf == NULL) {
+        fprintf(stderr, "error: out of memory\n");
+        exit(1);
+    }
+    size_t n;
+    while ((n = fread(buf + len, 1, cap - len, in)) > 0) {
+        len += n;
+        if (len == cap) {
+            cap *= 2;
+            char *grown = realloc(buf, cap);
+            if (grown == NULL) {
+                fprintf(stderr, "error: out of memory\n");
+                exit(1);
+            }
+            buf = grown;
+        }
+    }
+    const plenty_str *out = plenty_str_from(buf, (int64_t)len);
+    free(buf);
+    return out;
+}
+
+// `:read-bytes`: everything left on stdin.
+const plenty_str *plenty_read_all(void) {
+    return plenty_read_stream(stdin);
+}
+
+// `:write-bytes`: the bytes verbatim, no quoting and no newline.
+void plenty_write_bytes(const plenty_str *s) {
+    fwrite(s->bytes, 1, (size_t)s->len, stdout);
+}
+
+// `:utf8-decode-lossy`: the valid sequences copied through and each
+// invalid one replaced by U+FFFD, as `String::from_utf8_lossy` does. A
+// replacement is three bytes for at least one skipped, so three times the
+// input's length always has room.
+const plenty_str *plenty_utf8_decode_lossy(const plenty_str *s) {
+    const unsigned char *b = (const unsigned char *)s->bytes;
+    plenty_str *out = plenty_str_alloc(s->len * 3);
+    int64_t n = 0;
+    for (int64_t i = 0; i < s->len;) {
+        int64_t step = plenty_utf8_step(b, s->len, i);
+        if (step > 0) {
+            memcpy(out->bytes + n, b + i, (size_t)step);
+            n += step;
+            i += step;
+        } else {
+            memcpy(out->bytes + n, "\xef\xbf\xbd", 3);
+            n += 3;
+            i -= step;
+        }
+    }
+    out->len = n;
+    out->bytes[n] = '\0';
+    return out;
+}
+
+// A `File`: the open stream, or NULL once `:close-file` has run. A failed
+// `:open-file` hands out a NULL `plenty_file *` instead, and every helper
+// below reads either kind of NULL as a file at its end.
+typedef struct {
+    FILE *f;
+} plenty_file;
+
+// `s` as the nul-terminated string the C library wants, or NULL if it
+// holds a nul of its own, which no file or variable name can.
+static char *plenty_cstr(const plenty_str *s) {
+    if (memchr(s->bytes, '\0', (size_t)s->len) != NULL) {
+        return NULL;
+    }
+    char *out = plenty_alloc(s->len + 1);
+    memcpy(out, s->bytes, (size_t)s->len);
+    out[s->len] = '\0';
+    return out;
+}
+
+static FILE *plenty_fopen(const plenty_str *path, const char *mode) {
+    char *cpath = plenty_cstr(path);
+    if (cpath == NULL) {
+        return NULL;
+    }
+    FILE *f = fopen(cpath, mode);
+    free(cpath);
+    return f;
+}
+
+// `:read-file`: the whole file, or NULL if it cannot be read or is not
+// UTF-8 text.
+const plenty_str *plenty_read_file(const plenty_str *path) {
+    FILE *f = plenty_fopen(path, "rb");
+    if (f == NULL) {
+        return NULL;
+    }
+    const plenty_str *text = plenty_read_stream(f);
+    int failed = ferror(f);
+    fclose(f);
+    if (failed || !plenty_utf8_valid(text)) {
+        return NULL;
+    }
+    return text;
+}
+
+// `:write-file` and `:append-file`: whether all of `text` reached the file.
+int8_t plenty_write_file(const plenty_str *path, const plenty_str *text, int8_t append) {
+    FILE *f = plenty_fopen(path, append ? "ab" : "wb");
+    if (f == NULL) {
+        return 0;
+    }
+    size_t n = fwrite(text->bytes, 1, (size_t)text->len, f);
+    int closed = fclose(f) == 0;
+    return n == (size_t)text->len && closed;
+}
+
+// `:file-exists`: anything `stat` can see, directories included, as
+// Rust's `Path::exists` judges it.
+int8_t plenty_file_exists(const plenty_str *path) {
+    char *cpath = plenty_cstr(path);
+    if (cpath == NULL) {
+        return 0;
+    }
+    struct stat st;
+    int found = stat(cpath, &st) == 0;
+    free(cpath);
+    return found;
+}
+
+plenty_file *plenty_open_file(const plenty_str *path) {
+    FILE *f = plenty_fopen(path, "rb");
+    if (f == NULL) {
+        return NULL;
+    }
+    plenty_file *file = plenty_alloc((int64_t)sizeof(plenty_file));
+    file->f = f;
+    return file;
+}
+
+// `:file-readline-bytes`: `plenty_readline` on the file.
+const plenty_str *plenty_file_readline_bytes(plenty_file *file) {
+    if (file == NULL || file->f == NULL) {
+        return NULL;
+    }
+    char *line = NULL;
+    size_t cap = 0;
+    ssize_t n = getline(&line, &cap, file->f);
+    if (n == -1) {
+        free(line);
+        return NULL;
+    }
+    if (n > 0 && line[n - 1] == '\n') {
+        n--;
+        if (n > 0 && line[n - 1] == '\r') {
+            n--;
+        }
+    }
+    const plenty_str *out = plenty_str_from(line, (int64_t)n);
+    free(line);
+    return out;
+}
+
+// `:file-readline`: the same, except that a line that is not UTF-8 is
+// NULL too.
+const plenty_str *plenty_file_readline(plenty_file *file) {
+    const plenty_str *out = plenty_file_readline_bytes(file);
+    return out != NULL && plenty_utf8_valid(out) ? out : NULL;
+}
+
+void plenty_close_file(plenty_file *file) {
+    if (file != NULL && file->f != NULL) {
+        fclose(file->f);
+        file->f = NULL;
+    }
+}
+
+void plenty_print_file(const plenty_file *file) {
+    (void)file;
+    fputs("<file>", plenty_out());
+}
+
+// `:argc`: the arguments after the executable's own name, as the
+// interpreter counts those after the program's path.
+int64_t plenty_argc(void) {
+    return plenty_arg_count > 1 ? (int64_t)plenty_arg_count - 1 : 0;
+}
+
+// `:arg`: argument `i` counting from the first after the executable's
+// name, or NULL if there is none or it is not UTF-8.
+const plenty_str *plenty_arg(int64_t i) {
+    if (i < 0 || i >= plenty_argc()) {
+        return NULL;
+    }
+    const char *arg = plenty_arg_values[i + 1];
+    const plenty_str *out = plenty_str_from(arg, (int64_t)strlen(arg));
+    return plenty_utf8_valid(out) ? out : NULL;
+}
+
+// `:env`: NULL for an unset variable, a value that is not UTF-8, or a
+// name no variable can have, which the interpreter also refuses.
+const plenty_str *plenty_env(const plenty_str *name) {
+    if (name->len == 0 || memchr(name->bytes, '=', (size_t)name->len) != NULL) {
+        return NULL;
+    }
+    char *cname = plenty_cstr(name);
+    if (cname == NULL) {
+        return NULL;
+    }
+    const char *value = getenv(cname);
+    free(cname);
+    if (value == NULL) {
+        return NULL;
+    }
+    const plenty_str *out = plenty_str_from(value, (int64_t)strlen(value));
+    return plenty_utf8_valid(out) ? out : NULL;
+}
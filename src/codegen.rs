@@ -32,8 +32,8 @@
 //!
 //! Phase c.4 adds strings. Every string literal referenced by the source
 //! (whether by `Op::PushStr` or by a `Pattern::Str` inside a match) is
-//! emitted as one static-data symbol per `StrId`, carrying the UTF-8
-//! bytes plus a trailing nul. `Op::PushStr` lowers to `global_value` —
+//! emitted as one static-data symbol per `StrId`, carrying its byte
+//! length, the UTF-8 bytes, and a trailing nul. `Op::PushStr` lowers to `global_value` —
 //! the data's address — and onto the compile-time stack tagged as
 //! `Ty::Str` (CLIF `i64` for the host pointer width). `Op::Add` and
 //! `Op::Eq` now dispatch on operand types: integer pairs take the
@@ -49,14 +49,15 @@
 //! artifact. Every Plenty op lowers, and the user no longer needs to
 //! run `cc` by hand.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::path::Path;
 use std::rc::Rc;
 
 use cranelift_codegen::ir::condcodes::IntCC;
 use cranelift_codegen::ir::{
-    types, AbiParam, Block, BlockArg, Function, InstBuilder, Signature, TrapCode, UserFuncName,
+    types, AbiParam, Block, BlockArg, Function, InstBuilder, MemFlags, Signature, TrapCode,
+    UserFuncName,
 };
 use cranelift_codegen::isa::CallConv;
 use cranelift_codegen::settings::Configurable;
@@ -66,7 +67,9 @@ use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module};
 use cranelift_object::{ObjectBuilder, ObjectModule};
 
 use crate::lexer;
-use crate::op::{self, FnSig, MatchArm, Op, Pattern, Ty};
+use crate::op::{
+    self, FnSig, FnTy, FormatPiece, FormatSpec, ImportPaths, MatchArm, Op, Pattern, StructTy, Ty,
+};
 use crate::value::{Heap, StrId, Value};
 
 // ---- Cranelift API reference ----
@@ -131,10 +134,25 @@ use crate::value::{Heap, StrId, Value};
 /// missing, the error message identifies the link step as the failure
 /// site so users can install a C toolchain or wrap an alternative
 /// compiler as `cc`.
-pub fn compile_source_to_executable(source: &str, output: &Path) -> Result<()> {
+///
+/// `import "path"` forms resolve through `imports` at compile time; the
+/// executable carries the imported definitions and reads no files.
+pub fn compile_source_to_executable(
+    source: &str,
+    imports: &ImportPaths,
+    output: &Path,
+) -> Result<()> {
     let toks = lexer::lex(source)?;
     let mut heap = Heap::default();
-    let ops = op::compile(&toks, &mut heap)?;
+    let mut ops = op::compile(
+        &toks,
+        &mut heap,
+        imports,
+        &HashMap::new(),
+        &HashSet::new(),
+        &HashMap::new(),
+    )?;
+    op::infer(&mut ops, &HashMap::new())?;
     op::check(&ops, Vec::new(), &HashMap::new())?;
 
     // Tempfile names blend the process id and a nanosecond timestamp:
@@ -217,11 +235,14 @@ fn compile_to_object(ops: &[Op], heap: &Heap, output: &Path) -> Result<()> {
     // (top-level, nested under another definition, or inside a match
     // arm), declare each as a Cranelift symbol with the tail-call
     // convention so its body can `return_call` other user functions.
+    let mut fn_refs: HashMap<String, usize> = HashMap::new();
+    collect_fn_refs(ops, &mut fn_refs);
     let mut user_fns: HashMap<String, UserFn> = HashMap::new();
-    collect_user_fns(ops, &mut module, &mut user_fns)?;
+    let mut instances = Instances::default();
+    collect_user_fns(ops, &fn_refs, &mut module, &mut user_fns, &mut instances)?;
     // AOT mode is closed-world: every `Call`/`TailCall` in `ops` must
     // resolve to a definition collected above (§11.1).
-    check_calls_resolve(ops, &user_fns)?;
+    check_calls_resolve(ops, &user_fns, &instances)?;
 
     // Pass 1b: emit one read-only data symbol per source string literal.
     // We walk the ops (recursing into bodies and match arms) collecting
@@ -246,6 +267,7 @@ fn compile_to_object(ops: &[Op], heap: &Heap, output: &Path) -> Result<()> {
         emit_user_function(
             name,
             &user_fns,
+            &mut instances,
             &str_data,
             eof_empty_str,
             &runtime,
@@ -259,12 +281,28 @@ fn compile_to_object(ops: &[Op], heap: &Heap, output: &Path) -> Result<()> {
     emit_main(
         ops,
         &user_fns,
+        &mut instances,
         &str_data,
         eof_empty_str,
         &runtime,
         &mut module,
     )?;
 
+    // Pass 4: emit the instances of generic functions that Passes 2 and 3
+    // called. An instance's body may call further generic functions, so
+    // this runs until no new instance turns up.
+    while let Some(key) = instances.pending.pop() {
+        emit_user_function(
+            &key,
+            &user_fns,
+            &mut instances,
+            &str_data,
+            eof_empty_str,
+            &runtime,
+            &mut module,
+        )?;
+    }
+
     let product = module.finish();
     let bytes = product.emit()?;
     std::fs::write(output, bytes)?;
@@ -304,12 +342,19 @@ struct Runtime {
     print_u64: FuncId,
     print_bool: FuncId,
     print_str: FuncId,
+    /// `plenty_print_char(u32) -> ()` — a char quoted and escaped as `.`
+    /// shows it.
+    print_char: FuncId,
+    /// `plenty_print_bytes(*const plenty_str) -> ()` — a byte string as
+    /// `.` shows it, `b"..."` with non-printable bytes escaped.
+    print_bytes: FuncId,
     print_open_bracket: FuncId,
     print_close_bracket: FuncId,
     print_space: FuncId,
-    /// `plenty_concat(*const u8, *const u8) -> *const u8` — c.4.
+    /// `plenty_concat(*const plenty_str, *const plenty_str) -> *const
+    /// plenty_str` — c.4.
     concat: FuncId,
-    /// `plenty_str_eq(*const u8, *const u8) -> i8` — c.4.
+    /// `plenty_str_eq(*const plenty_str, *const plenty_str) -> i8` — c.4.
     str_eq: FuncId,
     /// `plenty_trap_overflow() -> !` — prints `error: integer overflow`
     /// to stderr and `exit(1)`s. The lowerer calls this from the
@@ -319,20 +364,171 @@ struct Runtime {
     /// to stderr and `exit(1)`s. Called from the zero-check branch of
     /// the `Div` lowering.
     trap_div_zero: FuncId,
-    /// `plenty_readline() -> *const u8` — read one newline-terminated
-    /// line from stdin, strip the trailing newline, return a malloc'd
-    /// nul-terminated buffer. Returns NULL on EOF. Owned (never freed)
-    /// to match the interpreter's append-only `Heap` (§12.1).
+    /// `plenty_trap_negative_exponent() -> !` — prints `error: negative
+    /// exponent` to stderr and `exit(1)`s. Called from `pow` on a signed
+    /// exponent below zero.
+    trap_negative_exponent: FuncId,
+    /// `plenty_readline() -> *const plenty_str` — read one
+    /// newline-terminated line from stdin, strip the trailing newline,
+    /// return it as a fresh string. Returns NULL on EOF. Owned (never freed)
+    /// to match the interpreter's append-only `Heap` (§12.1). A line that
+    /// is not UTF-8 exits with the interpreter's error.
     readline: FuncId,
-    /// `plenty_contains(*const u8 haystack, *const u8 needle) -> i8` —
-    /// returns 1 if `needle` is a byte-substring of `haystack`, 0
-    /// otherwise. Wraps `strstr`.
+    /// `plenty_readline_bytes() -> *const plenty_str` — `plenty_readline`
+    /// without the UTF-8 check, for `:readline-bytes`.
+    readline_bytes: FuncId,
+    /// `plenty_read_all() -> *const plenty_str` — the rest of stdin, as
+    /// a fresh byte string.
+    read_all: FuncId,
+    /// `plenty_write_bytes(*const plenty_str) -> ()` — the bytes, verbatim,
+    /// to stdout.
+    write_bytes: FuncId,
+    /// `plenty_read_file(*const plenty_str path) -> *const plenty_str` —
+    /// the file's contents, or NULL if it cannot be read as UTF-8 text.
+    read_file: FuncId,
+    /// `plenty_write_file(*const plenty_str path, *const plenty_str text,
+    /// i8 append) -> i8` — write or append `text`, creating the file;
+    /// whether that worked.
+    write_file: FuncId,
+    /// `plenty_file_exists(*const plenty_str path) -> i8`.
+    file_exists: FuncId,
+    /// `plenty_open_file(*const plenty_str path) -> *plenty_file` — a
+    /// handle for reading, or NULL if the open failed. The file words
+    /// treat a NULL handle as one already at its end.
+    open_file: FuncId,
+    /// `plenty_file_readline(*plenty_file) -> *const plenty_str` — the
+    /// next line, stripped like `plenty_readline`'s; NULL at the end,
+    /// once closed, or for a line that is not UTF-8.
+    file_readline: FuncId,
+    /// `plenty_file_readline_bytes(*plenty_file) -> *const plenty_str` —
+    /// the same without the UTF-8 check, so NULL means the end only.
+    file_readline_bytes: FuncId,
+    /// `plenty_close_file(*plenty_file) -> ()`; closing twice is harmless.
+    close_file: FuncId,
+    /// `plenty_print_file(*plenty_file) -> ()` — prints `<file>`.
+    print_file: FuncId,
+    /// `plenty_argc() -> i64` — the arguments after the executable's own
+    /// name.
+    argc: FuncId,
+    /// `plenty_arg(i64) -> *const plenty_str` — argument `i` after the
+    /// executable's name, or NULL if there is none or it is not UTF-8.
+    arg: FuncId,
+    /// `plenty_env(*const plenty_str name) -> *const plenty_str` — the
+    /// variable's value, or NULL if it is unset or not UTF-8.
+    env: FuncId,
+    /// `plenty_utf8_valid(*const plenty_str) -> i8` — whether the bytes
+    /// are valid UTF-8, by the rules `String::from_utf8` applies.
+    utf8_valid: FuncId,
+    /// `plenty_utf8_decode_lossy(*const plenty_str) -> *const
+    /// plenty_str` — the bytes as text, each invalid sequence replaced
+    /// by U+FFFD.
+    utf8_decode_lossy: FuncId,
+    /// `plenty_bytes_len(*const plenty_str) -> i64` — the length in
+    /// bytes.
+    bytes_len: FuncId,
+    /// `plenty_bytes_slice(*const plenty_str, i64 start, i64 end) ->
+    /// *const plenty_str` — the bytes in `[start, end)`; exits with the
+    /// interpreter's error on a range outside the byte string.
+    bytes_slice: FuncId,
+    /// `plenty_contains(*const plenty_str haystack, *const plenty_str
+    /// needle) -> i8` — returns 1 if `needle` is a byte-substring of
+    /// `haystack`, 0 otherwise.
     contains: FuncId,
-    /// `plenty_println(*const u8) -> ()` — write the string raw to
+    /// `plenty_str_len(*const plenty_str) -> i64` — the length in chars.
+    str_len: FuncId,
+    /// `plenty_str_slice(*const plenty_str, i64 start, i64 end) -> *const
+    /// plenty_str` — the chars in `[start, end)`; exits with the
+    /// interpreter's error on a range outside the string.
+    str_slice: FuncId,
+    /// `plenty_str_split(*const plenty_str, *const plenty_str sep) ->
+    /// *const plenty_split` — a fresh block holding the text before the
+    /// first `sep`, the text after it and a found flag, at byte offsets
+    /// 0, 8 and 16.
+    str_split: FuncId,
+    /// `plenty_str_trim(*const plenty_str) -> *const plenty_str`.
+    str_trim: FuncId,
+    /// `plenty_str_starts_with(*const plenty_str, *const plenty_str) -> i8`.
+    str_starts_with: FuncId,
+    /// `plenty_str_ends_with(*const plenty_str, *const plenty_str) -> i8`.
+    str_ends_with: FuncId,
+    /// `plenty_str_find(*const plenty_str, *const plenty_str) -> i64` —
+    /// the char index of the first match, or `-1`.
+    str_find: FuncId,
+    /// `plenty_str_replace(*const plenty_str, *const plenty_str from,
+    /// *const plenty_str to) -> *const plenty_str`.
+    str_replace: FuncId,
+    /// `plenty_str_upper(*const plenty_str) -> *const plenty_str`.
+    str_upper: FuncId,
+    /// `plenty_str_lower(*const plenty_str) -> *const plenty_str`.
+    str_lower: FuncId,
+    /// `plenty_str_repeat(*const plenty_str, i64) -> *const plenty_str` —
+    /// exits with the interpreter's error on a negative count.
+    str_repeat: FuncId,
+    /// `plenty_parse_int(*const plenty_str, i8 signed, i64 bits) -> *const
+    /// plenty_parsed` — a fresh block holding the parsed value, widened
+    /// to 64 bits, at offset 0 and the success flag at offset 8.
+    parse_int: FuncId,
+    /// `plenty_capture_begin() -> ()` — redirect the print helpers into a
+    /// memory buffer, so `:to-str` renders a value exactly as `.` would.
+    capture_begin: FuncId,
+    /// `plenty_capture_end() -> *const plenty_str` — stop capturing and
+    /// return the captured text.
+    capture_end: FuncId,
+    /// `plenty_write_str(*const plenty_str) -> ()` — write the bytes
+    /// verbatim, without quotes or a newline, where the print helpers
+    /// write. `:format` emits its literal text and `{}` strings through it.
+    write_str: FuncId,
+    /// `plenty_write_char(u32) -> ()` — the char's UTF-8 bytes, as
+    /// `plenty_write_str` writes a string's.
+    write_char: FuncId,
+    /// `plenty_next_char(*const plenty_str, i64) -> u64` — the char at a
+    /// byte offset and its width in bytes, packed as `(c << 8) | width`;
+    /// a width of 0 is the end of the string. Exits with the
+    /// interpreter's error on an offset that is not a char boundary.
+    next_char: FuncId,
+    /// `plenty_char_at(*const plenty_str, i64) -> u32` — exits with the
+    /// interpreter's error on an index outside the string.
+    char_at: FuncId,
+    /// `plenty_from_char(u32) -> *const plenty_str`.
+    from_char: FuncId,
+    /// `plenty_trap_not_char(i64, i8 signed) -> !` — prints the
+    /// interpreter's `:as-char` error for the integer, widened to 64 bits,
+    /// and `exit(1)`s.
+    trap_not_char: FuncId,
+    /// `plenty_print_hex(u64) -> ()` — an integer's bits, zero-extended to
+    /// 64, in lowercase hex — `:format`'s `{:x}`.
+    print_hex: FuncId,
+    /// `plenty_digits_i64(i64) -> *const plenty_str` — decimal text with
+    /// no width suffix, for signed integers widened to 64 bits.
+    digits_i64: FuncId,
+    /// `plenty_digits_u64(u64) -> *const plenty_str` — the same, for
+    /// unsigned integers.
+    digits_u64: FuncId,
+    /// `plenty_println(*const plenty_str) -> ()` — write the string raw to
     /// stdout, followed by a single `\n`. The bare-text output
     /// primitive; `plenty_print_str` (the `.` path) escapes and
     /// quotes, `plenty_println` does not.
     println: FuncId,
+    /// `plenty_eprintln(*const plenty_str) -> ()` — `plenty_println` to
+    /// stderr.
+    eprintln: FuncId,
+    /// `plenty_stderr_begin() -> ()` — point the print helpers at stderr
+    /// for `:eprint`.
+    stderr_begin: FuncId,
+    /// `plenty_stderr_end() -> ()` — point them back at stdout.
+    stderr_end: FuncId,
+    /// `plenty_exit(i32) -> !` — end the program with the status, stdout
+    /// flushed as a return from `main` would leave it.
+    exit: FuncId,
+    /// `plenty_alloc(i64) -> *mut u8` — `malloc` that exits with
+    /// `error: out of memory` instead of returning `NULL`. Closure
+    /// environments are allocated here and, like runtime strings,
+    /// never freed (§12.1).
+    alloc: FuncId,
+    /// `plenty_print_raw(*const u8) -> ()` — write the C string verbatim
+    /// with no newline. Prints the constant label of a function
+    /// reference inside `.` and `:print`.
+    print_raw: FuncId,
 }
 
 fn declare_runtime(module: &mut ObjectModule) -> Result<Runtime> {
@@ -356,6 +552,19 @@ fn declare_runtime(module: &mut ObjectModule) -> Result<Runtime> {
         sig.returns.push(AbiParam::new(ret));
         Ok(module.declare_function(name, Linkage::Import, &sig)?)
     }
+    fn import(
+        module: &mut ObjectModule,
+        name: &str,
+        params: &[types::Type],
+        ret: types::Type,
+    ) -> Result<FuncId> {
+        let mut sig = module.make_signature();
+        sig.call_conv = CallConv::SystemV;
+        sig.params
+            .extend(params.iter().map(|&param| AbiParam::new(param)));
+        sig.returns.push(AbiParam::new(ret));
+        Ok(module.declare_function(name, Linkage::Import, &sig)?)
+    }
     fn nullary(module: &mut ObjectModule, name: &str) -> Result<FuncId> {
         let mut sig = module.make_signature();
         sig.call_conv = CallConv::SystemV;
@@ -372,6 +581,8 @@ fn declare_runtime(module: &mut ObjectModule) -> Result<Runtime> {
         print_u64: one_arg(module, "plenty_print_u64", types::I64)?,
         print_bool: one_arg(module, "plenty_print_bool", types::I8)?,
         print_str: one_arg(module, "plenty_print_str", PTR_TY)?,
+        print_char: one_arg(module, "plenty_print_char", types::I32)?,
+        print_bytes: one_arg(module, "plenty_print_bytes", PTR_TY)?,
         print_open_bracket: nullary(module, "plenty_print_open_bracket")?,
         print_close_bracket: nullary(module, "plenty_print_close_bracket")?,
         print_space: nullary(module, "plenty_print_space")?,
@@ -379,30 +590,140 @@ fn declare_runtime(module: &mut ObjectModule) -> Result<Runtime> {
         str_eq: two_args_one_return(module, "plenty_str_eq", PTR_TY, PTR_TY, types::I8)?,
         trap_overflow: nullary(module, "plenty_trap_overflow")?,
         trap_div_zero: nullary(module, "plenty_trap_div_zero")?,
+        trap_negative_exponent: nullary(module, "plenty_trap_negative_exponent")?,
         readline: {
             let mut sig = module.make_signature();
             sig.call_conv = CallConv::SystemV;
             sig.returns.push(AbiParam::new(PTR_TY));
             module.declare_function("plenty_readline", Linkage::Import, &sig)?
         },
+        readline_bytes: import(module, "plenty_readline_bytes", &[], PTR_TY)?,
+        read_all: import(module, "plenty_read_all", &[], PTR_TY)?,
+        write_bytes: one_arg(module, "plenty_write_bytes", PTR_TY)?,
+        utf8_valid: import(module, "plenty_utf8_valid", &[PTR_TY], types::I8)?,
+        utf8_decode_lossy: import(module, "plenty_utf8_decode_lossy", &[PTR_TY], PTR_TY)?,
+        bytes_len: import(module, "plenty_bytes_len", &[PTR_TY], types::I64)?,
+        bytes_slice: import(
+            module,
+            "plenty_bytes_slice",
+            &[PTR_TY, types::I64, types::I64],
+            PTR_TY,
+        )?,
+        read_file: import(module, "plenty_read_file", &[PTR_TY], PTR_TY)?,
+        write_file: import(
+            module,
+            "plenty_write_file",
+            &[PTR_TY, PTR_TY, types::I8],
+            types::I8,
+        )?,
+        file_exists: import(module, "plenty_file_exists", &[PTR_TY], types::I8)?,
+        open_file: import(module, "plenty_open_file", &[PTR_TY], PTR_TY)?,
+        file_readline: import(module, "plenty_file_readline", &[PTR_TY], PTR_TY)?,
+        file_readline_bytes: import(module, "plenty_file_readline_bytes", &[PTR_TY], PTR_TY)?,
+        close_file: one_arg(module, "plenty_close_file", PTR_TY)?,
+        print_file: one_arg(module, "plenty_print_file", PTR_TY)?,
+        argc: import(module, "plenty_argc", &[], types::I64)?,
+        arg: import(module, "plenty_arg", &[types::I64], PTR_TY)?,
+        env: import(module, "plenty_env", &[PTR_TY], PTR_TY)?,
         contains: two_args_one_return(module, "plenty_contains", PTR_TY, PTR_TY, types::I8)?,
+        str_len: import(module, "plenty_str_len", &[PTR_TY], types::I64)?,
+        str_slice: import(
+            module,
+            "plenty_str_slice",
+            &[PTR_TY, types::I64, types::I64],
+            PTR_TY,
+        )?,
+        str_split: import(module, "plenty_str_split", &[PTR_TY, PTR_TY], PTR_TY)?,
+        str_trim: import(module, "plenty_str_trim", &[PTR_TY], PTR_TY)?,
+        str_starts_with: import(
+            module,
+            "plenty_str_starts_with",
+            &[PTR_TY, PTR_TY],
+            types::I8,
+        )?,
+        str_ends_with: import(module, "plenty_str_ends_with", &[PTR_TY, PTR_TY], types::I8)?,
+        str_find: import(module, "plenty_str_find", &[PTR_TY, PTR_TY], types::I64)?,
+        str_replace: import(
+            module,
+            "plenty_str_replace",
+            &[PTR_TY, PTR_TY, PTR_TY],
+            PTR_TY,
+        )?,
+        str_upper: import(module, "plenty_str_upper", &[PTR_TY], PTR_TY)?,
+        str_lower: import(module, "plenty_str_lower", &[PTR_TY], PTR_TY)?,
+        str_repeat: import(module, "plenty_str_repeat", &[PTR_TY, types::I64], PTR_TY)?,
+        parse_int: import(
+            module,
+            "plenty_parse_int",
+            &[PTR_TY, types::I8, types::I64],
+            PTR_TY,
+        )?,
+        capture_begin: nullary(module, "plenty_capture_begin")?,
+        capture_end: import(module, "plenty_capture_end", &[], PTR_TY)?,
+        write_str: one_arg(module, "plenty_write_str", PTR_TY)?,
+        print_hex: one_arg(module, "plenty_print_hex", types::I64)?,
+        write_char: one_arg(module, "plenty_write_char", types::I32)?,
+        next_char: import(
+            module,
+            "plenty_next_char",
+            &[PTR_TY, types::I64],
+            types::I64,
+        )?,
+        char_at: import(module, "plenty_char_at", &[PTR_TY, types::I64], types::I32)?,
+        from_char: import(module, "plenty_from_char", &[types::I32], PTR_TY)?,
+        trap_not_char: {
+            let mut sig = module.make_signature();
+            sig.call_conv = CallConv::SystemV;
+            sig.params.push(AbiParam::new(types::I64));
+            sig.params.push(AbiParam::new(types::I8));
+            module.declare_function("plenty_trap_not_char", Linkage::Import, &sig)?
+        },
+        digits_i64: import(module, "plenty_digits_i64", &[types::I64], PTR_TY)?,
+        digits_u64: import(module, "plenty_digits_u64", &[types::I64], PTR_TY)?,
         println: one_arg(module, "plenty_println", PTR_TY)?,
+        eprintln: one_arg(module, "plenty_eprintln", PTR_TY)?,
+        stderr_begin: nullary(module, "plenty_stderr_begin")?,
+        stderr_end: nullary(module, "plenty_stderr_end")?,
+        exit: one_arg(module, "plenty_exit", types::I32)?,
+        alloc: {
+            let mut sig = module.make_signature();
+            sig.call_conv = CallConv::SystemV;
+            sig.params.push(AbiParam::new(types::I64));
+            sig.returns.push(AbiParam::new(PTR_TY));
+            module.declare_function("plenty_alloc", Linkage::Import, &sig)?
+        },
+        print_raw: one_arg(module, "plenty_print_raw", PTR_TY)?,
     })
 }
 
 /// The CLIF type used for every Plenty `Str` value. Strings are passed
-/// around as nul-terminated C-style pointers (see `runtime/plenty_runtime.c`),
-/// and AOT mode only targets the host architecture today — every host
-/// we care about is 64-bit, so the pointer width is `types::I64`. If we
-/// ever cross-compile to a 32-bit target, this needs to come from
-/// `module.target_config().pointer_type()` instead.
+/// around as pointers to a length-prefixed `plenty_str` (see
+/// `runtime/plenty_runtime.c`), and AOT mode only targets the host
+/// architecture today — every host we care about is 64-bit, so the
+/// pointer width is `types::I64`. If we ever cross-compile to a 32-bit
+/// target, this needs to come from `module.target_config().pointer_type()`
+/// instead.
 const PTR_TY: types::Type = types::I64;
 
+/// Byte offset of the `i`-th captured value in a function reference's
+/// environment. Word 0 is the code address; each capture takes one
+/// 8-byte word whatever its width.
+fn env_offset(i: usize) -> i32 {
+    8 * (i as i32 + 1)
+}
+
+/// Byte offset of the `i`-th field of a struct value, which is a block of
+/// one 8-byte word per field.
+fn field_offset(i: usize) -> i32 {
+    8 * i as i32
+}
+
 /// Walk `ops` recursively and collect every `StrId` referenced by a
 /// `PushStr` or `Pattern::Str`. For each unique `StrId`, declare a
-/// read-only data symbol in `module` whose contents are the literal's
-/// UTF-8 bytes plus a trailing nul (so C string helpers can scan with
-/// `strlen` / `strcmp`).
+/// read-only data symbol in `module` laid out as the runtime's
+/// `plenty_str`: the byte length as a little-endian `i64`, the literal's
+/// UTF-8 bytes, then a trailing nul. The length is what the runtime
+/// reads, so a literal with an interior `\0` keeps every byte.
 fn declare_str_data(
     ops: &[Op],
     heap: &Heap,
@@ -420,28 +741,34 @@ fn declare_str_data(
         let name = format!("plenty_str_{i}");
         let data_id = module.declare_data(&name, Linkage::Local, false, false)?;
         let s = heap.str(id);
-        let mut bytes: Vec<u8> = Vec::with_capacity(s.len() + 1);
-        bytes.extend_from_slice(s.as_bytes());
-        bytes.push(0);
-        let mut desc = DataDescription::new();
-        desc.define(bytes.into_boxed_slice());
-        module.define_data(data_id, &desc)?;
+        module.define_data(data_id, &str_data(s))?;
         out.insert(id, data_id);
     }
     Ok(out)
 }
 
-/// One read-only data symbol holding a single nul byte — i.e. the C
-/// representation of `""`. [`Lowerer::lower_readline`] substitutes its
-/// address for the `NULL` returned by `plenty_readline` on EOF, so the
-/// value pushed onto the compile-time stack as `Ty::Str` is always a
-/// valid C string. Always declared (one byte of `.rodata`) so the
-/// Lowerer doesn't need to know whether the module uses `:readline`.
+/// The `plenty_str` image of `s`: an 8-aligned little-endian `i64`
+/// length, the bytes, and a trailing nul.
+fn str_data(s: &str) -> DataDescription {
+    let mut bytes: Vec<u8> = Vec::with_capacity(8 + s.len() + 1);
+    bytes.extend_from_slice(&(s.len() as i64).to_le_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+    bytes.push(0);
+    let mut desc = DataDescription::new();
+    desc.define(bytes.into_boxed_slice());
+    desc.set_align(8);
+    desc
+}
+
+/// One read-only data symbol holding the `plenty_str` representation of
+/// `""`. [`Lowerer::lower_readline`] substitutes its address for the
+/// `NULL` returned by `plenty_readline` on EOF, so the value pushed onto
+/// the compile-time stack as `Ty::Str` is always a valid string. Always
+/// declared (nine bytes of `.rodata`) so the Lowerer doesn't need to
+/// know whether the module uses `:readline`.
 fn declare_eof_empty_str(module: &mut ObjectModule) -> Result<DataId> {
     let id = module.declare_data("plenty_readline_eof_empty", Linkage::Local, false, false)?;
-    let mut desc = DataDescription::new();
-    desc.define(vec![0u8].into_boxed_slice());
-    module.define_data(id, &desc)?;
+    module.define_data(id, &str_data(""))?;
     Ok(id)
 }
 
@@ -454,15 +781,25 @@ fn collect_str_ids(ops: &[Op], out: &mut Vec<StrId>, seen: &mut HashMap<StrId, (
             Op::PushStr(id) if seen.insert(*id, ()).is_none() => out.push(*id),
             Op::Match(arms) => {
                 for arm in arms.iter() {
-                    if let Pattern::Str(id) = arm.pattern {
-                        if seen.insert(id, ()).is_none() {
-                            out.push(id);
-                        }
+                    for pattern in &arm.patterns {
+                        collect_pattern_str_ids(pattern, out, seen);
+                    }
+                    for block in arm.blocks() {
+                        collect_str_ids(block, out, seen);
                     }
-                    collect_str_ids(&arm.body, out, seen);
                 }
             }
             Op::DefineFn(_, f) => collect_str_ids(&f.body, out, seen),
+            Op::Import(_, body) => collect_str_ids(body, out, seen),
+            Op::Format(pieces) => {
+                for piece in pieces.iter() {
+                    if let FormatPiece::Text(id) = piece {
+                        if seen.insert(*id, ()).is_none() {
+                            out.push(*id);
+                        }
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -471,10 +808,74 @@ fn collect_str_ids(ops: &[Op], out: &mut Vec<StrId>, seen: &mut HashMap<StrId, (
 /// Declaration for a single user-defined Plenty function. Pass 1
 /// allocates one of these per `DefineFn` reachable from the source set;
 /// Pass 2 reads it back when emitting bodies and resolving calls.
+#[derive(Clone)]
 struct UserFn {
     id: FuncId,
     sig: Rc<FnSig>,
     body: Rc<[Op]>,
+    /// `Some(k)` for a quotation body, which is only ever reached through
+    /// a function reference: it takes the reference's environment as a
+    /// hidden first parameter and reads its first `k` inputs from there
+    /// (see [`Lowerer::lower_push_closure`]). `None` for a named function.
+    captured: Option<usize>,
+    /// For an instance of a `..R` function: the types of the values `..R`
+    /// stands for at its calls — those beneath the arguments that a
+    /// function-typed argument consumes. They are passed in ahead of the
+    /// inputs, the body starts with them on its stack, and the instance
+    /// returns its whole final stack. Empty otherwise.
+    below: Vec<Ty>,
+}
+
+/// Generic functions and their instances. A generic function has no code
+/// of its own: each call is lowered against an *instance*, a copy of the
+/// body declared for the concrete types on the stack at that call (§8,
+/// Generic signatures). Instances are keyed by the function's name and
+/// those types, so calls that agree on them share one.
+#[derive(Default)]
+struct Instances {
+    /// Signature of every generic function, for [`op::check_call`] to
+    /// instantiate.
+    sigs: HashMap<String, Rc<FnSig>>,
+    bodies: HashMap<String, Rc<[Op]>>,
+    declared: HashMap<String, UserFn>,
+    /// Instances declared but not yet emitted.
+    pending: Vec<String>,
+}
+
+/// The string literals of one pattern, for [`collect_str_ids`].
+fn collect_pattern_str_ids(pattern: &Pattern, out: &mut Vec<StrId>, seen: &mut HashMap<StrId, ()>) {
+    match pattern {
+        Pattern::Str(id) if seen.insert(*id, ()).is_none() => out.push(*id),
+        Pattern::Or(alternatives) => {
+            for alternative in alternatives.iter() {
+                collect_pattern_str_ids(alternative, out, seen);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Every function named by a `PushFn` or `PushClosure` in `ops`, with how
+/// many of its inputs the reference captures (0 for `PushFn`).
+fn collect_fn_refs(ops: &[Op], out: &mut HashMap<String, usize>) {
+    for op in ops {
+        match op {
+            Op::PushFn(name, _) => {
+                out.insert(name.clone(), 0);
+            }
+            Op::PushClosure(name, _, slots) => {
+                out.insert(name.clone(), slots.len());
+            }
+            Op::DefineFn(_, f) => collect_fn_refs(&f.body, out),
+            Op::Import(_, body) => collect_fn_refs(body, out),
+            Op::Match(arms) => {
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    collect_fn_refs(block, out);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 /// Build a Cranelift `Signature` from a Plenty `FnSig`. User functions
@@ -485,14 +886,47 @@ struct UserFn {
 /// `return_call`), so `plenty_main` — which has SystemV convention,
 /// because it is called from C — invokes user functions with a regular
 /// `call` instruction.
-fn user_fn_signature(module: &ObjectModule, sig: &FnSig) -> Signature {
+///
+/// A quotation body (`captured` is `Some`) instead takes its environment
+/// pointer followed by the inputs it does not capture: exactly the
+/// signature `:call` builds from a function-reference type.
+fn user_fn_signature(module: &ObjectModule, sig: &FnSig, captured: Option<usize>) -> Signature {
+    match captured {
+        Some(k) => ref_call_signature(module, &sig.closure_ty(k)),
+        None => {
+            let mut cl = module.make_signature();
+            cl.call_conv = CallConv::Tail;
+            for (_, ty) in &sig.inputs {
+                cl.params.push(AbiParam::new(clif_type(ty)));
+            }
+            for ty in &sig.outputs {
+                cl.returns.push(AbiParam::new(clif_type(ty)));
+            }
+            cl
+        }
+    }
+}
+
+/// The signature of an instance whose body starts with values of the
+/// types in `below` on its stack (see [`UserFn::below`]).
+fn instance_signature(module: &ObjectModule, below: &[Ty], sig: &FnSig) -> Signature {
+    let mut cl = user_fn_signature(module, sig, None);
+    let params = below.iter().map(|ty| AbiParam::new(clif_type(ty)));
+    cl.params.splice(0..0, params);
+    cl
+}
+
+/// The signature `:call` uses for a reference of type `ft`: the
+/// environment pointer, then `ft`'s inputs.
+fn ref_call_signature(module: &ObjectModule, ft: &FnTy) -> Signature {
     let mut cl = module.make_signature();
     cl.call_conv = CallConv::Tail;
-    for (_, ty) in &sig.inputs {
-        cl.params.push(AbiParam::new(clif_type(*ty)));
+    cl.params.push(AbiParam::new(PTR_TY));
+    for ty in &ft.inputs {
+        cl.params.push(AbiParam::new(clif_type(ty)));
     }
-    for ty in &sig.outputs {
-        cl.returns.push(AbiParam::new(clif_type(*ty)));
+    for ty in &ft.outputs {
+        cl.returns.push(AbiParam::new(clif_type(ty)));
     }
     cl
 }
@@ -502,36 +936,50 @@ fn user_fn_signature(module: &ObjectModule, sig: &FnSig) -> Signature {
 /// match arm. Each definition becomes a Cranelift symbol with linkage
 /// `Local` (visible only within this object). Redefinition is rejected
 /// here, before any codegen, per the AOT closed-world rule (§11.1).
+///
+/// A generic definition is not declared; it goes into `generic`, to be
+/// instantiated per call.
 fn collect_user_fns(
     ops: &[Op],
+    fn_refs: &HashMap<String, usize>,
     module: &mut ObjectModule,
     out: &mut HashMap<String, UserFn>,
+    generic: &mut Instances,
 ) -> Result<()> {
     for op in ops {
         match op {
             Op::DefineFn(name, f) => {
-                if out.contains_key(name) {
+                if out.contains_key(name) || generic.sigs.contains_key(name) {
                     return Err(format!(
                         "AOT compilation does not allow redefining `{name}` \
                          (the REPL allows it; compiled programs do not)"
                     )
                     .into());
                 }
-                let cl_sig = user_fn_signature(module, &f.sig);
-                let id = module.declare_function(name, Linkage::Local, &cl_sig)?;
-                out.insert(
-                    name.clone(),
-                    UserFn {
-                        id,
-                        sig: Rc::clone(&f.sig),
-                        body: Rc::clone(&f.body),
-                    },
-                );
-                collect_user_fns(&f.body, module, out)?;
+                if f.sig.is_generic() {
+                    generic.sigs.insert(name.clone(), Rc::clone(&f.sig));
+                    generic.bodies.insert(name.clone(), Rc::clone(&f.body));
+                } else {
+                    let captured = fn_refs.get(name).copied();
+                    let cl_sig = user_fn_signature(module, &f.sig, captured);
+                    let id = module.declare_function(name, Linkage::Local, &cl_sig)?;
+                    out.insert(
+                        name.clone(),
+                        UserFn {
+                            id,
+                            sig: Rc::clone(&f.sig),
+                            body: Rc::clone(&f.body),
+                            captured,
+                            below: Vec::new(),
+                        },
+                    );
+                }
+                collect_user_fns(&f.body, fn_refs, module, out, generic)?;
             }
+            Op::Import(_, body) => collect_user_fns(body, fn_refs, module, out, generic)?,
             Op::Match(arms) => {
-                for arm in arms.iter() {
-                    collect_user_fns(&arm.body, module, out)?;
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    collect_user_fns(block, fn_refs, module, out, generic)?;
                 }
             }
             _ => {}
@@ -545,10 +993,16 @@ fn collect_user_fns(
 /// most programs; the AOT-specific check exists because the checker
 /// also accepts calls into the VM's pre-existing dictionary, which is
 /// not available in compiled code (§11.1, closed-world).
-fn check_calls_resolve(ops: &[Op], fns: &HashMap<String, UserFn>) -> Result<()> {
+fn check_calls_resolve(
+    ops: &[Op],
+    fns: &HashMap<String, UserFn>,
+    generic: &Instances,
+) -> Result<()> {
     for op in ops {
         match op {
-            Op::Call(name) | Op::TailCall(name) if !fns.contains_key(name) => {
+            Op::Call(name) | Op::TailCall(name)
+                if !fns.contains_key(name) && !generic.sigs.contains_key(name) =>
+            {
                 return Err(format!(
                     "AOT compilation cannot resolve call to `{name}` \
                      (compiled programs are closed-world; every called \
@@ -556,10 +1010,19 @@ fn check_calls_resolve(ops: &[Op], fns: &HashMap<String, UserFn>) -> Result<()>
                 )
                 .into());
             }
-            Op::DefineFn(_, f) => check_calls_resolve(&f.body, fns)?,
+            Op::PushFn(name, _) | Op::PushClosure(name, _, _) if !fns.contains_key(name) => {
+                return Err(format!(
+                    "AOT compilation cannot resolve call to `{name}` \
+                     (compiled programs are closed-world; every called \
+                     function must be defined in the same source)"
+                )
+                .into());
+            }
+            Op::DefineFn(_, f) => check_calls_resolve(&f.body, fns, generic)?,
+            Op::Import(_, body) => check_calls_resolve(body, fns, generic)?,
             Op::Match(arms) => {
-                for arm in arms.iter() {
-                    check_calls_resolve(&arm.body, fns)?;
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    check_calls_resolve(block, fns, generic)?;
                 }
             }
             _ => {}
@@ -574,16 +1037,26 @@ fn check_calls_resolve(ops: &[Op], fns: &HashMap<String, UserFn>) -> Result<()>
 /// through without a tail call, emit a `return` carrying the values
 /// remaining on the compile-time stack (the type checker has already
 /// ensured those values match the declared outputs).
+///
+/// `name` is either a named function's or a generic instance's key.
 fn emit_user_function(
     name: &str,
     fns: &HashMap<String, UserFn>,
+    instances: &mut Instances,
     str_data: &HashMap<StrId, DataId>,
     eof_empty_str: DataId,
     runtime: &Runtime,
     module: &mut ObjectModule,
 ) -> Result<()> {
-    let decl = &fns[name];
-    let cl_sig = user_fn_signature(module, &decl.sig);
+    let decl = match fns.get(name) {
+        Some(decl) => decl.clone(),
+        None => instances.declared[name].clone(),
+    };
+    let cl_sig = if decl.below.is_empty() {
+        user_fn_signature(module, &decl.sig, decl.captured)
+    } else {
+        instance_signature(module, &decl.below, &decl.sig)
+    };
 
     let mut ctx = Context::new();
     ctx.func = Function::with_name_signature(UserFuncName::user(0, decl.id.as_u32()), cl_sig);
@@ -600,12 +1073,34 @@ fn emit_user_function(
         // block-param value makes c.3 cleaner — match arms become new
         // blocks, and a `Variable` is visible across blocks where a
         // raw block-param value would have to be threaded explicitly.
+        //
+        // A quotation body's first block param is its environment; its
+        // captured inputs are loaded from there once, at entry, and the
+        // remaining inputs follow the environment in the block params.
         let mut locals: Vec<(Variable, Ty)> = Vec::with_capacity(decl.sig.inputs.len());
+        let (captured, first_param) = match decl.captured {
+            Some(k) => (k, 1),
+            None => (0, decl.below.len()),
+        };
+        // An instance's values from beneath its caller's arguments start
+        // out on its stack.
+        let params = bcx.block_params(entry);
+        let below: Vec<StackEntry> = params
+            .iter()
+            .zip(&decl.below)
+            .map(|(&v, ty)| (v, ty.clone()))
+            .collect();
         for (i, (_, ty)) in decl.sig.inputs.iter().enumerate() {
-            let var = bcx.declare_var(clif_type(*ty));
-            let param = bcx.block_params(entry)[i];
-            bcx.def_var(var, param);
-            locals.push((var, *ty));
+            let var = bcx.declare_var(clif_type(ty));
+            let value = if i < captured {
+                let env = bcx.block_params(entry)[0];
+                bcx.ins()
+                    .load(clif_type(ty), MemFlags::trusted(), env, env_offset(i))
+            } else {
+                bcx.block_params(entry)[first_param + i - captured]
+            };
+            bcx.def_var(var, value);
+            locals.push((var, ty.clone()));
         }
 
         let mut lower = Lowerer {
@@ -613,10 +1108,11 @@ fn emit_user_function(
             module,
             runtime,
             user_fns: fns,
+            instances,
             str_data,
             eof_empty_str,
-            locals: &locals,
-            stack: Vec::new(),
+            locals,
+            stack: below,
             terminated: false,
         };
         for op in decl.body.iter() {
@@ -650,6 +1146,7 @@ fn emit_user_function(
 fn emit_main(
     ops: &[Op],
     fns: &HashMap<String, UserFn>,
+    instances: &mut Instances,
     str_data: &HashMap<StrId, DataId>,
     eof_empty_str: DataId,
     runtime: &Runtime,
@@ -680,9 +1177,10 @@ fn emit_main(
             module,
             runtime,
             user_fns: fns,
+            instances,
             str_data,
             eof_empty_str,
-            locals: &[],
+            locals: Vec::new(),
             stack: Vec::new(),
             terminated: false,
         };
@@ -704,15 +1202,24 @@ fn emit_main(
 /// Cranelift treats both with the same machine type, the individual
 /// instruction (`sdiv` vs `udiv`, `icmp slt` vs `icmp ult`) picks the
 /// interpretation. `Str` is a host pointer (`PTR_TY`), the address of
-/// a nul-terminated byte sequence in either the module's data section
-/// (literals) or the runtime heap (results of `plenty_concat`).
-fn clif_type(ty: Ty) -> types::Type {
+/// a length-prefixed byte sequence in either the module's data section
+/// (literals) or the runtime heap (results of `plenty_concat`). `Bytes`
+/// has the same layout; only the checker tells the two apart.
+fn clif_type(ty: &Ty) -> types::Type {
     match ty {
         Ty::I8 | Ty::U8 | Ty::Bool => types::I8,
         Ty::I16 | Ty::U16 => types::I16,
-        Ty::I32 | Ty::U32 => types::I32,
+        // A char is its scalar value.
+        Ty::I32 | Ty::U32 | Ty::Char => types::I32,
         Ty::I64 | Ty::U64 => types::I64,
-        Ty::Str => PTR_TY,
+        // A string or byte string is the address of its bytes; a file
+        // handle, of the runtime's `plenty_file`; a function
+        // reference is the address of the function's code; a struct value,
+        // the address of its fields.
+        Ty::Str | Ty::Bytes | Ty::File | Ty::Fn(_) | Ty::Struct(_) => PTR_TY,
+        Ty::Var(_) | Ty::Row(_) | Ty::Hole(_) => {
+            unreachable!("generic functions are lowered per instance")
+        }
     }
 }
 
@@ -729,22 +1236,38 @@ fn int_value_bits(value: Value) -> i64 {
         Value::U16(n) => i64::from(n),
         Value::U32(n) => i64::from(n),
         Value::U64(n) => n as i64,
-        Value::Str(_) | Value::Bool(_) => panic!("non-integer literal in PushInt"),
+        Value::Str(_)
+        | Value::Bytes(_)
+        | Value::File(_)
+        | Value::Bool(_)
+        | Value::Char(_)
+        | Value::Fn(_)
+        | Value::Closure(_)
+        | Value::Record(_) => panic!("non-integer literal in PushInt"),
     }
 }
 
-/// Width of an integer type in bits. Used to drive cast lowering.
-fn width_bits(ty: Ty) -> u8 {
+/// Width of an integer type in bits. Used to drive cast lowering, where a
+/// `Char` counts as the `u32` of its scalar value.
+fn width_bits(ty: &Ty) -> u8 {
     match ty {
         Ty::I8 | Ty::U8 => 8,
         Ty::I16 | Ty::U16 => 16,
-        Ty::I32 | Ty::U32 => 32,
+        Ty::I32 | Ty::U32 | Ty::Char => 32,
         Ty::I64 | Ty::U64 => 64,
-        Ty::Bool | Ty::Str => panic!("non-integer in width_bits"),
+        Ty::Bool
+        | Ty::Str
+        | Ty::Bytes
+        | Ty::File
+        | Ty::Fn(_)
+        | Ty::Struct(_)
+        | Ty::Var(_)
+        | Ty::Row(_)
+        | Ty::Hole(_) => panic!("non-integer in width_bits"),
     }
 }
 
-fn is_signed(ty: Ty) -> bool {
+fn is_signed(ty: &Ty) -> bool {
     matches!(ty, Ty::I8 | Ty::I16 | Ty::I32 | Ty::I64)
 }
 
@@ -761,6 +1284,33 @@ enum ArithKind {
     Mul,
 }
 
+/// What a division-family op pushes. All four share the divisor-zero and
+/// signed-minimum-by-`-1` checks.
+#[derive(Clone, Copy)]
+enum DivKind {
+    /// `/` — the truncated quotient.
+    Quot,
+    /// `rem` — the remainder, with the dividend's sign.
+    Rem,
+    /// `mod` — the Euclidean remainder, never negative.
+    Euclid,
+    /// `divmod` — the quotient, then the remainder.
+    QuotRem,
+}
+
+/// Which two-operand bitwise CLIF instruction to emit. None of them can
+/// trap; the shifts pick arithmetic or logical by the `Ty` tag.
+#[derive(Clone, Copy)]
+enum BitKind {
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    Rotl,
+    Rotr,
+}
+
 /// Which shared trap block to branch into on a failed check. The two
 /// kinds map one-to-one to the two runtime helpers and the two
 /// possible interpreter error messages.
@@ -768,6 +1318,7 @@ enum ArithKind {
 enum TrapKind {
     Overflow,
     DivZero,
+    NegativeExponent,
 }
 
 struct Lowerer<'a, 'b> {
@@ -778,21 +1329,25 @@ struct Lowerer<'a, 'b> {
     /// Populated by Pass 1 before any body is emitted, so forward
     /// references and mutual recursion resolve cleanly.
     user_fns: &'a HashMap<String, UserFn>,
+    /// Generic functions, and the instances calls to them have declared
+    /// so far.
+    instances: &'a mut Instances,
     /// Read-only data symbol per source string literal. `Op::PushStr`
     /// emits a `global_value` against the matching entry; pattern
     /// compares in `Op::Match` use the same map for the `Pattern::Str`
     /// case. Populated once per module by `declare_str_data`.
     str_data: &'a HashMap<StrId, DataId>,
-    /// Read-only data symbol holding a single `\0` byte — i.e. `""`.
+    /// Read-only data symbol holding the zero-length string `""`.
     /// `Op::ReadLine` substitutes its address for `NULL` on EOF so
     /// the value pushed onto the compile-time stack as `Ty::Str` is
-    /// always a valid C string. Declared once per module by
+    /// always a valid string. Declared once per module by
     /// [`declare_eof_empty_str`].
     eof_empty_str: DataId,
-    /// The active function's input variables, indexed by the local
-    /// slot `Op::LoadLocal` was emitted with. Empty when lowering
+    /// The active function's input variables, then those of the match
+    /// arms being lowered that bind values, indexed by the local slot
+    /// `Op::LoadLocal` was emitted with. Empty when lowering
     /// `plenty_main` (top-level has no locals).
-    locals: &'a [(Variable, Ty)],
+    locals: Vec<(Variable, Ty)>,
     stack: Vec<StackEntry>,
     /// Set after a `TailCall` lowers to `return_call`, which is a
     /// block terminator. Once set, the outer loop in
@@ -804,19 +1359,58 @@ impl Lowerer<'_, '_> {
     fn lower(&mut self, op: &Op) -> Result<()> {
         match op {
             Op::PushInt(value) => {
-                let ty = Ty::from(*value);
-                let v = self.bcx.ins().iconst(clif_type(ty), int_value_bits(*value));
+                let ty = Ty::of_scalar(*value).expect("PushInt carries an integer");
+                let v = self
+                    .bcx
+                    .ins()
+                    .iconst(clif_type(&ty), int_value_bits(*value));
                 self.stack.push((v, ty));
             }
             Op::PushBool(b) => {
                 let v = self.bcx.ins().iconst(types::I8, if *b { 1 } else { 0 });
                 self.stack.push((v, Ty::Bool));
             }
+            Op::PushChar(c) => {
+                let v = self.bcx.ins().iconst(types::I32, i64::from(u32::from(*c)));
+                self.stack.push((v, Ty::Char));
+            }
             Op::PushStr(id) => self.lower_push_str(*id)?,
             Op::Add => self.lower_add()?,
             Op::Sub => self.lower_checked_arith(ArithKind::Sub)?,
             Op::Mul => self.lower_checked_arith(ArithKind::Mul)?,
-            Op::Div => self.lower_div()?,
+            Op::Div => self.lower_div(DivKind::Quot)?,
+            Op::WrappingAdd => {
+                let (a, b, ty) = self.pop_int_pair()?;
+                let v = self.bcx.ins().iadd(a, b);
+                self.stack.push((v, ty));
+            }
+            Op::WrappingMul => {
+                let (a, b, ty) = self.pop_int_pair()?;
+                let v = self.bcx.ins().imul(a, b);
+                self.stack.push((v, ty));
+            }
+            Op::SaturatingAdd => self.lower_saturating(ArithKind::Add)?,
+            Op::SaturatingSub => self.lower_saturating(ArithKind::Sub)?,
+            Op::Rem => self.lower_div(DivKind::Rem)?,
+            Op::Mod => self.lower_div(DivKind::Euclid)?,
+            Op::DivMod => self.lower_div(DivKind::QuotRem)?,
+            Op::Abs => self.lower_abs_neg(false)?,
+            Op::Neg => self.lower_abs_neg(true)?,
+            Op::Min => self.lower_min_max(false)?,
+            Op::Max => self.lower_min_max(true)?,
+            Op::Pow => self.lower_pow()?,
+            Op::BitAnd => self.lower_bitwise(BitKind::And)?,
+            Op::BitOr => self.lower_bitwise(BitKind::Or)?,
+            Op::BitXor => self.lower_bitwise(BitKind::Xor)?,
+            Op::BitNot => {
+                let (v, ty) = self.stack.pop().ok_or("AOT: stack underflow on `bnot`")?;
+                let not = self.bcx.ins().bnot(v);
+                self.stack.push((not, ty));
+            }
+            Op::Shl => self.lower_bitwise(BitKind::Shl)?,
+            Op::Shr => self.lower_bitwise(BitKind::Shr)?,
+            Op::Rotl => self.lower_bitwise(BitKind::Rotl)?,
+            Op::Rotr => self.lower_bitwise(BitKind::Rotr)?,
             Op::Eq => self.lower_eq()?,
             Op::Lt => self.int_cmp(IntCC::SignedLessThan, IntCC::UnsignedLessThan)?,
             Op::Gt => self.int_cmp(IntCC::SignedGreaterThan, IntCC::UnsignedGreaterThan)?,
@@ -838,7 +1432,11 @@ impl Lowerer<'_, '_> {
                 self.stack.pop().ok_or("AOT: stack underflow on `drop`")?;
             }
             Op::Dup => {
-                let value = *self.stack.last().ok_or("AOT: stack underflow on `dup`")?;
+                let value = self
+                    .stack
+                    .last()
+                    .ok_or("AOT: stack underflow on `dup`")?
+                    .clone();
                 self.stack.push(value);
             }
             Op::Swap => {
@@ -852,14 +1450,18 @@ impl Lowerer<'_, '_> {
                 let len = self.stack.len();
                 self.stack.swap(len - 1, len - 2);
             }
+            Op::Cast(Ty::Char) => self.lower_as_char()?,
             Op::Cast(target) => {
                 let (v, src) = self.stack.pop().ok_or("AOT: stack underflow on cast")?;
-                let cast = self.cast(v, src, *target);
-                self.stack.push((cast, *target));
+                let cast = self.cast(v, &src, target);
+                self.stack.push((cast, target.clone()));
             }
             Op::Display => self.lower_display()?,
             Op::Clear => self.stack.clear(),
             Op::LoadLocal(i) => self.lower_load_local(*i)?,
+            Op::PushFn(name, _) => self.lower_push_fn(name)?,
+            Op::PushClosure(name, _, slots) => self.lower_push_closure(name, slots)?,
+            Op::CallIndirect => self.lower_call_indirect()?,
             Op::Call(name) => self.lower_call(name)?,
             Op::TailCall(name) => self.lower_tail_call(name)?,
             // `DefineFn` is hoisted into a top-level Cranelift function by
@@ -867,48 +1469,343 @@ impl Lowerer<'_, '_> {
             // body is already being emitted elsewhere and the definition
             // itself has no runtime effect.
             Op::DefineFn(_, _) => {}
+            // An import holds only definitions (and further imports), all
+            // of which Pass 1 has already collected.
+            Op::Import(_, _) => {}
             Op::Match(arms) => self.lower_match(arms)?,
-            Op::ReadLine => self.lower_readline()?,
+            Op::ReadLine => self.lower_maybe_str(self.runtime.readline, &[], Ty::Str),
+            Op::ReadLineBytes => self.lower_maybe_str(self.runtime.readline_bytes, &[], Ty::Bytes),
+            Op::ReadBytes => {
+                let inst = self.call_runtime(self.runtime.read_all, &[]);
+                let v = self.bcx.inst_results(inst)[0];
+                self.stack.push((v, Ty::Bytes));
+            }
+            Op::WriteBytes => {
+                let (v, _) = self.pop_typed(Ty::Bytes)?;
+                self.call_runtime(self.runtime.write_bytes, &[v]);
+            }
+            // A `Str` is already its UTF-8 bytes, in the layout `Bytes`
+            // shares.
+            Op::Utf8Encode => {
+                let (v, _) = self.pop_typed(Ty::Str)?;
+                self.stack.push((v, Ty::Bytes));
+            }
+            Op::Utf8Decode => self.lower_utf8_decode()?,
+            Op::Utf8DecodeLossy => {
+                let v = self.lower_str_call(self.runtime.utf8_decode_lossy, 1)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::BytesLen => {
+                let v = self.lower_str_call(self.runtime.bytes_len, 1)?;
+                self.stack.push((v, Ty::I64));
+            }
+            Op::BytesSlice => {
+                let v = self.lower_str_call(self.runtime.bytes_slice, 3)?;
+                self.stack.push((v, Ty::Bytes));
+            }
+            Op::ReadFile => {
+                let (path, _) = self.pop_typed(Ty::Str)?;
+                self.lower_maybe_str(self.runtime.read_file, &[path], Ty::Str);
+            }
+            Op::WriteFile => self.lower_write_file(false)?,
+            Op::AppendFile => self.lower_write_file(true)?,
+            Op::FileExists => {
+                let (path, _) = self.pop_typed(Ty::Str)?;
+                let inst = self.call_runtime(self.runtime.file_exists, &[path]);
+                let v = self.bcx.inst_results(inst)[0];
+                self.stack.push((v, Ty::Bool));
+            }
+            // A failed open is a NULL handle, which the other file helpers
+            // accept, so the handle needs no substitute.
+            Op::OpenFile => {
+                let (path, _) = self.pop_typed(Ty::Str)?;
+                let inst = self.call_runtime(self.runtime.open_file, &[path]);
+                let file = self.bcx.inst_results(inst)[0];
+                let zero = self.bcx.ins().iconst(PTR_TY, 0);
+                let opened = self.bcx.ins().icmp(IntCC::NotEqual, file, zero);
+                self.stack.push((file, Ty::File));
+                self.stack.push((opened, Ty::Bool));
+            }
+            Op::FileReadLine => {
+                let (file, _) = self.pop_typed(Ty::File)?;
+                self.lower_maybe_str(self.runtime.file_readline, &[file], Ty::Str);
+            }
+            Op::FileReadLineBytes => {
+                let (file, _) = self.pop_typed(Ty::File)?;
+                self.lower_maybe_str(self.runtime.file_readline_bytes, &[file], Ty::Bytes);
+            }
+            Op::CloseFile => {
+                let (file, _) = self.pop_typed(Ty::File)?;
+                self.call_runtime(self.runtime.close_file, &[file]);
+            }
+            Op::ArgCount => {
+                let inst = self.call_runtime(self.runtime.argc, &[]);
+                let n = self.bcx.inst_results(inst)[0];
+                self.stack.push((n, Ty::I64));
+            }
+            Op::Arg => {
+                let (i, _) = self.pop_typed(Ty::I64)?;
+                self.lower_maybe_str(self.runtime.arg, &[i], Ty::Str);
+            }
+            Op::Env => {
+                let (name, _) = self.pop_typed(Ty::Str)?;
+                self.lower_maybe_str(self.runtime.env, &[name], Ty::Str);
+            }
             Op::Contains => self.lower_contains()?,
+            Op::StrLen => {
+                let v = self.lower_str_call(self.runtime.str_len, 1)?;
+                self.stack.push((v, Ty::I64));
+            }
+            Op::Slice => {
+                let v = self.lower_str_call(self.runtime.str_slice, 3)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::Split => self.lower_split()?,
+            Op::Parse(target) => self.lower_parse(target)?,
+            Op::ToStr => self.lower_to_str()?,
+            Op::Format(pieces) => self.lower_format(pieces)?,
+            Op::ToDigits => {
+                let (v, ty) = self
+                    .stack
+                    .pop()
+                    .ok_or("AOT: stack underflow on :to-digits")?;
+                let (wide_ty, digits) = if is_signed(&ty) {
+                    (Ty::I64, self.runtime.digits_i64)
+                } else {
+                    (Ty::U64, self.runtime.digits_u64)
+                };
+                let wide = self.cast(v, &ty, &wide_ty);
+                let digits = self.module.declare_func_in_func(digits, self.bcx.func);
+                let inst = self.bcx.ins().call(digits, &[wide]);
+                let text = self.bcx.inst_results(inst)[0];
+                self.stack.push((text, Ty::Str));
+            }
+            Op::Trim => {
+                let v = self.lower_str_call(self.runtime.str_trim, 1)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::StartsWith => {
+                let v = self.lower_str_call(self.runtime.str_starts_with, 2)?;
+                self.stack.push((v, Ty::Bool));
+            }
+            Op::EndsWith => {
+                let v = self.lower_str_call(self.runtime.str_ends_with, 2)?;
+                self.stack.push((v, Ty::Bool));
+            }
+            Op::Find => {
+                let index = self.lower_str_call(self.runtime.str_find, 2)?;
+                let found = self
+                    .bcx
+                    .ins()
+                    .icmp_imm(IntCC::SignedGreaterThanOrEqual, index, 0);
+                self.stack.push((index, Ty::I64));
+                self.stack.push((found, Ty::Bool));
+            }
+            Op::Replace => {
+                let v = self.lower_str_call(self.runtime.str_replace, 3)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::Upper => {
+                let v = self.lower_str_call(self.runtime.str_upper, 1)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::Lower => {
+                let v = self.lower_str_call(self.runtime.str_lower, 1)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::Repeat => {
+                let v = self.lower_str_call(self.runtime.str_repeat, 2)?;
+                self.stack.push((v, Ty::Str));
+            }
+            Op::NextChar => {
+                let (i, _) = self.pop_typed(Ty::I64)?;
+                let (s, _) = self.pop_typed(Ty::Str)?;
+                let inst = self.call_runtime(self.runtime.next_char, &[s, i]);
+                let packed = self.bcx.inst_results(inst)[0];
+                let width = self.bcx.ins().band_imm(packed, 0xff);
+                let c = self.bcx.ins().ushr_imm(packed, 8);
+                let c = self.bcx.ins().ireduce(types::I32, c);
+                let next = self.bcx.ins().iadd(i, width);
+                let found = self.bcx.ins().icmp_imm(IntCC::NotEqual, width, 0);
+                self.stack.push((c, Ty::Char));
+                self.stack.push((next, Ty::I64));
+                self.stack.push((found, Ty::Bool));
+            }
+            Op::CharAt => {
+                let v = self.lower_str_call(self.runtime.char_at, 2)?;
+                self.stack.push((v, Ty::Char));
+            }
+            Op::FromChar => {
+                let v = self.lower_str_call(self.runtime.from_char, 1)?;
+                self.stack.push((v, Ty::Str));
+            }
             Op::PrintLn => self.lower_println()?,
             Op::Print => self.lower_print()?,
+            Op::EPrintLn => {
+                let (v, _) = self.pop_typed(Ty::Str)?;
+                self.call_runtime(self.runtime.eprintln, &[v]);
+            }
+            Op::EPrint => {
+                self.call_runtime(self.runtime.stderr_begin, &[]);
+                self.lower_print()?;
+                self.call_runtime(self.runtime.stderr_end, &[]);
+            }
+            // `plenty_exit` does not return; the code after it is dead but
+            // still well-formed.
+            Op::Exit => {
+                let (code, _) = self.pop_typed(Ty::I32)?;
+                self.call_runtime(self.runtime.exit, &[code]);
+            }
+            // Struct words were resolved against the declaration at compile
+            // time; the declaration itself emits nothing.
+            Op::DefineStruct(_) | Op::DefineConst(..) => {}
+            Op::MakeStruct(st) => self.lower_make_struct(st)?,
+            Op::GetField(st, i) => {
+                let (record, _) = self.pop_typed(Ty::Struct(Rc::clone(st)))?;
+                let v = self.load_field(record, st, *i as usize);
+                self.stack.push((v, st.fields[*i as usize].1.clone()));
+            }
+            Op::SetField(st, i) => self.lower_set_field(st, *i as usize)?,
         }
         Ok(())
     }
 
-    /// Lower `Op::ReadLine`: call `plenty_readline`, which returns a
-    /// malloc'd nul-terminated buffer or `NULL` on EOF. We turn `NULL`
+    /// Lower `Op::MakeStruct`: allocate one word per field and store the
+    /// field values, deepest first, into place.
+    fn lower_make_struct(&mut self, st: &Rc<StructTy>) -> Result<()> {
+        let n = st.fields.len();
+        let split = self
+            .stack
+            .len()
+            .checked_sub(n)
+            .ok_or("AOT: stack underflow on a struct constructor")?;
+        let values: Vec<StackEntry> = self.stack.drain(split..).collect();
+        let record = self.alloc_record(n);
+        for (i, (v, _)) in values.into_iter().enumerate() {
+            self.bcx
+                .ins()
+                .store(MemFlags::trusted(), v, record, field_offset(i));
+        }
+        self.stack.push((record, Ty::Struct(Rc::clone(st))));
+        Ok(())
+    }
+
+    /// Lower `Op::SetField`: copy the record into a fresh block, with the
+    /// popped value in place of field `i`. Struct values are immutable, so
+    /// the old record is never written to.
+    fn lower_set_field(&mut self, st: &Rc<StructTy>, i: usize) -> Result<()> {
+        let (value, _) = self.pop_typed(st.fields[i].1.clone())?;
+        let (old, ty) = self.pop_typed(Ty::Struct(Rc::clone(st)))?;
+        let record = self.alloc_record(st.fields.len());
+        for j in 0..st.fields.len() {
+            let v = if j == i {
+                value
+            } else {
+                self.load_field(old, st, j)
+            };
+            self.bcx
+                .ins()
+                .store(MemFlags::trusted(), v, record, field_offset(j));
+        }
+        self.stack.push((record, ty));
+        Ok(())
+    }
+
+    /// Allocate the block for a struct value of `n` fields.
+    fn alloc_record(&mut self, n: usize) -> cranelift_codegen::ir::Value {
+        let size = self
+            .bcx
+            .ins()
+            .iconst(types::I64, i64::from(field_offset(n)));
+        let alloc = self
+            .module
+            .declare_func_in_func(self.runtime.alloc, self.bcx.func);
+        let inst = self.bcx.ins().call(alloc, &[size]);
+        self.bcx.inst_results(inst)[0]
+    }
+
+    /// Load field `i` of the struct value at `record`.
+    fn load_field(
+        &mut self,
+        record: cranelift_codegen::ir::Value,
+        st: &StructTy,
+        i: usize,
+    ) -> cranelift_codegen::ir::Value {
+        self.bcx.ins().load(
+            clif_type(&st.fields[i].1),
+            MemFlags::trusted(),
+            record,
+            field_offset(i),
+        )
+    }
+
+    /// Lower a word whose helper returns a fresh `plenty_str` or `NULL`
+    /// for "nothing" — `Op::ReadLine` calling `plenty_readline`, which
+    /// returns `NULL` on EOF, and the file reads. We turn `NULL`
     /// into the address of `plenty_readline_eof_empty` (the `""` data
     /// symbol) so the `Ty::Str` we push is always dereferenceable; the
     /// "got a line?" Bool is `ptr != 0`. The user discriminates via
     /// `match` on the Bool — see DESIGN.md §11.8 for the surface.
-    fn lower_readline(&mut self) -> Result<()> {
-        let readline = self
-            .module
-            .declare_func_in_func(self.runtime.readline, self.bcx.func);
-        let inst = self.bcx.ins().call(readline, &[]);
+    ///
+    /// `plenty_readline` never decodes, so `:readline-bytes` is the same
+    /// call with the line pushed as `line_ty`, `Ty::Bytes`.
+    fn lower_maybe_str(
+        &mut self,
+        func: FuncId,
+        args: &[cranelift_codegen::ir::Value],
+        line_ty: Ty,
+    ) {
+        let inst = self.call_runtime(func, args);
         let ptr = self.bcx.inst_results(inst)[0];
         let zero = self.bcx.ins().iconst(PTR_TY, 0);
         // got_line = (ptr != 0). Cranelift's `icmp` over a non-Bool
         // operand still produces an `i1`-widened-to-`i8`, which is
         // Plenty's Bool ABI.
         let got_line = self.bcx.ins().icmp(IntCC::NotEqual, ptr, zero);
-        // The EOF empty-string fallback: a 1-byte `\0` data symbol
-        // emitted unconditionally per module. Substituting it for
-        // `NULL` keeps the pushed `Ty::Str` always pointing at a
-        // valid C string.
+        // The EOF empty-string fallback: a zero-length string data
+        // symbol emitted unconditionally per module. Substituting it
+        // for `NULL` keeps the pushed `Ty::Str` always pointing at a
+        // valid string.
         let eof_gv = self
             .module
             .declare_data_in_func(self.eof_empty_str, self.bcx.func);
         let eof_addr = self.bcx.ins().global_value(PTR_TY, eof_gv);
         let safe_ptr = self.bcx.ins().select(got_line, ptr, eof_addr);
-        self.stack.push((safe_ptr, Ty::Str));
+        self.stack.push((safe_ptr, line_ty));
         self.stack.push((got_line, Ty::Bool));
+    }
+
+    /// Lower `Op::WriteFile` / `Op::AppendFile`: pop `path text`, call
+    /// `plenty_write_file` with the mode, push its success flag.
+    fn lower_write_file(&mut self, append: bool) -> Result<()> {
+        let (text, _) = self.pop_typed(Ty::Str)?;
+        let (path, _) = self.pop_typed(Ty::Str)?;
+        let mode = self.bcx.ins().iconst(types::I8, i64::from(append));
+        let inst = self.call_runtime(self.runtime.write_file, &[path, text, mode]);
+        let ok = self.bcx.inst_results(inst)[0];
+        self.stack.push((ok, Ty::Bool));
+        Ok(())
+    }
+
+    /// Lower `Op::Utf8Decode`: valid bytes are already the string, so
+    /// push them retyped, or the empty string when `plenty_utf8_valid`
+    /// says no, and its answer as the Bool.
+    fn lower_utf8_decode(&mut self) -> Result<()> {
+        let (bytes, _) = self.pop_typed(Ty::Bytes)?;
+        let inst = self.call_runtime(self.runtime.utf8_valid, &[bytes]);
+        let valid = self.bcx.inst_results(inst)[0];
+        let empty_gv = self
+            .module
+            .declare_data_in_func(self.eof_empty_str, self.bcx.func);
+        let empty = self.bcx.ins().global_value(PTR_TY, empty_gv);
+        let text = self.bcx.ins().select(valid, bytes, empty);
+        self.stack.push((text, Ty::Str));
+        self.stack.push((valid, Ty::Bool));
         Ok(())
     }
 
     /// Lower `Op::Contains`: pop `haystack needle`, call
-    /// `plenty_contains` (a thin wrapper over `strstr`), push the
+    /// `plenty_contains` (a byte-substring scan), push the
     /// returned `i8` as Plenty `Bool`.
     fn lower_contains(&mut self) -> Result<()> {
         let needle = self
@@ -930,6 +1827,210 @@ impl Lowerer<'_, '_> {
         Ok(())
     }
 
+    /// Lower a string word with a single result: pop its `arity`
+    /// operands, deepest first, pass them to the runtime helper `func` and
+    /// return the helper's result for the caller to push with its type.
+    /// The checker has already fixed the operand types.
+    fn lower_str_call(
+        &mut self,
+        func: FuncId,
+        arity: usize,
+    ) -> Result<cranelift_codegen::ir::Value> {
+        let split = self
+            .stack
+            .len()
+            .checked_sub(arity)
+            .ok_or("AOT: stack underflow on a string word")?;
+        let args: Vec<_> = self
+            .stack
+            .split_off(split)
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect();
+        let callee = self.module.declare_func_in_func(func, self.bcx.func);
+        let inst = self.bcx.ins().call(callee, &args);
+        Ok(self.bcx.inst_results(inst)[0])
+    }
+
+    /// Lower `Op::Split`: `plenty_str_split` returns its three results in
+    /// a fresh block; load the two strings and the found flag out of it.
+    fn lower_split(&mut self) -> Result<()> {
+        let block = self.lower_str_call(self.runtime.str_split, 2)?;
+        let flags = MemFlags::trusted();
+        let before = self.bcx.ins().load(PTR_TY, flags, block, 0);
+        let after = self.bcx.ins().load(PTR_TY, flags, block, 8);
+        let found = self.bcx.ins().load(types::I8, flags, block, 16);
+        self.stack.push((before, Ty::Str));
+        self.stack.push((after, Ty::Str));
+        self.stack.push((found, Ty::Bool));
+        Ok(())
+    }
+
+    /// Lower `Op::Parse`: `plenty_parse_int` parses at the target's width
+    /// and signedness and returns the value, widened to 64 bits, with the
+    /// success flag in a fresh block; load both and narrow the value.
+    fn lower_parse(&mut self, target: &Ty) -> Result<()> {
+        let (text, _) = self.pop_typed(Ty::Str)?;
+        let signed = self.bcx.ins().iconst(types::I8, is_signed(target) as i64);
+        let bits = self
+            .bcx
+            .ins()
+            .iconst(types::I64, i64::from(width_bits(target)));
+        let parse = self
+            .module
+            .declare_func_in_func(self.runtime.parse_int, self.bcx.func);
+        let inst = self.bcx.ins().call(parse, &[text, signed, bits]);
+        let block = self.bcx.inst_results(inst)[0];
+        let flags = MemFlags::trusted();
+        let wide = self.bcx.ins().load(types::I64, flags, block, 0);
+        let ok = self.bcx.ins().load(types::I8, flags, block, 8);
+        let value = self.cast(wide, &Ty::I64, target);
+        self.stack.push((value, target.clone()));
+        self.stack.push((ok, Ty::Bool));
+        Ok(())
+    }
+
+    /// Lower `Op::ToStr`: render the value with the helpers `.` uses while
+    /// the runtime captures their output, then push the captured text.
+    fn lower_to_str(&mut self) -> Result<()> {
+        let (v, ty) = self.stack.pop().ok_or("AOT: stack underflow on :to-str")?;
+        let begin = self
+            .module
+            .declare_func_in_func(self.runtime.capture_begin, self.bcx.func);
+        self.bcx.ins().call(begin, &[]);
+        self.print_value(v, &ty)?;
+        let end = self
+            .module
+            .declare_func_in_func(self.runtime.capture_end, self.bcx.func);
+        let inst = self.bcx.ins().call(end, &[]);
+        let text = self.bcx.inst_results(inst)[0];
+        self.stack.push((text, Ty::Str));
+        Ok(())
+    }
+
+    /// Lower `Op::Format`: like `:to-str`, capture the print helpers'
+    /// output while the template's text and each placeholder's value are
+    /// written in order, then push the captured text.
+    fn lower_format(&mut self, pieces: &[FormatPiece]) -> Result<()> {
+        let holes = pieces
+            .iter()
+            .filter(|piece| matches!(piece, FormatPiece::Hole(_)))
+            .count();
+        let split = self
+            .stack
+            .len()
+            .checked_sub(holes)
+            .ok_or("AOT: stack underflow on :format")?;
+        let mut args = self.stack.split_off(split).into_iter();
+        self.call_runtime(self.runtime.capture_begin, &[]);
+        for piece in pieces {
+            match piece {
+                FormatPiece::Text(id) => {
+                    let text = self.str_addr(*id)?;
+                    self.call_runtime(self.runtime.write_str, &[text]);
+                }
+                FormatPiece::Hole(spec) => {
+                    let (v, ty) = args.next().expect("one value per placeholder");
+                    self.format_value(v, &ty, *spec)?;
+                }
+            }
+        }
+        let inst = self.call_runtime(self.runtime.capture_end, &[]);
+        let text = self.bcx.inst_results(inst)[0];
+        self.stack.push((text, Ty::Str));
+        Ok(())
+    }
+
+    /// Write one `:format` placeholder's value, mirroring
+    /// `Vm::format_value`.
+    fn format_value(
+        &mut self,
+        v: cranelift_codegen::ir::Value,
+        ty: &Ty,
+        spec: FormatSpec,
+    ) -> Result<()> {
+        match spec {
+            FormatSpec::Display if *ty == Ty::Str => {
+                self.call_runtime(self.runtime.write_str, &[v]);
+            }
+            FormatSpec::Display if *ty == Ty::Char => {
+                self.call_runtime(self.runtime.write_char, &[v]);
+            }
+            FormatSpec::Display if ty.is_int() => {
+                let (wide_ty, digits) = if is_signed(ty) {
+                    (Ty::I64, self.runtime.digits_i64)
+                } else {
+                    (Ty::U64, self.runtime.digits_u64)
+                };
+                let wide = self.cast(v, ty, &wide_ty);
+                let inst = self.call_runtime(digits, &[wide]);
+                let text = self.bcx.inst_results(inst)[0];
+                self.call_runtime(self.runtime.write_str, &[text]);
+            }
+            FormatSpec::Display | FormatSpec::Debug => self.print_value(v, ty)?,
+            FormatSpec::Hex => {
+                // Reinterpret as unsigned first so the widening zero-extends.
+                let unsigned = match width_bits(ty) {
+                    8 => Ty::U8,
+                    16 => Ty::U16,
+                    32 => Ty::U32,
+                    _ => Ty::U64,
+                };
+                let bits = self.cast(v, ty, &unsigned);
+                let wide = self.cast(bits, &unsigned, &Ty::U64);
+                self.call_runtime(self.runtime.print_hex, &[wide]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit a call to the runtime helper `func` and return the call
+    /// instruction, whose results the caller reads if it needs them.
+    fn call_runtime(
+        &mut self,
+        func: FuncId,
+        args: &[cranelift_codegen::ir::Value],
+    ) -> cranelift_codegen::ir::Inst {
+        let callee = self.module.declare_func_in_func(func, self.bcx.func);
+        self.bcx.ins().call(callee, args)
+    }
+
+    /// Lower `:as-char`: widen the integer to 64 bits by its signedness,
+    /// then trap unless, read as unsigned, it is at most `0x10FFFF` and
+    /// outside the surrogates `0xD800..=0xDFFF` — a negative value reads
+    /// as far too large.
+    fn lower_as_char(&mut self) -> Result<()> {
+        let (v, src) = self.stack.pop().ok_or("AOT: stack underflow on cast")?;
+        let signed = is_signed(&src);
+        let wide_ty = if signed { Ty::I64 } else { Ty::U64 };
+        let wide = self.cast(v, &src, &wide_ty);
+        let too_big = self
+            .bcx
+            .ins()
+            .icmp_imm(IntCC::UnsignedGreaterThan, wide, 0x10FFFF);
+        let offset = self.bcx.ins().iadd_imm(wide, -0xD800);
+        let surrogate = self
+            .bcx
+            .ins()
+            .icmp_imm(IntCC::UnsignedLessThan, offset, 0x800);
+        let invalid = self.bcx.ins().bor(too_big, surrogate);
+
+        let trap_block = self.bcx.create_block();
+        let after = self.bcx.create_block();
+        self.bcx.ins().brif(invalid, trap_block, &[], after, &[]);
+        self.bcx.switch_to_block(trap_block);
+        self.bcx.seal_block(trap_block);
+        let signed_flag = self.bcx.ins().iconst(types::I8, i64::from(signed));
+        self.call_runtime(self.runtime.trap_not_char, &[wide, signed_flag]);
+        self.bcx.ins().trap(TrapCode::unwrap_user(3));
+        self.bcx.switch_to_block(after);
+        self.bcx.seal_block(after);
+
+        let c = self.cast(wide, &wide_ty, &Ty::Char);
+        self.stack.push((c, Ty::Char));
+        Ok(())
+    }
+
     /// Lower `Op::PrintLn`: pop one `Ty::Str` address and forward it
     /// to `plenty_println`, which writes the bytes verbatim plus a
     /// single `\n`.
@@ -947,10 +2048,7 @@ impl Lowerer<'_, '_> {
     /// uses, but do not add brackets or a newline.
     fn lower_print(&mut self) -> Result<()> {
         let (value, ty) = self.stack.pop().ok_or("AOT: stack underflow on :print")?;
-        let printer = self.printer_for(ty);
-        let local = self.module.declare_func_in_func(printer, self.bcx.func);
-        self.bcx.ins().call(local, &[value]);
-        Ok(())
+        self.print_value(value, &ty)
     }
 
     /// Lower a signed-or-unsigned checked arithmetic op (add/sub/mul).
@@ -963,7 +2061,7 @@ impl Lowerer<'_, '_> {
     /// the runtime helper `plenty_trap_overflow`.
     fn lower_checked_arith(&mut self, kind: ArithKind) -> Result<()> {
         let (a, b, ty) = self.pop_int_pair()?;
-        let signed = is_signed(ty);
+        let signed = is_signed(&ty);
         let (result, of) = match (kind, signed) {
             (ArithKind::Add, true) => self.bcx.ins().sadd_overflow(a, b),
             (ArithKind::Add, false) => self.bcx.ins().uadd_overflow(a, b),
@@ -977,21 +2075,66 @@ impl Lowerer<'_, '_> {
         Ok(())
     }
 
-    /// Lower `Op::Div`: explicit divisor-zero check (interpreter
-    /// distinguishes `"division by zero"` from `"integer overflow"`),
-    /// then for signed types an explicit INT_MIN/-1 check (the only
-    /// non-zero divisor for which `sdiv` traps inside Cranelift —
+    /// Lower `+|` or `-|`: the same `*_overflow` instruction the checked
+    /// op uses, with a `select` of the bound it crossed in place of the
+    /// trap. Unsigned, `+` can only cross the maximum and `-` only zero.
+    /// Signed, the bound is set by the sign of `b`: adding a negative or
+    /// subtracting a positive can only go below the minimum.
+    fn lower_saturating(&mut self, kind: ArithKind) -> Result<()> {
+        let (a, b, ty) = self.pop_int_pair()?;
+        let cty = clif_type(&ty);
+        let (min, max) = ty.int_range().expect("integer type");
+        // `max` is one past the largest value; the `as` keeps the bit
+        // pattern, which is all `iconst` sees.
+        let min = self.bcx.ins().iconst(cty, min as i64);
+        let max = self.bcx.ins().iconst(cty, (max - 1) as i64);
+        let (result, of, bound) = match (kind, is_signed(&ty)) {
+            (ArithKind::Add, false) => {
+                let (r, of) = self.bcx.ins().uadd_overflow(a, b);
+                (r, of, max)
+            }
+            (ArithKind::Sub, false) => {
+                let (r, of) = self.bcx.ins().usub_overflow(a, b);
+                (r, of, min)
+            }
+            (ArithKind::Add, true) => {
+                let (r, of) = self.bcx.ins().sadd_overflow(a, b);
+                let b_is_neg = self.bcx.ins().icmp_imm(IntCC::SignedLessThan, b, 0);
+                (r, of, self.bcx.ins().select(b_is_neg, min, max))
+            }
+            (ArithKind::Sub, true) => {
+                let (r, of) = self.bcx.ins().ssub_overflow(a, b);
+                let b_is_neg = self.bcx.ins().icmp_imm(IntCC::SignedLessThan, b, 0);
+                (r, of, self.bcx.ins().select(b_is_neg, max, min))
+            }
+            (ArithKind::Mul, _) => unreachable!("there is no saturating multiply"),
+        };
+        let v = self.bcx.ins().select(of, bound, result);
+        self.stack.push((v, ty));
+        Ok(())
+    }
+
+    /// Lower `/`, `rem`, `mod` and `divmod`: explicit divisor-zero check
+    /// (interpreter distinguishes `"division by zero"` from `"integer
+    /// overflow"`), then for signed types an explicit INT_MIN/-1 check (the
+    /// only non-zero divisor for which `sdiv` traps inside Cranelift —
     /// catching it ourselves lets us emit the same `"integer overflow"`
-    /// message the interpreter does), then the bare `sdiv`/`udiv`.
-    fn lower_div(&mut self) -> Result<()> {
+    /// message the interpreter does; its `checked_rem` fails there too),
+    /// then the bare `sdiv`/`udiv` and `srem`/`urem`.
+    ///
+    /// `mod` corrects a negative `srem` result by adding `|b|` — as a
+    /// wrapping add, so a divisor of INT_MIN (whose `ineg` wraps to
+    /// itself) still lands on the right value.
+    fn lower_div(&mut self, kind: DivKind) -> Result<()> {
         let (a, b, ty) = self.pop_int_pair()?;
-        let cty = clif_type(ty);
+        let cty = clif_type(&ty);
+        let signed = is_signed(&ty);
 
         let zero = self.bcx.ins().iconst(cty, 0);
         let b_is_zero = self.bcx.ins().icmp(IntCC::Equal, b, zero);
         self.trap_if(b_is_zero, TrapKind::DivZero);
 
-        if is_signed(ty) {
+        if signed {
             // Only one signed-division overflow case exists: INT_MIN / -1.
             // (Result `-INT_MIN` is not representable at the same width.)
             let int_min = match ty {
@@ -1009,10 +2152,182 @@ impl Lowerer<'_, '_> {
             self.trap_if(overflow, TrapKind::Overflow);
         }
 
-        let v = if is_signed(ty) {
-            self.bcx.ins().sdiv(a, b)
+        if matches!(kind, DivKind::Quot | DivKind::QuotRem) {
+            let q = if signed {
+                self.bcx.ins().sdiv(a, b)
+            } else {
+                self.bcx.ins().udiv(a, b)
+            };
+            self.stack.push((q, ty.clone()));
+        }
+        if matches!(kind, DivKind::Rem | DivKind::QuotRem | DivKind::Euclid) {
+            let mut r = if signed {
+                self.bcx.ins().srem(a, b)
+            } else {
+                self.bcx.ins().urem(a, b)
+            };
+            if signed && matches!(kind, DivKind::Euclid) {
+                let neg_b = self.bcx.ins().ineg(b);
+                let b_is_neg = self.bcx.ins().icmp(IntCC::SignedLessThan, b, zero);
+                let abs_b = self.bcx.ins().select(b_is_neg, neg_b, b);
+                let adjusted = self.bcx.ins().iadd(r, abs_b);
+                let r_is_neg = self.bcx.ins().icmp(IntCC::SignedLessThan, r, zero);
+                r = self.bcx.ins().select(r_is_neg, adjusted, r);
+            }
+            self.stack.push((r, ty));
+        }
+        Ok(())
+    }
+
+    /// Lower `abs` (`negate == false`) or `neg`. Both are `0 - a` where
+    /// they change the value, checked like `-`: for a signed `a` that
+    /// traps only on INT_MIN, and `abs` keeps a non-negative `a` as is.
+    /// An unsigned `abs` is the identity; an unsigned `neg` traps unless
+    /// `a` is `0`, as `usub_overflow(0, a)` reports.
+    fn lower_abs_neg(&mut self, negate: bool) -> Result<()> {
+        let (a, ty) = self.stack.pop().ok_or("AOT: stack underflow")?;
+        let signed = is_signed(&ty);
+        if !negate && !signed {
+            self.stack.push((a, ty));
+            return Ok(());
+        }
+        let zero = self.bcx.ins().iconst(clif_type(&ty), 0);
+        let (negated, of) = if signed {
+            self.bcx.ins().ssub_overflow(zero, a)
+        } else {
+            self.bcx.ins().usub_overflow(zero, a)
+        };
+        self.trap_if(of, TrapKind::Overflow);
+        let v = if negate {
+            negated
+        } else {
+            let a_is_neg = self.bcx.ins().icmp(IntCC::SignedLessThan, a, zero);
+            self.bcx.ins().select(a_is_neg, negated, a)
+        };
+        self.stack.push((v, ty));
+        Ok(())
+    }
+
+    /// Lower `min` (`larger == false`) or `max` with the signedness-aware
+    /// CLIF instruction.
+    fn lower_min_max(&mut self, larger: bool) -> Result<()> {
+        let (a, b, ty) = self.pop_int_pair()?;
+        let v = match (larger, is_signed(&ty)) {
+            (false, true) => self.bcx.ins().smin(a, b),
+            (false, false) => self.bcx.ins().umin(a, b),
+            (true, true) => self.bcx.ins().smax(a, b),
+            (true, false) => self.bcx.ins().umax(a, b),
+        };
+        self.stack.push((v, ty));
+        Ok(())
+    }
+
+    /// Lower `pow` as a square-and-multiply loop over the exponent's bits:
+    ///
+    /// ```text
+    /// head(acc, base, e):  e == 0 ? done(acc) : step
+    /// step:                if e odd { acc *= base }   (checked)
+    ///                      e >>= 1; e == 0 ? done(acc) : square
+    /// square:              base *= base               (checked)
+    ///                      jump head(acc, base, e)
+    /// ```
+    ///
+    /// `base` is squared only while bits remain, so a product overflows
+    /// exactly when the true power does not fit — the interpreter's
+    /// `checked_pow` outcome. A negative signed exponent traps first.
+    fn lower_pow(&mut self) -> Result<()> {
+        let (base, exp, ty) = self.pop_int_pair()?;
+        let cty = clif_type(&ty);
+        let signed = is_signed(&ty);
+        if signed {
+            let exp_is_neg = self.bcx.ins().icmp_imm(IntCC::SignedLessThan, exp, 0);
+            self.trap_if(exp_is_neg, TrapKind::NegativeExponent);
+        }
+
+        let head = self.bcx.create_block();
+        let step = self.bcx.create_block();
+        let square = self.bcx.create_block();
+        let done = self.bcx.create_block();
+        for _ in 0..3 {
+            self.bcx.append_block_param(head, cty);
+        }
+        let result = self.bcx.append_block_param(done, cty);
+
+        let one = self.bcx.ins().iconst(cty, 1);
+        self.bcx
+            .ins()
+            .jump(head, &[one.into(), base.into(), exp.into()]);
+
+        self.bcx.switch_to_block(head);
+        let params = self.bcx.block_params(head).to_vec();
+        let (acc, base, e) = (params[0], params[1], params[2]);
+        self.bcx.ins().brif(e, step, &[], done, &[acc.into()]);
+
+        self.bcx.switch_to_block(step);
+        self.bcx.seal_block(step);
+        let odd = self.bcx.ins().band_imm(e, 1);
+        let (product, of) = if signed {
+            self.bcx.ins().smul_overflow(acc, base)
         } else {
-            self.bcx.ins().udiv(a, b)
+            self.bcx.ins().umul_overflow(acc, base)
+        };
+        let odd_flag = self.bcx.ins().icmp_imm(IntCC::NotEqual, odd, 0);
+        let of = self.bcx.ins().band(of, odd_flag);
+        self.trap_if(of, TrapKind::Overflow);
+        let acc = self.bcx.ins().select(odd_flag, product, acc);
+        let e = self.bcx.ins().ushr_imm(e, 1);
+        self.bcx.ins().brif(e, square, &[], done, &[acc.into()]);
+
+        self.bcx.switch_to_block(square);
+        self.bcx.seal_block(square);
+        let (squared, of) = if signed {
+            self.bcx.ins().smul_overflow(base, base)
+        } else {
+            self.bcx.ins().umul_overflow(base, base)
+        };
+        self.trap_if(of, TrapKind::Overflow);
+        self.bcx
+            .ins()
+            .jump(head, &[acc.into(), squared.into(), e.into()]);
+        self.bcx.seal_block(head);
+
+        self.bcx.switch_to_block(done);
+        self.bcx.seal_block(done);
+        self.stack.push((result, ty));
+        Ok(())
+    }
+
+    /// Lower a two-operand bitwise op. `rotl`/`rotr` already take the
+    /// count modulo the width, as the interpreter's `rotate_*` does. The
+    /// shifts do too, which the interpreter does not: there a count of at
+    /// least the width (read as unsigned) shifts every bit out. So a shift
+    /// also computes that result — `0`, or for `sshr` the sign fill,
+    /// `a >> (width - 1)` — and `select`s it when the count is too big.
+    fn lower_bitwise(&mut self, kind: BitKind) -> Result<()> {
+        let (a, b, ty) = self.pop_int_pair()?;
+        let v = match kind {
+            BitKind::And => self.bcx.ins().band(a, b),
+            BitKind::Or => self.bcx.ins().bor(a, b),
+            BitKind::Xor => self.bcx.ins().bxor(a, b),
+            BitKind::Rotl => self.bcx.ins().rotl(a, b),
+            BitKind::Rotr => self.bcx.ins().rotr(a, b),
+            BitKind::Shl | BitKind::Shr => {
+                let cty = clif_type(&ty);
+                let width = i64::from(cty.bits());
+                let (shifted, fill) = match (kind, is_signed(&ty)) {
+                    (BitKind::Shl, _) => (self.bcx.ins().ishl(a, b), self.bcx.ins().iconst(cty, 0)),
+                    (_, true) => (
+                        self.bcx.ins().sshr(a, b),
+                        self.bcx.ins().sshr_imm(a, width - 1),
+                    ),
+                    (_, false) => (self.bcx.ins().ushr(a, b), self.bcx.ins().iconst(cty, 0)),
+                };
+                let too_far = self
+                    .bcx
+                    .ins()
+                    .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, b, width);
+                self.bcx.ins().select(too_far, fill, shifted)
+            }
         };
         self.stack.push((v, ty));
         Ok(())
@@ -1042,6 +2357,7 @@ impl Lowerer<'_, '_> {
         let helper = match kind {
             TrapKind::Overflow => self.runtime.trap_overflow,
             TrapKind::DivZero => self.runtime.trap_div_zero,
+            TrapKind::NegativeExponent => self.runtime.trap_negative_exponent,
         };
         let local = self.module.declare_func_in_func(helper, self.bcx.func);
         self.bcx.ins().call(local, &[]);
@@ -1055,7 +2371,7 @@ impl Lowerer<'_, '_> {
     /// Lower an integer ordering comparison with signedness-aware `icmp` codes.
     fn int_cmp(&mut self, signed: IntCC, unsigned: IntCC) -> Result<()> {
         let (a, b, ty) = self.pop_int_pair()?;
-        let cc = if is_signed(ty) { signed } else { unsigned };
+        let cc = if is_signed(&ty) { signed } else { unsigned };
         let v = self.bcx.ins().icmp(cc, a, b);
         self.stack.push((v, Ty::Bool));
         Ok(())
@@ -1107,8 +2423,8 @@ impl Lowerer<'_, '_> {
     fn cast(
         &mut self,
         v: cranelift_codegen::ir::Value,
-        from: Ty,
-        to: Ty,
+        from: &Ty,
+        to: &Ty,
     ) -> cranelift_codegen::ir::Value {
         let from_bits = width_bits(from);
         let to_bits = width_bits(to);
@@ -1149,16 +2465,63 @@ impl Lowerer<'_, '_> {
             if i > 0 {
                 self.bcx.ins().call(space, &[]);
             }
-            let printer = self.printer_for(*ty);
-            let local = self.module.declare_func_in_func(printer, self.bcx.func);
-            self.bcx.ins().call(local, &[*v]);
+            self.print_value(*v, ty)?;
         }
         self.bcx.ins().call(close, &[]);
         Ok(())
     }
 
-    /// The runtime-helper `FuncId` that prints one value of `ty`.
-    fn printer_for(&self, ty: Ty) -> FuncId {
+    /// Emit the call that prints one value of `ty`. A function reference
+    /// prints as its type, like the interpreter's `<fn ( Str -> Bool )>`;
+    /// the type is known statically, so the label is a constant string and
+    /// the pointer itself is never shown.
+    ///
+    /// A struct value prints as `user{name: "bob", id: 1i64}`: its fields
+    /// are loaded and printed one by one between constant labels.
+    fn print_value(&mut self, v: cranelift_codegen::ir::Value, ty: &Ty) -> Result<()> {
+        match ty {
+            Ty::Fn(ft) => return self.print_label(&format!("<fn {ft}>")),
+            Ty::Struct(st) => {
+                for (i, (name, field_ty)) in st.fields.iter().enumerate() {
+                    let label = if i == 0 {
+                        format!("{}{{{name}: ", st.name)
+                    } else {
+                        format!(", {name}: ")
+                    };
+                    self.print_label(&label)?;
+                    let field = self.load_field(v, st, i);
+                    self.print_value(field, field_ty)?;
+                }
+                return self.print_label("}");
+            }
+            _ => {}
+        }
+        let printer = self.printer_for(ty);
+        let local = self.module.declare_func_in_func(printer, self.bcx.func);
+        self.bcx.ins().call(local, &[v]);
+        Ok(())
+    }
+
+    /// Emit the call that writes the constant text `label` verbatim.
+    fn print_label(&mut self, label: &str) -> Result<()> {
+        let mut bytes = label.as_bytes().to_vec();
+        bytes.push(0);
+        let data_id = self.module.declare_anonymous_data(false, false)?;
+        let mut desc = DataDescription::new();
+        desc.define(bytes.into_boxed_slice());
+        self.module.define_data(data_id, &desc)?;
+        let gv = self.module.declare_data_in_func(data_id, self.bcx.func);
+        let label = self.bcx.ins().global_value(PTR_TY, gv);
+        let raw = self
+            .module
+            .declare_func_in_func(self.runtime.print_raw, self.bcx.func);
+        self.bcx.ins().call(raw, &[label]);
+        Ok(())
+    }
+
+    /// The runtime-helper `FuncId` that prints one value of a non-function
+    /// type `ty`.
+    fn printer_for(&self, ty: &Ty) -> FuncId {
         match ty {
             Ty::I8 => self.runtime.print_i8,
             Ty::I16 => self.runtime.print_i16,
@@ -1170,6 +2533,15 @@ impl Lowerer<'_, '_> {
             Ty::U64 => self.runtime.print_u64,
             Ty::Bool => self.runtime.print_bool,
             Ty::Str => self.runtime.print_str,
+            Ty::Bytes => self.runtime.print_bytes,
+            Ty::File => self.runtime.print_file,
+            Ty::Char => self.runtime.print_char,
+            Ty::Fn(_) | Ty::Struct(_) => {
+                unreachable!("function references and structs print through `print_value`")
+            }
+            Ty::Var(_) | Ty::Row(_) | Ty::Hole(_) => {
+                unreachable!("generic functions are lowered per instance")
+            }
         }
     }
 
@@ -1177,6 +2549,13 @@ impl Lowerer<'_, '_> {
     /// that holds this literal's bytes, push the address (typed as
     /// `Ty::Str`) onto the compile-time stack.
     fn lower_push_str(&mut self, id: StrId) -> Result<()> {
+        let addr = self.str_addr(id)?;
+        self.stack.push((addr, Ty::Str));
+        Ok(())
+    }
+
+    /// The address of the data symbol holding the literal `id`.
+    fn str_addr(&mut self, id: StrId) -> Result<cranelift_codegen::ir::Value> {
         let data_id = *self.str_data.get(&id).ok_or_else(|| -> Box<dyn Error> {
             // `declare_str_data` is supposed to register every StrId
             // reachable through ops; missing here means the collection
@@ -1184,36 +2563,35 @@ impl Lowerer<'_, '_> {
             format!("AOT: PushStr({id:?}) without a declared data symbol").into()
         })?;
         let gv = self.module.declare_data_in_func(data_id, self.bcx.func);
-        let addr = self.bcx.ins().global_value(PTR_TY, gv);
-        self.stack.push((addr, Ty::Str));
-        Ok(())
+        Ok(self.bcx.ins().global_value(PTR_TY, gv))
     }
 
     /// Lower `Op::Add`: integers go through the checked-overflow
-    /// arithmetic path; the `Str Str` case calls into the runtime's
-    /// `plenty_concat`, which allocates a fresh nul-terminated buffer
+    /// arithmetic path; the `Str Str` and `Bytes Bytes` cases call into
+    /// the runtime's `plenty_concat`, which allocates a fresh string
     /// and returns its address. The polymorphic `+` is the only op
     /// that mixes these two backends — every other arithmetic op
     /// stays integer-only (`check::arith` rejects `Str Str` for `-`,
     /// `*`, `/`).
     fn lower_add(&mut self) -> Result<()> {
         let len = self.stack.len();
-        if len >= 2 && self.stack[len - 1].1 == Ty::Str && self.stack[len - 2].1 == Ty::Str {
+        if len >= 2 && matches!(self.stack[len - 1].1, Ty::Str | Ty::Bytes) {
             let b = self.stack.pop().expect("len >= 2").0;
-            let a = self.stack.pop().expect("len >= 2").0;
+            let (a, ty) = self.stack.pop().expect("len >= 2");
             let concat = self
                 .module
                 .declare_func_in_func(self.runtime.concat, self.bcx.func);
             let inst = self.bcx.ins().call(concat, &[a, b]);
             let v = self.bcx.inst_results(inst)[0];
-            self.stack.push((v, Ty::Str));
+            self.stack.push((v, ty));
             return Ok(());
         }
         self.lower_checked_arith(ArithKind::Add)
     }
 
-    /// Lower equality and inequality. Strings use the runtime content
-    /// comparison; integers and Bools use CLIF's fixed-width `icmp`.
+    /// Lower equality and inequality. Strings and byte strings use the
+    /// runtime content comparison; integers, chars and Bools use CLIF's
+    /// fixed-width `icmp`. The checker has made both operands one type.
     fn lower_eq(&mut self) -> Result<()> {
         self.lower_equality(IntCC::Equal, false)
     }
@@ -1224,7 +2602,7 @@ impl Lowerer<'_, '_> {
 
     fn lower_equality(&mut self, cc: IntCC, negate_string_result: bool) -> Result<()> {
         let len = self.stack.len();
-        if len >= 2 && self.stack[len - 1].1 == Ty::Str && self.stack[len - 2].1 == Ty::Str {
+        if len >= 2 && matches!(self.stack[len - 1].1, Ty::Str | Ty::Bytes) {
             let b = self.stack.pop().expect("len >= 2").0;
             let a = self.stack.pop().expect("len >= 2").0;
             let str_eq = self
@@ -1270,7 +2648,7 @@ impl Lowerer<'_, '_> {
         let (var, ty) = self
             .locals
             .get(i as usize)
-            .copied()
+            .cloned()
             .ok_or_else(|| -> Box<dyn Error> {
                 format!("AOT: LoadLocal({i}) has no matching input").into()
             })?;
@@ -1281,17 +2659,15 @@ impl Lowerer<'_, '_> {
 
     /// Pop the inputs for a call to `name` from the compile-time stack,
     /// returning them in call order (deepest = position 0) along with
-    /// the callee's declaration.
-    fn pop_call_args(
-        &mut self,
-        name: &str,
-    ) -> Result<(&UserFn, Vec<cranelift_codegen::ir::Value>)> {
-        let decl = self.user_fns.get(name).ok_or_else(|| -> Box<dyn Error> {
-            // Should have been caught by `check_calls_resolve`; this
-            // is the defensive arm for direct-construction paths.
-            format!("AOT: undefined function `{name}`").into()
-        })?;
-        let n = decl.sig.inputs.len();
+    /// the callee's declaration — for a generic function, the instance
+    /// for the types on the stack, declared now if this is its first call.
+    /// A `..R` instance also takes the values its row covers.
+    fn pop_call_args(&mut self, name: &str) -> Result<(UserFn, Vec<cranelift_codegen::ir::Value>)> {
+        let decl = match self.user_fns.get(name) {
+            Some(decl) => decl.clone(),
+            None => self.instance(name)?,
+        };
+        let n = decl.below.len() + decl.sig.inputs.len();
         if self.stack.len() < n {
             return Err(format!("AOT: stack underflow calling `{name}`").into());
         }
@@ -1302,17 +2678,174 @@ impl Lowerer<'_, '_> {
         Ok((decl, args))
     }
 
+    /// The instance of generic function `name` for the current stack,
+    /// declaring it (and queueing its body for Pass 4) if it is new. The
+    /// checker's own call rule works out what the instance returns and
+    /// how far below the arguments `..R` reaches.
+    fn instance(&mut self, name: &str) -> Result<UserFn> {
+        let sig = self
+            .instances
+            .sigs
+            .get(name)
+            .ok_or_else(|| -> Box<dyn Error> {
+                // Should have been caught by `check_calls_resolve`; this
+                // is the defensive arm for direct-construction paths.
+                format!("AOT: undefined function `{name}`").into()
+            })?;
+        let sig = Rc::clone(sig);
+        let mut tys: Vec<Ty> = self.stack.iter().map(|(_, ty)| ty.clone()).collect();
+        let n = sig.inputs.len();
+        let split = tys
+            .len()
+            .checked_sub(n)
+            .ok_or_else(|| format!("AOT: stack underflow calling `{name}`"))?;
+        let inputs: Vec<(String, Ty)> = sig
+            .inputs
+            .iter()
+            .zip(&tys[split..])
+            .map(|((param, _), ty)| (param.clone(), ty.clone()))
+            .collect();
+        let below = self.stack[..split].iter().map(|(_, ty)| ty.clone());
+        let kept = op::check_call(name, &mut tys, &self.instances.sigs)?;
+        let below: Vec<Ty> = below.skip(kept).collect();
+        let key = format!(
+            "{name}<{}>",
+            below
+                .iter()
+                .chain(inputs.iter().map(|(_, ty)| ty))
+                .map(|ty| ty.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        if let Some(decl) = self.instances.declared.get(&key) {
+            return Ok(decl.clone());
+        }
+        let instance_sig = FnSig {
+            inputs,
+            outputs: tys.split_off(kept),
+            rest: None,
+            infer_outputs: false,
+        };
+        let cl_sig = instance_signature(self.module, &below, &instance_sig);
+        let decl = UserFn {
+            id: self
+                .module
+                .declare_function(&key, Linkage::Local, &cl_sig)?,
+            sig: Rc::new(instance_sig),
+            body: Rc::clone(&self.instances.bodies[name]),
+            captured: None,
+            below,
+        };
+        self.instances.declared.insert(key.clone(), decl.clone());
+        self.instances.pending.push(key);
+        Ok(decl)
+    }
+
     /// Lower `Op::Call`: emit a regular call and push each return value
     /// onto the compile-time stack with its declared `Ty`.
     fn lower_call(&mut self, name: &str) -> Result<()> {
         let (decl, args) = self.pop_call_args(name)?;
-        let outputs = decl.sig.outputs.clone();
-        let func_id = decl.id;
-        let funcref = self.module.declare_func_in_func(func_id, self.bcx.func);
-        let inst = self.bcx.ins().call(funcref, &args);
+        self.emit_call(&decl, &args);
+        Ok(())
+    }
+
+    /// Call `decl` with `args` and push its results.
+    fn emit_call(&mut self, decl: &UserFn, args: &[cranelift_codegen::ir::Value]) {
+        let funcref = self.module.declare_func_in_func(decl.id, self.bcx.func);
+        let inst = self.bcx.ins().call(funcref, args);
         let results: Vec<cranelift_codegen::ir::Value> = self.bcx.inst_results(inst).to_vec();
-        debug_assert_eq!(results.len(), outputs.len());
-        for (v, ty) in results.into_iter().zip(outputs) {
+        debug_assert_eq!(results.len(), decl.sig.outputs.len());
+        for (v, ty) in results.into_iter().zip(decl.sig.outputs.iter().cloned()) {
+            self.stack.push((v, ty));
+        }
+    }
+
+    /// Lower `Op::PushFn`. Every function reference is the address of an
+    /// environment whose first word is the callee's code address (see
+    /// [`Lowerer::lower_push_closure`]); with nothing captured, that
+    /// environment is one constant word of static data, so pushing the
+    /// reference allocates nothing.
+    fn lower_push_fn(&mut self, name: &str) -> Result<()> {
+        let decl = self.user_fns.get(name).ok_or_else(|| -> Box<dyn Error> {
+            format!("AOT: reference to undefined function `{name}`").into()
+        })?;
+        let ty = Ty::Fn(Rc::new(decl.sig.fn_ty()));
+        let cell = self.module.declare_anonymous_data(false, false)?;
+        let mut desc = DataDescription::new();
+        // Explicit bytes, not `define_zeroinit`: zero-initialised data
+        // lands in `.bss`, which cannot carry the relocation below.
+        desc.define(vec![0u8; 8].into_boxed_slice());
+        let code = self.module.declare_func_in_data(decl.id, &mut desc);
+        desc.write_function_addr(0, code);
+        self.module.define_data(cell, &desc)?;
+        let gv = self.module.declare_data_in_func(cell, self.bcx.func);
+        let env = self.bcx.ins().global_value(PTR_TY, gv);
+        self.stack.push((env, ty));
+        Ok(())
+    }
+
+    /// Lower `Op::PushClosure`: allocate an environment of one word for
+    /// the callee's code address plus one word per captured local, and
+    /// store each into place. The callee loads the captures back out at
+    /// entry ([`emit_user_function`]).
+    fn lower_push_closure(&mut self, name: &str, slots: &[u8]) -> Result<()> {
+        let decl = self.user_fns.get(name).ok_or_else(|| -> Box<dyn Error> {
+            format!("AOT: reference to undefined function `{name}`").into()
+        })?;
+        let ty = Ty::Fn(Rc::new(decl.sig.closure_ty(slots.len())));
+        let size = self
+            .bcx
+            .ins()
+            .iconst(types::I64, i64::from(env_offset(slots.len())));
+        let alloc = self
+            .module
+            .declare_func_in_func(self.runtime.alloc, self.bcx.func);
+        let inst = self.bcx.ins().call(alloc, &[size]);
+        let env = self.bcx.inst_results(inst)[0];
+        let funcref = self.module.declare_func_in_func(decl.id, self.bcx.func);
+        let code = self.bcx.ins().func_addr(PTR_TY, funcref);
+        self.bcx.ins().store(MemFlags::trusted(), code, env, 0);
+        for (i, &slot) in slots.iter().enumerate() {
+            let (var, _) =
+                self.locals
+                    .get(slot as usize)
+                    .cloned()
+                    .ok_or_else(|| -> Box<dyn Error> {
+                        format!("AOT: closure captures local {slot}, which has no input").into()
+                    })?;
+            let v = self.bcx.use_var(var);
+            self.bcx
+                .ins()
+                .store(MemFlags::trusted(), v, env, env_offset(i));
+        }
+        self.stack.push((env, ty));
+        Ok(())
+    }
+
+    /// Lower `Op::CallIndirect` (`:call`): pop a function reference and its
+    /// arguments, load the code address from the reference's environment,
+    /// and emit `call_indirect` passing the environment first — the
+    /// signature every quotation body is declared with
+    /// ([`user_fn_signature`]).
+    fn lower_call_indirect(&mut self) -> Result<()> {
+        let (callee, ty) = self.stack.pop().ok_or("AOT: stack underflow on :call")?;
+        let Ty::Fn(ft) = ty else {
+            return Err(format!("AOT: `:call` on a {ty}, not a function reference").into());
+        };
+        let n = ft.inputs.len();
+        if self.stack.len() < n {
+            return Err("AOT: stack underflow on :call".into());
+        }
+        let split = self.stack.len() - n;
+        let mut args = vec![callee];
+        args.extend(self.stack.drain(split..).map(|(v, _)| v));
+        let sig_ref = self
+            .bcx
+            .import_signature(ref_call_signature(self.module, &ft));
+        let code = self.bcx.ins().load(PTR_TY, MemFlags::trusted(), callee, 0);
+        let inst = self.bcx.ins().call_indirect(sig_ref, code, &args);
+        let results: Vec<cranelift_codegen::ir::Value> = self.bcx.inst_results(inst).to_vec();
+        for (v, ty) in results.into_iter().zip(ft.outputs.iter().cloned()) {
             self.stack.push((v, ty));
         }
         Ok(())
@@ -1323,8 +2856,20 @@ impl Lowerer<'_, '_> {
     /// primitive for Plenty's recursive control flow (§11.8). The
     /// instruction is a block terminator, so we set `self.terminated`
     /// and the outer loop stops feeding ops to this lowerer.
+    ///
+    /// `return_call` hands back only the callee's results, so if values
+    /// the caller must also return sit beneath the arguments, the call is
+    /// lowered as a plain call followed by a `return` of the whole stack.
     fn lower_tail_call(&mut self, name: &str) -> Result<()> {
         let (decl, args) = self.pop_call_args(name)?;
+        if !self.stack.is_empty() {
+            self.emit_call(&decl, &args);
+            let returns: Vec<cranelift_codegen::ir::Value> =
+                self.stack.iter().map(|(v, _)| *v).collect();
+            self.bcx.ins().return_(&returns);
+            self.terminated = true;
+            return Ok(());
+        }
         let func_id = decl.id;
         let funcref = self.module.declare_func_in_func(func_id, self.bcx.func);
         self.bcx.ins().return_call(funcref, &args);
@@ -1332,6 +2877,123 @@ impl Lowerer<'_, '_> {
         Ok(())
     }
 
+    /// Emit the test of one pattern against the scrutinee `scrut`: the
+    /// `i8` flag that is set when it matches, or `None` for a pattern that
+    /// matches without a test (`_`, or an or-pattern with a `_` in it).
+    fn pattern_test(
+        &mut self,
+        pattern: &Pattern,
+        scrut: cranelift_codegen::ir::Value,
+        scrut_ty: &Ty,
+    ) -> Result<Option<cranelift_codegen::ir::Value>> {
+        Ok(Some(match pattern {
+            Pattern::Wildcard => return Ok(None),
+            Pattern::Bool(b) => {
+                let pat = self.bcx.ins().iconst(types::I8, i64::from(*b as i8));
+                self.bcx.ins().icmp(IntCC::Equal, scrut, pat)
+            }
+            Pattern::Int { value, .. } => {
+                // The checker has already established that an untyped
+                // pattern fits the scrutinee or that a typed pattern has
+                // the same type. `iconst` therefore receives the exact
+                // bit pattern to compare at the scrutinee's width.
+                let pat = self
+                    .bcx
+                    .ins()
+                    .iconst(clif_type(scrut_ty), int_value_bits(*value));
+                self.bcx.ins().icmp(IntCC::Equal, scrut, pat)
+            }
+            Pattern::Range { lo, hi } => {
+                // Two ordered compares at the scrutinee's signedness, one
+                // per bound a range spells out; `0u8..` needs none.
+                let (ge, le) = if is_signed(scrut_ty) {
+                    (
+                        IntCC::SignedGreaterThanOrEqual,
+                        IntCC::SignedLessThanOrEqual,
+                    )
+                } else {
+                    (
+                        IntCC::UnsignedGreaterThanOrEqual,
+                        IntCC::UnsignedLessThanOrEqual,
+                    )
+                };
+                let (min, end) = scrut_ty.int_range().expect("ranges match integers");
+                let mut test = None;
+                for (bound, cc, limit) in [(lo, ge, min), (hi, le, end - 1)] {
+                    let Some(bound) = bound.filter(|b| b.value.as_i128() != Some(limit)) else {
+                        continue;
+                    };
+                    let pat = self
+                        .bcx
+                        .ins()
+                        .iconst(clif_type(scrut_ty), int_value_bits(bound.value));
+                    let flag = self.bcx.ins().icmp(cc, scrut, pat);
+                    test = Some(match test {
+                        Some(prev) => self.bcx.ins().band(prev, flag),
+                        None => flag,
+                    });
+                }
+                return Ok(test);
+            }
+            Pattern::Char(c) => {
+                self.bcx
+                    .ins()
+                    .icmp_imm(IntCC::Equal, scrut, i64::from(u32::from(*c)))
+            }
+            Pattern::CharRange { lo, hi } => {
+                // As for an unsigned range: a compare per bound that is
+                // not the end of the char range.
+                let mut test = None;
+                for (bound, cc, limit) in [
+                    (lo, IntCC::UnsignedGreaterThanOrEqual, '\0'),
+                    (hi, IntCC::UnsignedLessThanOrEqual, char::MAX),
+                ] {
+                    let Some(bound) = bound.filter(|&b| b != limit) else {
+                        continue;
+                    };
+                    let flag = self
+                        .bcx
+                        .ins()
+                        .icmp_imm(cc, scrut, i64::from(u32::from(bound)));
+                    test = Some(match test {
+                        Some(prev) => self.bcx.ins().band(prev, flag),
+                        None => flag,
+                    });
+                }
+                return Ok(test);
+            }
+            Pattern::Or(alternatives) => {
+                let mut test = None;
+                for alternative in alternatives.iter() {
+                    let Some(flag) = self.pattern_test(alternative, scrut, scrut_ty)? else {
+                        return Ok(None);
+                    };
+                    test = Some(match test {
+                        Some(prev) => self.bcx.ins().bor(prev, flag),
+                        None => flag,
+                    });
+                }
+                test.expect("an or-pattern has alternatives")
+            }
+            Pattern::Str(id) => {
+                // String compares are runtime calls — `plenty_str_eq`
+                // does the byte-for-byte comparison and returns a
+                // Plenty Bool (`i8`). The data symbol for `id` was
+                // already declared by `declare_str_data`.
+                let data_id = *self.str_data.get(id).ok_or_else(|| -> Box<dyn Error> {
+                    format!("AOT: Pattern::Str({id:?}) without declared data").into()
+                })?;
+                let gv = self.module.declare_data_in_func(data_id, self.bcx.func);
+                let pat_addr = self.bcx.ins().global_value(PTR_TY, gv);
+                let str_eq = self
+                    .module
+                    .declare_func_in_func(self.runtime.str_eq, self.bcx.func);
+                let call = self.bcx.ins().call(str_eq, &[scrut, pat_addr]);
+                self.bcx.inst_results(call)[0]
+            }
+        }))
+    }
+
     /// Lower `Op::Match`: one CLIF block per arm, a linear `brif` chain
     /// for dispatch, and a single join block whose params carry the
     /// agreed stack shape every arm leaves (§11.8). The type checker
@@ -1346,9 +3008,15 @@ impl Lowerer<'_, '_> {
     /// block is unreachable — we still need a terminator so Cranelift
     /// accepts the function, so we emit a defensive `trap` there.
     fn lower_match(&mut self, arms: &[MatchArm]) -> Result<()> {
-        let (scrut, scrut_ty) = self.stack.pop().ok_or("AOT: stack underflow on match")?;
+        let arity = arms.first().map_or(1, |arm| arm.patterns.len());
+        let split = self
+            .stack
+            .len()
+            .checked_sub(arity)
+            .ok_or("AOT: stack underflow on match")?;
+        let scruts = self.stack.split_off(split);
         // The state every arm starts from — the data stack at the
-        // point `match` consumes its scrutinee.
+        // point `match` consumes its scrutinees.
         let entry_stack = self.stack.clone();
 
         // One block per arm body; arms are sealed once the dispatch
@@ -1359,74 +3027,76 @@ impl Lowerer<'_, '_> {
 
         // --- Dispatch chain --------------------------------------------------
         // We're currently in whatever block called `lower_match`. Each
-        // non-wildcard pattern emits an `icmp eq` + `brif`; the false
-        // branch falls into a fresh block we switch to for the next
-        // compare. A wildcard short-circuits with an unconditional jump
-        // and renders any trailing arms unreachable (the checker would
-        // already have noticed if a useful arm came after `_`).
-        let mut chain_terminated = false;
+        // arm folds the tests of its patterns — an `icmp eq` per literal,
+        // an ordered `icmp` per range bound, a string compare per `Str`,
+        // `bor`-ed across an or-pattern's alternatives — into one flag
+        // with `band`, and branches once on it: to the arm's guard or
+        // body on a match, to the next arm's tests otherwise. A guard is
+        // lowered inline on a stack of the scrutinees and branches on the
+        // `Bool` it leaves. An unguarded arm with no tests jumps
+        // unconditionally and renders any trailing arms unreachable (the
+        // checker would already have noticed if a useful arm came after
+        // it).
+        let mut bound: Vec<Vec<(Variable, Ty)>> = Vec::with_capacity(arms.len());
         for (i, arm) in arms.iter().enumerate() {
-            if chain_terminated {
-                break;
+            // An arm's bindings are variables defined here, ahead of its
+            // tests, so both its guard and its body can read them.
+            let vars: Vec<(Variable, Ty)> = arm
+                .binds
+                .iter()
+                .map(|&column| {
+                    let (value, ty) = scruts[column].clone();
+                    let var = self.bcx.declare_var(clif_type(&ty));
+                    self.bcx.def_var(var, value);
+                    (var, ty)
+                })
+                .collect();
+            let next_arm = self.bcx.create_block();
+            let mut test = None;
+            for (pattern, (scrut, scrut_ty)) in arm.patterns.iter().zip(&scruts) {
+                let Some(flag) = self.pattern_test(pattern, *scrut, scrut_ty)? else {
+                    continue;
+                };
+                test = Some(match test {
+                    Some(prev) => self.bcx.ins().band(prev, flag),
+                    None => flag,
+                });
             }
-            match arm.pattern {
-                Pattern::Wildcard => {
-                    self.bcx.ins().jump(arm_blocks[i], &[]);
-                    chain_terminated = true;
-                }
-                Pattern::Bool(b) => {
-                    let pat = self.bcx.ins().iconst(types::I8, i64::from(b as i8));
-                    let eq = self.bcx.ins().icmp(IntCC::Equal, scrut, pat);
-                    let next = self.bcx.create_block();
-                    self.bcx.ins().brif(eq, arm_blocks[i], &[], next, &[]);
-                    self.bcx.switch_to_block(next);
-                    self.bcx.seal_block(next);
-                }
-                Pattern::Int { value, .. } => {
-                    // The checker has already established that an untyped
-                    // pattern fits the scrutinee or that a typed pattern has
-                    // the same type. `iconst` therefore receives the exact
-                    // bit pattern to compare at the scrutinee's width.
-                    let pat = self
-                        .bcx
-                        .ins()
-                        .iconst(clif_type(scrut_ty), int_value_bits(value));
-                    let eq = self.bcx.ins().icmp(IntCC::Equal, scrut, pat);
-                    let next = self.bcx.create_block();
-                    self.bcx.ins().brif(eq, arm_blocks[i], &[], next, &[]);
-                    self.bcx.switch_to_block(next);
-                    self.bcx.seal_block(next);
-                }
-                Pattern::Str(id) => {
-                    // String compares are runtime calls — `plenty_str_eq`
-                    // does the byte-for-byte comparison and returns a
-                    // Plenty Bool (`i8`). The data symbol for `id` was
-                    // already declared by `declare_str_data`.
-                    let data_id = *self.str_data.get(&id).ok_or_else(|| -> Box<dyn Error> {
-                        format!("AOT: Pattern::Str({id:?}) without declared data").into()
-                    })?;
-                    let gv = self.module.declare_data_in_func(data_id, self.bcx.func);
-                    let pat_addr = self.bcx.ins().global_value(PTR_TY, gv);
-                    let str_eq = self
-                        .module
-                        .declare_func_in_func(self.runtime.str_eq, self.bcx.func);
-                    let call = self.bcx.ins().call(str_eq, &[scrut, pat_addr]);
-                    let eq = self.bcx.inst_results(call)[0];
-                    let next = self.bcx.create_block();
-                    self.bcx.ins().brif(eq, arm_blocks[i], &[], next, &[]);
-                    self.bcx.switch_to_block(next);
-                    self.bcx.seal_block(next);
+            if let Some(flag) = test {
+                let matched = self.bcx.create_block();
+                self.bcx.ins().brif(flag, matched, &[], next_arm, &[]);
+                self.bcx.switch_to_block(matched);
+                self.bcx.seal_block(matched);
+            }
+            if let Some(guard) = &arm.guard {
+                self.stack = scruts.clone();
+                let depth = self.locals.len();
+                self.locals.extend(vars.iter().cloned());
+                for op in guard.iter() {
+                    self.lower(op)?;
                 }
+                self.locals.truncate(depth);
+                let (accepted, _) = self.stack.pop().ok_or("AOT: guard left no Bool")?;
+                self.stack = entry_stack.clone();
+                self.bcx
+                    .ins()
+                    .brif(accepted, arm_blocks[i], &[], next_arm, &[]);
+            } else {
+                self.bcx.ins().jump(arm_blocks[i], &[]);
+            }
+            self.bcx.switch_to_block(next_arm);
+            self.bcx.seal_block(next_arm);
+            bound.push(vars);
+            if test.is_none() && arm.guard.is_none() {
+                break;
             }
         }
-        if !chain_terminated {
-            // No wildcard arm matched the chain's fall-through path.
-            // The checker enforces exhaustiveness, so this is dead
-            // code under any well-formed source — emit a trap so
-            // direct-VM-construction bugs surface loudly instead of
-            // walking off the end of the function.
-            self.bcx.ins().trap(TrapCode::unwrap_user(1));
-        }
+        // No arm matched on the chain's fall-through path. The checker
+        // enforces exhaustiveness, so this is dead code under any
+        // well-formed source — emit a trap so direct-VM-construction bugs
+        // surface loudly instead of walking off the end of the function.
+        // After an all-wildcard arm the block has no predecessors at all.
+        self.bcx.ins().trap(TrapCode::unwrap_user(1));
 
         // --- Arm bodies ------------------------------------------------------
         // The join block's param types are decided by the first
@@ -1441,20 +3111,24 @@ impl Lowerer<'_, '_> {
             self.bcx.seal_block(arm_blocks[i]);
             self.stack = entry_stack.clone();
             self.terminated = false;
+            let depth = self.locals.len();
+            self.locals
+                .extend(bound.get(i).into_iter().flatten().cloned());
             for op in arm.body.iter() {
                 if self.terminated {
                     break;
                 }
                 self.lower(op)?;
             }
+            self.locals.truncate(depth);
             if self.terminated {
                 continue;
             }
             any_arm_falls_through = true;
             if join_param_types.is_none() {
-                let types: Vec<Ty> = self.stack.iter().map(|(_, t)| *t).collect();
+                let types: Vec<Ty> = self.stack.iter().map(|(_, t)| t.clone()).collect();
                 for ty in &types {
-                    self.bcx.append_block_param(join_block, clif_type(*ty));
+                    self.bcx.append_block_param(join_block, clif_type(ty));
                 }
                 join_param_types = Some(types);
             }
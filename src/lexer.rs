@@ -7,15 +7,18 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 /// One lexical unit of Plenty source.
 ///
-/// Plenty's grammar is "whitespace-separated words" plus one form of string
-/// literal. A token carries a borrowed slice of the source; the lexer
+/// Plenty's grammar is "whitespace-separated words" plus string literals,
+/// escaped or raw. A token carries a borrowed slice of the source; the lexer
 /// allocates nothing. Escape sequences inside `"..."` are not interpreted
 /// here — they are resolved when the compiler interns the text into the heap.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Tok<'a> {
-    /// Raw inner content of a `"..."` literal; `\"` and `\\` escapes
+    /// Inner content of an escaped `"..."` literal; its escapes are
     /// not yet decoded.
     Text(&'a str),
+    /// Content of a raw `r"..."` (or `r#"..."#`) literal, taken verbatim:
+    /// backslashes are ordinary characters.
+    RawText(&'a str),
     /// An unquoted word — a number, an operator, or a name. Resolved later,
     /// when compiled to an [`Op`](crate::op::Op).
     Word(&'a str),
@@ -29,8 +32,19 @@ pub enum Tok<'a> {
 /// the next unescaped `"`, capturing everything between verbatim — newlines,
 /// spaces, comment markers, and operator characters all included. Inside the
 /// literal, `\X` consumes both characters without interpreting them, so `\"`
-/// does not close the string. The only lex error is an unterminated string
-/// literal.
+/// does not close the string.
+///
+/// A raw literal `r"..."` runs to the next `"` and has no escapes at all.
+/// To hold a `"`, it may be fenced with hashes — `r#"..."#` runs to the
+/// first `"#`, `r##"..."##` to the first `"##`, and so on.
+///
+/// A char literal `'x'` or `'\...'` stays part of its word — so a range
+/// pattern `'a'..='z'` is one word — but is quoted: a space, `"`, `#` or
+/// structural character between its quotes does not end the word. One opens
+/// at the start of a word or after the `.` or `=` of a range, and only when
+/// a single char and a closing `'` follow, or a `\` does; otherwise a `'`
+/// is an ordinary character, as in the type variable `'T`. The only lex
+/// errors are an unterminated string or escaped char literal.
 pub fn lex(source: &str) -> Result<Vec<Tok<'_>>> {
     fn is_structural(c: char) -> bool {
         matches!(c, '{' | '}' | '[' | ']' | ';')
@@ -54,6 +68,12 @@ pub fn lex(source: &str) -> Result<Vec<Tok<'_>>> {
             toks.push(Tok::Word(&source[i..i + c.len_utf8()]));
             continue;
         }
+        if let Some((body, end)) = raw_literal(source, i) {
+            let body = body?;
+            toks.push(Tok::RawText(body));
+            while iter.next_if(|&(j, _)| j < end).is_some() {}
+            continue;
+        }
         if c == '"' {
             let start = i + 1;
             let end;
@@ -75,22 +95,26 @@ pub fn lex(source: &str) -> Result<Vec<Tok<'_>>> {
             toks.push(Tok::Text(&source[start..end]));
         } else {
             let start = i;
-            let end;
+            let mut end = match char_literal(source, i) {
+                Some(end) => end?,
+                None => i + c.len_utf8(),
+            };
             loop {
+                while iter.next_if(|&(j, _)| j < end).is_some() {}
                 match iter.peek() {
-                    Some(&(j, c2))
+                    Some(&(j, '\'')) if source[..j].ends_with(['.', '=']) => {
+                        end = match char_literal(source, j) {
+                            Some(end) => end?,
+                            None => j + 1,
+                        };
+                    }
+                    Some(&(_, c2))
                         if c2.is_whitespace() || c2 == '"' || c2 == '#' || is_structural(c2) =>
                     {
-                        end = j;
-                        break;
-                    }
-                    Some(_) => {
-                        iter.next();
-                    }
-                    None => {
-                        end = source.len();
                         break;
                     }
+                    Some(&(j, c2)) => end = j + c2.len_utf8(),
+                    None => break,
                 }
             }
             toks.push(Tok::Word(&source[start..end]));
@@ -98,3 +122,51 @@ pub fn lex(source: &str) -> Result<Vec<Tok<'_>>> {
     }
     Ok(toks)
 }
+
+/// If a raw literal starts at byte `i` of `source`, its content and the
+/// byte offset just past it. `r` opens one only when the quote, after any
+/// hashes, follows immediately; otherwise it starts an ordinary word.
+fn raw_literal(source: &str, i: usize) -> Option<(Result<&str>, usize)> {
+    let rest = source[i..].strip_prefix('r')?;
+    let hashes = rest.bytes().take_while(|&b| b == b'#').count();
+    let body_start = i + 1 + hashes + 1;
+    if rest.as_bytes().get(hashes) != Some(&b'"') {
+        return None;
+    }
+    let close = format!("\"{}", "#".repeat(hashes));
+    Some(match source[body_start..].find(&close) {
+        Some(len) => (
+            Ok(&source[body_start..body_start + len]),
+            body_start + len + close.len(),
+        ),
+        None => (Err("unterminated raw string literal".into()), source.len()),
+    })
+}
+
+/// If a char literal starts at byte `i` of `source`, the byte offset just
+/// past it. `'` opens one only when a single char and a closing `'` follow
+/// it, or when a `\` does; an escaped literal runs to the next `'` not
+/// preceded by a `\`, and must close on the same line.
+pub fn char_literal(source: &str, i: usize) -> Option<Result<usize>> {
+    let rest = source[i..].strip_prefix('\'')?;
+    let mut chars = rest.char_indices();
+    match chars.next()? {
+        (_, '\\') => {}
+        (_, '\'') => return None,
+        (_, c) => {
+            return rest[c.len_utf8()..]
+                .starts_with('\'')
+                .then_some(Ok(i + 1 + c.len_utf8() + 1))
+        }
+    }
+    let mut escaped = true;
+    for (j, c) in chars {
+        match c {
+            '\n' => break,
+            '\'' if !escaped => return Some(Ok(i + 1 + j + 1)),
+            '\\' if !escaped => escaped = true,
+            _ => escaped = false,
+        }
+    }
+    Some(Err("unterminated char literal".into()))
+}
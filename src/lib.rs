@@ -11,11 +11,13 @@
 
 mod codegen;
 mod lexer;
+mod lint;
 mod op;
 mod value;
 mod vm;
 
 pub use codegen::compile_source_to_executable;
-pub use op::{FnSig, Ty};
-pub use value::{StrId, Value};
+pub use lint::{lint_source, Warning, WarningKind};
+pub use op::{FnSig, ImportPaths, StructTy, Ty};
+pub use value::{BytesId, ClosureId, FileId, RecordId, StrId, Value};
 pub use vm::Vm;
@@ -0,0 +1,361 @@
+//! A static linter over the compiled `Op` tree (`plenty lint FILE`).
+//!
+//! The linter runs the same lex → compile → check pipeline as [`Vm::run`]
+//! and only inspects programs the checker accepts, so every warning is about
+//! a well-typed program that probably does not do what its author meant.
+//! Nothing here re-reads source text: each rule is a walk over `Op`s, with
+//! the [`Heap`] consulted only to compare string contents.
+//!
+//! [`Vm::run`]: crate::Vm::run
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::lexer;
+use crate::op::{self, CompiledFn, FnSig, ImportPaths, MatchArm, Op, Pattern};
+use crate::value::{Heap, StrId};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// The rule a [`Warning`] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An input declared in a function's header is never read by its body.
+    UnusedInput,
+    /// A function is defined but no call site outside its own body names it.
+    UnusedFunction,
+    /// A match arm follows a `_` arm, so it can never be selected.
+    UnreachableArm,
+    /// Two arms of the same match have identical bodies.
+    DuplicateArm,
+    /// A function calls itself from non-tail position, so each level of
+    /// recursion pushes a new interpreter frame (§11.8).
+    NonTailRecursion,
+    /// A top-level bare word pushed as text is spelled like a defined
+    /// function name, so it is probably a call missing its `:` or a typo.
+    LikelyTypo,
+}
+
+/// One lint finding. The message is name-bearing in the same way checker
+/// errors are (§9): it names the function a finding sits in, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Lint `source` and return every warning, in a stable order: per-function
+/// findings in definition order, then program-wide ones.
+///
+/// Compile and type errors are returned as `Err` — the linter has nothing
+/// useful to say about a program the checker rejects.
+///
+/// Imported files are compiled and checked but not linted: their
+/// definitions count as call targets, and their own findings belong to
+/// a `plenty lint` run on the imported file.
+pub fn lint_source(source: &str, imports: &ImportPaths) -> Result<Vec<Warning>> {
+    let toks = lexer::lex(source)?;
+    let mut heap = Heap::default();
+    let (mut ops, bare_words) = op::compile_with_bare_words(
+        &toks,
+        &mut heap,
+        imports,
+        &HashMap::new(),
+        &HashSet::new(),
+        &HashMap::new(),
+    )?;
+    op::infer(&mut ops, &HashMap::new())?;
+    op::check(&ops, Vec::new(), &HashMap::new())?;
+
+    let mut defs: Vec<(&str, &CompiledFn)> = Vec::new();
+    collect_defs(&ops, &mut defs);
+
+    let mut linter = Linter {
+        heap: &heap,
+        warnings: Vec::new(),
+    };
+    for (name, f) in &defs {
+        linter.unused_inputs(name, &f.sig, &f.body);
+        linter.non_tail_recursion(name, &f.body);
+        linter.arms(Some(name), &f.body);
+    }
+    linter.arms(None, &ops);
+    linter.unused_functions(&ops, &defs);
+    linter.likely_typos(&bare_words, &defs);
+    Ok(linter.warnings)
+}
+
+/// Every `DefineFn` reachable from `ops`, top-level and nested, in source
+/// order.
+fn collect_defs<'a>(ops: &'a [Op], out: &mut Vec<(&'a str, &'a CompiledFn)>) {
+    for op in ops {
+        match op {
+            Op::DefineFn(name, f) => {
+                out.push((name, f));
+                collect_defs(&f.body, out);
+            }
+            Op::Match(arms) => {
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    collect_defs(block, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Visit every op that runs as part of `body` itself: the body's own ops
+/// and, recursively, its match arms — but not the bodies of nested
+/// definitions, which are separate functions with their own locals (§11.5).
+fn walk_own_ops<'a>(body: &'a [Op], visit: &mut impl FnMut(&'a Op)) {
+    for op in body {
+        visit(op);
+        if let Op::Match(arms) = op {
+            for block in arms.iter().flat_map(MatchArm::blocks) {
+                walk_own_ops(block, visit);
+            }
+        }
+    }
+}
+
+struct Linter<'h> {
+    heap: &'h Heap,
+    warnings: Vec<Warning>,
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, kind: WarningKind, message: String) {
+        self.warnings.push(Warning { kind, message });
+    }
+
+    fn unused_inputs(&mut self, name: &str, sig: &FnSig, body: &[Op]) {
+        let mut used: HashSet<u8> = HashSet::new();
+        walk_own_ops(body, &mut |op| {
+            match op {
+                Op::LoadLocal(i) => {
+                    used.insert(*i);
+                }
+                // A closure's `with` list reads the captured inputs.
+                Op::PushClosure(_, _, slots) => used.extend(slots.iter().copied()),
+                _ => {}
+            }
+        });
+        for (i, (input, _)) in sig.inputs.iter().enumerate() {
+            if !used.contains(&(i as u8)) {
+                self.warn(
+                    WarningKind::UnusedInput,
+                    format!("{}: input `{input}` is never used", op::fn_scope(name)),
+                );
+            }
+        }
+    }
+
+    /// `Op::Call` (never `TailCall`) naming the enclosing function. The
+    /// tail-call pass has already turned every tail-position self-call
+    /// into `TailCall`, so whatever `Call` remains will grow the frames.
+    fn non_tail_recursion(&mut self, name: &str, body: &[Op]) {
+        let mut found = false;
+        walk_own_ops(body, &mut |op| {
+            if matches!(op, Op::Call(callee) if callee == name) {
+                found = true;
+            }
+        });
+        if found {
+            self.warn(
+                WarningKind::NonTailRecursion,
+                format!(
+                    "in `{name}`: recursive call to `{name}` is not in tail position; \
+                     every level of recursion adds an interpreter frame"
+                ),
+            );
+        }
+    }
+
+    /// Unreachable and duplicate arms in every match that runs as part of
+    /// `body`. `owner` is the enclosing function, for the message.
+    fn arms(&mut self, owner: Option<&str>, body: &[Op]) {
+        let mut matches: Vec<&Rc<[MatchArm]>> = Vec::new();
+        walk_own_ops(body, &mut |op| {
+            if let Op::Match(arms) = op {
+                matches.push(arms);
+            }
+        });
+        let prefix = owner
+            .map(|n| format!("{}: ", op::fn_scope(n)))
+            .unwrap_or_default();
+        for arms in matches {
+            if let Some(w) = arms.iter().position(|a| {
+                a.guard.is_none() && a.patterns.iter().all(|p| matches!(p, Pattern::Wildcard))
+            }) {
+                for i in w + 1..arms.len() {
+                    self.warn(
+                        WarningKind::UnreachableArm,
+                        format!("{prefix}match arm {i} follows the `_` arm {w} and can never run"),
+                    );
+                }
+            }
+            for j in 1..arms.len() {
+                if let Some(i) = (0..j).find(|&i| {
+                    guards_equivalent(&arms[i].guard, &arms[j].guard, self.heap)
+                        && ops_equivalent(&arms[i].body, &arms[j].body, self.heap)
+                }) {
+                    self.warn(
+                        WarningKind::DuplicateArm,
+                        format!("{prefix}match arms {i} and {j} have identical bodies"),
+                    );
+                }
+            }
+        }
+    }
+
+    /// A function is used if some call site outside its own body names it;
+    /// taking a reference to it counts as a call site. Self-recursion alone
+    /// does not count as a use.
+    fn unused_functions(&mut self, top: &[Op], defs: &[(&str, &CompiledFn)]) {
+        fn record<'a>(caller: Option<&str>, body: &'a [Op], called: &mut HashSet<&'a str>) {
+            walk_own_ops(body, &mut |op| {
+                if let Op::Call(callee)
+                | Op::TailCall(callee)
+                | Op::PushFn(callee, _)
+                | Op::PushClosure(callee, _, _) = op
+                {
+                    if Some(callee.as_str()) != caller {
+                        called.insert(callee);
+                    }
+                }
+            });
+        }
+        let mut called: HashSet<&str> = HashSet::new();
+        record(None, top, &mut called);
+        for (name, f) in defs {
+            record(Some(name), &f.body, &mut called);
+        }
+        let mut reported: HashSet<&str> = HashSet::new();
+        for (name, _) in defs {
+            if !called.contains(name) && reported.insert(name) {
+                self.warn(
+                    WarningKind::UnusedFunction,
+                    format!("function `{name}` is defined but never called"),
+                );
+            }
+        }
+    }
+
+    /// Top-level bare words that read like a defined function's name: an
+    /// exact match (a call missing its `:`) or an edit distance small
+    /// relative to the name's length.
+    fn likely_typos(&mut self, bare_words: &[StrId], defs: &[(&str, &CompiledFn)]) {
+        for &id in bare_words {
+            let word = self.heap.str(id);
+            let word = word.strip_prefix(':').unwrap_or(word);
+            let best = defs
+                .iter()
+                .map(|(name, _)| (*name, edit_distance(word, name)))
+                .filter(|&(name, d)| d <= typo_budget(name))
+                .min_by_key(|&(_, d)| d);
+            if let Some((name, _)) = best {
+                self.warn(
+                    WarningKind::LikelyTypo,
+                    format!(
+                        "bare word `{}` is pushed as text; did you mean `:{name}`?",
+                        self.heap.str(id)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// How many edits a bare word may be from `name` and still be flagged. Short
+/// names get less slack so ordinary words like `a` or `is` are not reported.
+fn typo_budget(name: &str) -> usize {
+    match name.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Edit distance over `char`s, counting an adjacent transposition as one
+/// edit (optimal string alignment) — `doubel` is one slip from `double`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Structural equality of two op sequences, comparing string literals by
+/// content rather than by `StrId` — two `"x"` literals are interned
+/// separately, so plain `==` would call them different.
+fn ops_equivalent(a: &[Op], b: &[Op], heap: &Heap) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|pair| match pair {
+            (Op::PushStr(x), Op::PushStr(y)) => heap.str(*x) == heap.str(*y),
+            (Op::PushFn(m, _), Op::PushFn(n, _)) => m == n,
+            (Op::PushClosure(m, _, x), Op::PushClosure(n, _, y)) => m == n && x == y,
+            (Op::Match(x), Op::Match(y)) => {
+                x.len() == y.len()
+                    && x.iter().zip(y.iter()).all(|(p, q)| {
+                        p.patterns.len() == q.patterns.len()
+                            && p.patterns
+                                .iter()
+                                .zip(&q.patterns)
+                                .all(|(a, b)| patterns_equivalent(a, b, heap))
+                            && guards_equivalent(&p.guard, &q.guard, heap)
+                            && ops_equivalent(&p.body, &q.body, heap)
+                    })
+            }
+            (Op::DefineFn(m, f), Op::DefineFn(n, g)) => {
+                m == n && f.sig == g.sig && ops_equivalent(&f.body, &g.body, heap)
+            }
+            (x, y) => x == y,
+        })
+}
+
+fn patterns_equivalent(a: &Pattern, b: &Pattern, heap: &Heap) -> bool {
+    match (a, b) {
+        (Pattern::Str(x), Pattern::Str(y)) => heap.str(*x) == heap.str(*y),
+        (Pattern::Or(x), Pattern::Or(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y.iter())
+                    .all(|(p, q)| patterns_equivalent(p, q, heap))
+        }
+        (x, y) => x == y,
+    }
+}
+
+/// Two arms' `when` guards are equivalent if neither has one, or both have
+/// equivalent ops.
+fn guards_equivalent(a: &Option<Rc<[Op]>>, b: &Option<Rc<[Op]>>, heap: &Heap) -> bool {
+    match (a, b) {
+        (Some(x), Some(y)) => ops_equivalent(x, y, heap),
+        (x, y) => x.is_none() && y.is_none(),
+    }
+}
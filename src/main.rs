@@ -24,7 +24,7 @@ use std::process::{Command, ExitCode};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use plenty::Vm;
+use plenty::{ImportPaths, Vm};
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::validate::{ValidationContext, ValidationResult, Validator};
@@ -47,7 +47,8 @@ const BANNER: &str = r#"
 const HELP: &str = "\
 Enter wraps. `;` (after a balanced `:`) submits. Ctrl-J (or Shift/Alt-Enter)
 force-submits. Ctrl-G edits the buffer in $EDITOR. Tab completes function
-names and builtins. `quit` or Ctrl-D exits.
+names and builtins. `:sig NAME` shows a function's signature, including one
+inferred from a `{ ? }` header. `quit` or Ctrl-D exits.
 ";
 
 const PROMPT: &str = "---> ";
@@ -58,7 +59,16 @@ const STATIC_WORDS: &[&str] = &[
     "true",
     "false",
     "match",
+    "match2",
+    "when",
+    "as",
     "end",
+    "import",
+    "module",
+    "private",
+    "const",
+    "struct",
+    "with",
     "not",
     "and",
     "or",
@@ -74,12 +84,35 @@ const STATIC_WORDS: &[&str] = &[
     "u32",
     "u64",
     "Str",
+    "Bytes",
+    "File",
     "Bool",
+    "Char",
     ".",
     "+",
     "-",
     "*",
     "/",
+    "+%",
+    "*%",
+    "+|",
+    "-|",
+    "rem",
+    "mod",
+    "divmod",
+    "abs",
+    "neg",
+    "min",
+    "max",
+    "pow",
+    "band",
+    "bor",
+    "bxor",
+    "bnot",
+    "shl",
+    "shr",
+    "rotl",
+    "rotr",
     "=",
     "!=",
     "<",
@@ -95,10 +128,60 @@ const STATIC_WORDS: &[&str] = &[
     ":as-u16",
     ":as-u32",
     ":as-u64",
+    ":as-char",
     ":readline",
+    ":readline-bytes",
+    ":read-bytes",
+    ":write-bytes",
+    ":utf8-decode",
+    ":utf8-encode",
+    ":utf8-decode-lossy",
+    ":bytes-len",
+    ":bytes-slice",
+    ":read-file",
+    ":write-file",
+    ":append-file",
+    ":file-exists",
+    ":open-file",
+    ":file-readline",
+    ":file-readline-bytes",
+    ":close-file",
+    ":argc",
+    ":arg",
+    ":env",
     ":contains",
+    ":len",
+    ":slice",
+    ":split",
+    ":trim",
+    ":starts-with",
+    ":ends-with",
+    ":find",
+    ":replace",
+    ":upper",
+    ":lower",
+    ":repeat",
+    ":next-char",
+    ":char-at",
+    ":from-char",
+    ":parse-i8",
+    ":parse-i16",
+    ":parse-i32",
+    ":parse-i64",
+    ":parse-u8",
+    ":parse-u16",
+    ":parse-u32",
+    ":parse-u64",
+    ":to-str",
+    ":to-digits",
+    ":format",
     ":println",
     ":print",
+    ":eprintln",
+    ":eprint",
+    ":exit",
+    ":call",
+    ":sig",
     "exit",
     "quit",
 ];
@@ -112,12 +195,13 @@ struct PlentyHelper {
 
 impl Validator for PlentyHelper {
     /// Submit only when the input is *structurally* complete — empty,
-    /// or all `:` definitions closed by `;`. Anything inside an open
-    /// `:` or an unterminated `"..."` keeps editing. A force-submit key
-    /// (Ctrl-J etc.) bypasses this entirely via `Cmd::AcceptLine`.
+    /// or all `:` definitions closed by `;`, or a `:sig NAME` command.
+    /// Anything inside an open `:` or an unterminated `"..."` keeps
+    /// editing. A force-submit key (Ctrl-J etc.) bypasses this entirely
+    /// via `Cmd::AcceptLine`.
     fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
         let input = ctx.input();
-        if input.trim().is_empty() {
+        if input.trim().is_empty() || sig_command(input.trim()).is_some() {
             return Ok(ValidationResult::Valid(None));
         }
         let depth = match definition_depth(input) {
@@ -204,23 +288,37 @@ impl ConditionalEventHandler for EditorTrigger {
 }
 
 const USAGE: &str = "\
-Usage: plenty [FILE]
+Usage: plenty [FILE [ARG...]]
        plenty --compile FILE -o OUT
+       plenty lint FILE
        plenty -h | --help
 
 With no arguments, starts the interactive REPL. With a file path, lexes,
 compiles, type-checks, and runs the file, then exits — stdout is the
-program's, stderr is for diagnostics. Exit status is 0 on success and
-non-zero on any compile, type, or runtime error.
+program's, stderr is for diagnostics. Exit status is 0 on success, the
+program's own status if it calls `:exit`, and non-zero on any compile,
+type, or runtime error. ARGs after the file are
+the program's own, read with `:argc` and `:arg`.
 
 `--compile FILE -o OUT` produces a native executable at OUT (AOT, §11.1).
 The C compiler `cc` must be on PATH; the embedded runtime is linked
 automatically. The AOT path covers every Plenty op.
+
+`lint FILE` compiles and type-checks the file without running it, then
+prints one `warning:` line per likely mistake: unused inputs, functions
+never called, unreachable or duplicate match arms, non-tail
+self-recursion, and bare words that look like misspelled function names.
+Exit status is 0 when there are no warnings and 1 otherwise.
+
+`import \"path\"` resolves relative to the importing file's directory
+(the working directory in the REPL), then each directory listed in the
+PLENTY_PATH environment variable, separated as PATH is.
 ";
 
 fn main() -> ExitCode {
     pretty_env_logger::init();
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    // `args_os`, so a script's own arguments need not be UTF-8.
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
     let outcome = match args.as_slice() {
         [] => repl(),
         [flag] if flag == "-h" || flag == "--help" => {
@@ -230,9 +328,16 @@ fn main() -> ExitCode {
         [flag, source, dash_o, out]
             if flag == "--compile" && (dash_o == "-o" || dash_o == "--output") =>
         {
-            compile_file(Path::new(source), Path::new(out))
+            compile_file(Path::new(source), Path::new(out)).map(|()| None)
+        }
+        [cmd, path] if cmd == "lint" => match lint_file(Path::new(path)) {
+            Ok(0) => return ExitCode::SUCCESS,
+            Ok(_) => return ExitCode::FAILURE,
+            Err(e) => Err(e),
+        },
+        [path, script_args @ ..] if !path.as_encoded_bytes().starts_with(b"-") => {
+            run_file(Path::new(path), script_args)
         }
-        [path] if !path.starts_with('-') => run_file(Path::new(path)),
         _ => {
             eprintln!("plenty: unrecognised arguments");
             eprint!("{USAGE}");
@@ -240,7 +345,9 @@ fn main() -> ExitCode {
         }
     };
     match outcome {
-        Ok(()) => ExitCode::SUCCESS,
+        Ok(None) => ExitCode::SUCCESS,
+        // The low byte, which is all of the status a process keeps.
+        Ok(Some(code)) => ExitCode::from(code as u8),
         Err(e) => {
             eprintln!("error: {e}");
             ExitCode::FAILURE
@@ -248,14 +355,19 @@ fn main() -> ExitCode {
     }
 }
 
-/// Read `path` as a single Plenty source and run it on a fresh [`Vm`].
-/// Used by the binary's file-execution mode (DESIGN.md §12.4); the REPL
-/// uses [`Vm::run`] directly so its state persists across inputs.
-fn run_file(path: &Path) -> Result<(), Box<dyn Error>> {
+/// Read `path` as a single Plenty source and run it on a fresh [`Vm`],
+/// with `args` as what `:arg` reads. Used by the binary's file-execution
+/// mode (DESIGN.md §12.4); the REPL uses [`Vm::run`] directly so its
+/// state persists across inputs. Returns the status the program passed
+/// to `:exit`, if it did.
+fn run_file(path: &Path, args: &[OsString]) -> Result<Option<i32>, Box<dyn Error>> {
     let source = std::fs::read_to_string(path)
         .map_err(|e| -> Box<dyn Error> { format!("reading {}: {e}", path.display()).into() })?;
     let mut vm = Vm::new();
-    vm.run(&source)
+    vm.set_import_paths(import_paths(path.parent()));
+    vm.set_args(args.to_vec());
+    vm.run(&source)?;
+    Ok(vm.exit_code())
 }
 
 /// Read `source` and produce a native executable at `output` (DESIGN.md
@@ -265,14 +377,43 @@ fn run_file(path: &Path) -> Result<(), Box<dyn Error>> {
 fn compile_file(source: &Path, output: &Path) -> Result<(), Box<dyn Error>> {
     let text = std::fs::read_to_string(source)
         .map_err(|e| -> Box<dyn Error> { format!("reading {}: {e}", source.display()).into() })?;
-    plenty::compile_source_to_executable(&text, output)
+    plenty::compile_source_to_executable(&text, &import_paths(source.parent()), output)
+}
+
+/// Lint `path` (see [`plenty::lint_source`]) and print each warning to
+/// stdout. Returns the number of warnings so `main` can pick the exit
+/// status; compile and type errors come back as `Err` like any other mode.
+fn lint_file(path: &Path) -> Result<usize, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| -> Box<dyn Error> { format!("reading {}: {e}", path.display()).into() })?;
+    let warnings = plenty::lint_source(&text, &import_paths(path.parent()))?;
+    for w in &warnings {
+        println!("{}: warning: {w}", path.display());
+    }
+    Ok(warnings.len())
 }
 
-fn repl() -> Result<(), Box<dyn Error>> {
+/// Where `import` looks: `base` (the source file's directory, or `None`
+/// for the working directory), then each directory in `PLENTY_PATH`.
+fn import_paths(base: Option<&Path>) -> ImportPaths {
+    let search = std::env::var_os("PLENTY_PATH")
+        .map(|v| std::env::split_paths(&v).collect())
+        .unwrap_or_default();
+    ImportPaths {
+        base: base.map(Path::to_path_buf),
+        search,
+        refused: false,
+    }
+}
+
+/// Run the interactive session until the user quits, or until a line runs
+/// `:exit`, whose status it returns.
+fn repl() -> Result<Option<i32>, Box<dyn Error>> {
     println!("{BANNER}");
     println!("{HELP}");
 
     let mut vm = Vm::new();
+    vm.set_import_paths(import_paths(None));
     let mut rl: Editor<PlentyHelper, _> = Editor::new()?;
     rl.set_helper(Some(PlentyHelper {
         fn_names: Vec::new(),
@@ -341,17 +482,39 @@ fn repl() -> Result<(), Box<dyn Error>> {
             break;
         }
         rl.add_history_entry(source.as_str())?;
+        if let Some(name) = sig_command(trimmed) {
+            match vm.function_sig(name) {
+                Some(sig) => println!(": {name} {sig}"),
+                None => eprintln!("error: no function named `{name}`"),
+            }
+            continue;
+        }
         if let Err(e) = vm.run(&source) {
             eprintln!("error: {e}");
         }
+        if let Some(code) = vm.exit_code() {
+            return Ok(Some(code));
+        }
+    }
+    Ok(None)
+}
+
+/// The function name in a `:sig NAME` REPL command, or `None` if `line` is
+/// not one. `:sig` is a REPL command rather than a word, so it is matched
+/// only as a whole line.
+fn sig_command(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (Some(":sig"), Some(name), None) => Some(name.strip_prefix(':').unwrap_or(name)),
+        _ => None,
     }
-    Ok(())
 }
 
 /// Count `:` definition-openers minus `;` closers in `input`, ignoring
-/// comments and anything inside a `"..."` literal. Returns `None` if the
-/// input ends mid-string, since the buffer is then known-incomplete regardless
-/// of bracket depth.
+/// comments and anything inside a `"..."` or raw `r"..."` literal or a
+/// char literal such as `';'`. Returns
+/// `None` if the input ends mid-string, since the buffer is then
+/// known-incomplete regardless of bracket depth.
 ///
 /// This is a structural check, not a full parse — it does not validate that
 /// `:` has a name or that a closer is otherwise well placed. The compiler
@@ -405,6 +568,12 @@ fn definition_depth(input: &str) -> Option<i32> {
             i += 1;
             continue;
         }
+        // `i` is where a word starts, the only place a raw literal can
+        // open; inside a word, as in `for"x"`, an `r` is an ordinary byte.
+        if let Some(len) = raw_literal_len(&input[i..]) {
+            i += len?;
+            continue;
+        }
         let start = i;
         while i < bytes.len()
             && !bytes[i].is_ascii_whitespace()
@@ -412,7 +581,11 @@ fn definition_depth(input: &str) -> Option<i32> {
             && bytes[i] != b'#'
             && !structural(bytes[i])
         {
-            i += 1;
+            let opens_literal = i == start || matches!(bytes[i - 1], b'.' | b'=');
+            match char_literal_len(&input[i..]) {
+                Some(len) if opens_literal => i += len,
+                _ => i += 1,
+            }
         }
         if &input[start..i] == ":" {
             depth += 1;
@@ -421,6 +594,41 @@ fn definition_depth(input: &str) -> Option<i32> {
     Some(depth)
 }
 
+/// If a raw literal (`r"..."`, `r#"..."#`) opens at the start of `rest`,
+/// its byte length, or `None` inside if it is unterminated — the lexer's
+/// rule: the quote must follow the `r` and any hashes immediately.
+fn raw_literal_len(rest: &str) -> Option<Option<usize>> {
+    let after_r = rest.strip_prefix('r')?;
+    let hashes = after_r.bytes().take_while(|&b| b == b'#').count();
+    let body = after_r[hashes..].strip_prefix('"')?;
+    let close = format!("\"{}", "#".repeat(hashes));
+    Some(
+        body.find(&close)
+            .map(|len| 1 + hashes + 1 + len + close.len()),
+    )
+}
+
+/// The byte length of the char literal (`'x'` or `'\...'`) at the start of
+/// `rest`, if one is there — the lexer's rule, so `';'` and `'#'` are not
+/// read as a closer or a comment.
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let body = rest.strip_prefix('\'')?;
+    let first = body.chars().next()?;
+    if first == '\\' {
+        let mut escaped = true;
+        for (j, c) in body.char_indices().skip(1) {
+            match c {
+                '\n' => return None,
+                '\'' if !escaped => return Some(j + 2),
+                '\\' if !escaped => escaped = true,
+                _ => escaped = false,
+            }
+        }
+        return None;
+    }
+    (first != '\'' && body[first.len_utf8()..].starts_with('\'')).then_some(first.len_utf8() + 2)
+}
+
 /// Open `initial` in `$EDITOR` (or `$VISUAL`, or a platform default),
 /// wait for the editor to exit, and return whatever was saved. The
 /// tempfile is named `.plenty` so an editor with syntax-aware modes can
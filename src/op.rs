@@ -5,12 +5,14 @@
 //! heap, function bodies compiled to nested `Op` sequences. A compiled program
 //! is just a `Vec<Op>`, run without ever re-lexing its source.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
-use crate::lexer::Tok;
+use crate::lexer::{self, Tok};
 use crate::value::{Heap, StrId, Value};
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
@@ -20,9 +22,16 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 /// Sized integers (§11.2): the user picks an exact bit width, signed or
 /// unsigned, so the program's memory footprint and overflow semantics are
 /// declared on the surface rather than hidden behind a polymorphic "Int".
-/// `Str` and `Bool` round out the vocabulary. Arrays and sum types are
-/// deferred (§12.7, §12.14); so are floating-point types (§12).
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// `Str` and `Bool` round out the vocabulary, and `Fn` types a reference
+/// to a function or quotation, written `( Str -> Bool )`. `Struct` is a
+/// record declared with `struct`, named by its declaration. Arrays and sum
+/// types are deferred (§12.7, §12.14); so are floating-point types (§12).
+///
+/// `Var` and `Row` make a signature generic: `'T` stands for any one type,
+/// `..R` for any run of types at the bottom of a stack effect — the rest of
+/// the caller's stack. Inside the generic function's body they are opaque
+/// types of their own; each call site instantiates them (see [`Subst`]).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Ty {
     I8,
     I16,
@@ -33,7 +42,55 @@ pub enum Ty {
     U32,
     U64,
     Str,
+    /// A byte string with no encoding promised, spelled `Bytes`.
+    Bytes,
+    /// A handle to a file opened for reading, spelled `File`.
+    File,
     Bool,
+    /// A Unicode scalar value, spelled `Char`.
+    Char,
+    Fn(Rc<FnTy>),
+    Struct(Rc<StructTy>),
+    /// A type variable `'T`, stored without its quote.
+    Var(Rc<str>),
+    /// A row variable `..R`, stored without its dots. Only ever the first
+    /// element of an input or output list.
+    Row(Rc<str>),
+    /// The still-unknown type of input `i` of a definition whose header
+    /// wrote `?` for it. Exists only while [`infer`] runs.
+    Hole(u8),
+}
+
+/// The type of a function reference: its stack effect with the input names
+/// dropped. Two references are interchangeable when these agree. Either
+/// both lists start with a [`Ty::Row`] or neither does.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FnTy {
+    pub inputs: Vec<Ty>,
+    pub outputs: Vec<Ty>,
+}
+
+/// A record type declared by `struct NAME { field Type ... }`: its name
+/// and its fields in declaration order, which is also the order the
+/// constructor takes them in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StructTy {
+    pub name: Rc<str>,
+    pub fields: Vec<(String, Ty)>,
+}
+
+impl fmt::Display for FnTy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        for ty in &self.inputs {
+            write!(f, " {ty}")?;
+        }
+        f.write_str(" ->")?;
+        for ty in &self.outputs {
+            write!(f, " {ty}")?;
+        }
+        f.write_str(" )")
+    }
 }
 
 impl Ty {
@@ -41,7 +98,7 @@ impl Ty {
     /// `Bool`) return `false`. Used by the checker to enforce the
     /// "arithmetic and ordering work on same-width integers only" rule
     /// without naming each width in eight places.
-    pub fn is_int(self) -> bool {
+    pub fn is_int(&self) -> bool {
         matches!(
             self,
             Ty::I8 | Ty::I16 | Ty::I32 | Ty::I64 | Ty::U8 | Ty::U16 | Ty::U32 | Ty::U64
@@ -52,7 +109,7 @@ impl Ty {
     /// this integer type, or `None` for non-integer types. Used to check
     /// that pattern literals (parsed as `i64`) fit the scrutinee's type
     /// at compile time, before the runtime narrowing of `pattern_matches`.
-    pub fn int_range(self) -> Option<(i128, i128)> {
+    pub fn int_range(&self) -> Option<(i128, i128)> {
         let r = match self {
             Ty::I8 => (i8::MIN as i128, i8::MAX as i128 + 1),
             Ty::I16 => (i16::MIN as i128, i16::MAX as i128 + 1),
@@ -62,14 +119,61 @@ impl Ty {
             Ty::U16 => (0, u16::MAX as i128 + 1),
             Ty::U32 => (0, u32::MAX as i128 + 1),
             Ty::U64 => (0, u64::MAX as i128 + 1),
-            Ty::Str | Ty::Bool => return None,
+            Ty::Str
+            | Ty::Bytes
+            | Ty::File
+            | Ty::Bool
+            | Ty::Char
+            | Ty::Fn(_)
+            | Ty::Struct(_)
+            | Ty::Var(_)
+            | Ty::Row(_)
+            | Ty::Hole(_) => return None,
         };
         Some(r)
     }
 }
 
+impl Ty {
+    /// `true` if a type or row variable occurs anywhere in this type.
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Ty::Var(_) | Ty::Row(_) => true,
+            Ty::Fn(f) => f.inputs.iter().chain(&f.outputs).any(Ty::is_generic),
+            _ => false,
+        }
+    }
+
+    /// Add the names of the type variables (`'T` as `T`) and row variables
+    /// (`..R` as `..R`) in this type to `out`.
+    fn collect_vars(&self, out: &mut HashSet<String>) {
+        match self {
+            Ty::Var(v) => {
+                out.insert(v.to_string());
+            }
+            Ty::Row(r) => {
+                out.insert(format!("..{r}"));
+            }
+            Ty::Fn(f) => {
+                for ty in f.inputs.iter().chain(&f.outputs) {
+                    ty.collect_vars(out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl fmt::Display for Ty {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Fn(fn_ty) => return fn_ty.fmt(f),
+            Ty::Struct(st) => return f.write_str(&st.name),
+            Ty::Var(name) => return write!(f, "'{name}"),
+            Ty::Row(name) => return write!(f, "..{name}"),
+            Ty::Hole(_) => return f.write_str("?"),
+            _ => {}
+        }
         f.write_str(match self {
             Ty::I8 => "i8",
             Ty::I16 => "i16",
@@ -80,18 +184,27 @@ impl fmt::Display for Ty {
             Ty::U32 => "u32",
             Ty::U64 => "u64",
             Ty::Str => "Str",
+            Ty::Bytes => "Bytes",
+            Ty::File => "File",
             Ty::Bool => "Bool",
+            Ty::Char => "Char",
+            Ty::Fn(_) | Ty::Struct(_) | Ty::Var(_) | Ty::Row(_) | Ty::Hole(_) => {
+                unreachable!("handled above")
+            }
         })
     }
 }
 
-/// Every `Value` has an unambiguous `Ty` — the value's runtime tag and the
-/// checker's type lattice line up one-to-one. This lets the REPL seed the
-/// checker's abstract stack from the live runtime stack, so a line containing
-/// only `+` sees the values left by the previous line (§11.6).
-impl From<Value> for Ty {
-    fn from(v: Value) -> Ty {
-        match v {
+impl Ty {
+    /// The type of a scalar `Value` — every variant but the function
+    /// references `Fn` and `Closure`, whose type is the signature of the
+    /// function they name and so needs the dictionary, and `Record`, whose
+    /// type is its struct's declaration. Scalar runtime tags
+    /// and the checker's types line up one-to-one, which lets the REPL seed
+    /// the checker's abstract stack from the live runtime stack, so a line
+    /// containing only `+` sees the values left by the previous line (§11.6).
+    pub fn of_scalar(v: Value) -> Option<Ty> {
+        Some(match v {
             Value::I8(_) => Ty::I8,
             Value::I16(_) => Ty::I16,
             Value::I32(_) => Ty::I32,
@@ -101,8 +214,18 @@ impl From<Value> for Ty {
             Value::U32(_) => Ty::U32,
             Value::U64(_) => Ty::U64,
             Value::Str(_) => Ty::Str,
+            Value::Bytes(_) => Ty::Bytes,
+            Value::File(_) => Ty::File,
             Value::Bool(_) => Ty::Bool,
-        }
+            Value::Char(_) => Ty::Char,
+            Value::Fn(_) | Value::Closure(_) | Value::Record(_) => return None,
+        })
+    }
+
+    /// The type of an integer `Value`, as carried by `Op::PushInt` and
+    /// integer patterns.
+    fn of_int(v: Value) -> Ty {
+        Ty::of_scalar(v).expect("integer values are scalars")
     }
 }
 
@@ -112,10 +235,83 @@ impl From<Value> for Ty {
 /// to them as locals (§11.5). Outputs are bare types because there is nothing
 /// for an output name to bind to; users may *write* output names for
 /// documentation (the parser accepts them) but they are discarded here.
+///
+/// A row-polymorphic signature `{ ..R x 'T -> ..S 'T }` keeps its input row
+/// in `rest`, since it names no local, and its output row inline as the
+/// first output. Either both are present or neither is.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FnSig {
     pub inputs: Vec<(String, Ty)>,
     pub outputs: Vec<Ty>,
+    /// `..R` before the inputs: the rest of the caller's stack, which the
+    /// body sees beneath the values it pushes.
+    pub rest: Option<Rc<str>>,
+    /// The header ended `-> ? }`: [`infer`] fills in `outputs` from what
+    /// the body leaves.
+    pub infer_outputs: bool,
+}
+
+impl fmt::Display for FnSig {
+    /// The header as it would be written: `{ ..R x i64 -> ..R Bool }`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("{")?;
+        if let Some(r) = &self.rest {
+            write!(f, " ..{r}")?;
+        }
+        for (name, ty) in &self.inputs {
+            write!(f, " {name} {ty}")?;
+        }
+        f.write_str(" ->")?;
+        for ty in &self.outputs {
+            write!(f, " {ty}")?;
+        }
+        if self.infer_outputs {
+            f.write_str(" ?")?;
+        }
+        f.write_str(" }")
+    }
+}
+
+impl FnSig {
+    /// The type a reference to this function has.
+    pub fn fn_ty(&self) -> FnTy {
+        self.closure_ty(0)
+    }
+
+    /// The type of a closure over this function that supplies its first
+    /// `captured` inputs itself.
+    pub fn closure_ty(&self, captured: usize) -> FnTy {
+        FnTy {
+            inputs: self.input_tys_from(captured),
+            outputs: self.outputs.clone(),
+        }
+    }
+
+    /// The types this function takes off the stack, from the `skip`-th
+    /// named input on, with the `rest` row (if any) first.
+    fn input_tys_from(&self, skip: usize) -> Vec<Ty> {
+        self.rest
+            .iter()
+            .map(|r| Ty::Row(Rc::clone(r)))
+            .chain(self.inputs[skip..].iter().map(|(_, t)| t.clone()))
+            .collect()
+    }
+
+    /// `true` while any part of the signature is `?`, to be inferred.
+    pub fn is_inferred(&self) -> bool {
+        self.infer_outputs || self.inputs.iter().any(|(_, t)| matches!(t, Ty::Hole(_)))
+    }
+
+    /// `true` if the signature has any type or row variable.
+    pub fn is_generic(&self) -> bool {
+        self.rest.is_some()
+            || self
+                .inputs
+                .iter()
+                .map(|(_, t)| t)
+                .chain(&self.outputs)
+                .any(Ty::is_generic)
+    }
 }
 
 /// A single instruction for the Plenty VM.
@@ -128,6 +324,8 @@ pub enum Op {
     PushStr(StrId),
     /// Push a `Bool` literal onto the stack (`true` / `false`).
     PushBool(bool),
+    /// Push a `Char` literal onto the stack (`'a'`, `'\n'`, `'\u{e9}'`).
+    PushChar(char),
     /// Pop two values; push their sum (integers) or concatenation (text).
     Add,
     /// Pop two integers `a b`; push `a - b`.
@@ -136,6 +334,61 @@ pub enum Op {
     Mul,
     /// Pop two integers `a b`; push `a / b`.
     Div,
+    /// Pop two same-width integers; push their sum, wrapped around at the
+    /// width instead of overflowing — `+%`.
+    WrappingAdd,
+    /// Pop two same-width integers; push their product, wrapped around at
+    /// the width — `*%`.
+    WrappingMul,
+    /// Pop two same-width integers; push their sum, clamped to the width's
+    /// range instead of overflowing — `+|`.
+    SaturatingAdd,
+    /// Pop two same-width integers `a b`; push `a - b`, clamped to the
+    /// width's range — `-|`.
+    SaturatingSub,
+    /// Pop two integers `a b`; push the remainder of `a / b`, which takes
+    /// the sign of `a` — `rem`.
+    Rem,
+    /// Pop two integers `a b`; push the Euclidean remainder of `a` by `b`,
+    /// which is never negative — `mod`.
+    Mod,
+    /// Pop two integers `a b`; push `a / b` and then `a rem b` — `divmod`.
+    DivMod,
+    /// Pop an integer; push its absolute value — `abs`.
+    Abs,
+    /// Pop an integer; push its negation — `neg`. Overflows for the
+    /// signed minimum and for any non-zero unsigned value.
+    Neg,
+    /// Pop two integers; push the smaller — `min`.
+    Min,
+    /// Pop two integers; push the larger — `max`.
+    Max,
+    /// Pop two integers `a n`; push `a` raised to the `n`th power —
+    /// `pow`. A negative `n` is an error.
+    Pow,
+    /// Pop two same-width integers; push their bitwise AND — `band`.
+    BitAnd,
+    /// Pop two same-width integers; push their bitwise OR — `bor`.
+    BitOr,
+    /// Pop two same-width integers; push their bitwise XOR — `bxor`.
+    BitXor,
+    /// Pop an integer; push its bitwise complement — `bnot`.
+    BitNot,
+    /// Pop two same-width integers `a n`; push `a` shifted left by `n`
+    /// bits — `shl`. The count is read as unsigned, and a count of at
+    /// least the width shifts every bit out, leaving `0`.
+    Shl,
+    /// Pop two same-width integers `a n`; push `a` shifted right by `n`
+    /// bits — `shr`. Arithmetic (sign-filling) for signed types, logical
+    /// for unsigned. A count of at least the width, read as unsigned,
+    /// leaves only the fill: `-1` for a negative signed `a`, else `0`.
+    Shr,
+    /// Pop two same-width integers `a n`; push `a` rotated left by `n`
+    /// bits — `rotl`. The count is taken modulo the width.
+    Rotl,
+    /// Pop two same-width integers `a n`; push `a` rotated right by `n`
+    /// bits — `rotr`. The count is taken modulo the width.
+    Rotr,
     /// Pop two values; push `true` if they are equal, `false` otherwise.
     /// Polymorphic over Int/Str/Bool (§11.8); mixed-type pairs are rejected
     /// by the type checker, never reached at runtime by a compiled source.
@@ -171,6 +424,26 @@ pub enum Op {
     /// The body is carved out of the token stream at compile time, so running
     /// this op never touches the runtime stack — whatever is on it stays put.
     DefineFn(String, CompiledFn),
+    /// Run the definitions of an imported file (`import "path"`), adding
+    /// each to the dictionary. The file was read and compiled at compile
+    /// time; the `String` is the path as resolved, for messages. The body
+    /// holds only `DefineFn`s and further `Import`s, so running it never
+    /// touches the data stack.
+    Import(String, Rc<[Op]>),
+    /// Push a reference to the function `name` — a quotation's generated
+    /// name, see [`Compiler::compile_quotation`]. The `StrId` is the same
+    /// name interned in the heap; it is what the runtime `Value::Fn`
+    /// carries.
+    PushFn(String, StrId),
+    /// Push a closure over the function `name`: the values of the active
+    /// call's locals at the listed slots, in order, become the function's
+    /// leading inputs when it is called. Built by a quotation with a `with`
+    /// list; see [`Compiler::compile_quotation`].
+    PushClosure(String, StrId, Rc<[u8]>),
+    /// Pop a function reference and invoke it — the `:call` word. The
+    /// arguments are checked against the reference's `Fn` type, so the
+    /// callee's own signature always agrees.
+    CallIndirect,
     /// Invoke a user-defined function by name. Non-tail position.
     Call(String),
     /// Invoke a user-defined function by name from tail position (§11.8).
@@ -185,6 +458,10 @@ pub enum Op {
     /// whose pattern matches runs; the value itself is *consumed* by the
     /// match. Exhaustiveness has been checked at compile time, so on a
     /// well-formed source the search always finds a match.
+    ///
+    /// `match2` pops the top *two* values and matches each arm's pair of
+    /// patterns against them together; every arm has as many patterns as
+    /// the match pops.
     Match(Rc<[MatchArm]>),
     /// Pop an integer of any width; push its representation at the target
     /// integer width. Surface syntax is `:as-i8` ... `:as-u64`. Conversion
@@ -193,6 +470,10 @@ pub enum Op {
     /// signedness change reinterprets the bit pattern. Casts that would
     /// silently change a value's mathematical meaning are still allowed —
     /// that is the whole point of an explicit cast word.
+    ///
+    /// A `Char` source casts as the `u32` of its scalar value. `:as-char`
+    /// (`Cast(Ty::Char)`) goes the other way from any integer, and is a
+    /// runtime error unless the value is a Unicode scalar value.
     Cast(Ty),
     /// Read one newline-terminated line from stdin into the heap and push
     /// (line, got-line?). On EOF, line is the empty string and the Bool
@@ -203,9 +484,126 @@ pub enum Op {
     ReadLine,
     /// Pop two strings `haystack needle`; push `true` if `needle` is a
     /// substring of `haystack`, `false` otherwise. Byte-level match
-    /// (`strstr` semantics in the AOT runtime, `str::contains` in the
+    /// (a length-bounded scan in the AOT runtime, `str::contains` in the
     /// interpreter — both byte-equivalent for valid UTF-8).
     Contains,
+    /// Pop a string; push its length in chars (Unicode scalar values),
+    /// not bytes — `:len`.
+    StrLen,
+    /// Pop `s start end`; push the chars of `s` in `[start, end)`. A
+    /// range outside the string, or with `start > end`, is a runtime
+    /// error — `:slice`.
+    Slice,
+    /// Pop `s sep`; split `s` at the first occurrence of `sep` and push
+    /// `(before after true)`, or `(s "" false)` when `sep` does not occur.
+    /// An empty separator is a runtime error — `:split`.
+    Split,
+    /// Pop a string; push it without leading and trailing ASCII
+    /// whitespace — `:trim`.
+    Trim,
+    /// Pop `s prefix`; push whether `s` starts with `prefix` —
+    /// `:starts-with`.
+    StartsWith,
+    /// Pop `s suffix`; push whether `s` ends with `suffix` — `:ends-with`.
+    EndsWith,
+    /// Pop `s needle`; push the char index of the first occurrence of
+    /// `needle` and `true`, or `-1 false` when it does not occur —
+    /// `:find`.
+    Find,
+    /// Pop `s from to`; push `s` with every non-overlapping occurrence of
+    /// `from` replaced by `to`, scanning left to right. An empty pattern
+    /// is a runtime error — `:replace`.
+    Replace,
+    /// Pop a string; push it with ASCII letters upper-cased — `:upper`.
+    Upper,
+    /// Pop a string; push it with ASCII letters lower-cased — `:lower`.
+    Lower,
+    /// Pop `s n`; push `s` repeated `n` times. A negative count is a
+    /// runtime error — `:repeat`.
+    Repeat,
+    /// Pop a string; parse it as a decimal integer of the target width and
+    /// push `(n true)`, or `(0 false)` when it is not one — `:parse-i8` ...
+    /// `:parse-u64`. The accepted text is Rust's `str::parse`: an optional
+    /// sign (`-` only for signed targets), then one or more ASCII digits,
+    /// with no surrounding whitespace.
+    Parse(Ty),
+    /// Pop one value of any type; push the text `.` would show for it —
+    /// `:to-str`.
+    ToStr,
+    /// Pop an integer of any width; push its decimal digits without the
+    /// width suffix — `:to-digits`.
+    ToDigits,
+    /// Pop one value per placeholder of a `:format` template, deepest for
+    /// the first, and push the template with each placeholder replaced by
+    /// its value's text. The template was a literal, split into pieces at
+    /// compile time so the checker knows how many values it takes.
+    Format(Rc<[FormatPiece]>),
+    /// Pop `s i`, where `i` is a byte offset into `s`; push the char that
+    /// starts there, the offset just past it, and `true` — or `'\0' i
+    /// false` when `i` is the end of `s`. An offset that is not a char
+    /// boundary is a runtime error. The step of a walk over a string's
+    /// chars, which copies nothing — `:next-char`.
+    NextChar,
+    /// Pop `s i`; push the char at char index `i`. An index outside the
+    /// string is a runtime error — `:char-at`.
+    CharAt,
+    /// Pop a char; push the one-char string holding it — `:from-char`.
+    FromChar,
+    /// Like `ReadLine`, but the line is pushed as `Bytes`, so input that is
+    /// not UTF-8 reads rather than failing — `:readline-bytes`.
+    ReadLineBytes,
+    /// Read the rest of stdin and push it as `Bytes` — `:read-bytes`.
+    ReadBytes,
+    /// Pop a byte string; write its bytes to stdout as they are —
+    /// `:write-bytes`.
+    WriteBytes,
+    /// Pop a byte string; push it as a `Str` and `true` if it is valid
+    /// UTF-8, else `"" false` — `:utf8-decode`.
+    Utf8Decode,
+    /// Pop a string; push its UTF-8 bytes — `:utf8-encode`.
+    Utf8Encode,
+    /// Pop a byte string; push it as a `Str`, each invalid sequence
+    /// replaced by U+FFFD — `:utf8-decode-lossy`.
+    Utf8DecodeLossy,
+    /// Pop a byte string; push its length in bytes — `:bytes-len`.
+    BytesLen,
+    /// Pop `bytes start end`; push the bytes in `[start, end)` —
+    /// `:bytes-slice`.
+    BytesSlice,
+    /// Pop a path; push the file's contents and `true`, or `"" false` if
+    /// it cannot be read as UTF-8 text — `:read-file`.
+    ReadFile,
+    /// Pop `path text`; replace the file's contents with `text`, creating
+    /// it if need be, and push whether that worked — `:write-file`.
+    WriteFile,
+    /// Pop `path text`; add `text` to the end of the file, creating it if
+    /// need be, and push whether that worked — `:append-file`.
+    AppendFile,
+    /// Pop a path; push whether something exists there — `:file-exists`.
+    FileExists,
+    /// Pop a path; push a `File` handle and whether the open worked. A
+    /// handle from a failed open reads as already at its end —
+    /// `:open-file`.
+    OpenFile,
+    /// Pop a `File`; push its next line and `true`, or `"" false` at the
+    /// end, once closed, or for a line that is not UTF-8 —
+    /// `:file-readline`.
+    FileReadLine,
+    /// Pop a `File`; push its next line undecoded and `true`, or `b""
+    /// false` only at the end or once closed — `:file-readline-bytes`.
+    FileReadLineBytes,
+    /// Pop a `File` and close it. Closing twice is harmless —
+    /// `:close-file`.
+    CloseFile,
+    /// Push how many arguments followed the program on the command
+    /// line — `:argc`.
+    ArgCount,
+    /// Pop `i`; push the `i`-th argument and `true`, counting from `0`,
+    /// or `"" false` if there is none or it is not UTF-8 — `:arg`.
+    Arg,
+    /// Pop a name; push the environment variable's value and `true`, or
+    /// `"" false` if it is unset or not UTF-8 — `:env`.
+    Env,
     /// Pop one string; write its bytes to stdout followed by a `\n`.
     /// This is the bare-text output primitive; `.` remains the stack
     /// introspection word.
@@ -213,21 +611,63 @@ pub enum Op {
     /// Pop one value of any type and render it without a newline. The
     /// rendering matches one entry in the `.` stack display.
     Print,
+    /// `PrintLn` to stderr, for diagnostics — `:eprintln`.
+    EPrintLn,
+    /// `Print` to stderr — `:eprint`.
+    EPrint,
+    /// Pop an `i32` and end the program with it as the exit status —
+    /// `:exit`.
+    Exit,
+    /// Declare a struct type (`struct NAME { ... }`). Its words were
+    /// resolved at compile time; running this only records the declaration
+    /// so later source, and the rendering of its values, can find it.
+    DefineStruct(Rc<StructTy>),
+    /// Declare a constant (`const NAME value ;`). Its uses were compiled
+    /// to the literal op; running this only records the pair so later
+    /// source can use the name too.
+    DefineConst(String, Box<Op>),
+    /// Pop one value per field, deepest first, and push a record of the
+    /// struct built from them — the `:NAME` constructor word.
+    MakeStruct(Rc<StructTy>),
+    /// Pop a record; push its `i`-th field — `:NAME.FIELD`.
+    GetField(Rc<StructTy>, u8),
+    /// Pop a record and a value; push a copy of the record with its `i`-th
+    /// field replaced by the value — `:NAME.with-FIELD`.
+    SetField(Rc<StructTy>, u8),
 }
 
-/// One arm of a [`Op::Match`]. The pattern is matched against the popped
-/// value; if the match succeeds, `body` is executed against the current
-/// data stack and the enclosing call's locals frame.
+/// One arm of a [`Op::Match`]. The patterns are matched against the popped
+/// values, the first pattern against the deepest; if all of them match and
+/// the `guard` (if any) leaves `true`, `body` is executed against the
+/// current data stack and the enclosing call's locals frame.
+///
+/// `binds` lists the columns whose matched values the arm names. They
+/// become locals of the guard and body, in that order, numbered after the
+/// locals already in scope where the match is.
+///
+/// The guard runs on a stack holding only copies of the matched values, and
+/// must leave exactly one `Bool`. It shares the enclosing call's locals.
 #[derive(Clone, Debug, PartialEq)]
 pub struct MatchArm {
-    pub pattern: Pattern,
+    pub patterns: Vec<Pattern>,
+    pub binds: Vec<usize>,
+    pub guard: Option<Rc<[Op]>>,
     pub body: Rc<[Op]>,
 }
 
-/// What a match-arm pattern can be. Today: typed literals plus the wildcard.
-/// Sum-type patterns with payload binders are designed (§11.8) but deferred
-/// until sum types themselves land (§12.14).
-#[derive(Clone, Copy, Debug, PartialEq)]
+impl MatchArm {
+    /// The op sequences this arm holds — its guard, if any, then its body —
+    /// for passes that walk every op in the source.
+    pub fn blocks(&self) -> impl Iterator<Item = &[Op]> {
+        self.guard.as_deref().into_iter().chain([&*self.body])
+    }
+}
+
+/// What a match-arm pattern can be. Today: typed literals, integer ranges,
+/// alternatives of those, and the wildcard. Sum-type patterns with payload
+/// binders are designed (§11.8) but deferred until sum types themselves
+/// land (§12.14).
+#[derive(Clone, Debug, PartialEq)]
 pub enum Pattern {
     /// An integer pattern. Unsuffixed literals retain the historic `i64`
     /// spelling and may match a narrower scrutinee when they fit; a suffixed
@@ -236,11 +676,49 @@ pub enum Pattern {
         value: Value,
         explicit_ty: bool,
     },
+    /// An inclusive integer range, `lo..=hi`. A missing bound (`..=hi`,
+    /// `lo..`) stands for the matched type's own limit. Each bound follows
+    /// the rules of an `Int` pattern.
+    Range {
+        lo: Option<IntLiteral>,
+        hi: Option<IntLiteral>,
+    },
     Str(StrId),
     Bool(bool),
+    Char(char),
+    /// An inclusive char range, `'a'..='z'`, with the same missing-bound
+    /// spellings as `Range`.
+    CharRange {
+        lo: Option<char>,
+        hi: Option<char>,
+    },
+    /// Any one of several patterns, `1 | 2 | 3`. Never nested, and never
+    /// holds fewer than two alternatives.
+    Or(Rc<[Pattern]>),
     Wildcard,
 }
 
+/// One piece of a `:format` template: literal text or a placeholder.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormatPiece {
+    /// Literal text, with `{{` and `}}` already unescaped.
+    Text(StrId),
+    /// A `{...}` placeholder, filled from one popped value.
+    Hole(FormatSpec),
+}
+
+/// How a `:format` placeholder renders its value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatSpec {
+    /// `{}`: text as-is, integers as bare digits, anything else as `.`
+    /// shows it.
+    Display,
+    /// `{:?}`: exactly as `.` shows the value — `:to-str`.
+    Debug,
+    /// `{:x}`: an integer's bit pattern in lowercase hex, at its width.
+    Hex,
+}
+
 /// A compiled function: the signature (§11.2), the docstring (§11.7), and
 /// the body.
 ///
@@ -252,21 +730,117 @@ pub struct CompiledFn {
     pub sig: Rc<FnSig>,
     pub doc: Rc<str>,
     pub body: Rc<[Op]>,
+    /// Declared `private` inside a `module`: callable only from functions
+    /// of the same module.
+    pub private: bool,
+}
+
+/// Where `import "path"` looks for files.
+///
+/// A relative path is tried against `base` first — the directory of the file
+/// doing the importing — and then against each `search` directory in order.
+/// The first existing file wins. An absolute path is used as written.
+#[derive(Clone, Debug, Default)]
+pub struct ImportPaths {
+    /// Directory of the source being compiled. `None` for text with no file
+    /// behind it (the REPL, an embedder's string), which resolves against
+    /// the process's working directory.
+    pub base: Option<PathBuf>,
+    /// Further directories to try, in order.
+    pub search: Vec<PathBuf>,
+    /// Refuse every `import` at compile time, before any path is looked
+    /// up — for a [`crate::Vm`] whose filesystem access is off.
+    pub refused: bool,
 }
 
 /// Compile lexed words into ops, interning string literals into `heap`.
 ///
 /// This is the only path from `Tok` to `Op`. It is used both for top-level
-/// source and, recursively, for function bodies, so it depends on nothing but
-/// the `Heap`.
-pub fn compile(toks: &[Tok], heap: &mut Heap) -> Result<Vec<Op>> {
-    Compiler {
+/// source and, recursively, for function bodies and imported files, so it
+/// depends on nothing but the `Heap`, where imports resolve, and what
+/// earlier compilations defined: the struct types in `prior_structs` and
+/// the function names in `prior_fns`, typically the VM's, so a REPL line
+/// can use a struct an earlier line declared and add to a module an
+/// earlier line opened, and the constants in `prior_consts`.
+pub fn compile(
+    toks: &[Tok],
+    heap: &mut Heap,
+    imports: &ImportPaths,
+    prior_structs: &HashMap<String, Rc<StructTy>>,
+    prior_fns: &HashSet<String>,
+    prior_consts: &HashMap<String, Op>,
+) -> Result<Vec<Op>> {
+    compile_with_bare_words(toks, heap, imports, prior_structs, prior_fns, prior_consts)
+        .map(|(ops, _)| ops)
+}
+
+/// Like [`compile`], but also report the `StrId` of every top-level bare
+/// word that fell through to text. A bare word and a `"..."` literal compile
+/// to the same `Op::PushStr`; the linter needs to tell `doubel` (likely a
+/// typo for `:double`) from a deliberately quoted `"doubel"`.
+pub fn compile_with_bare_words(
+    toks: &[Tok],
+    heap: &mut Heap,
+    imports: &ImportPaths,
+    prior_structs: &HashMap<String, Rc<StructTy>>,
+    prior_fns: &HashSet<String>,
+    prior_consts: &HashMap<String, Op>,
+) -> Result<(Vec<Op>, Vec<StrId>)> {
+    let mut consts = HashMap::new();
+    let mut structs = prior_structs.clone();
+    let mut modules: HashMap<String, HashSet<String>> = HashMap::new();
+    for name in prior_fns.iter().filter(|n| !is_quotation(n)) {
+        if let Some((module, name)) = name.rsplit_once('.') {
+            modules
+                .entry(module.to_string())
+                .or_default()
+                .insert(name.to_string());
+        }
+    }
+    let mut hoisted = Vec::new();
+    let mut loader = Loader {
+        search: imports.search.clone(),
+        refused: imports.refused,
+        active: Vec::new(),
+        done: HashSet::new(),
+    };
+    let mut compiler = Compiler {
         toks,
         pos: 0,
         heap,
         local_scopes: Vec::new(),
-    }
-    .compile_seq(Stop::EndOfInput)
+        bare_words: Vec::new(),
+        dir: imports.base.clone(),
+        loader: &mut loader,
+        module: None,
+        modules: &mut modules,
+        consts: &mut consts,
+        prior_consts,
+        structs: &mut structs,
+        hoisted: &mut hoisted,
+        op_starts: Vec::new(),
+    };
+    let body = compiler.compile_seq(Stop::EndOfInput)?;
+    let bare_words = compiler.bare_words;
+    let mut ops = hoisted;
+    ops.extend(body);
+    let mut private = HashSet::new();
+    collect_private(&ops, &mut private);
+    check_visibility(&ops, "", &private)?;
+    check_struct_names(&ops, &HashSet::new())?;
+    Ok((ops, bare_words))
+}
+
+/// Import bookkeeping shared by every file in one compilation: the search
+/// directories, whether imports are refused at all, the chain of files
+/// currently being compiled (for cycle detection), and the files already
+/// compiled (so each is compiled once).
+/// Paths are canonical, so two spellings of one file are the same entry.
+struct Loader {
+    search: Vec<PathBuf>,
+    refused: bool,
+    active: Vec<PathBuf>,
+    done: HashSet<PathBuf>,
 }
 
 /// What ends the run of tokens a [`Compiler::compile_seq`] call is reading.
@@ -291,13 +865,53 @@ enum Stop {
 /// `local_scopes` is a stack only so that nested definitions can push and pop
 /// cleanly; per §11.5, **only the innermost (topmost) scope is visible** at
 /// any point. Outer scopes are inaccessible by design: nested functions do
-/// not see their enclosing function's locals. Match-arm bodies do *not* push
-/// a new scope — they share their enclosing function's locals (§11.8).
+/// not see their enclosing function's locals, and a closure sees only the
+/// ones its `with` list names. Match-arm bodies do *not* push a new scope —
+/// they share their enclosing function's locals (§11.8).
 struct Compiler<'t, 'src> {
     toks: &'t [Tok<'src>],
     pos: usize,
     heap: &'t mut Heap,
-    local_scopes: Vec<Vec<String>>,
+    local_scopes: Vec<Vec<(String, Ty)>>,
+    /// Top-level bare words that compiled to text, in source order. Only
+    /// the linter reads this; see [`compile_with_bare_words`].
+    bare_words: Vec<StrId>,
+    /// Directory relative imports resolve against: the directory of the
+    /// file these tokens came from, or `None` for the working directory.
+    dir: Option<PathBuf>,
+    loader: &'t mut Loader,
+    /// The `module` whose body is being compiled, if any.
+    module: Option<Module>,
+    /// Every module opened so far, by name, with the functions it defines,
+    /// earlier inputs' included, so reopening a module can call what the
+    /// earlier block defined unqualified. Shared with imported files.
+    modules: &'t mut HashMap<String, HashSet<String>>,
+    /// `const` declarations seen so far, each as the literal op a use of
+    /// its name compiles to. Shared with imported files, like `loader`.
+    consts: &'t mut HashMap<String, Op>,
+    /// Constants earlier compilations declared, which this source may use
+    /// and, unlike its own, declare again.
+    prior_consts: &'t HashMap<String, Op>,
+    /// `struct` declarations seen so far, and those declared before this
+    /// compilation. Shared with imported files, like `consts`.
+    structs: &'t mut HashMap<String, Rc<StructTy>>,
+    /// Quotation bodies, as `DefineFn`s, in the order they were compiled.
+    /// They are placed ahead of the program's own ops, so every quotation
+    /// is defined before anything can push a reference to it.
+    hoisted: &'t mut Vec<Op>,
+    /// The token each top-level op began at, index for index with the ops
+    /// the `Stop::EndOfInput` sequence returns, so an import can name the
+    /// source word of an op it refuses.
+    op_starts: Vec<usize>,
+}
+
+/// A namespace opened by `module NAME ... end`. `names` is every function
+/// the body defines, gathered before the body is compiled so an unqualified
+/// `:name` can reach a definition further down the module, together with
+/// those an earlier block of the same module defined.
+struct Module {
+    name: String,
+    names: HashSet<String>,
 }
 
 impl Compiler<'_, '_> {
@@ -305,67 +919,255 @@ impl Compiler<'_, '_> {
     /// consuming the terminating delimiter where there is one.
     fn compile_seq(&mut self, stop: Stop) -> Result<Vec<Op>> {
         let mut ops = Vec::new();
+        let mut start = self.pos;
         while let Some(tok) = self.toks.get(self.pos).copied() {
+            if stop == Stop::EndOfInput {
+                self.op_starts.resize(ops.len(), start);
+                start = self.pos;
+            }
             self.pos += 1;
             match tok {
                 Tok::Word(";") if stop == Stop::Semicolon => return Ok(ops),
                 Tok::Word(";") => return Err("';' has no matching ':'".into()),
                 Tok::Word("]") if stop == Stop::CloseBracket => return Ok(ops),
                 Tok::Word("]") => return Err("']' has no matching '['".into()),
-                Tok::Word("[") => return Err("'[' is only valid inside a `match` arm".into()),
+                Tok::Word("[") => return Err(
+                    "'[' is only valid as a `match` arm or after a quotation's `{ ... }` header"
+                        .into(),
+                ),
+                Tok::Word("{") => {
+                    self.pos -= 1;
+                    ops.push(self.compile_quotation()?);
+                }
                 Tok::Word("end") => return Err("`end` has no matching `match`".into()),
-                Tok::Word("match") => ops.push(self.compile_match()?),
-                Tok::Word(":") => ops.push(self.compile_definition()?),
+                Tok::Word("match") => ops.push(self.compile_match(1)?),
+                Tok::Word("match2") => ops.push(self.compile_match(2)?),
+                Tok::Word("module") => {
+                    if stop != Stop::EndOfInput
+                        || !self.local_scopes.is_empty()
+                        || self.module.is_some()
+                    {
+                        return Err("`module` is only valid at the top level".into());
+                    }
+                    ops.extend(self.compile_module()?);
+                }
+                Tok::Word("private") if self.module.is_some() => {
+                    if self.toks.get(self.pos) != Some(&Tok::Word(":")) {
+                        return Err("`private` must be followed by a definition".into());
+                    }
+                    self.pos += 1;
+                    ops.push(self.compile_definition(true)?);
+                }
+                Tok::Word("const") => {
+                    if stop != Stop::EndOfInput || !self.local_scopes.is_empty() {
+                        return Err("`const` is only valid at the top level".into());
+                    }
+                    ops.push(self.compile_const()?);
+                }
+                Tok::Word("struct") => {
+                    if stop != Stop::EndOfInput || !self.local_scopes.is_empty() {
+                        return Err("`struct` is only valid at the top level".into());
+                    }
+                    ops.push(self.compile_struct()?);
+                }
+                Tok::Word("import") => {
+                    if stop != Stop::EndOfInput || !self.local_scopes.is_empty() {
+                        return Err("`import` is only valid at the top level".into());
+                    }
+                    if let Some(op) = self.compile_import()? {
+                        ops.push(op);
+                    }
+                }
+                Tok::Word(":") => ops.push(self.compile_definition(false)?),
+                Tok::Word(":format") => {
+                    // Only a `"..."` or `r"..."` token: a bare word or a
+                    // constant also compiles to `PushStr`, but is no template.
+                    let literal = matches!(
+                        self.pos.checked_sub(2).map(|i| self.toks[i]),
+                        Some(Tok::Text(_) | Tok::RawText(_))
+                    );
+                    let (true, Some(Op::PushStr(template))) = (literal, ops.pop()) else {
+                        return Err("`:format` must directly follow its template, a \
+                                    `\"...\"` or `r\"...\"` string literal"
+                            .into());
+                    };
+                    let template = self.heap.str(template).to_string();
+                    ops.push(Op::Format(parse_format(&template, self.heap)?.into()));
+                }
                 Tok::Word(w) => match self.lookup_local(w) {
                     Some(ix) => ops.push(Op::LoadLocal(ix)),
                     None => {
+                        if let Some(qualified) = self.module_call(w) {
+                            ops.push(Op::Call(qualified));
+                            continue;
+                        }
+                        if let Some(op) = self.const_op(w) {
+                            ops.push(op.clone());
+                            continue;
+                        }
+                        if let Some(op) = self.struct_word(w)? {
+                            ops.push(op);
+                            continue;
+                        }
                         let op = compile_word(w, self.heap)?;
-                        if !self.local_scopes.is_empty() && matches!(op, Op::PushStr(_)) {
-                            return Err(format!(
-                                "unknown word `{w}` in a function body; quote text as \"{w}\""
-                            )
-                            .into());
+                        if let Op::PushStr(id) = op {
+                            if !self.local_scopes.is_empty() {
+                                return Err(format!(
+                                    "unknown word `{w}` in a function body; quote text as \"{w}\""
+                                )
+                                .into());
+                            }
+                            self.bare_words.push(id);
                         }
                         ops.push(op);
                     }
                 },
-                Tok::Text(s) => ops.push(Op::PushStr(self.heap.add_str(unescape(s)?))),
+                Tok::Text(_) | Tok::RawText(_) => {
+                    ops.push(Op::PushStr(self.heap.add_str(text_value(tok)?)))
+                }
             }
         }
         match stop {
             Stop::Semicolon => Err("':' has no matching ';'".into()),
             Stop::CloseBracket => Err("'[' has no matching ']'".into()),
-            Stop::EndOfInput => Ok(ops),
+            Stop::EndOfInput => {
+                self.op_starts.resize(ops.len(), start);
+                Ok(ops)
+            }
         }
     }
 
-    /// If `name` is one of the enclosing function's input names, return its
-    /// index. Only the innermost (topmost) scope is consulted — nested
-    /// definitions deliberately do not inherit outer locals (§11.5).
+    /// If `name` is one of the enclosing function's input names, or a value
+    /// bound by an enclosing match arm, return its index. Only the innermost
+    /// (topmost) scope is consulted — nested definitions deliberately do not
+    /// inherit outer locals (§11.5). A binding shadows an input, or an outer
+    /// binding, of the same name.
     fn lookup_local(&self, name: &str) -> Option<u8> {
         let scope = self.local_scopes.last()?;
-        scope.iter().position(|n| n == name).map(|i| i as u8)
+        scope.iter().rposition(|(n, _)| n == name).map(|i| i as u8)
+    }
+
+    /// Inside a module, `:name` for a function the module defines calls the
+    /// qualified `module.name` — ahead of any builtin of the same spelling,
+    /// since builtin names are reserved only in the root namespace.
+    fn module_call(&self, word: &str) -> Option<String> {
+        let module = self.module.as_ref()?;
+        let name = word.strip_prefix(':')?;
+        module
+            .names
+            .contains(name)
+            .then(|| format!("{}.{name}", module.name))
+    }
+
+    /// Compile a `module NAME ... end` block. The `module` word has already
+    /// been consumed. The body may contain only definitions, each optionally
+    /// marked `private`; they compile to ordinary `DefineFn`s under their
+    /// qualified names, so a module leaves no trace in the op stream.
+    fn compile_module(&mut self) -> Result<Vec<Op>> {
+        let name = match self.toks.get(self.pos).copied() {
+            Some(Tok::Word(w)) if is_valid_module_name(w) => w.to_string(),
+            Some(Tok::Word(w)) => return Err(format!("`{w}` is not a valid module name").into()),
+            _ => return Err("`module` must be followed by a module name".into()),
+        };
+        self.pos += 1;
+        let mut names = self.scan_module_names();
+        if let Some(earlier) = self.modules.get(&name) {
+            names.extend(earlier.iter().cloned());
+        }
+        self.module = Some(Module {
+            names: names.clone(),
+            name: name.clone(),
+        });
+        let result = self.compile_module_body(&name);
+        self.module = None;
+        self.modules.insert(name, names);
+        result
+    }
+
+    fn compile_module_body(&mut self, name: &str) -> Result<Vec<Op>> {
+        let mut ops = Vec::new();
+        while let Some(tok) = self.toks.get(self.pos).copied() {
+            self.pos += 1;
+            match tok {
+                Tok::Word("end") => return Ok(ops),
+                Tok::Word(":") => ops.push(self.compile_definition(false)?),
+                Tok::Word("private") if self.toks.get(self.pos) == Some(&Tok::Word(":")) => {
+                    self.pos += 1;
+                    ops.push(self.compile_definition(true)?);
+                }
+                _ => return Err(format!("module `{name}` may contain only definitions").into()),
+            }
+        }
+        Err(format!("`module {name}` has no matching `end`").into())
+    }
+
+    /// The name after every `:` between the cursor and the `end` closing
+    /// the module, nested definitions included. `match` opens an `end` of
+    /// its own, so the scan counts them to find the right one.
+    fn scan_module_names(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        let mut depth = 0usize;
+        for pair in self.toks[self.pos..].windows(2) {
+            match pair {
+                [Tok::Word("match" | "match2"), _] => depth += 1,
+                [Tok::Word("end"), _] if depth == 0 => break,
+                [Tok::Word("end"), _] => depth -= 1,
+                [Tok::Word(":"), Tok::Word(name)] => {
+                    names.insert(name.to_string());
+                }
+                _ => {}
+            }
+        }
+        names
     }
 
     /// Compile a `: name { sig } "doc" body... ;` definition. The opening `:`
     /// has already been consumed; the cursor sits on the name. A nested `:`
     /// inside the body is handled by the recursive `compile_seq` call, so
     /// definitions nest.
-    fn compile_definition(&mut self) -> Result<Op> {
+    fn compile_definition(&mut self, private: bool) -> Result<Op> {
         let name = match self.toks.get(self.pos).copied() {
             Some(Tok::Word(w)) if w != ":" && w != ";" => w.to_string(),
             Some(Tok::Word(_)) | None => {
                 return Err("':' must be followed by a function name".into())
             }
-            Some(Tok::Text(_)) => {
+            Some(Tok::Text(_) | Tok::RawText(_)) => {
                 return Err("a function name must be a plain word, not a text literal".into())
             }
         };
         self.pos += 1;
-        if is_reserved_function_name(&name) {
-            return Err(format!("function name `{name}` is reserved for a builtin word").into());
+        if name.contains('.') {
+            return Err(format!(
+                "function name `{name}` may not contain '.'; qualified names \
+                 come from `module`"
+            )
+            .into());
         }
+        // Builtins own their `:name` spellings only in the root namespace;
+        // inside a module the definition is reached as `:module.name`.
+        let name = match &self.module {
+            Some(module) => format!("{}.{name}", module.name),
+            None if is_reserved_function_name(&name) => {
+                return Err(
+                    format!("function name `{name}` is reserved for a builtin word").into(),
+                );
+            }
+            None if self.structs.contains_key(&name) => {
+                return Err(format!(
+                    "function name `{name}` is taken by the constructor of struct `{name}`"
+                )
+                .into());
+            }
+            None => name,
+        };
         let sig: Rc<FnSig> = self.compile_sig(&name)?.into();
+        if sig.is_inferred() && !self.local_scopes.is_empty() {
+            return Err(format!(
+                "function `{name}`: only top-level definitions may infer their \
+                 signature with `?`"
+            )
+            .into());
+        }
         if sig.inputs.len() > u8::MAX as usize {
             return Err(format!(
                 "function `{name}` has too many inputs \
@@ -378,9 +1180,9 @@ impl Compiler<'_, '_> {
         // A docstring is optional. When present, it must immediately follow
         // the header, so tools can still identify it without parsing a body.
         let doc: Rc<str> = match self.toks.get(self.pos).copied() {
-            Some(Tok::Text(s)) => {
+            Some(tok @ (Tok::Text(_) | Tok::RawText(_))) => {
                 self.pos += 1;
-                unescape(s)?.into()
+                text_value(tok)?.into()
             }
             Some(_) | None => "".into(),
         };
@@ -388,8 +1190,7 @@ impl Compiler<'_, '_> {
         // a fresh scope per definition is what gives nested definitions their
         // own (non-inheriting) frame; pop on every exit, success or error, so
         // the scope stack tracks the lexical structure faithfully.
-        let locals: Vec<String> = sig.inputs.iter().map(|(n, _)| n.clone()).collect();
-        self.local_scopes.push(locals);
+        self.local_scopes.push(sig.inputs.clone());
         let body_result = self.compile_seq(Stop::Semicolon);
         self.local_scopes.pop();
         let mut body = body_result?;
@@ -403,112 +1204,654 @@ impl Compiler<'_, '_> {
                 sig,
                 doc,
                 body: body.into(),
+                private,
             },
         ))
     }
 
-    /// Compile a `match PATTERN [ BODY ] PATTERN [ BODY ] ... end` dispatch.
-    /// The opening `match` has already been consumed; the cursor sits on the
-    /// first pattern (or on `end` for an empty match, which is rejected).
-    fn compile_match(&mut self) -> Result<Op> {
-        let mut arms: Vec<MatchArm> = Vec::new();
-        loop {
-            // Pattern or end-of-match.
-            let pattern = match self.toks.get(self.pos).copied() {
-                Some(Tok::Word("end")) => {
-                    self.pos += 1;
-                    break;
-                }
-                Some(Tok::Word("[")) => {
-                    return Err("match arm is missing a pattern before `[`".into())
-                }
-                Some(Tok::Word(";")) | Some(Tok::Word("]")) | None => {
-                    return Err("`match` has no matching `end`".into())
-                }
-                Some(Tok::Word(w)) => {
-                    self.pos += 1;
-                    parse_pattern_word(w)?
-                }
-                Some(Tok::Text(s)) => {
-                    self.pos += 1;
-                    Pattern::Str(self.heap.add_str(unescape(s)?))
-                }
-            };
-            // Opening bracket — patterns are followed *only* by `[`.
-            match self.toks.get(self.pos).copied() {
-                Some(Tok::Word("[")) => self.pos += 1,
-                _ => {
-                    return Err(
-                        "match arm pattern must be followed by `[` to open the arm body".into(),
-                    )
-                }
-            }
-            // Body, up to the matching `]`. `compile_seq` consumes the `]`.
-            let body = self.compile_seq(Stop::CloseBracket)?;
-            arms.push(MatchArm {
-                pattern,
-                body: body.into(),
-            });
+    /// Compile a quotation `{ sig } [ body ]` — an anonymous function. The
+    /// cursor sits on the `{`. The body is compiled exactly like a
+    /// definition's, with its own inputs as its only locals (§11.5), under
+    /// a generated name no source word can spell; the definition is hoisted
+    /// to the front of the program and the quotation itself compiles to an
+    /// `Op::PushFn` of that name.
+    ///
+    /// `{ sig } with a b [ body ]` is a closure: `a` and `b` name inputs of
+    /// the enclosing function, whose values are copied into the closure when
+    /// it is built. They become the hoisted body's leading inputs, ahead of
+    /// the declared ones, and the quotation compiles to `Op::PushClosure`.
+    fn compile_quotation(&mut self) -> Result<Op> {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        // Inside a module the quotation belongs to it, so its body may call
+        // the module's private helpers.
+        let name = match &self.module {
+            Some(module) => format!("{}.<quote {n}>", module.name),
+            None => format!("<quote {n}>"),
+        };
+        let declared = self.compile_sig(&name)?;
+        // A reference is a single value with a single type; a generic one
+        // could not be compiled ahead of time without knowing every type it
+        // will be called at.
+        if declared.is_generic() {
+            return Err("a quotation's header may not use type or row variables".into());
         }
-        if arms.is_empty() {
-            return Err("`match` requires at least one arm".into());
+        if declared.is_inferred() {
+            return Err("a quotation's header may not use `?`; write its types".into());
+        }
+        let mut inputs = Vec::new();
+        let slots = self.compile_captures(&declared, &mut inputs)?;
+        let captured = slots.len();
+        inputs.extend(declared.inputs);
+        let sig = Rc::new(FnSig {
+            inputs,
+            outputs: declared.outputs,
+            rest: None,
+            infer_outputs: false,
+        });
+        if sig.inputs.len() > u8::MAX as usize {
+            return Err(format!(
+                "quotation has too many inputs and captures (max {}, got {})",
+                u8::MAX,
+                sig.inputs.len()
+            )
+            .into());
+        }
+        if self.toks.get(self.pos) != Some(&Tok::Word("[")) {
+            return Err("a quotation's `{ ... }` header must be followed by `[ body ]`".into());
+        }
+        self.pos += 1;
+        self.local_scopes.push(sig.inputs.clone());
+        let body_result = self.compile_seq(Stop::CloseBracket);
+        self.local_scopes.pop();
+        let mut body = body_result?;
+        mark_tail_calls(&mut body);
+        self.hoisted.push(Op::DefineFn(
+            name.clone(),
+            CompiledFn {
+                sig,
+                doc: "".into(),
+                body: body.into(),
+                private: false,
+            },
+        ));
+        let id = self.heap.add_str(name.clone());
+        if captured == 0 {
+            Ok(Op::PushFn(name, id))
+        } else {
+            Ok(Op::PushClosure(name, id, slots.into()))
         }
-        Ok(Op::Match(arms.into()))
     }
 
-    /// Compile a `{ name Type ... -> Type ... }` header (§11.2).
-    ///
-    /// Inputs are `name Type` pairs; outputs are either bare `Type`s or
-    /// `name Type` pairs (the name is documentation-only and discarded).
-    /// The `->` is mandatory; both sides may be empty. `fn_name` is used for
-    /// error messages only.
-    fn compile_sig(&mut self, fn_name: &str) -> Result<FnSig> {
-        match self.toks.get(self.pos).copied() {
-            Some(Tok::Word("{")) => self.pos += 1,
-            _ => {
+    /// Parse a quotation's optional `with NAME...` capture list, which runs
+    /// up to the body's `[`. Each name must be an input of the enclosing
+    /// function. Returns the captured locals' slots, and appends their names
+    /// and types, as the closure body will see them, to `inputs`.
+    fn compile_captures(
+        &mut self,
+        declared: &FnSig,
+        inputs: &mut Vec<(String, Ty)>,
+    ) -> Result<Vec<u8>> {
+        let mut slots = Vec::new();
+        if self.toks.get(self.pos) != Some(&Tok::Word("with")) {
+            return Ok(slots);
+        }
+        self.pos += 1;
+        while let Some(Tok::Word(w)) = self.toks.get(self.pos).copied() {
+            if w == "[" {
+                break;
+            }
+            self.pos += 1;
+            let Some(slot) = self.lookup_local(w) else {
                 return Err(format!(
-                    "function `{fn_name}` is missing a type header \
-                     (expected `{{ ... -> ... }}` after the name)"
+                    "quotation captures `{w}`, which is not an input of the enclosing function"
                 )
-                .into())
+                .into());
+            };
+            if inputs.iter().any(|(n, _)| n == w) {
+                return Err(format!("quotation captures `{w}` twice").into());
+            }
+            if declared.inputs.iter().any(|(n, _)| n == w) {
+                return Err(
+                    format!("quotation captures `{w}` and also declares it as an input").into(),
+                );
+            }
+            let ty = self
+                .local_scopes
+                .last()
+                .expect("lookup_local found a scope")[slot as usize]
+                .1
+                .clone();
+            if ty.is_generic() {
+                return Err(format!(
+                    "quotation captures `{w}`, whose type {ty} is generic; \
+                     only concrete values can be captured"
+                )
+                .into());
+            }
+            // A match-arm binding's type is only known once the match is
+            // checked, so it is in scope as a hole too.
+            if let Ty::Hole(_) = ty {
+                return Err(format!(
+                    "quotation captures `{w}`, whose type is `?` or comes from a \
+                     match pattern; only inputs with a declared type can be captured"
+                )
+                .into());
             }
+            slots.push(slot);
+            inputs.push((w.to_string(), ty));
+        }
+        if slots.is_empty() {
+            return Err("`with` must name at least one input to capture".into());
         }
+        Ok(slots)
+    }
 
-        let mut inputs = Vec::new();
-        loop {
-            match self.toks.get(self.pos).copied() {
-                Some(Tok::Word("->")) => {
-                    self.pos += 1;
-                    break;
-                }
-                Some(Tok::Word("}")) => {
-                    return Err(format!(
-                        "function `{fn_name}` type header is missing `->` \
-                         (write `{{ -> ... }}` for a function with no inputs)"
-                    )
-                    .into())
-                }
-                Some(Tok::Word(w)) if parse_type(w).is_some() => {
+    /// Compile a `const NAME value ;` declaration. The `const` word has
+    /// already been consumed. The value must be a single literal; every
+    /// later use of `NAME` compiles to that literal's op, so a constant
+    /// costs nothing at run time and is typed like the literal it stands
+    /// for. Emits no op of its own.
+    fn compile_const(&mut self) -> Result<Op> {
+        let name = match self.toks.get(self.pos).copied() {
+            Some(Tok::Word(w)) if is_valid_const_name(w) => w.to_string(),
+            Some(Tok::Word(w)) => return Err(format!("`{w}` is not a valid constant name").into()),
+            _ => return Err("`const` must be followed by a name".into()),
+        };
+        if self.consts.contains_key(&name) {
+            return Err(format!("constant `{name}` is already defined").into());
+        }
+        let op = match self.toks.get(self.pos + 1).copied() {
+            Some(tok @ (Tok::Text(_) | Tok::RawText(_))) => {
+                Op::PushStr(self.heap.add_str(text_value(tok)?))
+            }
+            Some(Tok::Word(w)) => match compile_word(w, self.heap)? {
+                op @ (Op::PushInt(_) | Op::PushBool(_) | Op::PushChar(_)) => op,
+                _ => {
                     return Err(format!(
-                        "function `{fn_name}` type header: input requires a name \
-                         before the type `{w}` (write `{{ x {w} -> ... }}`)"
-                    )
+                    "constant `{name}` must be an integer, text, Bool, or Char literal, got `{w}`"
+                )
+                    .into())
+                }
+            },
+            None => return Err(format!("constant `{name}` has no value").into()),
+        };
+        if self.toks.get(self.pos + 2) != Some(&Tok::Word(";")) {
+            return Err(format!("constant `{name}` must end with ';' after its value").into());
+        }
+        self.pos += 3;
+        self.consts.insert(name.clone(), op.clone());
+        Ok(Op::DefineConst(name, Box::new(op)))
+    }
+
+    /// The literal op a use of constant `name` compiles to, if one is
+    /// declared — by this source first, then by an earlier one.
+    fn const_op(&self, name: &str) -> Option<&Op> {
+        self.consts
+            .get(name)
+            .or_else(|| self.prior_consts.get(name))
+    }
+
+    /// Compile a `struct NAME { field Type ... }` declaration. The `struct`
+    /// word has already been consumed. From here on `NAME` is a type, `:NAME`
+    /// builds a record from one value per field, `:NAME.FIELD` reads a
+    /// field, and `:NAME.with-FIELD` replaces one. Declaring the same struct
+    /// again with the same fields is allowed, so a file can be re-run.
+    fn compile_struct(&mut self) -> Result<Op> {
+        let name = match self.toks.get(self.pos).copied() {
+            Some(Tok::Word(w))
+                if is_valid_module_name(w)
+                    && parse_type(w).is_none()
+                    && !is_reserved_function_name(w) =>
+            {
+                w.to_string()
+            }
+            Some(Tok::Word(w)) => return Err(format!("`{w}` is not a valid struct name").into()),
+            _ => return Err("`struct` must be followed by a name".into()),
+        };
+        self.pos += 1;
+        if self.toks.get(self.pos) != Some(&Tok::Word("{")) {
+            return Err(format!("struct `{name}` must list its fields in `{{ ... }}`").into());
+        }
+        self.pos += 1;
+        let mut fields: Vec<(String, Ty)> = Vec::new();
+        loop {
+            let field = match self.toks.get(self.pos).copied() {
+                Some(Tok::Word("}")) => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(Tok::Word(w)) if is_valid_field_name(w) => w.to_string(),
+                Some(Tok::Word(w)) => {
+                    return Err(format!("struct `{name}`: `{w}` is not a valid field name").into())
+                }
+                _ => return Err(format!("struct `{name}` has no closing `}}`").into()),
+            };
+            self.pos += 1;
+            if fields.iter().any(|(f, _)| *f == field) {
+                return Err(format!("struct `{name}` declares field `{field}` twice").into());
+            }
+            let ty = match self.toks.get(self.pos).copied() {
+                Some(Tok::Word(w)) if self.is_type_start(w) => self.consume_type(&name)?,
+                Some(Tok::Word(w)) => {
+                    return Err(format!(
+                        "struct `{name}`: field `{field}` has type `{w}`, which is not \
+                         a known type"
+                    )
+                    .into())
+                }
+                _ => return Err(format!("struct `{name}`: field `{field}` needs a type").into()),
+            };
+            if ty.is_generic() {
+                return Err(format!(
+                    "struct `{name}`: field `{field}` has type {ty}; struct fields \
+                     may not use type or row variables"
+                )
+                .into());
+            }
+            fields.push((field, ty));
+        }
+        if fields.is_empty() {
+            return Err(format!("struct `{name}` must have at least one field").into());
+        }
+        if fields.len() > u8::MAX as usize {
+            return Err(format!(
+                "struct `{name}` has too many fields (max {}, got {})",
+                u8::MAX,
+                fields.len()
+            )
+            .into());
+        }
+        let st = Rc::new(StructTy {
+            name: name.as_str().into(),
+            fields,
+        });
+        match self.structs.get(&name) {
+            Some(prior) if *prior != st => {
+                Err(format!("struct `{name}` is already declared with different fields").into())
+            }
+            _ => {
+                self.structs.insert(name, Rc::clone(&st));
+                Ok(Op::DefineStruct(st))
+            }
+        }
+    }
+
+    /// The op for a struct's constructor, accessor, or update word, or
+    /// `None` if `word` does not name a declared struct.
+    fn struct_word(&self, word: &str) -> Result<Option<Op>> {
+        let Some(name) = word.strip_prefix(':') else {
+            return Ok(None);
+        };
+        let (name, field) = match name.split_once('.') {
+            Some((name, field)) => (name, Some(field)),
+            None => (name, None),
+        };
+        let Some(st) = self.structs.get(name) else {
+            return Ok(None);
+        };
+        let Some(field) = field else {
+            return Ok(Some(Op::MakeStruct(Rc::clone(st))));
+        };
+        let (update, field) = match field.strip_prefix("with-") {
+            Some(field) => (true, field),
+            None => (false, field),
+        };
+        let Some(i) = st.fields.iter().position(|(f, _)| f == field) else {
+            return Err(format!("struct `{name}` has no field `{field}`").into());
+        };
+        let st = Rc::clone(st);
+        Ok(Some(if update {
+            Op::SetField(st, i as u8)
+        } else {
+            Op::GetField(st, i as u8)
+        }))
+    }
+
+    /// Compile an `import "path"` form. The `import` word has already been
+    /// consumed; the cursor sits on the path literal. Returns `None` when
+    /// the file was already compiled earlier in this compilation — its
+    /// definitions are already on their way into the dictionary.
+    fn compile_import(&mut self) -> Result<Option<Op>> {
+        let raw = match self.toks.get(self.pos).copied() {
+            Some(tok @ (Tok::Text(_) | Tok::RawText(_))) => text_value(tok)?,
+            _ => return Err("`import` must be followed by a quoted path".into()),
+        };
+        self.pos += 1;
+        if self.loader.refused {
+            return Err(format!(
+                "import `{raw}`: `import` needs filesystem access, which is disabled"
+            )
+            .into());
+        }
+        let path = self.resolve_import(&raw)?;
+        if let Some(at) = self.loader.active.iter().position(|p| *p == path) {
+            let chain: Vec<String> = self.loader.active[at..]
+                .iter()
+                .chain([&path])
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(format!("import cycle: {}", chain.join(" -> ")).into());
+        }
+        if self.loader.done.contains(&path) {
+            return Ok(None);
+        }
+        let shown = path.display().to_string();
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| -> Box<dyn Error> { format!("import `{shown}`: {e}").into() })?;
+        let toks = lexer::lex(&source)
+            .map_err(|e| -> Box<dyn Error> { format!("in import `{shown}`: {e}").into() })?;
+        self.loader.active.push(path.clone());
+        let mut nested = Compiler {
+            toks: &toks,
+            pos: 0,
+            heap: self.heap,
+            local_scopes: Vec::new(),
+            bare_words: Vec::new(),
+            dir: path.parent().map(Path::to_path_buf),
+            loader: self.loader,
+            module: None,
+            modules: self.modules,
+            consts: self.consts,
+            prior_consts: self.prior_consts,
+            structs: self.structs,
+            hoisted: self.hoisted,
+            op_starts: Vec::new(),
+        };
+        let result = nested.compile_seq(Stop::EndOfInput);
+        let starts = nested.op_starts;
+        self.loader.active.pop();
+        let body =
+            result.map_err(|e| -> Box<dyn Error> { format!("in import `{shown}`: {e}").into() })?;
+        if let Some(i) = body.iter().position(|op| {
+            !matches!(
+                op,
+                Op::DefineFn(..) | Op::DefineStruct(..) | Op::DefineConst(..) | Op::Import(..)
+            )
+        }) {
+            let found = match toks[starts[i]] {
+                Tok::Word(w) => w.to_string(),
+                Tok::Text(t) => format!("\"{t}\""),
+                Tok::RawText(t) => format!("r\"{t}\""),
+            };
+            return Err(format!(
+                "in import `{shown}`: an imported file may contain only definitions \
+                 and imports, found `{found}`"
+            )
+            .into());
+        }
+        self.loader.done.insert(path);
+        Ok(Some(Op::Import(shown, body.into())))
+    }
+
+    /// Find the file `raw` names: as written if absolute, else relative to
+    /// this file's directory, else relative to each search directory.
+    fn resolve_import(&self, raw: &str) -> Result<PathBuf> {
+        let rel = Path::new(raw);
+        let mut tried: Vec<PathBuf> = Vec::new();
+        if rel.is_absolute() {
+            tried.push(rel.to_path_buf());
+        } else {
+            tried.push(match &self.dir {
+                Some(dir) => dir.join(rel),
+                None => rel.to_path_buf(),
+            });
+            tried.extend(self.loader.search.iter().map(|dir| dir.join(rel)));
+        }
+        for candidate in &tried {
+            if candidate.is_file() {
+                return Ok(candidate.canonicalize()?);
+            }
+        }
+        let looked: Vec<String> = tried.iter().map(|p| p.display().to_string()).collect();
+        Err(format!(
+            "import `{raw}`: file not found (looked for {})",
+            looked.join(", ")
+        )
+        .into())
+    }
+
+    /// Compile a `match PATTERN [ BODY ] PATTERN [ BODY ] ... end` dispatch,
+    /// or, with `arity` 2, a `match2 PATTERN PATTERN [ BODY ] ... end` one.
+    /// The opening word has already been consumed; the cursor sits on the
+    /// first pattern (or on `end` for an empty match, which is rejected).
+    fn compile_match(&mut self, arity: usize) -> Result<Op> {
+        let word = if arity == 1 { "match" } else { "match2" };
+        let mut arms: Vec<MatchArm> = Vec::new();
+        loop {
+            // Patterns or end-of-match. A column may also name its value,
+            // `PATTERN as NAME`; `_ as NAME` names whatever it is.
+            let mut patterns = Vec::with_capacity(arity);
+            let mut binders: Vec<(usize, &str)> = Vec::new();
+            while patterns.len() < arity {
+                let pattern = match self.toks.get(self.pos).copied() {
+                    Some(Tok::Word("end")) if patterns.is_empty() => break,
+                    Some(Tok::Word(w @ ("[" | "when"))) if arity == 1 => {
+                        return Err(format!("match arm is missing a pattern before `{w}`").into())
+                    }
+                    Some(Tok::Word("[" | "when" | "end")) => {
+                        return Err(format!(
+                            "`{word}` arm needs {arity} patterns before `[`, got {}",
+                            patterns.len()
+                        )
+                        .into())
+                    }
+                    Some(Tok::Word(";")) | Some(Tok::Word("]")) | None => {
+                        return Err(format!("`{word}` has no matching `end`").into())
+                    }
+                    Some(Tok::Word(w)) if self.const_op(w).is_some() => {
+                        return Err(format!(
+                            "constant `{w}` cannot be a match-arm pattern; use its value"
+                        )
+                        .into())
+                    }
+                    // A bare name is never a binder, so a misspelt literal
+                    // such as `tru` cannot quietly match everything.
+                    Some(Tok::Word(w)) if is_valid_binding_name(w) => {
+                        return Err(format!(
+                            "match-arm pattern `{w}` is not a recognised literal; \
+                             to name the matched value, write `_ as {w}`"
+                        )
+                        .into())
+                    }
+                    Some(Tok::Word(w)) => parse_pattern_word(w)?,
+                    Some(tok @ (Tok::Text(_) | Tok::RawText(_))) => {
+                        Pattern::Str(self.heap.add_str(text_value(tok)?))
+                    }
+                };
+                self.pos += 1;
+                let pattern = self.or_pattern(pattern, word)?;
+                if self.toks.get(self.pos).copied() == Some(Tok::Word("as")) {
+                    match self.toks.get(self.pos + 1).copied() {
+                        Some(Tok::Word(w)) if is_valid_binding_name(w) => {
+                            self.pos += 2;
+                            binders.push((patterns.len(), w));
+                        }
+                        _ => {
+                            return Err(format!(
+                                "`as` in a `{word}` arm must be followed by a name to bind"
+                            )
+                            .into())
+                        }
+                    }
+                }
+                patterns.push(pattern);
+            }
+            if patterns.is_empty() {
+                self.pos += 1;
+                break;
+            }
+            // Bound values are locals of the arm's guard and body, numbered
+            // after the ones already in scope.
+            let scope_len = self.local_scopes.last().map_or(0, Vec::len);
+            if !binders.is_empty() {
+                let Some(scope) = self.local_scopes.last_mut() else {
+                    return Err("match-arm bindings are only valid inside a function body".into());
+                };
+                for (i, &(_, name)) in binders.iter().enumerate() {
+                    if binders[..i].iter().any(|&(_, n)| n == name) {
+                        return Err(format!("match arm binds `{name}` twice").into());
+                    }
+                }
+                if scope_len + binders.len() > u8::MAX as usize {
+                    return Err(format!(
+                        "match arm binds too many locals (a function has at most {})",
+                        u8::MAX
+                    )
+                    .into());
+                }
+                for (i, &(_, name)) in binders.iter().enumerate() {
+                    scope.push((name.to_string(), Ty::Hole((scope_len + i) as u8)));
+                }
+            }
+            // An optional `when [ GUARD ]` between the patterns and the body.
+            let guard = match self.toks.get(self.pos).copied() {
+                Some(Tok::Word("when")) => {
+                    self.pos += 1;
+                    if self.toks.get(self.pos).copied() != Some(Tok::Word("[")) {
+                        return Err(format!(
+                            "`when` in a `{word}` arm must be followed by a `[ ... ]` guard"
+                        )
+                        .into());
+                    }
+                    self.pos += 1;
+                    Some(self.compile_seq(Stop::CloseBracket)?.into())
+                }
+                _ => None,
+            };
+            // Opening bracket — patterns are followed *only* by `[`.
+            match self.toks.get(self.pos).copied() {
+                Some(Tok::Word("[")) => self.pos += 1,
+                _ => {
+                    return Err(format!(
+                        "match arm pattern must be followed by `[` to open the arm body \
+                         (`{word}` takes {arity} pattern(s) per arm)"
+                    )
+                    .into())
+                }
+            }
+            // Body, up to the matching `]`. `compile_seq` consumes the `]`.
+            let body = self.compile_seq(Stop::CloseBracket)?;
+            if let Some(scope) = self.local_scopes.last_mut() {
+                scope.truncate(scope_len);
+            }
+            arms.push(MatchArm {
+                patterns,
+                binds: binders.iter().map(|&(column, _)| column).collect(),
+                guard,
+                body: body.into(),
+            });
+        }
+        if arms.is_empty() {
+            return Err(format!("`{word}` requires at least one arm").into());
+        }
+        Ok(Op::Match(arms.into()))
+    }
+
+    /// Extend the pattern just parsed into an or-pattern if `|` follows it,
+    /// consuming each `| PATTERN` in turn.
+    fn or_pattern(&mut self, first: Pattern, word: &str) -> Result<Pattern> {
+        let mut alternatives = vec![first];
+        while self.toks.get(self.pos).copied() == Some(Tok::Word("|")) {
+            self.pos += 1;
+            let alternative = match self.toks.get(self.pos).copied() {
+                Some(Tok::Word("[" | "]" | "|" | ";" | "end" | "when" | "as")) | None => {
+                    return Err(format!(
+                        "`|` in a `{word}` arm must be followed by another pattern"
+                    )
+                    .into())
+                }
+                Some(Tok::Word(w)) => parse_pattern_word(w)?,
+                Some(tok @ (Tok::Text(_) | Tok::RawText(_))) => {
+                    Pattern::Str(self.heap.add_str(text_value(tok)?))
+                }
+            };
+            self.pos += 1;
+            alternatives.push(alternative);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().expect("holds the first pattern")
+        } else {
+            Pattern::Or(alternatives.into())
+        })
+    }
+
+    /// Compile a `{ name Type ... -> Type ... }` header (§11.2).
+    ///
+    /// Inputs are `name Type` pairs; outputs are either bare `Type`s or
+    /// `name Type` pairs (the name is documentation-only and discarded).
+    /// The `->` is mandatory; both sides may be empty. `fn_name` is used for
+    /// error messages only.
+    fn compile_sig(&mut self, fn_name: &str) -> Result<FnSig> {
+        let subject = describe_fn(fn_name);
+        match self.toks.get(self.pos).copied() {
+            Some(Tok::Word("{")) => self.pos += 1,
+            _ => {
+                return Err(format!(
+                    "{subject} is missing a type header \
+                     (expected `{{ ... -> ... }}` after the name)"
+                )
+                .into())
+            }
+        }
+
+        // `{ ? }` is short for `{ -> ? }`.
+        if let Some([Tok::Word("?"), Tok::Word("}")]) = self.toks.get(self.pos..self.pos + 2) {
+            self.pos += 2;
+            return Ok(FnSig {
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                rest: None,
+                infer_outputs: true,
+            });
+        }
+
+        let rest = self.consume_row();
+        let mut inputs = Vec::new();
+        loop {
+            match self.toks.get(self.pos).copied() {
+                Some(Tok::Word("->")) => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(Tok::Word("}")) => {
+                    return Err(format!(
+                        "{subject} type header is missing `->` \
+                         (write `{{ -> ... }}` for a function with no inputs)"
+                    )
+                    .into())
+                }
+                Some(Tok::Word(w)) if row_var(w).is_some() => {
+                    return Err(misplaced_row(fn_name, w));
+                }
+                Some(Tok::Word(w)) if self.is_type_start(w) => {
+                    return Err(format!(
+                        "{subject} type header: input requires a name \
+                         before the type `{w}` (write `{{ x {w} -> ... }}`)"
+                    )
                     .into())
                 }
                 Some(Tok::Word(w)) if !w.is_empty() => {
                     if !is_valid_input_name(w) {
                         return Err(format!(
-                            "function `{fn_name}` type header: `{w}` is not a valid input name"
+                            "{subject} type header: `{w}` is not a valid input name"
                         )
                         .into());
                     }
                     self.pos += 1;
-                    let ty = self.consume_type(fn_name)?;
+                    let ty = if self.toks.get(self.pos) == Some(&Tok::Word("?")) {
+                        self.pos += 1;
+                        // Over-long input lists are rejected by the caller;
+                        // the hole's index only has to be right below that.
+                        Ty::Hole(inputs.len().min(u8::MAX as usize) as u8)
+                    } else {
+                        self.consume_type(fn_name)?
+                    };
                     inputs.push((w.to_string(), ty));
                 }
                 Some(_) | None => {
                     return Err(format!(
-                        "function `{fn_name}` type header: unexpected token \
+                        "{subject} type header: unexpected token \
                          while reading inputs"
                     )
                     .into())
@@ -516,16 +1859,31 @@ impl Compiler<'_, '_> {
             }
         }
 
-        let mut outputs = Vec::new();
+        let mut outputs: Vec<Ty> = self.consume_row().map(Ty::Row).into_iter().collect();
+        let mut infer_outputs = false;
         loop {
             match self.toks.get(self.pos).copied() {
                 Some(Tok::Word("}")) => {
                     self.pos += 1;
                     break;
                 }
-                Some(Tok::Word(w)) if parse_type(w).is_some() => {
-                    self.pos += 1;
-                    outputs.push(parse_type(w).expect("just checked"));
+                Some(Tok::Word("?"))
+                    if outputs.is_empty()
+                        && self.toks.get(self.pos + 1) == Some(&Tok::Word("}")) =>
+                {
+                    self.pos += 2;
+                    infer_outputs = true;
+                    break;
+                }
+                Some(Tok::Word("?")) => {
+                    return Err(format!(
+                        "{subject} type header: an inferred output list \
+                         is written `-> ? }}`, with nothing else"
+                    )
+                    .into())
+                }
+                Some(Tok::Word(w)) if self.is_type_start(w) || row_var(w).is_some() => {
+                    outputs.push(self.consume_type(fn_name)?)
                 }
                 Some(Tok::Word(_)) => {
                     // Named output: name, then type. The name is discarded.
@@ -535,7 +1893,7 @@ impl Compiler<'_, '_> {
                 }
                 Some(_) | None => {
                     return Err(format!(
-                        "function `{fn_name}` type header: unexpected token \
+                        "{subject} type header: unexpected token \
                          while reading outputs (or missing `}}`)"
                     )
                     .into())
@@ -543,31 +1901,182 @@ impl Compiler<'_, '_> {
             }
         }
 
-        Ok(FnSig { inputs, outputs })
+        let sig = FnSig {
+            inputs,
+            outputs,
+            rest,
+            infer_outputs,
+        };
+        if sig.is_inferred() && sig.is_generic() {
+            return Err(format!(
+                "{subject} type header: `?` cannot be combined with \
+                 type or row variables"
+            )
+            .into());
+        }
+        if sig.rest.is_some() != matches!(sig.outputs.first(), Some(Ty::Row(_))) {
+            return Err(format!(
+                "{subject} type header: a row variable must start \
+                 both the inputs and the outputs"
+            )
+            .into());
+        }
+        // Every variable an output mentions must be fixed by the inputs, or
+        // a call site could not tell what the output is.
+        let mut bound = HashSet::new();
+        if let Some(r) = &sig.rest {
+            bound.insert(format!("..{r}"));
+        }
+        for (_, ty) in &sig.inputs {
+            ty.collect_vars(&mut bound);
+        }
+        let mut used = HashSet::new();
+        for ty in &sig.outputs {
+            ty.collect_vars(&mut used);
+        }
+        let mut unbound: Vec<&String> = used.difference(&bound).collect();
+        unbound.sort();
+        if let Some(v) = unbound.first() {
+            let v = if v.starts_with("..") {
+                v.to_string()
+            } else {
+                format!("'{v}")
+            };
+            return Err(format!(
+                "{subject} type header: `{v}` appears in the outputs \
+                 but not in the inputs"
+            )
+            .into());
+        }
+        Ok(sig)
+    }
+
+    /// Whether `w` begins a type (as opposed to naming an output).
+    fn is_type_start(&self, w: &str) -> bool {
+        self.named_type(w).is_some() || w == "(" || type_var(w).is_some()
     }
 
-    /// Consume one token and require it to name a Plenty type.
+    /// A type written as a single name: a builtin type or a declared struct.
+    fn named_type(&self, w: &str) -> Option<Ty> {
+        parse_type(w).or_else(|| self.structs.get(w).map(|st| Ty::Struct(Rc::clone(st))))
+    }
+
+    /// Consume a row variable `..R` if the cursor is on one.
+    fn consume_row(&mut self) -> Option<Rc<str>> {
+        let Some(Tok::Word(w)) = self.toks.get(self.pos).copied() else {
+            return None;
+        };
+        let row = row_var(w)?;
+        self.pos += 1;
+        Some(row)
+    }
+
+    /// Consume one type: a single type-name token, a type variable `'T`, or
+    /// a parenthesised function type `( IN... -> OUT... )`, whose parts may
+    /// themselves be function types and whose lists may each start with a
+    /// row variable.
     fn consume_type(&mut self, fn_name: &str) -> Result<Ty> {
+        let subject = describe_fn(fn_name);
         match self.toks.get(self.pos).copied() {
-            Some(Tok::Word(w)) => match parse_type(w) {
+            Some(Tok::Word("(")) => {
+                self.pos += 1;
+                let mut inputs: Vec<Ty> = self.consume_row().map(Ty::Row).into_iter().collect();
+                while self.toks.get(self.pos) != Some(&Tok::Word("->")) {
+                    inputs.push(self.consume_type(fn_name)?);
+                }
+                self.pos += 1;
+                let mut outputs: Vec<Ty> = self.consume_row().map(Ty::Row).into_iter().collect();
+                while self.toks.get(self.pos) != Some(&Tok::Word(")")) {
+                    outputs.push(self.consume_type(fn_name)?);
+                }
+                self.pos += 1;
+                let fn_ty = FnTy { inputs, outputs };
+                if matches!(fn_ty.inputs.first(), Some(Ty::Row(_)))
+                    != matches!(fn_ty.outputs.first(), Some(Ty::Row(_)))
+                {
+                    return Err(format!(
+                        "{subject} type header: in {fn_ty}, a row variable \
+                         must start both the inputs and the outputs"
+                    )
+                    .into());
+                }
+                Ok(Ty::Fn(Rc::new(fn_ty)))
+            }
+            Some(Tok::Word(w)) if row_var(w).is_some() => Err(misplaced_row(fn_name, w)),
+            Some(Tok::Word(w)) if type_var(w).is_some() => {
+                self.pos += 1;
+                Ok(Ty::Var(type_var(w).expect("just checked")))
+            }
+            Some(Tok::Word(w)) => match self.named_type(w) {
                 Some(ty) => {
                     self.pos += 1;
                     Ok(ty)
                 }
                 None => Err(format!(
-                    "function `{fn_name}` type header: `{w}` is not a known type \
-                     (expected one of `i8`..`i64`, `u8`..`u64`, `Str`, `Bool`)"
+                    "{subject} type header: `{w}` is not a known type \
+                     (expected one of `i8`..`i64`, `u8`..`u64`, `Str`, `Bool`, `Char`, `Bytes`, \
+                     `File`, a struct, a type variable `'T`, or a function type `( ... -> ... )`)"
                 )
                 .into()),
             },
-            _ => Err(format!(
-                "function `{fn_name}` type header: expected a type, found end of header"
-            )
-            .into()),
+            _ => Err(format!("{subject} type header: expected a type, found end of header").into()),
         }
     }
 }
 
+/// `'T` as a type variable named `T`.
+fn type_var(w: &str) -> Option<Rc<str>> {
+    w.strip_prefix('\'')
+        .filter(|n| is_valid_var_name(n))
+        .map(Rc::from)
+}
+
+/// `..R` as a row variable named `R`.
+fn row_var(w: &str) -> Option<Rc<str>> {
+    w.strip_prefix("..")
+        .filter(|n| is_valid_var_name(n))
+        .map(Rc::from)
+}
+
+fn is_valid_var_name(n: &str) -> bool {
+    !n.is_empty() && n.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Whether `name` is the generated name of a quotation's body. It holds a
+/// space, so no source word can spell it.
+pub(crate) fn is_quotation(name: &str) -> bool {
+    name.contains("<quote ")
+}
+
+/// How a message names the function `name`: "function `f`", or "a
+/// quotation" rather than its generated name.
+pub(crate) fn describe_fn(name: &str) -> String {
+    if is_quotation(name) {
+        "a quotation".into()
+    } else {
+        format!("function `{name}`")
+    }
+}
+
+/// The prefix that places a message inside the function `name`: "in `f`",
+/// or "in a quotation".
+pub(crate) fn fn_scope(name: &str) -> String {
+    if is_quotation(name) {
+        "in a quotation".into()
+    } else {
+        format!("in `{name}`")
+    }
+}
+
+fn misplaced_row(fn_name: &str, w: &str) -> Box<dyn Error> {
+    let subject = describe_fn(fn_name);
+    format!(
+        "{subject} type header: row variable `{w}` may only come \
+         first in an input or output list"
+    )
+    .into()
+}
+
 /// Parse a single word as a Plenty type name. Returns `None` for words that
 /// are not type names; that lets callers reject them with a context-specific
 /// message rather than a generic "not a type" error.
@@ -582,7 +2091,10 @@ fn parse_type(w: &str) -> Option<Ty> {
         "u32" => Some(Ty::U32),
         "u64" => Some(Ty::U64),
         "Str" => Some(Ty::Str),
+        "Bytes" => Some(Ty::Bytes),
+        "File" => Some(Ty::File),
         "Bool" => Some(Ty::Bool),
+        "Char" => Some(Ty::Char),
         _ => None,
     }
 }
@@ -590,88 +2102,129 @@ fn parse_type(w: &str) -> Option<Ty> {
 /// A parsed integer literal. `explicit_ty` distinguishes `1` (which may
 /// match any integer type when in range) from `1i64` (which matches `i64`
 /// only). The distinction matters only in match patterns.
-#[derive(Clone, Copy)]
-struct IntLiteral {
-    value: Value,
-    explicit_ty: bool,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntLiteral {
+    pub value: Value,
+    pub explicit_ty: bool,
 }
 
 /// Parse an integer literal. Unsuffixed literals are `i64`; suffixed literals
 /// use their declared type and must fit it. This makes `255u8` direct while
 /// preserving `:as-u8` for explicit narrowing and reinterpretation.
+///
+/// Digits are decimal, or hexadecimal, binary or octal after a `0x`, `0b` or
+/// `0o` prefix, and may be grouped with `_` (`1_000_000`, `0xFF_FF`). A
+/// leading `-` negates the value before the range check, so `-0x80i8` is
+/// `i8::MIN`. A decimal word that is not well formed (`2nd`) is not a
+/// literal at all; a prefixed one (`0xZZ`) is an error.
 fn parse_integer_literal(word: &str) -> Result<Option<IntLiteral>> {
-    if let Ok(n) = word.parse::<i64>() {
-        return Ok(Some(IntLiteral {
-            value: Value::I64(n),
-            explicit_ty: false,
-        }));
-    }
-
-    for (suffix, ty) in [
-        ("i8", Ty::I8),
-        ("i16", Ty::I16),
-        ("i32", Ty::I32),
-        ("i64", Ty::I64),
-        ("u8", Ty::U8),
-        ("u16", Ty::U16),
-        ("u32", Ty::U32),
-        ("u64", Ty::U64),
-    ] {
-        let Some(digits) = word.strip_suffix(suffix) else {
-            continue;
-        };
-        let starts_numeric =
-            digits.starts_with('-') || digits.as_bytes().first().is_some_and(u8::is_ascii_digit);
-        if !starts_numeric {
-            continue;
+    let (negative, unsigned) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+    if !unsigned.as_bytes().first().is_some_and(u8::is_ascii_digit) {
+        return Ok(None);
+    }
+    let (radix, base, body) = match unsigned.get(..2) {
+        Some("0x") => (16, "hexadecimal", &unsigned[2..]),
+        Some("0b") => (2, "binary", &unsigned[2..]),
+        Some("0o") => (8, "octal", &unsigned[2..]),
+        _ => (10, "decimal", unsigned),
+    };
+    // `i` and `u` are not digits in any radix, so the suffix starts at the
+    // first of them.
+    let (digits, suffix) = body.split_at(body.find(['i', 'u']).unwrap_or(body.len()));
+    let ty = match suffix {
+        "" => None,
+        "i8" => Some(Ty::I8),
+        "i16" => Some(Ty::I16),
+        "i32" => Some(Ty::I32),
+        "i64" => Some(Ty::I64),
+        "u8" => Some(Ty::U8),
+        "u16" => Some(Ty::U16),
+        "u32" => Some(Ty::U32),
+        "u64" => Some(Ty::U64),
+        _ if radix == 10 => return Ok(None),
+        _ => {
+            return Err(
+                format!("invalid {base} literal `{word}`: unknown suffix `{suffix}`").into(),
+            )
         }
-        let value = match ty {
-            Ty::I8 => digits
-                .parse::<i8>()
-                .map(Value::I8)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::I16 => digits
-                .parse::<i16>()
-                .map(Value::I16)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::I32 => digits
-                .parse::<i32>()
-                .map(Value::I32)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::I64 => digits
-                .parse::<i64>()
-                .map(Value::I64)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::U8 => digits
-                .parse::<u8>()
-                .map(Value::U8)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::U16 => digits
-                .parse::<u16>()
-                .map(Value::U16)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::U32 => digits
-                .parse::<u32>()
-                .map(Value::U32)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::U64 => digits
-                .parse::<u64>()
-                .map(Value::U64)
-                .map_err(|_| format!("integer literal `{word}` does not fit {ty}"))?,
-            Ty::Str | Ty::Bool => unreachable!("only integer suffixes are listed"),
-        };
-        return Ok(Some(IntLiteral {
-            value,
-            explicit_ty: true,
-        }));
+    };
+    let clean: String = digits.chars().filter(|&c| c != '_').collect();
+    if clean.is_empty() || !clean.chars().all(|c| c.is_digit(radix)) {
+        if radix == 10 {
+            return Ok(None);
+        }
+        return Err(format!("invalid {base} literal `{word}`").into());
     }
-    Ok(None)
+    let explicit_ty = ty.is_some();
+    let ty = ty.unwrap_or(Ty::I64);
+    let (min, end) = ty.int_range().expect("only integer suffixes are listed");
+    let n = u128::from_str_radix(&clean, radix)
+        .ok()
+        .and_then(|m| i128::try_from(m).ok())
+        .map(|m| if negative { -m } else { m })
+        .filter(|n| (min..end).contains(n))
+        .ok_or_else(|| format!("integer literal `{word}` does not fit {ty}"))?;
+    let value = match ty {
+        Ty::I8 => Value::I8(n as i8),
+        Ty::I16 => Value::I16(n as i16),
+        Ty::I32 => Value::I32(n as i32),
+        Ty::I64 => Value::I64(n as i64),
+        Ty::U8 => Value::U8(n as u8),
+        Ty::U16 => Value::U16(n as u16),
+        Ty::U32 => Value::U32(n as u32),
+        Ty::U64 => Value::U64(n as u64),
+        Ty::Str
+        | Ty::Bytes
+        | Ty::File
+        | Ty::Bool
+        | Ty::Char
+        | Ty::Fn(_)
+        | Ty::Struct(_)
+        | Ty::Var(_)
+        | Ty::Row(_)
+        | Ty::Hole(_) => {
+            unreachable!("only integer suffixes are listed")
+        }
+    };
+    Ok(Some(IntLiteral { value, explicit_ty }))
 }
 
 /// Parse a match-arm pattern from a bare word. Numbers parse as `Pattern::Int`,
-/// `true`/`false` as `Pattern::Bool`, `_` as `Pattern::Wildcard`. A pattern
-/// must be a literal or a wildcard — never an arbitrary word.
+/// `LO..=HI`, `..=HI` and `LO..` as `Pattern::Range`, `true`/`false` as
+/// `Pattern::Bool`, `_` as `Pattern::Wildcard`; char literals and ranges of
+/// them as `Pattern::Char` and `Pattern::CharRange`. A pattern must be a
+/// literal, a range, or a wildcard — never an arbitrary word.
 fn parse_pattern_word(w: &str) -> Result<Pattern> {
+    if let Some(pattern) = parse_char_pattern(w)? {
+        return Ok(pattern);
+    }
+    if let Some((lo, rest)) = w.split_once("..") {
+        let hi = match rest.strip_prefix('=') {
+            Some(hi) if !hi.is_empty() => Some(hi),
+            None if rest.is_empty() && !lo.is_empty() => None,
+            _ => {
+                return Err(
+                    format!("range pattern `{w}` must be `LO..=HI`, `..=HI`, or `LO..`").into(),
+                )
+            }
+        };
+        let bound = |b: &str| -> Result<Option<IntLiteral>> {
+            if b.is_empty() {
+                return Ok(None);
+            }
+            match parse_integer_literal(b)? {
+                Some(lit) => Ok(Some(lit)),
+                None => Err(format!("range pattern bound `{b}` is not an integer literal").into()),
+            }
+        };
+        return Ok(Pattern::Range {
+            lo: bound(lo)?,
+            hi: bound(hi.unwrap_or(""))?,
+        });
+    }
     if w == "_" {
         return Ok(Pattern::Wildcard);
     }
@@ -694,7 +2247,55 @@ fn parse_pattern_word(w: &str) -> Result<Pattern> {
     .into())
 }
 
-/// Decode the `\"` and `\\` escapes inside a raw string-literal slice. Any
+/// A char pattern — `'x'`, `'a'..='z'`, `..='z'` or `'a'..` — or `None`
+/// if `w` does not start with a char literal.
+fn parse_char_pattern(w: &str) -> Result<Option<Pattern>> {
+    let (lo, rest) = match lexer::char_literal(w, 0) {
+        Some(end) => {
+            let end = end?;
+            match parse_char_literal(&w[..end])? {
+                Some(c) => (Some(c), &w[end..]),
+                None => return Ok(None),
+            }
+        }
+        None if w.starts_with("..='") => (None, w),
+        None => return Ok(None),
+    };
+    if rest.is_empty() {
+        return Ok(lo.map(Pattern::Char));
+    }
+    let hi = match rest.strip_prefix("..=") {
+        Some(hi) => match parse_char_literal(hi)? {
+            Some(c) => Some(c),
+            None => return Err(format!("range pattern bound `{hi}` is not a char literal").into()),
+        },
+        None if rest == ".." => None,
+        None => {
+            return Err(format!("range pattern `{w}` must be `LO..=HI`, `..=HI`, or `LO..`").into())
+        }
+    };
+    Ok(Some(Pattern::CharRange { lo, hi }))
+}
+
+/// The char a char-literal word — `'x'`, or `'\...'` with one escape from
+/// [`unescape`] — stands for, or `None` if `w` is not one.
+fn parse_char_literal(w: &str) -> Result<Option<char>> {
+    match lexer::char_literal(w, 0) {
+        Some(Ok(end)) if end == w.len() => {}
+        _ => return Ok(None),
+    }
+    let text = unescape(&w[1..w.len() - 1])?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Some(c)),
+        _ => Err(format!("char literal `{w}` must hold exactly one char").into()),
+    }
+}
+
+/// Decode the escapes inside an escaped string- or char-literal slice:
+/// `\"`, `\'`, `\\`,
+/// `\n`, `\t`, `\r`, `\0`, `\xHH` (ASCII only, so the result stays valid
+/// UTF-8) and `\u{H..}` (one to six hex digits naming a Unicode scalar). Any
 /// other `\X` is an error. The lexer guarantees that every `\` is followed by
 /// some character, so trailing-backslash is unreachable from real input — the
 /// defensive check is cheap and keeps the function honest in isolation.
@@ -702,26 +2303,96 @@ fn unescape(raw: &str) -> Result<String> {
     let mut out = String::with_capacity(raw.len());
     let mut chars = raw.chars();
     while let Some(c) = chars.next() {
-        if c == '\\' {
-            match chars.next() {
-                Some('"') => out.push('"'),
-                Some('\\') => out.push('\\'),
-                Some(other) => return Err(format!("invalid escape: \\{other}").into()),
-                None => return Err("invalid escape: trailing backslash".into()),
-            }
-        } else {
+        if c != '\\' {
             out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\'') => out.push('\''),
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                let hex = digits.len() == 2 && digits.chars().all(|d| d.is_ascii_hexdigit());
+                let byte = match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if hex => byte,
+                    _ => {
+                        return Err(format!(
+                            "invalid escape: \\x{digits} (expected two hex digits)"
+                        )
+                        .into())
+                    }
+                };
+                if byte > 0x7f {
+                    return Err(format!(
+                        "invalid escape: \\x{digits} is above \\x7f; write \\u{{{byte:x}}} for that character"
+                    )
+                    .into());
+                }
+                out.push(char::from(byte));
+            }
+            Some('u') => {
+                if chars.next() != Some('{') {
+                    return Err("invalid escape: \\u must be followed by `{`".into());
+                }
+                let mut closed = false;
+                let digits: String = chars
+                    .by_ref()
+                    .take_while(|&d| {
+                        closed = d == '}';
+                        !closed
+                    })
+                    .collect();
+                let hex = (1..=6).contains(&digits.len())
+                    && digits.chars().all(|d| d.is_ascii_hexdigit());
+                let code = match u32::from_str_radix(&digits, 16) {
+                    Ok(code) if closed && hex => code,
+                    _ => {
+                        let close = if closed { "}" } else { "" };
+                        return Err(format!(
+                            "invalid escape: \\u{{{digits}{close} (expected 1 to 6 hex digits and `}}`)"
+                        )
+                        .into());
+                    }
+                };
+                let Some(c) = char::from_u32(code) else {
+                    return Err(format!(
+                        "invalid escape: \\u{{{digits}}} is not a Unicode scalar value"
+                    )
+                    .into());
+                };
+                out.push(c);
+            }
+            Some(other) => return Err(format!("invalid escape: \\{other}").into()),
+            None => return Err("invalid escape: trailing backslash".into()),
         }
     }
     Ok(out)
 }
 
+/// The text a string-literal token stands for: an escaped literal's content
+/// with its escapes decoded, or a raw literal's content as written.
+fn text_value(tok: Tok) -> Result<String> {
+    match tok {
+        Tok::Text(s) => unescape(s),
+        Tok::RawText(s) => Ok(s.to_string()),
+        Tok::Word(w) => unreachable!("`{w}` is not a string literal"),
+    }
+}
+
 /// Resolve a single ordinary word — never `:` or `;`, which the caller handles
-/// — into a number, a builtin, a function call (`:name`), or top-level text.
+/// — into a number, a char, a builtin, a function call (`:name`), or top-level text.
 fn compile_word(word: &str, heap: &mut Heap) -> Result<Op> {
     if let Some(lit) = parse_integer_literal(word)? {
         return Ok(Op::PushInt(lit.value));
     }
+    if let Some(c) = parse_char_literal(word)? {
+        return Ok(Op::PushChar(c));
+    }
     Ok(match word {
         "true" => Op::PushBool(true),
         "false" => Op::PushBool(false),
@@ -729,6 +2400,26 @@ fn compile_word(word: &str, heap: &mut Heap) -> Result<Op> {
         "-" => Op::Sub,
         "*" => Op::Mul,
         "/" => Op::Div,
+        "+%" => Op::WrappingAdd,
+        "*%" => Op::WrappingMul,
+        "+|" => Op::SaturatingAdd,
+        "-|" => Op::SaturatingSub,
+        "rem" => Op::Rem,
+        "mod" => Op::Mod,
+        "divmod" => Op::DivMod,
+        "abs" => Op::Abs,
+        "neg" => Op::Neg,
+        "min" => Op::Min,
+        "max" => Op::Max,
+        "pow" => Op::Pow,
+        "band" => Op::BitAnd,
+        "bor" => Op::BitOr,
+        "bxor" => Op::BitXor,
+        "bnot" => Op::BitNot,
+        "shl" => Op::Shl,
+        "shr" => Op::Shr,
+        "rotl" => Op::Rotl,
+        "rotr" => Op::Rotr,
         "=" => Op::Eq,
         "<" => Op::Lt,
         ">" => Op::Gt,
@@ -751,10 +2442,58 @@ fn compile_word(word: &str, heap: &mut Heap) -> Result<Op> {
         ":as-u16" => Op::Cast(Ty::U16),
         ":as-u32" => Op::Cast(Ty::U32),
         ":as-u64" => Op::Cast(Ty::U64),
+        ":as-char" => Op::Cast(Ty::Char),
         ":readline" => Op::ReadLine,
+        ":readline-bytes" => Op::ReadLineBytes,
+        ":read-bytes" => Op::ReadBytes,
+        ":write-bytes" => Op::WriteBytes,
+        ":utf8-decode" => Op::Utf8Decode,
+        ":utf8-encode" => Op::Utf8Encode,
+        ":utf8-decode-lossy" => Op::Utf8DecodeLossy,
+        ":bytes-len" => Op::BytesLen,
+        ":bytes-slice" => Op::BytesSlice,
+        ":read-file" => Op::ReadFile,
+        ":write-file" => Op::WriteFile,
+        ":append-file" => Op::AppendFile,
+        ":file-exists" => Op::FileExists,
+        ":open-file" => Op::OpenFile,
+        ":file-readline" => Op::FileReadLine,
+        ":file-readline-bytes" => Op::FileReadLineBytes,
+        ":close-file" => Op::CloseFile,
+        ":argc" => Op::ArgCount,
+        ":arg" => Op::Arg,
+        ":env" => Op::Env,
         ":contains" => Op::Contains,
+        ":len" => Op::StrLen,
+        ":slice" => Op::Slice,
+        ":split" => Op::Split,
+        ":trim" => Op::Trim,
+        ":starts-with" => Op::StartsWith,
+        ":ends-with" => Op::EndsWith,
+        ":find" => Op::Find,
+        ":replace" => Op::Replace,
+        ":upper" => Op::Upper,
+        ":lower" => Op::Lower,
+        ":repeat" => Op::Repeat,
+        ":next-char" => Op::NextChar,
+        ":char-at" => Op::CharAt,
+        ":from-char" => Op::FromChar,
+        ":parse-i8" => Op::Parse(Ty::I8),
+        ":parse-i16" => Op::Parse(Ty::I16),
+        ":parse-i32" => Op::Parse(Ty::I32),
+        ":parse-i64" => Op::Parse(Ty::I64),
+        ":parse-u8" => Op::Parse(Ty::U8),
+        ":parse-u16" => Op::Parse(Ty::U16),
+        ":parse-u32" => Op::Parse(Ty::U32),
+        ":parse-u64" => Op::Parse(Ty::U64),
+        ":to-str" => Op::ToStr,
+        ":to-digits" => Op::ToDigits,
         ":println" => Op::PrintLn,
         ":print" => Op::Print,
+        ":eprintln" => Op::EPrintLn,
+        ":eprint" => Op::EPrint,
+        ":exit" => Op::Exit,
+        ":call" => Op::CallIndirect,
         _ => match word.strip_prefix(':') {
             Some(name) if !name.is_empty() => Op::Call(name.to_string()),
             _ => Op::PushStr(heap.add_str(word.to_string())),
@@ -762,6 +2501,61 @@ fn compile_word(word: &str, heap: &mut Heap) -> Result<Op> {
     })
 }
 
+/// Split a `:format` template into literal text and placeholders. The
+/// placeholders are `{}`, `{:?}`, and `{:x}`; `{{` and `}}` stand for
+/// literal braces.
+fn parse_format(template: &str, heap: &mut Heap) -> Result<Vec<FormatPiece>> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let Some(close) = rest.find('}') else {
+                    return Err(format!("unclosed `{{` in `:format` template {template:?}").into());
+                };
+                let spec = match &rest[..close] {
+                    "" => FormatSpec::Display,
+                    ":?" => FormatSpec::Debug,
+                    ":x" => FormatSpec::Hex,
+                    other => {
+                        return Err(format!(
+                            "unknown `:format` placeholder `{{{other}}}`; \
+                             expected `{{}}`, `{{:?}}`, or `{{:x}}`"
+                        )
+                        .into())
+                    }
+                };
+                chars = rest[close + 1..].chars();
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(heap.add_str(std::mem::take(&mut text))));
+                }
+                pieces.push(FormatPiece::Hole(spec));
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' => {
+                return Err(format!(
+                    "unmatched `}}` in `:format` template {template:?}; write `}}}}` for a brace"
+                )
+                .into())
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(heap.add_str(text)));
+    }
+    Ok(pieces)
+}
+
 /// Names whose `:name` call spelling is already owned by a builtin. Rejecting
 /// matching definitions prevents a function that can never be called.
 fn is_reserved_function_name(name: &str) -> bool {
@@ -776,27 +2570,215 @@ fn is_reserved_function_name(name: &str) -> bool {
             | "as-u16"
             | "as-u32"
             | "as-u64"
+            | "as-char"
             | "readline"
+            | "readline-bytes"
+            | "read-bytes"
+            | "write-bytes"
+            | "utf8-decode"
+            | "utf8-encode"
+            | "utf8-decode-lossy"
+            | "bytes-len"
+            | "bytes-slice"
+            | "read-file"
+            | "write-file"
+            | "append-file"
+            | "file-exists"
+            | "open-file"
+            | "file-readline"
+            | "file-readline-bytes"
+            | "close-file"
+            | "argc"
+            | "arg"
+            | "env"
             | "contains"
+            | "len"
+            | "slice"
+            | "split"
+            | "trim"
+            | "starts-with"
+            | "ends-with"
+            | "find"
+            | "replace"
+            | "upper"
+            | "lower"
+            | "repeat"
+            | "next-char"
+            | "char-at"
+            | "from-char"
+            | "parse-i8"
+            | "parse-i16"
+            | "parse-i32"
+            | "parse-i64"
+            | "parse-u8"
+            | "parse-u16"
+            | "parse-u32"
+            | "parse-u64"
+            | "to-str"
+            | "to-digits"
+            | "format"
             | "println"
             | "print"
+            | "eprintln"
+            | "eprint"
+            | "exit"
+            | "call"
     )
 }
 
+/// Constant names follow the input-name rules, and cannot look like a
+/// qualified name or a delimiter.
+fn is_valid_const_name(name: &str) -> bool {
+    !name.contains('.') && !matches!(name, ";" | "[" | "]" | "_") && is_valid_input_name(name)
+}
+
+/// A match-arm binding is named like a constant, and cannot look like a
+/// range pattern.
+fn is_valid_binding_name(name: &str) -> bool {
+    !name.contains("..") && !matches!(name, "|" | "as") && is_valid_const_name(name)
+}
+
+/// Field names follow the input-name rules. A dot would make the accessor
+/// word ambiguous, and `with-` is how update words are spelled.
+fn is_valid_field_name(name: &str) -> bool {
+    !name.contains('.') && !name.starts_with("with-") && is_valid_const_name(name)
+}
+
+/// Module names are plain identifiers: no dots (modules do not nest), and
+/// nothing that already means something as a word.
+fn is_valid_module_name(name: &str) -> bool {
+    !name.contains('.') && !matches!(name, ";" | "[" | "]") && is_valid_input_name(name)
+}
+
+/// The module a qualified function name belongs to; `""` for the root
+/// namespace.
+fn module_of(name: &str) -> &str {
+    name.rsplit_once('.').map_or("", |(module, _)| module)
+}
+
+/// Every `private` definition reachable from `ops`.
+fn collect_private(ops: &[Op], out: &mut HashSet<String>) {
+    for op in ops {
+        match op {
+            Op::DefineFn(name, f) => {
+                if f.private {
+                    out.insert(name.clone());
+                }
+                collect_private(&f.body, out);
+            }
+            Op::Import(_, body) => collect_private(body, out),
+            Op::Match(arms) => {
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    collect_private(block, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reject any call to a private function from outside its module. `caller`
+/// is the module of the function whose body `ops` is (`""` at top level).
+/// Runs over the whole compilation at once, so a call written before the
+/// module that defines its callee is still caught.
+pub(crate) fn check_visibility(ops: &[Op], caller: &str, private: &HashSet<String>) -> Result<()> {
+    for op in ops {
+        match op {
+            Op::Call(name) | Op::TailCall(name)
+                if private.contains(name) && module_of(name) != caller =>
+            {
+                return Err(format!(
+                    "function `{name}` is private to module `{}`",
+                    module_of(name)
+                )
+                .into());
+            }
+            Op::DefineFn(name, f) => check_visibility(&f.body, module_of(name), private)?,
+            Op::Import(_, body) => check_visibility(body, caller, private)?,
+            Op::Match(arms) => {
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    check_visibility(block, caller, private)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Reject a struct whose name is already a function's, since its
+/// constructor would take over that function's `:name`. `prior` holds the
+/// functions defined before this compilation. A function declared after a
+/// struct of its name is refused as it compiles; this catches the other
+/// order, including a function defined earlier in the same source.
+pub(crate) fn check_struct_names(ops: &[Op], prior: &HashSet<String>) -> Result<()> {
+    fn structs<'a>(ops: &'a [Op], out: &mut Vec<&'a StructTy>) {
+        for op in ops {
+            match op {
+                Op::DefineStruct(st) => out.push(st),
+                Op::Import(_, body) => structs(body, out),
+                _ => {}
+            }
+        }
+    }
+    let mut declared = Vec::new();
+    structs(ops, &mut declared);
+    if declared.is_empty() {
+        return Ok(());
+    }
+    let mut defined = HashMap::new();
+    collect_sigs(ops, &mut defined);
+    match declared
+        .iter()
+        .find(|st| prior.contains(&*st.name) || defined.contains_key(&*st.name))
+    {
+        Some(st) => {
+            let name = &st.name;
+            Err(format!("struct name `{name}` is taken by function `{name}`").into())
+        }
+        None => Ok(()),
+    }
+}
+
 /// Input names are ordinary identifiers, not literals, operators, or call
 /// spellings. This prevents `{ 2 i64 -> ... }` from turning `2` in a body
 /// into a local load instead of an integer literal.
 fn is_valid_input_name(name: &str) -> bool {
     !matches!(parse_integer_literal(name), Ok(Some(_)) | Err(_))
+        && !matches!(parse_char_literal(name), Ok(Some(_)) | Err(_))
         && !matches!(
             name,
             "true"
                 | "false"
                 | "match"
+                | "match2"
+                | "when"
+                | "as"
                 | "end"
+                | "import"
+                | "module"
+                | "private"
+                | "const"
+                | "struct"
                 | "not"
                 | "and"
                 | "or"
+                | "rem"
+                | "mod"
+                | "divmod"
+                | "abs"
+                | "neg"
+                | "min"
+                | "max"
+                | "pow"
+                | "band"
+                | "bor"
+                | "bxor"
+                | "bnot"
+                | "shl"
+                | "shr"
+                | "rotl"
+                | "rotr"
                 | "drop"
                 | "dup"
                 | "swap"
@@ -804,6 +2786,10 @@ fn is_valid_input_name(name: &str) -> bool {
                 | "-"
                 | "*"
                 | "/"
+                | "+%"
+                | "*%"
+                | "+|"
+                | "-|"
                 | "="
                 | "!="
                 | "<"
@@ -813,6 +2799,9 @@ fn is_valid_input_name(name: &str) -> bool {
                 | "."
         )
         && !name.starts_with(':')
+        && !name.starts_with('\'')
+        && !name.starts_with("..")
+        && name != "?"
 }
 
 // --- tail-call detection (§11.8) -------------------------------------------
@@ -843,7 +2832,9 @@ fn mark_tail_calls(body: &mut [Op]) {
                     let mut new_body: Vec<Op> = arm.body.iter().cloned().collect();
                     mark_tail_calls(&mut new_body);
                     MatchArm {
-                        pattern: arm.pattern,
+                        patterns: arm.patterns.clone(),
+                        binds: arm.binds.clone(),
+                        guard: arm.guard.clone(),
                         body: new_body.into(),
                     }
                 })
@@ -893,7 +2884,7 @@ pub fn check(
     // `LoadLocal` here either), and there is no end-of-stream invariant.
     let mut stack = initial_stack;
     for op in ops {
-        step(op, &mut stack, &[], &sigs)?;
+        step(op, &mut stack, &mut [], &sigs)?;
     }
     Ok(())
 }
@@ -906,6 +2897,10 @@ pub fn check(
 /// caught at runtime as an "undefined function" error, which is fine —
 /// the checker's job is to catch *type* mismatches, not to police call
 /// ordering.
+///
+/// Names inside a `module` were qualified (`text.split`) by the compiler,
+/// as were the calls that reach them, so the table is keyed exactly as the
+/// VM's dictionary is and needs no resolution of its own.
 fn collect_sigs(ops: &[Op], out: &mut HashMap<String, Rc<FnSig>>) {
     for op in ops {
         match op {
@@ -913,9 +2908,10 @@ fn collect_sigs(ops: &[Op], out: &mut HashMap<String, Rc<FnSig>>) {
                 out.insert(name.clone(), Rc::clone(&f.sig));
                 collect_sigs(&f.body, out);
             }
+            Op::Import(_, body) => collect_sigs(body, out),
             Op::Match(arms) => {
-                for arm in arms.iter() {
-                    collect_sigs(&arm.body, out);
+                for block in arms.iter().flat_map(MatchArm::blocks) {
+                    collect_sigs(block, out);
                 }
             }
             _ => {}
@@ -931,23 +2927,27 @@ fn collect_sigs(ops: &[Op], out: &mut HashMap<String, Rc<FnSig>>) {
 fn step(
     op: &Op,
     stack: &mut Vec<Ty>,
-    locals: &[Ty],
+    locals: &mut [Ty],
     sigs: &HashMap<String, Rc<FnSig>>,
 ) -> Result<()> {
+    fill_holes(op, stack, locals, sigs);
     match op {
         // Unsuffixed integer literals are `i64`; a suffix records its chosen
         // width directly in the `Value` carried by the operation.
-        Op::PushInt(value) => stack.push(Ty::from(*value)),
+        Op::PushInt(value) => stack.push(Ty::of_int(*value)),
         Op::PushStr(_) => stack.push(Ty::Str),
         Op::PushBool(_) => stack.push(Ty::Bool),
+        Op::PushChar(_) => stack.push(Ty::Char),
         Op::Add => {
             let (a, b) = pop2(stack, "+")?;
-            let out = match (a, b) {
+            let out = match (&a, &b) {
                 (Ty::Str, Ty::Str) => Ty::Str,
-                (a, b) if a == b && a.is_int() => a,
+                (Ty::Bytes, Ty::Bytes) => Ty::Bytes,
+                (x, y) if x == y && x.is_int() => a.clone(),
                 _ => {
                     return Err(format!(
-                        "`+` requires same-width integers or (Str Str), got ({a} {b})"
+                        "`+` requires same-width integers, (Str Str) or (Bytes Bytes), \
+                         got ({a} {b})"
                     )
                     .into())
                 }
@@ -957,6 +2957,31 @@ fn step(
         Op::Sub => arith(stack, "-")?,
         Op::Mul => arith(stack, "*")?,
         Op::Div => arith(stack, "/")?,
+        Op::WrappingAdd => arith(stack, "+%")?,
+        Op::WrappingMul => arith(stack, "*%")?,
+        Op::SaturatingAdd => arith(stack, "+|")?,
+        Op::SaturatingSub => arith(stack, "-|")?,
+        Op::Rem => arith(stack, "rem")?,
+        Op::Mod => arith(stack, "mod")?,
+        Op::DivMod => {
+            // The remainder has the quotient's type.
+            arith(stack, "divmod")?;
+            let ty = stack.last().expect("`arith` pushed the quotient").clone();
+            stack.push(ty);
+        }
+        Op::Abs => int_unary(stack, "abs")?,
+        Op::Neg => int_unary(stack, "neg")?,
+        Op::Min => arith(stack, "min")?,
+        Op::Max => arith(stack, "max")?,
+        Op::Pow => arith(stack, "pow")?,
+        Op::BitAnd => arith(stack, "band")?,
+        Op::BitOr => arith(stack, "bor")?,
+        Op::BitXor => arith(stack, "bxor")?,
+        Op::BitNot => int_unary(stack, "bnot")?,
+        Op::Shl => arith(stack, "shl")?,
+        Op::Shr => arith(stack, "shr")?,
+        Op::Rotl => arith(stack, "rotl")?,
+        Op::Rotr => arith(stack, "rotr")?,
         Op::Eq => {
             let (a, b) = pop2(stack, "=")?;
             if a != b {
@@ -964,12 +2989,24 @@ fn step(
                     format!("`=` requires both operands of the same type, got ({a} {b})").into(),
                 );
             }
+            if let Ty::Fn(_) = a {
+                return Err(format!("`=` cannot compare function references ({a})").into());
+            }
+            if let Ty::Var(_) = a {
+                return Err(format!("`=` cannot compare values of a type variable ({a})").into());
+            }
+            if let Ty::Struct(_) = a {
+                return Err(format!("`=` cannot compare structs ({a})").into());
+            }
+            if a == Ty::File {
+                return Err(format!("`=` cannot compare file handles ({a})").into());
+            }
             stack.push(Ty::Bool);
         }
         Op::Lt => cmp_int(stack, "<")?,
         Op::Gt => cmp_int(stack, ">")?,
         Op::Not => {
-            let top = stack.pop().ok_or("stack underflow on `not`")?;
+            let top = pop1(stack, "not")?;
             if top != Ty::Bool {
                 return Err(format!("`not` requires Bool, got {top}").into());
             }
@@ -982,6 +3019,18 @@ fn step(
                     format!("`!=` requires both operands of the same type, got ({a} {b})").into(),
                 );
             }
+            if let Ty::Fn(_) = a {
+                return Err(format!("`!=` cannot compare function references ({a})").into());
+            }
+            if let Ty::Var(_) = a {
+                return Err(format!("`!=` cannot compare values of a type variable ({a})").into());
+            }
+            if let Ty::Struct(_) = a {
+                return Err(format!("`!=` cannot compare structs ({a})").into());
+            }
+            if a == Ty::File {
+                return Err(format!("`!=` cannot compare file handles ({a})").into());
+            }
             stack.push(Ty::Bool);
         }
         Op::Le => cmp_int(stack, "<=")?,
@@ -995,42 +3044,107 @@ fn step(
             stack.push(Ty::Bool);
         }
         Op::Drop => {
-            stack.pop().ok_or("stack underflow on `drop`")?;
+            pop1(stack, "drop")?;
         }
         Op::Dup => {
-            let top = *stack.last().ok_or("stack underflow on `dup`")?;
+            let top = pop1(stack, "dup")?;
+            stack.push(top.clone());
             stack.push(top);
         }
         Op::Swap => {
-            if stack.len() < 2 {
-                return Err(format!(
-                    "stack underflow on `swap` (need 2 values, have {})",
-                    stack.len()
-                )
-                .into());
-            }
-            let len = stack.len();
-            stack.swap(len - 1, len - 2);
+            let (a, b) = pop2(stack, "swap")?;
+            stack.push(b);
+            stack.push(a);
         }
         Op::Display => {}
         Op::Clear => stack.clear(),
         Op::LoadLocal(i) => {
-            let ty = locals.get(*i as usize).copied().ok_or_else(|| {
+            let ty = locals.get(*i as usize).cloned().ok_or_else(|| {
                 format!("LoadLocal({i}) has no matching input in the enclosing function")
             })?;
             stack.push(ty);
         }
         Op::DefineFn(name, f) => check_body(name, &f.sig, &f.body, sigs)?,
-        Op::Call(name) | Op::TailCall(name) => check_call(name, stack, sigs)?,
+        Op::DefineConst(..) => {}
+        // An imported body holds only definitions and imports, none of
+        // which touch the data stack.
+        Op::Import(path, body) => {
+            for op in body.iter() {
+                step(op, &mut Vec::new(), &mut [], sigs)
+                    .map_err(|e| -> Box<dyn Error> { format!("in import `{path}`: {e}").into() })?;
+            }
+        }
+        Op::Call(name) | Op::TailCall(name) => {
+            check_call(name, stack, sigs)?;
+        }
+        Op::PushFn(name, _) => {
+            let sig = sigs
+                .get(name)
+                .ok_or_else(|| format!("reference to undefined function `{name}`"))?;
+            stack.push(Ty::Fn(sig.fn_ty().into()));
+        }
+        Op::PushClosure(name, _, slots) => {
+            let sig = sigs
+                .get(name)
+                .ok_or_else(|| format!("reference to undefined function `{name}`"))?;
+            for (&slot, (input, ty)) in slots.iter().zip(&sig.inputs) {
+                let local = locals.get(slot as usize).ok_or_else(|| {
+                    format!("closure captures local {slot}, which the enclosing function lacks")
+                })?;
+                if local != ty {
+                    return Err(
+                        format!("closure captures `{input}` as {ty}, but it is {local}").into(),
+                    );
+                }
+            }
+            stack.push(Ty::Fn(sig.closure_ty(slots.len()).into()));
+        }
+        // The reference's type is fully known here, so its inputs must
+        // match exactly — its variables, if any, are the enclosing generic
+        // function's. A leading `..R` must therefore be matched by the
+        // whole rest of the stack, which is that function's own row.
+        Op::CallIndirect => {
+            let top = pop1(stack, ":call")?;
+            let Ty::Fn(fn_ty) = top else {
+                return Err(format!("`:call` requires a function reference, got {top}").into());
+            };
+            let n = match fn_ty.inputs.first() {
+                Some(Ty::Row(_)) => stack.len(),
+                _ => fn_ty.inputs.len(),
+            };
+            if stack.len() < n {
+                return Err(format!(
+                    "`:call` of {fn_ty}: needs {n} value(s) below the reference, have {}",
+                    stack.len()
+                )
+                .into());
+            }
+            let split = stack.len() - n;
+            if stack[split..] != fn_ty.inputs[..] {
+                return Err(format!(
+                    "`:call` of {fn_ty}: arguments are ({})",
+                    fmt_types(&stack[split..])
+                )
+                .into());
+            }
+            stack.truncate(split);
+            stack.extend(fn_ty.outputs.iter().cloned());
+        }
         Op::Match(arms) => check_match(arms, stack, locals, sigs)?,
         Op::Cast(target) => {
-            let top = stack.pop().ok_or("stack underflow on cast")?;
-            if !top.is_int() {
+            let top = pop1(stack, "cast")?;
+            let name = target.to_string().to_lowercase();
+            if !(top.is_int() || top == Ty::Char && *target != Ty::Char) {
+                let wanted = if *target == Ty::Char {
+                    "an integer"
+                } else {
+                    "an integer or Char"
+                };
                 return Err(
-                    format!("cast `:as-{target}` requires an integer source, got {top}").into(),
+                    format!("cast `:as-{name}` requires {wanted} source, got {top}").into(),
                 );
             }
-            stack.push(*target);
+            stack.push(target.clone());
         }
         Op::ReadLine => {
             stack.push(Ty::Str);
@@ -1043,26 +3157,196 @@ fn step(
             }
             stack.push(Ty::Bool);
         }
+        Op::StrLen
+        | Op::Slice
+        | Op::Split
+        | Op::Trim
+        | Op::StartsWith
+        | Op::EndsWith
+        | Op::Find
+        | Op::Replace
+        | Op::Upper
+        | Op::Lower
+        | Op::Repeat
+        | Op::NextChar
+        | Op::CharAt
+        | Op::FromChar
+        | Op::ReadLineBytes
+        | Op::ReadBytes
+        | Op::WriteBytes
+        | Op::Utf8Decode
+        | Op::Utf8Encode
+        | Op::Utf8DecodeLossy
+        | Op::BytesLen
+        | Op::BytesSlice
+        | Op::ReadFile
+        | Op::WriteFile
+        | Op::AppendFile
+        | Op::FileExists
+        | Op::OpenFile
+        | Op::FileReadLine
+        | Op::FileReadLineBytes
+        | Op::CloseFile
+        | Op::ArgCount
+        | Op::Arg
+        | Op::Env
+        | Op::EPrintLn
+        | Op::Exit => {
+            let (label, inputs, outputs) = fixed_effect(op).expect("a fixed-effect word");
+            let depth = value_depth(stack);
+            if depth < inputs.len() {
+                return Err(format!(
+                    "stack underflow on `{label}` (need {} values, have {depth})",
+                    inputs.len()
+                )
+                .into());
+            }
+            let args = stack.split_off(stack.len() - inputs.len());
+            if args != inputs {
+                return Err(format!(
+                    "`{label}` requires ({}), got ({})",
+                    fmt_types(inputs),
+                    fmt_types(&args)
+                )
+                .into());
+            }
+            stack.extend(outputs.iter().cloned());
+        }
+        Op::Parse(target) => {
+            let top = pop1(stack, &format!(":parse-{target}"))?;
+            if top != Ty::Str {
+                return Err(format!("`:parse-{target}` requires Str, got {top}").into());
+            }
+            stack.push(target.clone());
+            stack.push(Ty::Bool);
+        }
+        Op::ToStr => {
+            pop1(stack, ":to-str")?;
+            stack.push(Ty::Str);
+        }
+        Op::ToDigits => {
+            let top = pop1(stack, ":to-digits")?;
+            if !top.is_int() {
+                return Err(format!("`:to-digits` requires an integer, got {top}").into());
+            }
+            stack.push(Ty::Str);
+        }
+        Op::Format(pieces) => {
+            let specs: Vec<FormatSpec> = pieces
+                .iter()
+                .filter_map(|piece| match piece {
+                    FormatPiece::Hole(spec) => Some(*spec),
+                    FormatPiece::Text(_) => None,
+                })
+                .collect();
+            let depth = value_depth(stack);
+            if depth < specs.len() {
+                return Err(format!(
+                    "stack underflow on `:format` (need {} values for its placeholders, \
+                     have {depth})",
+                    specs.len()
+                )
+                .into());
+            }
+            let args = stack.split_off(stack.len() - specs.len());
+            for (i, (spec, ty)) in specs.iter().zip(&args).enumerate() {
+                if *spec == FormatSpec::Hex && !ty.is_int() {
+                    return Err(format!(
+                        "`:format` placeholder {} is `{{:x}}`, which requires an integer, got {ty}",
+                        i + 1
+                    )
+                    .into());
+                }
+            }
+            stack.push(Ty::Str);
+        }
         Op::PrintLn => {
-            let top = stack.pop().ok_or("stack underflow on `:println`")?;
+            let top = pop1(stack, ":println")?;
             if top != Ty::Str {
                 return Err(format!("`:println` requires Str, got {top}").into());
             }
         }
-        Op::Print => {
-            stack.pop().ok_or("stack underflow on `:print`")?;
+        Op::Print => {
+            pop1(stack, ":print")?;
+        }
+        Op::EPrint => {
+            pop1(stack, ":eprint")?;
+        }
+        Op::DefineStruct(_) => {}
+        Op::MakeStruct(st) => {
+            let n = st.fields.len();
+            if value_depth(stack) < n {
+                return Err(format!(
+                    "`:{}` needs {n} field value(s) on the stack, have {}",
+                    st.name,
+                    value_depth(stack)
+                )
+                .into());
+            }
+            let split = stack.len() - n;
+            for ((field, want), have) in st.fields.iter().zip(&stack[split..]) {
+                if want != have {
+                    return Err(format!(
+                        "`:{}`: field `{field}` expects {want}, got {have}",
+                        st.name
+                    )
+                    .into());
+                }
+            }
+            stack.truncate(split);
+            stack.push(Ty::Struct(Rc::clone(st)));
+        }
+        Op::GetField(st, i) => {
+            let (field, ty) = &st.fields[*i as usize];
+            let label = format!(":{}.{field}", st.name);
+            let top = pop1(stack, &label)?;
+            expect_struct(&top, st, &label)?;
+            stack.push(ty.clone());
+        }
+        Op::SetField(st, i) => {
+            let (field, ty) = &st.fields[*i as usize];
+            let label = format!(":{}.with-{field}", st.name);
+            let (record, value) = pop2(stack, &label)?;
+            expect_struct(&record, st, &label)?;
+            if value != *ty {
+                return Err(format!("`{label}` expects {ty}, got {value}").into());
+            }
+            stack.push(record);
         }
     }
     Ok(())
 }
 
+/// Require `ty` to be the struct `st`, for the word `label`.
+fn expect_struct(ty: &Ty, st: &Rc<StructTy>, label: &str) -> Result<()> {
+    match ty {
+        Ty::Struct(have) if have == st => Ok(()),
+        _ => Err(format!("`{label}` requires a {}, got {ty}", st.name).into()),
+    }
+}
+
+/// Pop one value off the abstract stack. The row at the bottom of a
+/// generic body's stack is the caller's values, which only a call can
+/// consume, so reaching it is an underflow too.
+fn pop1(stack: &mut Vec<Ty>, op_label: &str) -> Result<Ty> {
+    match stack.pop() {
+        Some(Ty::Row(r)) => Err(format!(
+            "stack underflow on `{op_label}` (only the rest of the caller's stack, \
+             `..{r}`, is left)"
+        )
+        .into()),
+        Some(ty) => Ok(ty),
+        None => Err(format!("stack underflow on `{op_label}`").into()),
+    }
+}
+
 /// Pop two values off the abstract stack; produce a uniform underflow
 /// error message that names the operator.
 fn pop2(stack: &mut Vec<Ty>, op_label: &str) -> Result<(Ty, Ty)> {
-    if stack.len() < 2 {
+    if value_depth(stack) < 2 {
         return Err(format!(
             "stack underflow on `{op_label}` (need 2 values, have {})",
-            stack.len()
+            value_depth(stack)
         )
         .into());
     }
@@ -1084,6 +3368,64 @@ fn arith(stack: &mut Vec<Ty>, op_label: &str) -> Result<()> {
     Ok(())
 }
 
+/// Stack effect for a one-operand integer op: an integer in, the same type out.
+fn int_unary(stack: &mut Vec<Ty>, op_label: &str) -> Result<()> {
+    let a = pop1(stack, op_label)?;
+    if !a.is_int() {
+        return Err(format!("`{op_label}` requires an integer, got {a}").into());
+    }
+    stack.push(a);
+    Ok(())
+}
+
+/// The signature of a builtin word with a fixed stack effect — the string,
+/// char and byte-string words: its surface name, the types it pops (deepest
+/// first) and the types it pushes. `None` for any other op.
+fn fixed_effect(op: &Op) -> Option<(&'static str, &'static [Ty], &'static [Ty])> {
+    Some(match op {
+        Op::StrLen => (":len", &[Ty::Str], &[Ty::I64]),
+        Op::Slice => (":slice", &[Ty::Str, Ty::I64, Ty::I64], &[Ty::Str]),
+        Op::Split => (":split", &[Ty::Str, Ty::Str], &[Ty::Str, Ty::Str, Ty::Bool]),
+        Op::Trim => (":trim", &[Ty::Str], &[Ty::Str]),
+        Op::StartsWith => (":starts-with", &[Ty::Str, Ty::Str], &[Ty::Bool]),
+        Op::EndsWith => (":ends-with", &[Ty::Str, Ty::Str], &[Ty::Bool]),
+        Op::Find => (":find", &[Ty::Str, Ty::Str], &[Ty::I64, Ty::Bool]),
+        Op::Replace => (":replace", &[Ty::Str, Ty::Str, Ty::Str], &[Ty::Str]),
+        Op::Upper => (":upper", &[Ty::Str], &[Ty::Str]),
+        Op::Lower => (":lower", &[Ty::Str], &[Ty::Str]),
+        Op::Repeat => (":repeat", &[Ty::Str, Ty::I64], &[Ty::Str]),
+        Op::NextChar => (
+            ":next-char",
+            &[Ty::Str, Ty::I64],
+            &[Ty::Char, Ty::I64, Ty::Bool],
+        ),
+        Op::CharAt => (":char-at", &[Ty::Str, Ty::I64], &[Ty::Char]),
+        Op::FromChar => (":from-char", &[Ty::Char], &[Ty::Str]),
+        Op::ReadLineBytes => (":readline-bytes", &[], &[Ty::Bytes, Ty::Bool]),
+        Op::ReadBytes => (":read-bytes", &[], &[Ty::Bytes]),
+        Op::WriteBytes => (":write-bytes", &[Ty::Bytes], &[]),
+        Op::Utf8Decode => (":utf8-decode", &[Ty::Bytes], &[Ty::Str, Ty::Bool]),
+        Op::Utf8Encode => (":utf8-encode", &[Ty::Str], &[Ty::Bytes]),
+        Op::Utf8DecodeLossy => (":utf8-decode-lossy", &[Ty::Bytes], &[Ty::Str]),
+        Op::BytesLen => (":bytes-len", &[Ty::Bytes], &[Ty::I64]),
+        Op::BytesSlice => (":bytes-slice", &[Ty::Bytes, Ty::I64, Ty::I64], &[Ty::Bytes]),
+        Op::ReadFile => (":read-file", &[Ty::Str], &[Ty::Str, Ty::Bool]),
+        Op::WriteFile => (":write-file", &[Ty::Str, Ty::Str], &[Ty::Bool]),
+        Op::AppendFile => (":append-file", &[Ty::Str, Ty::Str], &[Ty::Bool]),
+        Op::FileExists => (":file-exists", &[Ty::Str], &[Ty::Bool]),
+        Op::OpenFile => (":open-file", &[Ty::Str], &[Ty::File, Ty::Bool]),
+        Op::FileReadLine => (":file-readline", &[Ty::File], &[Ty::Str, Ty::Bool]),
+        Op::FileReadLineBytes => (":file-readline-bytes", &[Ty::File], &[Ty::Bytes, Ty::Bool]),
+        Op::CloseFile => (":close-file", &[Ty::File], &[]),
+        Op::ArgCount => (":argc", &[], &[Ty::I64]),
+        Op::Arg => (":arg", &[Ty::I64], &[Ty::Str, Ty::Bool]),
+        Op::Env => (":env", &[Ty::Str], &[Ty::Str, Ty::Bool]),
+        Op::EPrintLn => (":eprintln", &[Ty::Str], &[]),
+        Op::Exit => (":exit", &[Ty::I32], &[]),
+        _ => return None,
+    })
+}
+
 /// Stack effect for integer ordering: same-width integers in, Bool out.
 fn cmp_int(stack: &mut Vec<Ty>, op_label: &str) -> Result<()> {
     let (a, b) = pop2(stack, op_label)?;
@@ -1097,15 +3439,35 @@ fn cmp_int(stack: &mut Vec<Ty>, op_label: &str) -> Result<()> {
 /// Stack effect for a `Call(name)`: verify the top of the stack matches
 /// the function's declared inputs in declaration order, then replace them
 /// with the declared outputs.
-fn check_call(name: &str, stack: &mut Vec<Ty>, sigs: &HashMap<String, Rc<FnSig>>) -> Result<()> {
+///
+/// A generic callee's variables are instantiated by unifying its inputs
+/// with the argument types ([`Subst`]); the outputs are then pushed with
+/// the bindings applied. Its `..R` row matches the values just below the
+/// arguments — as many as a function-typed argument fixed `R` to, or none
+/// if nothing did — and those values are replaced by the output row.
+///
+/// Returns how many values at the bottom of the stack the call left
+/// alone: everything beneath the arguments and the row's values.
+pub(crate) fn check_call(
+    name: &str,
+    stack: &mut Vec<Ty>,
+    sigs: &HashMap<String, Rc<FnSig>>,
+) -> Result<usize> {
     let sig = sigs
         .get(name)
         .ok_or_else(|| format!("call to undefined function `{name}`"))?;
+    if sig.is_inferred() {
+        return Err(format!(
+            "calling `{name}`: its signature is inferred (`?`), so it must be \
+             defined before any function that calls it, and cannot call itself"
+        )
+        .into());
+    }
     let n = sig.inputs.len();
-    if stack.len() < n {
+    if value_depth(stack) < n {
         return Err(format!(
             "calling `{name}`: needs {n} value(s) on the stack, have {}",
-            stack.len()
+            value_depth(stack)
         )
         .into());
     }
@@ -1113,9 +3475,11 @@ fn check_call(name: &str, stack: &mut Vec<Ty>, sigs: &HashMap<String, Rc<FnSig>>
     // direction as the runtime drain in `Vm::call`. So the type at
     // `stack[split + i]` must match `inputs[i]`.
     let split = stack.len() - n;
+    let mut subst = Subst::default();
     for (i, (param, expected)) in sig.inputs.iter().enumerate() {
-        let actual = stack[split + i];
-        if actual != *expected {
+        let actual = &stack[split + i];
+        if !subst.unify(expected, actual) {
+            let expected = subst.apply(expected);
             return Err(format!(
                 "calling `{name}`: argument `{param}` (position {i}) \
                  expects {expected}, got {actual}"
@@ -1123,97 +3487,215 @@ fn check_call(name: &str, stack: &mut Vec<Ty>, sigs: &HashMap<String, Rc<FnSig>>
             .into());
         }
     }
-    stack.truncate(split);
-    for out in &sig.outputs {
-        stack.push(*out);
+    let mut cut = split;
+    if let Some(row) = &sig.rest {
+        let below = &stack[..split];
+        match subst.rows.get(row) {
+            Some(bound) if below.ends_with(bound) => cut = split - bound.len(),
+            Some(bound) => {
+                return Err(format!(
+                    "calling `{name}`: `..{row}` is [{}] here, but the stack below \
+                     the arguments is [{}]",
+                    fmt_types(bound),
+                    fmt_types(below)
+                )
+                .into())
+            }
+            None => {
+                subst.rows.insert(Rc::clone(row), Vec::new());
+            }
+        }
+    }
+    let outputs = subst.apply_seq(&sig.outputs);
+    stack.truncate(cut);
+    stack.extend(outputs);
+    Ok(cut)
+}
+
+/// How many values sit on an abstract stack above the row marking the rest
+/// of a generic function's caller's stack, if there is one.
+fn value_depth(stack: &[Ty]) -> usize {
+    match stack.first() {
+        Some(Ty::Row(_)) => stack.len() - 1,
+        _ => stack.len(),
+    }
+}
+
+/// Bindings for a generic callee's type variables (`'T` to a type) and
+/// row variables (`..R` to a run of types), built up by unifying its
+/// signature with the types at one call site.
+///
+/// Only the callee's variables are bound. Variables in the *argument*
+/// types belong to the calling function's own signature, where they are
+/// rigid: they match only themselves.
+#[derive(Default)]
+pub(crate) struct Subst {
+    tys: HashMap<Rc<str>, Ty>,
+    rows: HashMap<Rc<str>, Vec<Ty>>,
+}
+
+impl Subst {
+    /// Unify the callee-side `pattern` with the argument type `actual`,
+    /// binding variables as needed. `false` on a mismatch.
+    fn unify(&mut self, pattern: &Ty, actual: &Ty) -> bool {
+        match (pattern, actual) {
+            // A row marker is the rest of the stack, never one value.
+            (_, Ty::Row(_)) => false,
+            (Ty::Var(v), _) => match self.tys.get(v) {
+                Some(bound) => bound == actual,
+                None => {
+                    self.tys.insert(Rc::clone(v), actual.clone());
+                    true
+                }
+            },
+            (Ty::Fn(p), Ty::Fn(a)) => {
+                self.unify_seq(&p.inputs, &a.inputs) && self.unify_seq(&p.outputs, &a.outputs)
+            }
+            _ => pattern == actual,
+        }
+    }
+
+    /// Unify a whole input or output list. A leading row in `pattern`
+    /// matches whatever `actual` has before the fixed part.
+    fn unify_seq(&mut self, pattern: &[Ty], actual: &[Ty]) -> bool {
+        let (row, fixed) = match pattern.split_first() {
+            Some((Ty::Row(r), fixed)) => (Some(r), fixed),
+            _ => (None, pattern),
+        };
+        let Some(split) = actual.len().checked_sub(fixed.len()) else {
+            return false;
+        };
+        if !fixed
+            .iter()
+            .zip(&actual[split..])
+            .all(|(p, a)| self.unify(p, a))
+        {
+            return false;
+        }
+        let Some(row) = row else {
+            return split == 0;
+        };
+        let prefix = &actual[..split];
+        match self.rows.get(row) {
+            Some(bound) => bound == prefix,
+            None => {
+                self.rows.insert(Rc::clone(row), prefix.to_vec());
+                true
+            }
+        }
+    }
+
+    /// `ty` with every bound variable replaced by its binding.
+    fn apply(&self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Var(v) => self.tys.get(v).cloned().unwrap_or_else(|| ty.clone()),
+            Ty::Fn(f) => Ty::Fn(Rc::new(FnTy {
+                inputs: self.apply_seq(&f.inputs),
+                outputs: self.apply_seq(&f.outputs),
+            })),
+            _ => ty.clone(),
+        }
+    }
+
+    /// A list with bindings applied; a bound row expands in place.
+    fn apply_seq(&self, seq: &[Ty]) -> Vec<Ty> {
+        let mut out = Vec::with_capacity(seq.len());
+        for ty in seq {
+            match ty {
+                Ty::Row(r) if self.rows.contains_key(r) => out.extend(self.rows[r].iter().cloned()),
+                _ => out.push(self.apply(ty)),
+            }
+        }
+        out
     }
-    Ok(())
 }
 
 /// Stack effect for `match`: pop the matched value's type, type-check
 /// every arm body against a copy of the abstract stack, require all arm
 /// results to agree pointwise, and require exhaustiveness (§11.8).
 ///
+/// A `match2` pops two types and checks each arm's patterns column by
+/// column; exhaustiveness is then over every combination of the two.
+///
 /// The agreed-on shape becomes the post-match stack.
 fn check_match(
     arms: &[MatchArm],
     stack: &mut Vec<Ty>,
-    locals: &[Ty],
+    locals: &mut [Ty],
     sigs: &HashMap<String, Rc<FnSig>>,
 ) -> Result<()> {
-    let matched_ty = stack
-        .pop()
-        .ok_or("stack underflow on `match` (no value to match against)")?;
-    if arms.is_empty() {
+    let Some(first) = arms.first() else {
         return Err("`match` requires at least one arm".into());
-    }
+    };
+    let arity = first.patterns.len();
+    let word = if arity == 1 { "match" } else { "match2" };
+    let matched_tys = if arity == 1 {
+        vec![pop1(stack, word)?]
+    } else {
+        if value_depth(stack) < arity {
+            return Err(format!(
+                "stack underflow on `{word}` (need {arity} values, have {})",
+                value_depth(stack)
+            )
+            .into());
+        }
+        stack.split_off(stack.len() - arity)
+    };
 
     // Pattern compatibility — each pattern must be reachable on the
-    // matched type. Wildcards are always reachable; integer patterns are
-    // legal against any integer width but their value must fit (otherwise
-    // the arm could never fire after the runtime narrowing in
-    // `pattern_matches`).
+    // matched type in its column. A guard sees only copies of the matched
+    // values and must leave one `Bool`.
     for arm in arms {
-        let compatible = match (matched_ty, arm.pattern) {
-            (_, Pattern::Wildcard) => true,
-            (Ty::Str, Pattern::Str(_)) => true,
-            (Ty::Bool, Pattern::Bool(_)) => true,
-            (t, Pattern::Int { value, explicit_ty }) if t.is_int() => {
-                let pattern_ty = Ty::from(value);
-                if explicit_ty {
-                    if pattern_ty != t {
-                        return Err(format!(
-                            "pattern literal has type {pattern_ty}, but the matched type is {matched_ty}"
-                        )
-                        .into());
-                    }
-                } else {
-                    let Value::I64(n) = value else {
-                        unreachable!("unsuffixed integer patterns are i64")
-                    };
-                    let (lo, hi) = t.int_range().expect("integer types have a range");
-                    let n = n as i128;
-                    if n < lo || n >= hi {
-                        return Err(format!(
-                            "pattern literal {n} is out of range for {matched_ty}"
-                        )
-                        .into());
-                    }
-                }
-                true
+        if arm.patterns.len() != arity {
+            return Err(format!("every `{word}` arm must have {arity} pattern(s)").into());
+        }
+        for (ty, pattern) in matched_tys.iter().zip(&arm.patterns) {
+            check_pattern(ty, pattern)?;
+        }
+        if let Some(guard) = &arm.guard {
+            let mut guard_stack = matched_tys.clone();
+            let mut guard_locals = arm_locals(arm, locals, &matched_tys);
+            for op in guard.iter() {
+                step(op, &mut guard_stack, &mut guard_locals, sigs)?;
+            }
+            let n = locals.len();
+            locals.clone_from_slice(&guard_locals[..n]);
+            settle(&mut guard_stack, locals);
+            if guard_stack != [Ty::Bool] {
+                return Err(format!(
+                    "a `when` guard must leave exactly [Bool], but leaves [{}]",
+                    fmt_types(&guard_stack)
+                )
+                .into());
             }
-            _ => false,
-        };
-        if !compatible {
-            return Err(format!(
-                "match-arm pattern is incompatible with the matched type {matched_ty}"
-            )
-            .into());
         }
     }
 
-    // Exhaustiveness — Bool requires both literals (or a wildcard);
-    // every other type (integers and Str) is treated as unbounded and
-    // requires a wildcard arm. We deliberately do not special-case `u8`
-    // (256 values, technically exhaustible by listing); that would be a
-    // soft rule and §11.2 chose the hard one.
-    let has_wildcard = arms.iter().any(|a| matches!(a.pattern, Pattern::Wildcard));
-    let exhaustive = match matched_ty {
-        Ty::Bool => {
-            has_wildcard
-                || (arms
-                    .iter()
-                    .any(|a| matches!(a.pattern, Pattern::Bool(true)))
-                    && arms
-                        .iter()
-                        .any(|a| matches!(a.pattern, Pattern::Bool(false))))
+    // Exhaustiveness — Bool requires both literals (or a wildcard); an
+    // integer column is covered when its literals and ranges span the
+    // type's whole `int_range`, a `Char` column when they span every
+    // Unicode scalar value; `Str` and every other type require a
+    // wildcard. Guarded arms never count towards coverage, since their
+    // guard may say no.
+    if let Some(missing) = uncovered(arms, &matched_tys) {
+        return Err(if arity == 1 && matches!(missing[0], Sample::Other) {
+            format!(
+                "non-exhaustive `match` on {} (add the missing arm or `_`)",
+                matched_tys[0]
+            )
+        } else {
+            let shown: Vec<String> = missing.iter().map(Sample::to_string).collect();
+            let on = if arity == 1 {
+                matched_tys[0].to_string()
+            } else {
+                format!("({})", fmt_types(&matched_tys))
+            };
+            format!(
+                "non-exhaustive `{word}` on {on}: no arm matches `{}`",
+                shown.join(" ")
+            )
         }
-        _ => has_wildcard,
-    };
-    if !exhaustive {
-        return Err(
-            format!("non-exhaustive `match` on {matched_ty} (add the missing arm or `_`)").into(),
-        );
+        .into());
     }
 
     // Check every arm body against a fresh copy of the abstract stack;
@@ -1222,12 +3704,18 @@ fn check_match(
     let mut joined: Option<Vec<Ty>> = None;
     for (i, arm) in arms.iter().enumerate() {
         let mut arm_stack = snapshot.clone();
+        let mut body_locals = arm_locals(arm, locals, &matched_tys);
         for op in arm.body.iter() {
-            step(op, &mut arm_stack, locals, sigs)?;
+            step(op, &mut arm_stack, &mut body_locals, sigs)?;
         }
-        match &joined {
+        let n = locals.len();
+        locals.clone_from_slice(&body_locals[..n]);
+        // An arm may have fixed an input's `?` after earlier arms finished.
+        settle(&mut arm_stack, locals);
+        match &mut joined {
             None => joined = Some(arm_stack),
             Some(expected) => {
+                settle(expected, locals);
                 if &arm_stack != expected {
                     return Err(format!(
                         "match arm {i} leaves [{}], but the first arm leaves [{}] \
@@ -1244,11 +3732,581 @@ fn check_match(
     Ok(())
 }
 
+/// The locals an arm's guard and body see: those in scope at the match,
+/// then the types of the values the arm binds.
+fn arm_locals(arm: &MatchArm, locals: &[Ty], matched_tys: &[Ty]) -> Vec<Ty> {
+    let mut all = locals.to_vec();
+    all.extend(arm.binds.iter().map(|&column| matched_tys[column].clone()));
+    all
+}
+
+/// Check that `pattern` can match a value of type `ty`. Wildcards always
+/// can; integer patterns are legal against any integer width but their
+/// value must fit (otherwise the arm could never fire after the runtime
+/// narrowing in `pattern_matches`), and a range must not be empty.
+fn check_pattern(ty: &Ty, pattern: &Pattern) -> Result<()> {
+    let compatible = match (ty, pattern) {
+        (_, Pattern::Wildcard) => true,
+        (Ty::Str, Pattern::Str(_)) => true,
+        (Ty::Bool, Pattern::Bool(_)) => true,
+        (Ty::Char, Pattern::Char(_)) => true,
+        (Ty::Char, Pattern::CharRange { lo, hi }) => {
+            let lo = lo.unwrap_or('\0');
+            let hi = hi.unwrap_or(char::MAX);
+            if lo > hi {
+                return Err(format!("range pattern {lo:?}..={hi:?} is empty").into());
+            }
+            true
+        }
+        (t, Pattern::Int { value, explicit_ty }) if t.is_int() => {
+            check_int_pattern(t, *value, *explicit_ty)?;
+            true
+        }
+        (t, Pattern::Range { lo, hi }) if t.is_int() => {
+            for bound in [lo, hi].into_iter().flatten() {
+                check_int_pattern(t, bound.value, bound.explicit_ty)?;
+            }
+            let (min, end) = t.int_range().expect("integer types have a range");
+            let lo = lo.map_or(min, |b| int_of(b.value));
+            let hi = hi.map_or(end - 1, |b| int_of(b.value));
+            if lo > hi {
+                return Err(format!("range pattern {lo}..={hi} is empty").into());
+            }
+            true
+        }
+        (t, Pattern::Or(alternatives)) => {
+            for alternative in alternatives.iter() {
+                check_pattern(t, alternative)?;
+            }
+            true
+        }
+        _ => false,
+    };
+    if !compatible {
+        return Err(format!("match-arm pattern is incompatible with the matched type {ty}").into());
+    }
+    Ok(())
+}
+
+/// Check an integer literal, or a range bound, against the integer type
+/// `ty` it is matched against.
+fn check_int_pattern(ty: &Ty, value: Value, explicit_ty: bool) -> Result<()> {
+    let pattern_ty = Ty::of_int(value);
+    if explicit_ty {
+        if pattern_ty != *ty {
+            return Err(format!(
+                "pattern literal has type {pattern_ty}, but the matched type is {ty}"
+            )
+            .into());
+        }
+    } else {
+        let (lo, hi) = ty.int_range().expect("integer types have a range");
+        let n = int_of(value);
+        if n < lo || n >= hi {
+            return Err(format!("pattern literal {n} is out of range for {ty}").into());
+        }
+    }
+    Ok(())
+}
+
+/// The value of an integer pattern literal.
+fn int_of(value: Value) -> i128 {
+    value.as_i128().expect("integer patterns hold integers")
+}
+
+/// One matched value tried by the exhaustiveness check: a `Bool`, an
+/// interval `lo..=hi` of an integer type whose own range is `min..=max`, an
+/// interval of chars, or a value none of a column's patterns name, which
+/// only `_` covers.
+#[derive(Clone, Copy)]
+enum Sample {
+    Bool(bool),
+    Int {
+        lo: i128,
+        hi: i128,
+        min: i128,
+        max: i128,
+    },
+    /// The chars `lo..=hi`.
+    Char {
+        lo: char,
+        hi: char,
+    },
+    Other,
+}
+
+/// Shown as the pattern an arm covering the sample would need.
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Sample::Bool(b) => write!(f, "{b}"),
+            Sample::Int { lo, hi, min, max } => match (lo == min, hi == max) {
+                (true, true) => write!(f, "_"),
+                _ if lo == hi => write!(f, "{lo}"),
+                (true, false) => write!(f, "..={hi}"),
+                (false, true) => write!(f, "{lo}.."),
+                (false, false) => write!(f, "{lo}..={hi}"),
+            },
+            Sample::Char { lo, hi } => match (lo == '\0', hi == char::MAX) {
+                (true, true) => write!(f, "_"),
+                _ if lo == hi => write!(f, "{lo:?}"),
+                (true, false) => write!(f, "..={hi:?}"),
+                (false, true) => write!(f, "{lo:?}.."),
+                (false, false) => write!(f, "{lo:?}..={hi:?}"),
+            },
+            Sample::Other => write!(f, "_"),
+        }
+    }
+}
+
+/// A combination of matched values no unguarded arm covers, if there is one.
+///
+/// Each `Bool` column stands for its two values. An integer or `Char`
+/// column is cut into intervals at every literal and range bound its
+/// patterns mention;
+/// every pattern covers each interval wholly or not at all, so one value
+/// of each interval stands for the rest of it. Any other column
+/// stands for a value none of its literals name, which only `_` covers — a
+/// column's literals need not be tried, since whatever covers the unnamed
+/// value covers them too.
+fn uncovered(arms: &[MatchArm], tys: &[Ty]) -> Option<Vec<Sample>> {
+    let mut candidates: Vec<Vec<Sample>> = vec![Vec::new()];
+    for (column, ty) in tys.iter().enumerate() {
+        let values: Vec<Sample> = match ty {
+            Ty::Bool => vec![Sample::Bool(true), Sample::Bool(false)],
+            t if t.is_int() => {
+                let (min, end) = t.int_range().expect("integer types have a range");
+                let mut cuts = vec![min];
+                for arm in arms {
+                    push_cuts(&arm.patterns[column], &mut cuts);
+                }
+                cuts.retain(|&c| c >= min && c < end);
+                cuts.sort_unstable();
+                cuts.dedup();
+                cuts.push(end);
+                cuts.windows(2)
+                    .map(|w| Sample::Int {
+                        lo: w[0],
+                        hi: w[1] - 1,
+                        min,
+                        max: end - 1,
+                    })
+                    .collect()
+            }
+            Ty::Char => {
+                let mut cuts = vec![0];
+                for arm in arms {
+                    push_cuts(&arm.patterns[column], &mut cuts);
+                }
+                // No char is a surrogate, so a cut inside them moves past.
+                for cut in &mut cuts {
+                    if (0xD800..0xE000).contains(cut) {
+                        *cut = 0xE000;
+                    }
+                }
+                cuts.sort_unstable();
+                cuts.dedup();
+                cuts.push(i128::from(u32::from(char::MAX)) + 1);
+                let char_at = |n: i128| {
+                    let n = u32::try_from(n).expect("cuts are in the char range");
+                    char::from_u32(n).unwrap_or('\u{d7ff}')
+                };
+                cuts.windows(2)
+                    .map(|w| Sample::Char {
+                        lo: char_at(w[0]),
+                        hi: char_at(w[1] - 1),
+                    })
+                    .collect()
+            }
+            _ => vec![Sample::Other],
+        };
+        candidates = candidates
+            .into_iter()
+            .flat_map(|prefix| {
+                values.iter().map(move |&v| {
+                    let mut next = prefix.clone();
+                    next.push(v);
+                    next
+                })
+            })
+            .collect();
+    }
+    candidates.into_iter().find(|values| {
+        !arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .any(|arm| arm.patterns.iter().zip(values).all(|(p, &v)| covers(p, v)))
+    })
+}
+
+/// Where `pattern` starts or stops matching, as the first integer on each
+/// side of the change.
+fn push_cuts(pattern: &Pattern, cuts: &mut Vec<i128>) {
+    match pattern {
+        Pattern::Int { value, .. } => {
+            let n = int_of(*value);
+            cuts.extend([n, n + 1]);
+        }
+        Pattern::Range { lo, hi } => {
+            cuts.extend(lo.map(|b| int_of(b.value)));
+            cuts.extend(hi.map(|b| int_of(b.value) + 1));
+        }
+        Pattern::Char(c) => {
+            let n = i128::from(u32::from(*c));
+            cuts.extend([n, n + 1]);
+        }
+        Pattern::CharRange { lo, hi } => {
+            cuts.extend(lo.map(|c| i128::from(u32::from(c))));
+            cuts.extend(hi.map(|c| i128::from(u32::from(c)) + 1));
+        }
+        Pattern::Or(alternatives) => {
+            for alternative in alternatives.iter() {
+                push_cuts(alternative, cuts);
+            }
+        }
+        Pattern::Str(_) | Pattern::Bool(_) | Pattern::Wildcard => {}
+    }
+}
+
+/// Whether `pattern` matches the sample value `v`.
+fn covers(pattern: &Pattern, v: Sample) -> bool {
+    match (pattern, v) {
+        (Pattern::Wildcard, _) => true,
+        (Pattern::Bool(b), Sample::Bool(v)) => *b == v,
+        (Pattern::Int { value, .. }, Sample::Int { lo: n, .. }) => int_of(*value) == n,
+        (Pattern::Range { lo, hi }, Sample::Int { lo: n, .. }) => {
+            lo.is_none_or(|b| int_of(b.value) <= n) && hi.is_none_or(|b| n <= int_of(b.value))
+        }
+        (Pattern::Char(c), Sample::Char { lo, .. }) => *c == lo,
+        (Pattern::CharRange { lo, hi }, Sample::Char { lo: c, .. }) => {
+            lo.is_none_or(|b| b <= c) && hi.is_none_or(|b| c <= b)
+        }
+        (Pattern::Or(alternatives), v) => alternatives.iter().any(|p| covers(p, v)),
+        _ => false,
+    }
+}
+
+/// Fill in the `?` parts of every top-level definition's signature in
+/// `ops` (and in the files they import), in source order, from what its
+/// body does (§8, Signature inference). Runs between [`compile`] and
+/// [`check`]; afterwards each definition carries an ordinary signature,
+/// which is what the dictionary stores and `:sig` shows.
+pub fn infer(ops: &mut [Op], prior_sigs: &HashMap<String, Rc<FnSig>>) -> Result<()> {
+    let mut sigs = prior_sigs.clone();
+    collect_sigs(ops, &mut sigs);
+    infer_defs(ops, &mut sigs)
+}
+
+fn infer_defs(ops: &mut [Op], sigs: &mut HashMap<String, Rc<FnSig>>) -> Result<()> {
+    for op in ops {
+        match op {
+            Op::DefineFn(name, f) if f.sig.is_inferred() => {
+                let sig = Rc::new(infer_sig(name, &f.sig, &f.body, sigs)?);
+                sigs.insert(name.clone(), Rc::clone(&sig));
+                f.sig = sig;
+            }
+            Op::Import(_, body) => {
+                let mut defs = body.to_vec();
+                infer_defs(&mut defs, sigs)?;
+                *body = defs.into();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Infer one signature. The body is checked with each `?` input as a
+/// [`Ty::Hole`], which the first op that needs a particular type there
+/// fills in ([`fill_holes`]); the outputs, if `?`, are whatever the body
+/// leaves. An input nothing constrains becomes a type variable — the
+/// function works for any type there. The result is then checked like a
+/// written signature, so inference can only accept what the checker does.
+fn infer_sig(
+    fn_name: &str,
+    sig: &FnSig,
+    body: &[Op],
+    sigs: &HashMap<String, Rc<FnSig>>,
+) -> Result<FnSig> {
+    let mut locals: Vec<Ty> = sig.inputs.iter().map(|(_, t)| t.clone()).collect();
+    let mut stack = Vec::new();
+    for op in body {
+        step(op, &mut stack, &mut locals, sigs)
+            .map_err(|e| -> Box<dyn Error> { format!("{}: {e}", fn_scope(fn_name)).into() })?;
+    }
+    if !sig.infer_outputs && stack.len() == sig.outputs.len() {
+        for (have, want) in stack.iter().zip(&sig.outputs) {
+            bind_hole(&mut locals, have, want);
+        }
+    }
+    let mut fresh = ('A'..='Z').map(|c| Rc::<str>::from(c.to_string()));
+    for ty in &mut locals {
+        if let Ty::Hole(_) = ty {
+            let var = fresh.next().ok_or_else(|| {
+                format!(
+                    "{}: too many unconstrained `?` inputs",
+                    describe_fn(fn_name)
+                )
+            })?;
+            *ty = Ty::Var(var);
+        }
+    }
+    settle(&mut stack, &locals);
+    let inferred = FnSig {
+        inputs: sig
+            .inputs
+            .iter()
+            .zip(locals)
+            .map(|((name, _), ty)| (name.clone(), ty))
+            .collect(),
+        outputs: if sig.infer_outputs {
+            stack
+        } else {
+            sig.outputs.clone()
+        },
+        rest: None,
+        infer_outputs: false,
+    };
+    check_body(fn_name, &inferred, body, sigs)?;
+    Ok(inferred)
+}
+
+/// Before `op` runs during inference, give every `?` input it is about to
+/// consume the type `op` needs there, where that is unambiguous from `op`
+/// and the other operands. Two unknown integers default to `i64`, as an
+/// unsuffixed literal does. Does nothing outside inference, when no input
+/// is a hole.
+fn fill_holes(op: &Op, stack: &mut [Ty], locals: &mut [Ty], sigs: &HashMap<String, Rc<FnSig>>) {
+    if !locals.iter().any(|t| matches!(t, Ty::Hole(_))) {
+        return;
+    }
+    settle(stack, locals);
+    let n = stack.len();
+    match op {
+        Op::Add
+        | Op::Sub
+        | Op::Mul
+        | Op::Div
+        | Op::WrappingAdd
+        | Op::WrappingMul
+        | Op::SaturatingAdd
+        | Op::SaturatingSub
+        | Op::Rem
+        | Op::Mod
+        | Op::DivMod
+        | Op::Min
+        | Op::Max
+        | Op::Pow
+        | Op::BitAnd
+        | Op::BitOr
+        | Op::BitXor
+        | Op::Shl
+        | Op::Shr
+        | Op::Rotl
+        | Op::Rotr
+        | Op::Eq
+        | Op::Ne
+        | Op::Lt
+        | Op::Le
+        | Op::Gt
+        | Op::Ge
+            if n >= 2 =>
+        {
+            let (a, b) = (&stack[n - 2], &stack[n - 1]);
+            match (a, b) {
+                (Ty::Hole(_), Ty::Hole(_)) => {
+                    bind_hole(locals, a, &Ty::I64);
+                    bind_hole(locals, b, &Ty::I64);
+                }
+                (Ty::Hole(_), known) => bind_hole(locals, a, known),
+                (known, Ty::Hole(_)) => bind_hole(locals, b, known),
+                _ => {}
+            }
+        }
+        Op::Not | Op::And | Op::Or => {
+            let k = if matches!(op, Op::Not) { 1 } else { 2 };
+            for ty in &stack[n.saturating_sub(k)..] {
+                bind_hole(locals, ty, &Ty::Bool);
+            }
+        }
+        Op::Contains | Op::PrintLn | Op::Parse(_) => {
+            let k = if matches!(op, Op::Contains) { 2 } else { 1 };
+            for ty in &stack[n.saturating_sub(k)..] {
+                bind_hole(locals, ty, &Ty::Str);
+            }
+        }
+        Op::StrLen
+        | Op::Slice
+        | Op::Split
+        | Op::Trim
+        | Op::StartsWith
+        | Op::EndsWith
+        | Op::Find
+        | Op::Replace
+        | Op::Upper
+        | Op::Lower
+        | Op::Repeat
+        | Op::NextChar
+        | Op::CharAt
+        | Op::FromChar
+        | Op::ReadLineBytes
+        | Op::ReadBytes
+        | Op::WriteBytes
+        | Op::Utf8Decode
+        | Op::Utf8Encode
+        | Op::Utf8DecodeLossy
+        | Op::BytesLen
+        | Op::BytesSlice
+        | Op::ReadFile
+        | Op::WriteFile
+        | Op::AppendFile
+        | Op::FileExists
+        | Op::OpenFile
+        | Op::FileReadLine
+        | Op::FileReadLineBytes
+        | Op::CloseFile
+        | Op::ArgCount
+        | Op::Arg
+        | Op::Env
+        | Op::EPrintLn
+        | Op::Exit => {
+            let (_, inputs, _) = fixed_effect(op).expect("a fixed-effect word");
+            let k = inputs.len().min(n);
+            for (want, have) in inputs[inputs.len() - k..].iter().zip(&stack[n - k..]) {
+                bind_hole(locals, have, want);
+            }
+        }
+        Op::Format(pieces) => {
+            let hex: Vec<bool> = pieces
+                .iter()
+                .filter_map(|piece| match piece {
+                    FormatPiece::Hole(spec) => Some(*spec == FormatSpec::Hex),
+                    FormatPiece::Text(_) => None,
+                })
+                .collect();
+            let k = hex.len().min(n);
+            for (is_hex, have) in hex[hex.len() - k..].iter().zip(&stack[n - k..]) {
+                if *is_hex {
+                    bind_hole(locals, have, &Ty::I64);
+                }
+            }
+        }
+        Op::Cast(_) | Op::BitNot | Op::Abs | Op::Neg | Op::ToDigits if n >= 1 => {
+            bind_hole(locals, &stack[n - 1], &Ty::I64)
+        }
+        Op::Call(name) | Op::TailCall(name) => {
+            if let Some(callee) = sigs.get(name) {
+                let k = callee.inputs.len().min(n);
+                let args = || {
+                    callee.inputs[callee.inputs.len() - k..]
+                        .iter()
+                        .zip(&stack[n - k..])
+                };
+                // A generic input takes whatever the other arguments bind
+                // its variables to, as the call itself will.
+                let mut subst = Subst::default();
+                for ((_, want), have) in args() {
+                    if !matches!(have, Ty::Hole(_)) {
+                        subst.unify(want, have);
+                    }
+                }
+                for ((_, want), have) in args() {
+                    let want = subst.apply(want);
+                    if !want.is_generic() {
+                        bind_hole(locals, have, &want);
+                    }
+                }
+            }
+        }
+        Op::CallIndirect if n >= 1 => {
+            if let Ty::Fn(f) = &stack[n - 1] {
+                let k = f.inputs.len().min(n - 1);
+                for (want, have) in f.inputs[f.inputs.len() - k..]
+                    .iter()
+                    .zip(&stack[n - 1 - k..n - 1])
+                {
+                    bind_hole(locals, have, want);
+                }
+            }
+        }
+        Op::MakeStruct(st) => {
+            let k = st.fields.len().min(n);
+            for ((_, want), have) in st.fields[st.fields.len() - k..].iter().zip(&stack[n - k..]) {
+                bind_hole(locals, have, want);
+            }
+        }
+        Op::GetField(st, _) if n >= 1 => {
+            bind_hole(locals, &stack[n - 1], &Ty::Struct(Rc::clone(st)));
+        }
+        Op::SetField(st, i) if n >= 2 => {
+            bind_hole(locals, &stack[n - 2], &Ty::Struct(Rc::clone(st)));
+            bind_hole(locals, &stack[n - 1], &st.fields[*i as usize].1);
+        }
+        Op::Match(arms) => {
+            let arity = arms.first().map_or(0, |arm| arm.patterns.len());
+            if n >= arity {
+                for (column, have) in stack[n - arity..].iter().enumerate() {
+                    let pattern_ty = arms
+                        .iter()
+                        .find_map(|arm| pattern_ty(&arm.patterns[column]));
+                    if let Some(ty) = pattern_ty {
+                        bind_hole(locals, have, &ty);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    settle(stack, locals);
+}
+
+/// The type a pattern names, if it names one: `_` matches anything.
+fn pattern_ty(pattern: &Pattern) -> Option<Ty> {
+    match pattern {
+        Pattern::Int { value, .. } => Some(Ty::of_int(*value)),
+        Pattern::Range { lo, hi } => lo.or(*hi).map(|b| Ty::of_int(b.value)),
+        Pattern::Str(_) => Some(Ty::Str),
+        Pattern::Bool(_) => Some(Ty::Bool),
+        Pattern::Char(_) | Pattern::CharRange { .. } => Some(Ty::Char),
+        Pattern::Or(alternatives) => alternatives.iter().find_map(pattern_ty),
+        Pattern::Wildcard => None,
+    }
+}
+
+/// If `ty` is an input's hole, fix that input's type to `to`.
+fn bind_hole(locals: &mut [Ty], ty: &Ty, to: &Ty) {
+    if let Ty::Hole(i) = ty {
+        locals[*i as usize] = to.clone();
+    }
+}
+
+/// Replace each hole on `stack` whose input has since been given a type,
+/// including holes inside a function type's inputs and outputs.
+fn settle(stack: &mut [Ty], locals: &[Ty]) {
+    for ty in stack {
+        *ty = settled(ty, locals);
+    }
+}
+
+fn settled(ty: &Ty, locals: &[Ty]) -> Ty {
+    match ty {
+        Ty::Hole(i) => locals[*i as usize].clone(),
+        Ty::Fn(f) => Ty::Fn(Rc::new(FnTy {
+            inputs: f.inputs.iter().map(|t| settled(t, locals)).collect(),
+            outputs: f.outputs.iter().map(|t| settled(t, locals)).collect(),
+        })),
+        _ => ty.clone(),
+    }
+}
+
 /// Check one function body against its declared sig.
 ///
 /// The body's abstract data stack starts **empty** — inputs are drained
 /// into the locals frame by `Op::Call`, not left on the stack — and the
 /// inputs become the body's `locals` for `LoadLocal` to resolve against.
+/// A `..R` signature's body starts with just the row `..R` instead.
+/// Type variables are rigid here: `'T` is a type of its own that matches
+/// only itself.
 /// At end of body the abstract stack must equal the declared outputs
 /// exactly; anything else is a type error.
 fn check_body(
@@ -1257,15 +4315,18 @@ fn check_body(
     body: &[Op],
     sigs: &HashMap<String, Rc<FnSig>>,
 ) -> Result<()> {
-    let locals: Vec<Ty> = sig.inputs.iter().map(|(_, t)| *t).collect();
-    let mut stack: Vec<Ty> = Vec::new();
+    let mut locals: Vec<Ty> = sig.inputs.iter().map(|(_, t)| t.clone()).collect();
+    // A row-polymorphic body starts on top of the caller's stack, which it
+    // sees only as the opaque row.
+    let mut stack: Vec<Ty> = sig.rest.iter().map(|r| Ty::Row(Rc::clone(r))).collect();
     for op in body {
-        step(op, &mut stack, &locals, sigs)
-            .map_err(|e| -> Box<dyn Error> { format!("in `{fn_name}`: {e}").into() })?;
+        step(op, &mut stack, &mut locals, sigs)
+            .map_err(|e| -> Box<dyn Error> { format!("{}: {e}", fn_scope(fn_name)).into() })?;
     }
     if stack != sig.outputs {
         return Err(format!(
-            "function `{fn_name}` body leaves [{}], but signature declares outputs [{}]",
+            "{} body leaves [{}], but signature declares outputs [{}]",
+            describe_fn(fn_name),
             fmt_types(&stack),
             fmt_types(&sig.outputs),
         )
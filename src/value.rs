@@ -1,6 +1,10 @@
 //! The data layer: the values that live on the Plenty stack, and the heap
 //! that backs the ones too large to store inline.
 
+use std::fs::File;
+use std::io::BufReader;
+use std::rc::Rc;
+
 /// A handle to a string held in a [`Heap`].
 ///
 /// Four bytes wide, so a string-typed stack slot is no more expensive than an
@@ -8,6 +12,26 @@
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct StrId(u32);
 
+/// A handle to a byte string held in a [`Heap`]. Like a [`StrId`], four
+/// bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BytesId(u32);
+
+/// A handle to an open file held in a [`Heap`]. Like a [`StrId`], four
+/// bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+/// A handle to a closure environment held in a [`Heap`]: the function a
+/// closure calls and the values it captured. Like a [`StrId`], four bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClosureId(u32);
+
+/// A handle to a struct value held in a [`Heap`]. Like a [`StrId`], four
+/// bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RecordId(u32);
+
 /// A value on the Plenty stack.
 ///
 /// Deliberately small — 16 bytes — because the stack is the one data structure
@@ -24,7 +48,21 @@ pub struct StrId(u32);
 ///
 /// `Bool` is its own variant, not an integer alias: §11.2's "no implicit
 /// conversions" rule says a `Bool` is a `Bool`, and the only way to get one
-/// is to produce one (a literal, or a comparison).
+/// is to produce one (a literal, or a comparison). `Char` is likewise
+/// its own variant, a Unicode scalar value; `:as-u32` and `:as-char` are the
+/// only ways across to the integers.
+///
+/// `Bytes` is a byte string with no encoding promised, kept apart from
+/// `Str` so that a `Str` is always valid UTF-8; `:utf8-decode` is the
+/// checked way across. `File` is a handle to a file opened for reading;
+/// the open file itself lives in the heap.
+///
+/// `Fn` is a reference to a function or quotation. It holds the function's
+/// name, interned like any text, and is resolved against the dictionary when
+/// called — so it costs no more than a `Str`. `Closure` is a function
+/// reference that also carries captured values; those live in the heap.
+/// `Record` is a value of a `struct` type, whose fields live in the heap
+/// too.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Value {
     I8(i8),
@@ -36,7 +74,32 @@ pub enum Value {
     U32(u32),
     U64(u64),
     Str(StrId),
+    Bytes(BytesId),
+    File(FileId),
     Bool(bool),
+    Char(char),
+    Fn(StrId),
+    Closure(ClosureId),
+    Record(RecordId),
+}
+
+impl Value {
+    /// The mathematical value of an integer of any width, or `None` for a
+    /// value that is not an integer. Every width widens losslessly to
+    /// `i128`, so values of different widths compare correctly.
+    pub fn as_i128(self) -> Option<i128> {
+        Some(match self {
+            Value::I8(n) => n as i128,
+            Value::I16(n) => n as i128,
+            Value::I32(n) => n as i128,
+            Value::I64(n) => n as i128,
+            Value::U8(n) => n as i128,
+            Value::U16(n) => n as i128,
+            Value::U32(n) => n as i128,
+            Value::U64(n) => n as i128,
+            _ => return None,
+        })
+    }
 }
 
 /// Backing store for values that do not fit in a 16-byte stack slot.
@@ -47,6 +110,29 @@ pub enum Value {
 #[derive(Default)]
 pub struct Heap {
     strings: Vec<String>,
+    bytes: Vec<Vec<u8>>,
+    /// Files opened by `:open-file`; `None` once closed, or for a handle
+    /// whose open failed.
+    files: Vec<Option<BufReader<File>>>,
+    closures: Vec<Closure>,
+    records: Vec<Record>,
+}
+
+/// A closure's environment: the name of the function it calls and the
+/// values it captured, which that function receives as its leading inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Closure {
+    pub name: StrId,
+    pub captured: Rc<[Value]>,
+}
+
+/// A struct value: the name of its struct and its fields' values, in
+/// declaration order. Records are immutable; updating a field stores a new
+/// one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub name: Rc<str>,
+    pub fields: Rc<[Value]>,
 }
 
 impl Heap {
@@ -64,4 +150,62 @@ impl Heap {
     pub fn str(&self, id: StrId) -> &str {
         &self.strings[id.0 as usize]
     }
+
+    /// Store a byte string and return a handle to it.
+    pub fn add_bytes(&mut self, b: Vec<u8>) -> BytesId {
+        let id = BytesId(self.bytes.len() as u32);
+        self.bytes.push(b);
+        id
+    }
+
+    /// Borrow the byte string behind `id`. Panics under the same
+    /// conditions as [`Heap::str`].
+    pub fn bytes(&self, id: BytesId) -> &[u8] {
+        &self.bytes[id.0 as usize]
+    }
+
+    /// Store an open file, or `None` for a handle that reads nothing, and
+    /// return a handle to it.
+    pub fn add_file(&mut self, file: Option<BufReader<File>>) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push(file);
+        id
+    }
+
+    /// The open file behind `id`, or `None` if it was never opened or has
+    /// been closed. Panics under the same conditions as [`Heap::str`].
+    pub fn file(&mut self, id: FileId) -> Option<&mut BufReader<File>> {
+        self.files[id.0 as usize].as_mut()
+    }
+
+    /// Close the file behind `id`; closing it again does nothing.
+    pub fn close_file(&mut self, id: FileId) {
+        self.files[id.0 as usize] = None;
+    }
+
+    /// Store a closure environment and return a handle to it.
+    pub fn add_closure(&mut self, closure: Closure) -> ClosureId {
+        let id = ClosureId(self.closures.len() as u32);
+        self.closures.push(closure);
+        id
+    }
+
+    /// Borrow the closure environment behind `id`. Panics under the same
+    /// conditions as [`Heap::str`].
+    pub fn closure(&self, id: ClosureId) -> &Closure {
+        &self.closures[id.0 as usize]
+    }
+
+    /// Store a struct value and return a handle to it.
+    pub fn add_record(&mut self, record: Record) -> RecordId {
+        let id = RecordId(self.records.len() as u32);
+        self.records.push(record);
+        id
+    }
+
+    /// Borrow the struct value behind `id`. Panics under the same
+    /// conditions as [`Heap::str`].
+    pub fn record(&self, id: RecordId) -> &Record {
+        &self.records[id.0 as usize]
+    }
 }
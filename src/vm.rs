@@ -8,15 +8,19 @@
 //! locals; tail calls pop the enclosing Call frame and push a replacement,
 //! which is what makes recursive iteration bounded.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::ffi::OsString;
 use std::rc::Rc;
 
 use log::debug;
 
 use crate::lexer;
-use crate::op::{self, CompiledFn, FnSig, MatchArm, Op, Pattern, Ty};
-use crate::value::{Heap, Value};
+use crate::op::{
+    self, CompiledFn, FnSig, FnTy, FormatPiece, FormatSpec, ImportPaths, MatchArm, Op, Pattern,
+    StructTy, Ty,
+};
+use crate::value::{Closure, FileId, Heap, Record, StrId, Value};
 
 /// Dispatch a checked integer binary operation across every supported
 /// width. The compiler's type checker (§11.6) has already ensured the
@@ -51,6 +55,91 @@ macro_rules! checked_int_binop {
     }};
 }
 
+/// Dispatch a total same-width integer operation across every integer width.
+/// `$body` computes the result from `$a` and `$b`, bound to the unwrapped
+/// operands; it is expanded once per width, so it may use any method the
+/// integer types share.
+macro_rules! int_binop {
+    ($self:expr, |$a:ident, $b:ident| $body:expr) => {{
+        let b = $self.pop()?;
+        let a = $self.pop()?;
+        let result = match (a, b) {
+            (Value::I8($a), Value::I8($b)) => Value::I8($body),
+            (Value::I16($a), Value::I16($b)) => Value::I16($body),
+            (Value::I32($a), Value::I32($b)) => Value::I32($body),
+            (Value::I64($a), Value::I64($b)) => Value::I64($body),
+            (Value::U8($a), Value::U8($b)) => Value::U8($body),
+            (Value::U16($a), Value::U16($b)) => Value::U16($body),
+            (Value::U32($a), Value::U32($b)) => Value::U32($body),
+            (Value::U64($a), Value::U64($b)) => Value::U64($body),
+            (a, b) => {
+                return Err(format!(
+                    "integer operation requires same-width integers, got {} and {}",
+                    $self.render(a),
+                    $self.render(b)
+                )
+                .into())
+            }
+        };
+        $self.stack.push(result);
+        Ok(())
+    }};
+}
+
+/// Dispatch a one-operand integer operation across every integer width, in
+/// the manner of [`int_binop!`].
+macro_rules! int_unop {
+    ($self:expr, |$a:ident| $body:expr) => {{
+        let a = $self.pop()?;
+        let result = match a {
+            Value::I8($a) => Value::I8($body),
+            Value::I16($a) => Value::I16($body),
+            Value::I32($a) => Value::I32($body),
+            Value::I64($a) => Value::I64($body),
+            Value::U8($a) => Value::U8($body),
+            Value::U16($a) => Value::U16($body),
+            Value::U32($a) => Value::U32($body),
+            Value::U64($a) => Value::U64($body),
+            a => {
+                return Err(format!(
+                    "integer operation requires an integer, got {}",
+                    $self.render(a)
+                )
+                .into())
+            }
+        };
+        $self.stack.push(result);
+        Ok(())
+    }};
+}
+
+/// Dispatch a fallible one-operand integer operation across every integer
+/// width. `$body` yields an `Option`; `None` becomes the error `$err`.
+macro_rules! checked_int_unop {
+    ($self:expr, |$a:ident| $body:expr, $err:expr) => {{
+        let a = $self.pop()?;
+        let result = match a {
+            Value::I8($a) => Value::I8($body.ok_or($err)?),
+            Value::I16($a) => Value::I16($body.ok_or($err)?),
+            Value::I32($a) => Value::I32($body.ok_or($err)?),
+            Value::I64($a) => Value::I64($body.ok_or($err)?),
+            Value::U8($a) => Value::U8($body.ok_or($err)?),
+            Value::U16($a) => Value::U16($body.ok_or($err)?),
+            Value::U32($a) => Value::U32($body.ok_or($err)?),
+            Value::U64($a) => Value::U64($body.ok_or($err)?),
+            a => {
+                return Err(format!(
+                    "integer operation requires an integer, got {}",
+                    $self.render(a)
+                )
+                .into())
+            }
+        };
+        $self.stack.push(result);
+        Ok(())
+    }};
+}
+
 /// Dispatch an ordering comparison across every integer width. `$method` is a
 /// `PartialOrd` method such as `lt`, `le`, `gt`, or `ge`; the result is always
 /// a `Bool`.
@@ -83,6 +172,13 @@ macro_rules! int_cmp {
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+/// A shift count as the `u32` Rust's shift methods take. A count no `u32`
+/// holds — including any negative count, which is read as unsigned — is
+/// past every width, so it saturates rather than truncating.
+fn shift_count<T: TryInto<u32>>(n: T) -> u32 {
+    n.try_into().unwrap_or(u32::MAX)
+}
+
 /// One execution context on the VM's `frames` stack.
 ///
 /// A `Frame` is either a *call* frame (owns the locals slot starting at
@@ -90,11 +186,29 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 /// run; `owns_locals = false`, `locals_start` inherited from the nearest
 /// enclosing call). Inheriting `locals_start` lets `LoadLocal` resolve
 /// against `self.frames.last()` without walking the stack.
+///
+/// A match arm that binds values pushes them onto the end of `locals`;
+/// `bound` counts them, so they are removed when its frame finishes.
+///
+/// A `when` guard is a block frame too. Its `guard` holds the match it
+/// belongs to, so the arm walk resumes where it left off once the guard
+/// finishes.
 struct Frame {
     body: Rc<[Op]>,
     pc: usize,
     locals_start: usize,
     owns_locals: bool,
+    bound: usize,
+    guard: Option<Box<GuardState>>,
+}
+
+/// A `match` waiting on the `when` guard of arm `arm`: its arms, the
+/// values it popped, and the data stack to put back when the guard is done.
+struct GuardState {
+    arms: Rc<[MatchArm]>,
+    arm: usize,
+    values: Vec<Value>,
+    saved: Vec<Value>,
 }
 
 /// A running Plenty interpreter.
@@ -118,6 +232,21 @@ pub struct Vm {
     /// frame pushed at the start of `run` is popped when its ops are
     /// exhausted, or torn down on error).
     frames: Vec<Frame>,
+    /// Where `import "path"` looks for files; see [`Vm::set_import_paths`].
+    import_paths: ImportPaths,
+    /// Every `struct` declared so far, by name. Later `run`s compile
+    /// against it, and rendering a record looks up its field names here.
+    structs: HashMap<String, Rc<StructTy>>,
+    /// Every `const` declared so far, as the literal op its name stands
+    /// for. Later `run`s compile against it, as they do `structs`.
+    consts: HashMap<String, Op>,
+    /// Whether the file words are refused; see
+    /// [`Vm::set_filesystem_access`].
+    no_filesystem: bool,
+    /// What `:argc` and `:arg` see; see [`Vm::set_args`].
+    args: Vec<OsString>,
+    /// The status `:exit` asked for during the last `run`.
+    exit_code: Option<i32>,
 }
 
 impl Vm {
@@ -125,6 +254,29 @@ impl Vm {
         Vm::default()
     }
 
+    /// Set where `import "path"` resolves in later [`Vm::run`] calls. The
+    /// default — no base directory, no search path — resolves relative to
+    /// the process's working directory only.
+    pub fn set_import_paths(&mut self, paths: ImportPaths) {
+        self.import_paths = paths;
+    }
+
+    /// Allow or refuse the file words (`:read-file`, `:open-file`, ...) and
+    /// `import` in later [`Vm::run`] calls. Access is allowed by default;
+    /// with it off, each of those words is a runtime error before it
+    /// touches anything, and a source that imports fails to compile.
+    pub fn set_filesystem_access(&mut self, allowed: bool) {
+        self.no_filesystem = !allowed;
+    }
+
+    /// Set the command-line arguments `:argc` and `:arg` report in later
+    /// [`Vm::run`] calls — the ones after the program's own path, so
+    /// `:arg` of `0` is the first of `args`. None by default. An argument
+    /// that is not UTF-8 counts toward `:argc` but reads as `"" false`.
+    pub fn set_args(&mut self, args: Vec<OsString>) {
+        self.args = args;
+    }
+
     /// Lex, compile, type-check, and execute `source`.
     ///
     /// The flow is **lex → compile → check → exec** (§7, §9, §11.6, §11.8).
@@ -140,10 +292,37 @@ impl Vm {
     /// and their locals are always torn down before `run` returns, whether
     /// by success or by error: subsequent `run` calls always start with an
     /// empty `frames` stack.
+    ///
+    /// `:exit` stops execution early without it being an error: `run`
+    /// returns `Ok` and [`Vm::exit_code`] holds the requested status.
     pub fn run(&mut self, source: &str) -> Result<()> {
         debug!("run: {source:?}");
+        self.exit_code = None;
         let toks = lexer::lex(source)?;
-        let ops = op::compile(&toks, &mut self.heap)?;
+        let imports = ImportPaths {
+            refused: self.import_paths.refused || self.no_filesystem,
+            ..self.import_paths.clone()
+        };
+        let prior_names: HashSet<String> = self.functions.keys().cloned().collect();
+        let mut ops = op::compile(
+            &toks,
+            &mut self.heap,
+            &imports,
+            &self.structs,
+            &prior_names,
+            &self.consts,
+        )?;
+        // `op::compile` only knows the private helpers this source
+        // defines; calls to ones an earlier `run` defined are checked here,
+        // so they fail as private rather than as undefined.
+        let prior_private: HashSet<String> = self
+            .functions
+            .iter()
+            .filter(|(_, f)| f.private)
+            .map(|(n, _)| n.clone())
+            .collect();
+        op::check_visibility(&ops, "", &prior_private)?;
+        op::check_struct_names(&ops, &prior_names)?;
         // The checker sees the union of (already-defined sigs ∪ sigs in
         // this source). Cloning the `Rc<FnSig>`s is one refcount bump per
         // entry — cheap, and it lets `op::check` own its working table.
@@ -156,7 +335,8 @@ impl Vm {
         // line containing only `+` sees the values left by the previous
         // line (§11.6). `Value -> Ty` is total: every value's runtime tag
         // maps to exactly one checker type.
-        let initial_stack: Vec<Ty> = self.stack.iter().map(|&v| Ty::from(v)).collect();
+        let initial_stack: Vec<Ty> = self.stack.iter().map(|&v| self.value_ty(v)).collect();
+        op::infer(&mut ops, &prior_sigs)?;
         op::check(&ops, initial_stack, &prior_sigs)?;
 
         // Push the top-level frame and run the interpreter loop. The
@@ -168,20 +348,35 @@ impl Vm {
             pc: 0,
             locals_start: 0,
             owns_locals: false,
+            bound: 0,
+            guard: None,
         });
         let result = self.run_loop();
 
         // Tear down whatever frames remain — empty on success, non-empty on
         // error. Calling code is entitled to assume a clean frames stack
-        // before the next `run`.
+        // before the next `run`. A guard that failed puts back the stack
+        // its match saw, the outermost one last.
         while let Some(frame) = self.frames.pop() {
-            if frame.owns_locals {
-                self.locals.truncate(frame.locals_start);
+            self.release_locals(&frame);
+            if let Some(state) = frame.guard {
+                self.stack = state.saved;
             }
         }
+        // `:exit` unwinds as an error so nested loops stop too; it is
+        // the only op that sets `exit_code`, and nothing runs after it.
+        if self.exit_code.is_some() {
+            return Ok(());
+        }
         result
     }
 
+    /// The status passed to `:exit` if the last [`Vm::run`] ended that
+    /// way, or `None` if it ran to the end or failed.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
     /// Render the stack the way Plenty itself would print it — `[1 2 "three"]`.
     ///
     /// This is a *language-level* view, deliberately independent of how the VM
@@ -192,9 +387,15 @@ impl Vm {
         format!("[{}]", rendered.join(" "))
     }
 
-    /// The names of every currently-defined function, sorted.
+    /// The names of every currently-defined function, sorted. Quotation
+    /// bodies are defined under generated names and are not listed.
     pub fn function_names(&self) -> Vec<&str> {
-        let mut names: Vec<&str> = self.functions.keys().map(String::as_str).collect();
+        let mut names: Vec<&str> = self
+            .functions
+            .keys()
+            .map(String::as_str)
+            .filter(|n| !op::is_quotation(n))
+            .collect();
         names.sort_unstable();
         names
     }
@@ -228,7 +429,7 @@ impl Vm {
             // Fetch the next op, unwinding exhausted frames as needed.
             let op = loop {
                 let Some(frame) = self.frames.last_mut() else {
-                    // No frames left → top-level done.
+                    // No frames left → done.
                     return Ok(());
                 };
                 if frame.pc < frame.body.len() {
@@ -237,10 +438,11 @@ impl Vm {
                     break op;
                 }
                 // Frame is at end-of-body. Pop it, cleaning up its locals
-                // slot if it owns one.
+                // slot if it owns one; a finished guard resumes its match.
                 let finished = self.frames.pop().expect("checked just above");
-                if finished.owns_locals {
-                    self.locals.truncate(finished.locals_start);
+                self.release_locals(&finished);
+                if let Some(state) = finished.guard {
+                    self.finish_guard(*state)?;
                 }
             };
 
@@ -248,6 +450,16 @@ impl Vm {
         }
     }
 
+    /// Drop the locals a finished frame added: a call's whole slot, or the
+    /// values a match arm bound.
+    fn release_locals(&mut self, frame: &Frame) {
+        if frame.owns_locals {
+            self.locals.truncate(frame.locals_start);
+        } else {
+            self.locals.truncate(self.locals.len() - frame.bound);
+        }
+    }
+
     /// Execute one op against the current frame.
     fn exec_op(&mut self, op: Op) -> Result<()> {
         match op {
@@ -256,10 +468,31 @@ impl Vm {
             Op::PushInt(n) => self.stack.push(n),
             Op::PushStr(id) => self.stack.push(Value::Str(id)),
             Op::PushBool(b) => self.stack.push(Value::Bool(b)),
+            Op::PushChar(c) => self.stack.push(Value::Char(c)),
             Op::Add => self.add()?,
             Op::Sub => self.sub()?,
             Op::Mul => self.mul()?,
             Op::Div => self.div()?,
+            Op::WrappingAdd => self.wrapping_add()?,
+            Op::WrappingMul => self.wrapping_mul()?,
+            Op::SaturatingAdd => self.saturating_add()?,
+            Op::SaturatingSub => self.saturating_sub()?,
+            Op::Rem => self.rem()?,
+            Op::Mod => self.modulo()?,
+            Op::DivMod => self.divmod()?,
+            Op::Abs => self.abs()?,
+            Op::Neg => self.neg()?,
+            Op::Min => self.min()?,
+            Op::Max => self.max()?,
+            Op::Pow => self.pow()?,
+            Op::BitAnd => self.bit_and()?,
+            Op::BitOr => self.bit_or()?,
+            Op::BitXor => self.bit_xor()?,
+            Op::BitNot => self.bit_not()?,
+            Op::Shl => self.shl()?,
+            Op::Shr => self.shr()?,
+            Op::Rotl => self.rotl()?,
+            Op::Rotr => self.rotr()?,
             Op::Eq => self.eq()?,
             Op::Lt => self.lt()?,
             Op::Gt => self.gt()?,
@@ -277,15 +510,223 @@ impl Vm {
             Op::DefineFn(name, func) => {
                 self.functions.insert(name, func);
             }
+            Op::Import(_, body) => {
+                for op in body.iter() {
+                    self.exec_op(op.clone())?;
+                }
+            }
+            Op::PushFn(_, id) => self.stack.push(Value::Fn(id)),
+            Op::PushClosure(_, name, slots) => self.push_closure(name, &slots)?,
+            Op::CallIndirect => match self.pop()? {
+                Value::Fn(id) => {
+                    let name = self.heap.str(id).to_string();
+                    self.do_call(&name)?
+                }
+                Value::Closure(id) => {
+                    let closure = self.heap.closure(id).clone();
+                    let name = self.heap.str(closure.name).to_string();
+                    self.do_call_with(&name, &closure.captured)?
+                }
+                other => {
+                    return Err(format!(
+                        "`:call` requires a function reference, got {}",
+                        self.render(other)
+                    )
+                    .into())
+                }
+            },
             Op::Call(name) => self.do_call(&name)?,
             Op::TailCall(name) => self.do_tail_call(&name)?,
             Op::LoadLocal(i) => self.load_local(i)?,
             Op::Match(arms) => self.do_match(arms)?,
             Op::Cast(target) => self.cast(target)?,
             Op::ReadLine => self.readline()?,
+            Op::ReadLineBytes => self.readline_bytes()?,
+            Op::ReadBytes => {
+                use std::io::Read;
+                let mut buf = Vec::new();
+                std::io::stdin().lock().read_to_end(&mut buf)?;
+                self.push_bytes(buf);
+            }
+            Op::WriteBytes => {
+                use std::io::Write;
+                let b = self.pop_bytes(":write-bytes")?;
+                std::io::stdout().write_all(&b)?;
+            }
+            Op::Utf8Decode => {
+                let b = self.pop_bytes(":utf8-decode")?;
+                let decoded = String::from_utf8(b);
+                let ok = decoded.is_ok();
+                self.push_str(decoded.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::Utf8Encode => {
+                let s = self.pop_str(":utf8-encode")?;
+                self.push_bytes(s.into_bytes());
+            }
+            Op::Utf8DecodeLossy => {
+                let b = self.pop_bytes(":utf8-decode-lossy")?;
+                self.push_str(String::from_utf8_lossy(&b).into_owned());
+            }
+            Op::BytesLen => {
+                let b = self.pop_bytes(":bytes-len")?;
+                self.stack.push(Value::I64(b.len() as i64));
+            }
+            Op::BytesSlice => self.bytes_slice()?,
+            Op::ReadFile => {
+                self.require_filesystem(":read-file")?;
+                let path = self.pop_str(":read-file")?;
+                let text = std::fs::read_to_string(path);
+                let ok = text.is_ok();
+                self.push_str(text.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::WriteFile => self.write_file(":write-file", false)?,
+            Op::AppendFile => self.write_file(":append-file", true)?,
+            Op::FileExists => {
+                self.require_filesystem(":file-exists")?;
+                let path = self.pop_str(":file-exists")?;
+                let exists = std::path::Path::new(&path).exists();
+                self.stack.push(Value::Bool(exists));
+            }
+            Op::OpenFile => {
+                self.require_filesystem(":open-file")?;
+                let path = self.pop_str(":open-file")?;
+                let file = std::fs::File::open(path).ok().map(std::io::BufReader::new);
+                let ok = file.is_some();
+                let id = self.heap.add_file(file);
+                self.stack.push(Value::File(id));
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::FileReadLine => {
+                let line = self.file_line(":file-readline")?;
+                let text = line.and_then(|b| String::from_utf8(b).ok());
+                let ok = text.is_some();
+                self.push_str(text.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::FileReadLineBytes => {
+                let line = self.file_line(":file-readline-bytes")?;
+                let ok = line.is_some();
+                self.push_bytes(line.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::CloseFile => {
+                self.require_filesystem(":close-file")?;
+                let id = self.pop_file(":close-file")?;
+                self.heap.close_file(id);
+            }
+            Op::ArgCount => self.stack.push(Value::I64(self.args.len() as i64)),
+            Op::Arg => {
+                let i = self.pop_i64(":arg")?;
+                let arg = usize::try_from(i)
+                    .ok()
+                    .and_then(|i| self.args.get(i))
+                    .and_then(|a| a.to_str())
+                    .map(str::to_string);
+                let ok = arg.is_some();
+                self.push_str(arg.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
+            Op::Env => {
+                let name = self.pop_str(":env")?;
+                // `env::var_os` may panic on a name the platform cannot
+                // hold; none of these names any variable.
+                let valid = !name.is_empty() && !name.contains(['=', '\0']);
+                let value = valid
+                    .then(|| std::env::var_os(&name))
+                    .flatten()
+                    .and_then(|v| v.into_string().ok());
+                let ok = value.is_some();
+                self.push_str(value.unwrap_or_default());
+                self.stack.push(Value::Bool(ok));
+            }
             Op::Contains => self.contains()?,
+            Op::StrLen => self.str_len()?,
+            Op::Slice => self.slice()?,
+            Op::Split => self.split()?,
+            Op::Trim => self.trim()?,
+            Op::StartsWith => self.starts_with()?,
+            Op::EndsWith => self.ends_with()?,
+            Op::Find => self.find()?,
+            Op::Replace => self.replace()?,
+            Op::Upper => self.upper()?,
+            Op::Lower => self.lower()?,
+            Op::Repeat => self.repeat()?,
+            Op::NextChar => self.next_char()?,
+            Op::CharAt => self.char_at()?,
+            Op::FromChar => match self.pop()? {
+                Value::Char(c) => self.push_str(c.to_string()),
+                other => {
+                    return Err(
+                        format!("`:from-char` requires Char, got {}", self.render(other)).into(),
+                    )
+                }
+            },
+            Op::Parse(target) => self.parse(&target)?,
+            Op::ToStr => {
+                let value = self.pop()?;
+                let text = self.render(value);
+                self.push_str(text);
+            }
+            Op::ToDigits => self.digits()?,
+            Op::Format(pieces) => self.format(&pieces)?,
             Op::PrintLn => self.println_word()?,
             Op::Print => self.print_word()?,
+            Op::EPrintLn => {
+                let s = self.pop_str(":eprintln")?;
+                eprintln!("{s}");
+            }
+            Op::EPrint => {
+                let value = self.pop()?;
+                eprint!("{}", self.render(value));
+            }
+            Op::Exit => {
+                let code = match self.pop()? {
+                    Value::I32(code) => code,
+                    other => {
+                        return Err(
+                            format!("`:exit` requires i32, got {}", self.render(other)).into()
+                        )
+                    }
+                };
+                self.exit_code = Some(code);
+                return Err("`:exit`".into());
+            }
+            Op::DefineStruct(st) => {
+                self.structs.insert(st.name.to_string(), st);
+            }
+            Op::DefineConst(name, op) => {
+                self.consts.insert(name, *op);
+            }
+            Op::MakeStruct(st) => {
+                let split = self
+                    .stack
+                    .len()
+                    .checked_sub(st.fields.len())
+                    .ok_or("stack underflow")?;
+                let fields: Rc<[Value]> = self.stack.drain(split..).collect();
+                let id = self.heap.add_record(Record {
+                    name: Rc::clone(&st.name),
+                    fields,
+                });
+                self.stack.push(Value::Record(id));
+            }
+            Op::GetField(_, i) => {
+                let record = self.pop_record()?;
+                self.stack.push(record.fields[i as usize]);
+            }
+            Op::SetField(_, i) => {
+                let value = self.pop()?;
+                let record = self.pop_record()?;
+                let mut fields = record.fields.to_vec();
+                fields[i as usize] = value;
+                let id = self.heap.add_record(Record {
+                    name: record.name,
+                    fields: fields.into(),
+                });
+                self.stack.push(Value::Record(id));
+            }
         }
         Ok(())
     }
@@ -318,10 +759,77 @@ impl Vm {
         Ok(())
     }
 
+    /// `:readline-bytes`: [`Vm::readline`] for input that need not be
+    /// UTF-8 — the line is pushed as `Bytes`, stripped the same way.
+    fn readline_bytes(&mut self) -> Result<()> {
+        use std::io::BufRead;
+        let mut buf = Vec::new();
+        let n = std::io::stdin().lock().read_until(b'\n', &mut buf)?;
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+        self.push_bytes(buf);
+        self.stack.push(Value::Bool(n > 0));
+        Ok(())
+    }
+
+    /// Fail `word` if [`Vm::set_filesystem_access`] turned the file words
+    /// off.
+    fn require_filesystem(&self, word: &str) -> Result<()> {
+        if self.no_filesystem {
+            return Err(format!("`{word}` needs filesystem access, which is disabled").into());
+        }
+        Ok(())
+    }
+
+    /// `:write-file` / `:append-file`: pop `path text`, write `text` to
+    /// the file, replacing or extending it, and push whether that worked.
+    fn write_file(&mut self, word: &str, append: bool) -> Result<()> {
+        use std::io::Write;
+        self.require_filesystem(word)?;
+        let text = self.pop_str(word)?;
+        let path = self.pop_str(word)?;
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .and_then(|mut f| f.write_all(text.as_bytes()));
+        self.stack.push(Value::Bool(written.is_ok()));
+        Ok(())
+    }
+
+    /// Pop a file handle for `word` and read its next line, without the
+    /// line ending, or `None` at the end, once closed, or on a read error,
+    /// rather than failing the program. `:file-readline` then also turns
+    /// a line that is not UTF-8 into `"" false`; `:file-readline-bytes`
+    /// keeps it.
+    fn file_line(&mut self, word: &str) -> Result<Option<Vec<u8>>> {
+        use std::io::BufRead;
+        self.require_filesystem(word)?;
+        let id = self.pop_file(word)?;
+        let mut buf = Vec::new();
+        let got = match self.heap.file(id) {
+            Some(file) => file.read_until(b'\n', &mut buf).is_ok_and(|n| n > 0),
+            None => false,
+        };
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+        Ok(got.then_some(buf))
+    }
+
     /// `:contains`: pop `haystack needle`, push whether `needle` occurs
     /// in `haystack` as a contiguous byte substring. `str::contains` on
     /// `&str` is the byte-level scan that matches the AOT runtime's
-    /// `strstr`.
+    /// `plenty_contains`.
     fn contains(&mut self) -> Result<()> {
         let needle = self.pop()?;
         let hay = self.pop()?;
@@ -340,6 +848,298 @@ impl Vm {
         }
     }
 
+    /// `:len`: pop a string, push its length in chars. Indices of every
+    /// string word count chars, so `:len` is the bound `:slice` checks.
+    fn str_len(&mut self) -> Result<()> {
+        let s = self.pop_str(":len")?;
+        self.stack.push(Value::I64(s.chars().count() as i64));
+        Ok(())
+    }
+
+    /// `:slice`: pop `s start end`, push the chars in `[start, end)`.
+    fn slice(&mut self) -> Result<()> {
+        let end = self.pop_i64(":slice")?;
+        let start = self.pop_i64(":slice")?;
+        let s = self.pop_str(":slice")?;
+        let len = s.chars().count() as i64;
+        if start < 0 || start > end || end > len {
+            return Err(format!(
+                "`:slice` range {start}..{end} is out of bounds for a string of {len} chars"
+            )
+            .into());
+        }
+        let out: String = s
+            .chars()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect();
+        self.push_str(out);
+        Ok(())
+    }
+
+    /// `:bytes-slice`: pop `bytes start end`, push the bytes in
+    /// `[start, end)`. Unlike `:slice` the offsets count bytes, so any
+    /// in-range cut is allowed.
+    fn bytes_slice(&mut self) -> Result<()> {
+        let end = self.pop_i64(":bytes-slice")?;
+        let start = self.pop_i64(":bytes-slice")?;
+        let b = self.pop_bytes(":bytes-slice")?;
+        let len = b.len() as i64;
+        if start < 0 || start > end || end > len {
+            return Err(format!(
+                "`:bytes-slice` range {start}..{end} is out of bounds for {len} bytes"
+            )
+            .into());
+        }
+        self.push_bytes(b[start as usize..end as usize].to_vec());
+        Ok(())
+    }
+
+    /// `:split`: pop `s sep`, push the text either side of the first
+    /// `sep` and `true`, or `s "" false` when `sep` does not occur.
+    fn split(&mut self) -> Result<()> {
+        let sep = self.pop_str(":split")?;
+        let s = self.pop_str(":split")?;
+        if sep.is_empty() {
+            return Err("`:split` separator must not be empty".into());
+        }
+        let (before, after, found) = match s.split_once(&sep) {
+            Some((before, after)) => (before.to_string(), after.to_string(), true),
+            None => (s.clone(), String::new(), false),
+        };
+        self.push_str(before);
+        self.push_str(after);
+        self.stack.push(Value::Bool(found));
+        Ok(())
+    }
+
+    /// `:trim`: strip leading and trailing ASCII whitespace — the set
+    /// `char::is_ascii_whitespace` names, which the AOT runtime repeats.
+    fn trim(&mut self) -> Result<()> {
+        let s = self.pop_str(":trim")?;
+        let out = s
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .to_string();
+        self.push_str(out);
+        Ok(())
+    }
+
+    fn starts_with(&mut self) -> Result<()> {
+        let prefix = self.pop_str(":starts-with")?;
+        let s = self.pop_str(":starts-with")?;
+        self.stack.push(Value::Bool(s.starts_with(&prefix)));
+        Ok(())
+    }
+
+    fn ends_with(&mut self) -> Result<()> {
+        let suffix = self.pop_str(":ends-with")?;
+        let s = self.pop_str(":ends-with")?;
+        self.stack.push(Value::Bool(s.ends_with(&suffix)));
+        Ok(())
+    }
+
+    /// `:find`: pop `s needle`, push the char index of the first match
+    /// and `true`, or `-1 false`. An empty needle is found at 0.
+    fn find(&mut self) -> Result<()> {
+        let needle = self.pop_str(":find")?;
+        let s = self.pop_str(":find")?;
+        match s.find(&needle) {
+            Some(byte) => {
+                self.stack
+                    .push(Value::I64(s[..byte].chars().count() as i64));
+                self.stack.push(Value::Bool(true));
+            }
+            None => {
+                self.stack.push(Value::I64(-1));
+                self.stack.push(Value::Bool(false));
+            }
+        }
+        Ok(())
+    }
+
+    fn replace(&mut self) -> Result<()> {
+        let to = self.pop_str(":replace")?;
+        let from = self.pop_str(":replace")?;
+        let s = self.pop_str(":replace")?;
+        if from.is_empty() {
+            return Err("`:replace` pattern must not be empty".into());
+        }
+        self.push_str(s.replace(&from, &to));
+        Ok(())
+    }
+
+    fn upper(&mut self) -> Result<()> {
+        let s = self.pop_str(":upper")?;
+        self.push_str(s.to_ascii_uppercase());
+        Ok(())
+    }
+
+    fn lower(&mut self) -> Result<()> {
+        let s = self.pop_str(":lower")?;
+        self.push_str(s.to_ascii_lowercase());
+        Ok(())
+    }
+
+    /// `:repeat`: pop `s n`, push `s` repeated `n` times. A result too
+    /// large to allocate is `out of memory`, the AOT runtime's message.
+    fn repeat(&mut self) -> Result<()> {
+        let n = self.pop_i64(":repeat")?;
+        let s = self.pop_str(":repeat")?;
+        if n < 0 {
+            return Err(format!("`:repeat` count must not be negative, got {n}").into());
+        }
+        let total = usize::try_from(n)
+            .ok()
+            .and_then(|n| s.len().checked_mul(n))
+            .ok_or("out of memory")?;
+        let mut out = String::new();
+        out.try_reserve(total).map_err(|_| "out of memory")?;
+        if !s.is_empty() {
+            for _ in 0..n {
+                out.push_str(&s);
+            }
+        }
+        self.push_str(out);
+        Ok(())
+    }
+
+    /// `:next-char`: pop `s i`, push the char at byte offset `i`, the
+    /// offset after it, and `true`, or `'\0' i false` at the end of `s`.
+    /// The string is read in place, so a walk costs nothing per step.
+    fn next_char(&mut self) -> Result<()> {
+        let i = self.pop_i64(":next-char")?;
+        let s = match self.pop()? {
+            Value::Str(id) => self.heap.str(id),
+            other => {
+                return Err(format!("`:next-char` requires Str, got {}", self.render(other)).into())
+            }
+        };
+        let Some(at) = usize::try_from(i).ok().filter(|&at| s.is_char_boundary(at)) else {
+            return Err(format!(
+                "`:next-char` offset {i} is not a char boundary of a string of {} bytes",
+                s.len()
+            )
+            .into());
+        };
+        let (c, next) = match s[at..].chars().next() {
+            Some(c) => (Some(c), i + c.len_utf8() as i64),
+            None => (None, i),
+        };
+        self.stack.push(Value::Char(c.unwrap_or('\0')));
+        self.stack.push(Value::I64(next));
+        self.stack.push(Value::Bool(c.is_some()));
+        Ok(())
+    }
+
+    /// `:char-at`: pop `s i`, push the char at char index `i`.
+    fn char_at(&mut self) -> Result<()> {
+        let i = self.pop_i64(":char-at")?;
+        let s = self.pop_str(":char-at")?;
+        let c = usize::try_from(i).ok().and_then(|i| s.chars().nth(i));
+        let Some(c) = c else {
+            return Err(format!(
+                "`:char-at` index {i} is out of bounds for a string of {} chars",
+                s.chars().count()
+            )
+            .into());
+        };
+        self.stack.push(Value::Char(c));
+        Ok(())
+    }
+
+    /// `:parse-T`: pop a string, push `(n true)` if `str::parse` accepts
+    /// it as a `T`, else `(0 false)`. The AOT runtime's `plenty_parse_int`
+    /// accepts exactly the same text.
+    fn parse(&mut self, target: &Ty) -> Result<()> {
+        let s = self.pop_str(&format!(":parse-{target}"))?;
+        let parsed = match target {
+            Ty::I8 => s.parse::<i8>().ok().map(i128::from),
+            Ty::I16 => s.parse::<i16>().ok().map(i128::from),
+            Ty::I32 => s.parse::<i32>().ok().map(i128::from),
+            Ty::I64 => s.parse::<i64>().ok().map(i128::from),
+            Ty::U8 => s.parse::<u8>().ok().map(i128::from),
+            Ty::U16 => s.parse::<u16>().ok().map(i128::from),
+            Ty::U32 => s.parse::<u32>().ok().map(i128::from),
+            Ty::U64 => s.parse::<u64>().ok().map(i128::from),
+            _ => None,
+        };
+        let value = int_value(target, parsed.unwrap_or(0))
+            .ok_or_else(|| format!("cannot parse to {target}"))?;
+        self.stack.push(value);
+        self.stack.push(Value::Bool(parsed.is_some()));
+        Ok(())
+    }
+
+    /// `:to-digits`: pop an integer, push its decimal text with no width
+    /// suffix.
+    fn digits(&mut self) -> Result<()> {
+        let value = self.pop()?;
+        let Some(n) = value.as_i128() else {
+            return Err(format!(
+                "`:to-digits` requires an integer, got {}",
+                self.render(value)
+            )
+            .into());
+        };
+        self.push_str(n.to_string());
+        Ok(())
+    }
+
+    /// `:format`: pop one value per placeholder, the deepest for the
+    /// first, and push the filled-in template.
+    fn format(&mut self, pieces: &[FormatPiece]) -> Result<()> {
+        let holes = pieces
+            .iter()
+            .filter(|piece| matches!(piece, FormatPiece::Hole(_)))
+            .count();
+        let split = self
+            .stack
+            .len()
+            .checked_sub(holes)
+            .ok_or("stack underflow on `:format`")?;
+        let mut args = self.stack.split_off(split).into_iter();
+        let mut out = String::new();
+        for piece in pieces {
+            match piece {
+                FormatPiece::Text(id) => out.push_str(self.heap.str(*id)),
+                FormatPiece::Hole(spec) => {
+                    let value = args.next().expect("one value per placeholder");
+                    out.push_str(&self.format_value(value, *spec)?);
+                }
+            }
+        }
+        self.push_str(out);
+        Ok(())
+    }
+
+    /// The text one `:format` placeholder shows for `value`.
+    fn format_value(&self, value: Value, spec: FormatSpec) -> Result<String> {
+        Ok(match (spec, value) {
+            (FormatSpec::Display, Value::Str(id)) => self.heap.str(id).to_string(),
+            (FormatSpec::Display, Value::Char(c)) => c.to_string(),
+            (FormatSpec::Display, v) => match v.as_i128() {
+                Some(n) => n.to_string(),
+                None => self.render(v),
+            },
+            (FormatSpec::Debug, v) => self.render(v),
+            (FormatSpec::Hex, Value::I8(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::I16(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::I32(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::I64(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::U8(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::U16(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::U32(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, Value::U64(n)) => format!("{n:x}"),
+            (FormatSpec::Hex, v) => {
+                return Err(format!(
+                    "`:format` placeholder `{{:x}}` requires an integer, got {}",
+                    self.render(v)
+                )
+                .into())
+            }
+        })
+    }
+
     /// `:println`: pop one string and write its bytes to stdout followed
     /// by a `\n`. Unlike `.`, no quoting and no surrounding brackets —
     /// this is the bare-text output primitive.
@@ -407,6 +1207,30 @@ impl Vm {
         Ok(())
     }
 
+    /// Build a closure over the function `name`, copying the active call's
+    /// locals at `slots` into a heap-held environment.
+    fn push_closure(&mut self, name: StrId, slots: &[u8]) -> Result<()> {
+        let frame = self
+            .frames
+            .last()
+            .ok_or("PushClosure executed outside any frame")?;
+        let captured = slots
+            .iter()
+            .map(|&i| {
+                self.locals
+                    .get(frame.locals_start + i as usize)
+                    .copied()
+                    .ok_or("PushClosure slot out of range")
+            })
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+        let id = self.heap.add_closure(Closure {
+            name,
+            captured: captured.into(),
+        });
+        self.stack.push(Value::Closure(id));
+        Ok(())
+    }
+
     /// `+`: same-width integer addition, or text concatenation. Mixed
     /// integer widths reach the runtime only via direct VM construction;
     /// the type checker rejects them first.
@@ -416,6 +1240,12 @@ impl Vm {
         // free.
         let len = self.stack.len();
         if len >= 2 {
+            if let (Value::Bytes(a), Value::Bytes(b)) = (self.stack[len - 2], self.stack[len - 1]) {
+                self.stack.truncate(len - 2);
+                let joined = [self.heap.bytes(a), self.heap.bytes(b)].concat();
+                self.push_bytes(joined);
+                return Ok(());
+            }
             if let (Value::Str(_), Value::Str(_)) = (self.stack[len - 2], self.stack[len - 1]) {
                 let b = match self.stack.pop() {
                     Some(Value::Str(id)) => id,
@@ -442,58 +1272,158 @@ impl Vm {
         checked_int_binop!(self, checked_mul, "integer overflow")
     }
 
+    fn bit_and(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a & b)
+    }
+
+    fn bit_or(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a | b)
+    }
+
+    fn bit_xor(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a ^ b)
+    }
+
+    fn bit_not(&mut self) -> Result<()> {
+        int_unop!(self, |a| !a)
+    }
+
+    /// `shl`: a count of at least the width shifts every bit out, leaving
+    /// `0`.
+    fn shl(&mut self) -> Result<()> {
+        int_binop!(self, |a, n| a.checked_shl(shift_count(n)).unwrap_or(0))
+    }
+
+    /// `shr`: arithmetic for signed widths, logical for unsigned — Rust's
+    /// `>>` on each. A count of at least the width leaves only the fill,
+    /// which is `a` shifted by `width - 1` (the sign bit, smeared for
+    /// signed widths) and then once more. `wrapping_shr(u32::MAX)` shifts
+    /// by exactly `width - 1`, since it masks the count to the width.
+    fn shr(&mut self) -> Result<()> {
+        int_binop!(self, |a, n| a
+            .checked_shr(shift_count(n))
+            .unwrap_or(a.wrapping_shr(u32::MAX) >> 1))
+    }
+
+    /// `rotl`: `rotate_left` reduces the count modulo the width, and every
+    /// width divides 2^32, so truncating the count to `u32` first is exact
+    /// — a negative count rotates the other way, as its bit pattern says.
+    fn rotl(&mut self) -> Result<()> {
+        int_binop!(self, |a, n| a.rotate_left(n as u32))
+    }
+
+    fn rotr(&mut self) -> Result<()> {
+        int_binop!(self, |a, n| a.rotate_right(n as u32))
+    }
+
+    fn wrapping_add(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.wrapping_add(b))
+    }
+
+    fn wrapping_mul(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.wrapping_mul(b))
+    }
+
+    fn saturating_add(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.saturating_add(b))
+    }
+
+    fn saturating_sub(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.saturating_sub(b))
+    }
+
     /// Division separates "divisor was zero" from "result would overflow"
     /// so the user sees the more specific message; `checked_div` would
     /// otherwise collapse both into a single `None`.
     fn div(&mut self) -> Result<()> {
+        self.check_divisor()?;
+        checked_int_binop!(self, checked_div, "integer overflow")
+    }
+
+    /// `rem` traps exactly where `/` does: `checked_rem` is `None` for
+    /// the signed minimum by `-1`, although the remainder would be `0`.
+    fn rem(&mut self) -> Result<()> {
+        self.check_divisor()?;
+        checked_int_binop!(self, checked_rem, "integer overflow")
+    }
+
+    fn modulo(&mut self) -> Result<()> {
+        self.check_divisor()?;
+        checked_int_binop!(self, checked_rem_euclid, "integer overflow")
+    }
+
+    /// `divmod` is `/` and `rem` on the same operands, so it fails exactly
+    /// when they do.
+    fn divmod(&mut self) -> Result<()> {
         let b = self.pop()?;
         let a = self.pop()?;
-        let zero = matches!(
-            b,
-            Value::I8(0)
-                | Value::I16(0)
-                | Value::I32(0)
-                | Value::I64(0)
-                | Value::U8(0)
-                | Value::U16(0)
-                | Value::U32(0)
-                | Value::U64(0)
-        );
-        if zero {
-            return Err("division by zero".into());
+        self.stack.extend([a, b]);
+        self.div()?;
+        self.stack.extend([a, b]);
+        self.rem()
+    }
+
+    /// Fail with `division by zero` if the divisor on top of the stack is
+    /// zero, before the division pops it.
+    fn check_divisor(&self) -> Result<()> {
+        match self.stack.last().and_then(|b| b.as_i128()) {
+            Some(0) => Err("division by zero".into()),
+            _ => Ok(()),
         }
-        let result = match (a, b) {
-            (Value::I8(a), Value::I8(b)) => Value::I8(a.checked_div(b).ok_or("integer overflow")?),
-            (Value::I16(a), Value::I16(b)) => {
-                Value::I16(a.checked_div(b).ok_or("integer overflow")?)
-            }
-            (Value::I32(a), Value::I32(b)) => {
-                Value::I32(a.checked_div(b).ok_or("integer overflow")?)
-            }
-            (Value::I64(a), Value::I64(b)) => {
-                Value::I64(a.checked_div(b).ok_or("integer overflow")?)
-            }
-            (Value::U8(a), Value::U8(b)) => Value::U8(a.checked_div(b).ok_or("integer overflow")?),
-            (Value::U16(a), Value::U16(b)) => {
-                Value::U16(a.checked_div(b).ok_or("integer overflow")?)
-            }
-            (Value::U32(a), Value::U32(b)) => {
-                Value::U32(a.checked_div(b).ok_or("integer overflow")?)
-            }
-            (Value::U64(a), Value::U64(b)) => {
-                Value::U64(a.checked_div(b).ok_or("integer overflow")?)
+    }
+
+    /// `abs` of an unsigned value is the value itself; of the signed
+    /// minimum, an overflow.
+    fn abs(&mut self) -> Result<()> {
+        let a = self.pop()?;
+        let result = match a {
+            Value::I8(a) => Value::I8(a.checked_abs().ok_or("integer overflow")?),
+            Value::I16(a) => Value::I16(a.checked_abs().ok_or("integer overflow")?),
+            Value::I32(a) => Value::I32(a.checked_abs().ok_or("integer overflow")?),
+            Value::I64(a) => Value::I64(a.checked_abs().ok_or("integer overflow")?),
+            Value::U8(_) | Value::U16(_) | Value::U32(_) | Value::U64(_) => a,
+            other => {
+                return Err(format!("`abs` requires an integer, got {}", self.render(other)).into())
             }
-            (a, b) => {
+        };
+        self.stack.push(result);
+        Ok(())
+    }
+
+    /// `neg` is `0 a -`: `checked_neg` overflows for the signed minimum and
+    /// for every unsigned value but `0`.
+    fn neg(&mut self) -> Result<()> {
+        checked_int_unop!(self, |a| a.checked_neg(), "integer overflow")
+    }
+
+    fn min(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.min(b))
+    }
+
+    fn max(&mut self) -> Result<()> {
+        int_binop!(self, |a, b| a.max(b))
+    }
+
+    /// `pow` with an exponent of the base's width. A negative exponent is
+    /// an error of its own. `checked_pow` takes a `u32`, so a larger
+    /// exponent is cut down to `128` or `129`, keeping its parity: past
+    /// `64` every base but `0`, `1` and `-1` overflows anyway, and for
+    /// those three only the parity matters.
+    fn pow(&mut self) -> Result<()> {
+        let exp = self.pop()?;
+        let n = match exp.as_i128() {
+            Some(n) if n < 0 => return Err("negative exponent".into()),
+            Some(n) => n,
+            None => {
                 return Err(format!(
-                    "arithmetic requires same-width integers, got {} and {}",
-                    self.render(a),
-                    self.render(b)
+                    "`pow` requires an integer exponent, got {}",
+                    self.render(exp)
                 )
                 .into())
             }
         };
-        self.stack.push(result);
-        Ok(())
+        let n = if n > 128 { 128 + n % 2 } else { n } as u32;
+        checked_int_unop!(self, |a| a.checked_pow(n), "integer overflow")
     }
 
     /// `=`: polymorphic equality over every scalar type. Mixed-type pairs
@@ -512,7 +1442,9 @@ impl Vm {
             (Value::U32(a), Value::U32(b)) => a == b,
             (Value::U64(a), Value::U64(b)) => a == b,
             (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
             (Value::Str(a), Value::Str(b)) => self.heap.str(a) == self.heap.str(b),
+            (Value::Bytes(a), Value::Bytes(b)) => self.heap.bytes(a) == self.heap.bytes(b),
             (a, b) => {
                 return Err(format!(
                     "cannot compare {} and {} with `=`",
@@ -574,40 +1506,39 @@ impl Vm {
 
     /// `:as-T`: pop any integer; push it reinterpreted/extended/truncated
     /// to width `target`. Going through `i128` keeps the conversion table
-    /// to two short matches — every source widens losslessly to `i128`,
-    /// then Rust's `as` rules narrow it to the target. The semantics
+    /// to one short match — every source widens losslessly to `i128`
+    /// ([`Value::as_i128`]), then Rust's `as` rules narrow it to the target. The semantics
     /// match `source as target` directly.
+    ///
+    /// A `Char` casts as the `u32` of its scalar value; `:as-char` fails on
+    /// any integer that is not a Unicode scalar value.
     fn cast(&mut self, target: Ty) -> Result<()> {
         let v = self.pop()?;
-        let wide: i128 = match v {
-            Value::I8(n) => n as i128,
-            Value::I16(n) => n as i128,
-            Value::I32(n) => n as i128,
-            Value::I64(n) => n as i128,
-            Value::U8(n) => n as i128,
-            Value::U16(n) => n as i128,
-            Value::U32(n) => n as i128,
-            Value::U64(n) => n as i128,
-            other => {
-                return Err(format!(
-                    "cast `:as-{target}` requires an integer, got {}",
-                    self.render(other)
-                )
-                .into())
-            }
+        let wide = match v {
+            Value::Char(c) if target != Ty::Char => Some(i128::from(u32::from(c))),
+            v => v.as_i128(),
         };
-        let result = match target {
-            Ty::I8 => Value::I8(wide as i8),
-            Ty::I16 => Value::I16(wide as i16),
-            Ty::I32 => Value::I32(wide as i32),
-            Ty::I64 => Value::I64(wide as i64),
-            Ty::U8 => Value::U8(wide as u8),
-            Ty::U16 => Value::U16(wide as u16),
-            Ty::U32 => Value::U32(wide as u32),
-            Ty::U64 => Value::U64(wide as u64),
-            // Defensive: the checker rejects casts to non-integer targets.
-            Ty::Str | Ty::Bool => return Err(format!("cannot cast to {target}").into()),
+        if target == Ty::Char {
+            if let Some(c) = wide
+                .and_then(|n| u32::try_from(n).ok())
+                .and_then(char::from_u32)
+            {
+                self.stack.push(Value::Char(c));
+                return Ok(());
+            }
+            if let Some(n) = wide {
+                return Err(format!("`:as-char` {n} is not a Unicode scalar value").into());
+            }
+        }
+        let Some(wide) = wide else {
+            return Err(format!(
+                "cast `:as-{target}` requires an integer, got {}",
+                self.render(v)
+            )
+            .into());
         };
+        // Defensive: the checker rejects casts to non-integer targets.
+        let result = int_value(&target, wide).ok_or_else(|| format!("cannot cast to {target}"))?;
         self.stack.push(result);
         Ok(())
     }
@@ -628,12 +1559,19 @@ impl Vm {
     /// push a Call frame. Control returns automatically when that frame's
     /// `pc` reaches its body's end (see `run_loop`).
     fn do_call(&mut self, name: &str) -> Result<()> {
+        self.do_call_with(name, &[])
+    }
+
+    /// [`Vm::do_call`] for a closure: `captured` supplies the callee's
+    /// leading inputs, and only the rest are drained from the stack.
+    fn do_call_with(&mut self, name: &str, captured: &[Value]) -> Result<()> {
         let (sig, body) = self.lookup_fn(name)?;
-        let n = sig.inputs.len();
+        let n = sig.inputs.len() - captured.len();
         if self.stack.len() < n {
             return Err(format!("stack underflow calling `{name}`").into());
         }
         let locals_start = self.locals.len();
+        self.locals.extend_from_slice(captured);
         // Drain preserves order: `inputs[0]` is the deepest popped value and
         // ends up at `locals[locals_start]`, which is what the compiler
         // assumes when it emits `LoadLocal(0)` for that name.
@@ -644,6 +1582,8 @@ impl Vm {
             pc: 0,
             locals_start,
             owns_locals: true,
+            bound: 0,
+            guard: None,
         });
         Ok(())
     }
@@ -680,6 +1620,8 @@ impl Vm {
                     pc: 0,
                     locals_start,
                     owns_locals: true,
+                    bound: 0,
+                    guard: None,
                 });
                 return Ok(());
             }
@@ -687,101 +1629,126 @@ impl Vm {
         }
     }
 
-    /// Pop the matched value, walk arms, push a block frame for the first
-    /// matching arm. Exhaustiveness is the checker's job (§11.8); the
-    /// runtime `no arm matched` error is defensive only.
+    /// Pop the matched value (or values, for `match2`), walk arms, push a
+    /// block frame for the first matching arm. Exhaustiveness is the
+    /// checker's job (§11.8); the runtime `no arm matched` error is
+    /// defensive only.
     fn do_match(&mut self, arms: Rc<[MatchArm]>) -> Result<()> {
-        let value = self.pop()?;
-        for arm in arms.iter() {
-            if self.pattern_matches(arm.pattern, value) {
-                // Inherit the enclosing call's locals from the current
-                // frame (which is the one running this `Match` op).
-                let locals_start = self.frames.last().map(|f| f.locals_start).unwrap_or(0);
+        let arity = arms.first().map_or(1, |arm| arm.patterns.len());
+        let split = self
+            .stack
+            .len()
+            .checked_sub(arity)
+            .ok_or("stack underflow")?;
+        let values = self.stack.split_off(split);
+        self.match_from(arms, 0, values)
+    }
+
+    /// Walk `arms` from index `start` against `values`. An arm with a
+    /// guard is not decided here: its guard is pushed as a frame of the
+    /// main loop, on a stack holding only copies of the matched values,
+    /// and [`Vm::finish_guard`] picks the walk up again when it is done —
+    /// so recursing through a guard grows `frames`, not the host stack.
+    fn match_from(&mut self, arms: Rc<[MatchArm]>, start: usize, values: Vec<Value>) -> Result<()> {
+        for (i, arm) in arms.iter().enumerate().skip(start) {
+            if !arm
+                .patterns
+                .iter()
+                .zip(&values)
+                .all(|(pat, &val)| self.pattern_matches(pat, val))
+            {
+                continue;
+            }
+            // The arm's bindings are in place before its guard runs, and
+            // are taken down again if the guard says no.
+            self.locals
+                .extend(arm.binds.iter().map(|&column| values[column]));
+            // Inherit the enclosing call's locals from the current
+            // frame (which is the one running this `Match` op).
+            let locals_start = self.frames.last().map(|f| f.locals_start).unwrap_or(0);
+            if let Some(guard) = &arm.guard {
+                let saved = std::mem::replace(&mut self.stack, values.clone());
                 self.frames.push(Frame {
-                    body: Rc::clone(&arm.body),
+                    body: Rc::clone(guard),
                     pc: 0,
                     locals_start,
                     owns_locals: false,
+                    bound: 0,
+                    guard: Some(Box::new(GuardState {
+                        arms: Rc::clone(&arms),
+                        arm: i,
+                        values,
+                        saved,
+                    })),
                 });
                 return Ok(());
             }
+            self.frames.push(Frame {
+                body: Rc::clone(&arm.body),
+                pc: 0,
+                locals_start,
+                owns_locals: false,
+                bound: arm.binds.len(),
+                guard: None,
+            });
+            return Ok(());
         }
         Err("no `match` arm matched (the checker should have caught this)".into())
     }
 
+    /// A guard's frame has finished: put the data stack back and read the
+    /// `Bool` it left. `true` runs its arm's body with the bindings already
+    /// in place; `false` takes them down and tries the next arm.
+    fn finish_guard(&mut self, state: GuardState) -> Result<()> {
+        let GuardState {
+            arms,
+            arm,
+            values,
+            saved,
+        } = state;
+        let left = std::mem::replace(&mut self.stack, saved);
+        let passed = match left[..] {
+            [Value::Bool(b)] => b,
+            _ => return Err("a `when` guard must leave exactly one Bool".into()),
+        };
+        let binds = arms[arm].binds.len();
+        if !passed {
+            self.locals.truncate(self.locals.len() - binds);
+            return self.match_from(arms, arm + 1, values);
+        }
+        let locals_start = self.frames.last().map(|f| f.locals_start).unwrap_or(0);
+        self.frames.push(Frame {
+            body: Rc::clone(&arms[arm].body),
+            pc: 0,
+            locals_start,
+            owns_locals: false,
+            bound: binds,
+            guard: None,
+        });
+        Ok(())
+    }
+
     /// Match one pattern against one value. Pure: never modifies VM state.
     ///
-    /// The checker confirms each untyped integer pattern fits the scrutinee's
-    /// range. Typed patterns must already share the scrutinee's type, so a
-    /// direct `Value` comparison is sufficient for them.
-    fn pattern_matches(&self, pat: Pattern, val: Value) -> bool {
+    /// The checker confirms each integer pattern, and each range bound, fits
+    /// the scrutinee's type, so comparing mathematical values is exact.
+    fn pattern_matches(&self, pat: &Pattern, val: Value) -> bool {
         match (pat, val) {
             (Pattern::Wildcard, _) => true,
-            (Pattern::Bool(a), Value::Bool(b)) => a == b,
-            (Pattern::Str(a), Value::Str(b)) => self.heap.str(a) == self.heap.str(b),
-            (
-                Pattern::Int {
-                    value: a,
-                    explicit_ty: true,
-                },
-                b,
-            ) => a == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::I8(b),
-            ) => a as i8 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::I16(b),
-            ) => a as i16 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::I32(b),
-            ) => a as i32 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::I64(b),
-            ) => a == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::U8(b),
-            ) => a as u8 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::U16(b),
-            ) => a as u16 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::U32(b),
-            ) => a as u32 == b,
-            (
-                Pattern::Int {
-                    value: Value::I64(a),
-                    explicit_ty: false,
-                },
-                Value::U64(b),
-            ) => a as u64 == b,
+            (Pattern::Bool(a), Value::Bool(b)) => *a == b,
+            (Pattern::Str(a), Value::Str(b)) => self.heap.str(*a) == self.heap.str(b),
+            (Pattern::Char(a), Value::Char(b)) => *a == b,
+            (Pattern::CharRange { lo, hi }, Value::Char(c)) => {
+                lo.is_none_or(|b| b <= c) && hi.is_none_or(|b| c <= b)
+            }
+            (Pattern::Int { value, .. }, v) => value.as_i128() == v.as_i128(),
+            (Pattern::Range { lo, hi }, v) => v.as_i128().is_some_and(|n| {
+                lo.is_none_or(|b| b.value.as_i128() <= Some(n))
+                    && hi.is_none_or(|b| Some(n) <= b.value.as_i128())
+            }),
+            (Pattern::Or(alternatives), v) => {
+                alternatives.iter().any(|p| self.pattern_matches(p, v))
+            }
             _ => false,
         }
     }
@@ -803,6 +1770,54 @@ impl Vm {
         self.stack.pop().ok_or_else(|| "stack underflow".into())
     }
 
+    /// Pop a string, cloning it out of the heap.
+    fn pop_str(&mut self, word: &str) -> Result<String> {
+        match self.pop()? {
+            Value::Str(id) => Ok(self.heap.str(id).to_string()),
+            other => Err(format!("`{word}` requires Str, got {}", self.render(other)).into()),
+        }
+    }
+
+    fn pop_i64(&mut self, word: &str) -> Result<i64> {
+        match self.pop()? {
+            Value::I64(n) => Ok(n),
+            other => Err(format!("`{word}` requires i64, got {}", self.render(other)).into()),
+        }
+    }
+
+    fn push_str(&mut self, s: String) {
+        let id = self.heap.add_str(s);
+        self.stack.push(Value::Str(id));
+    }
+
+    /// Pop a byte string, cloning it out of the heap.
+    fn pop_bytes(&mut self, word: &str) -> Result<Vec<u8>> {
+        match self.pop()? {
+            Value::Bytes(id) => Ok(self.heap.bytes(id).to_vec()),
+            other => Err(format!("`{word}` requires Bytes, got {}", self.render(other)).into()),
+        }
+    }
+
+    fn push_bytes(&mut self, b: Vec<u8>) {
+        let id = self.heap.add_bytes(b);
+        self.stack.push(Value::Bytes(id));
+    }
+
+    fn pop_file(&mut self, word: &str) -> Result<FileId> {
+        match self.pop()? {
+            Value::File(id) => Ok(id),
+            other => Err(format!("`{word}` requires File, got {}", self.render(other)).into()),
+        }
+    }
+
+    /// Pop a struct value, cloning it out of the heap.
+    fn pop_record(&mut self) -> Result<Record> {
+        match self.pop()? {
+            Value::Record(id) => Ok(self.heap.record(id).clone()),
+            other => Err(format!("expected a struct value, got {}", self.render(other)).into()),
+        }
+    }
+
     /// Render a single value as Plenty would print it. Every integer
     /// carries a width suffix (`42i64`, `255u8`, `-1i8`) — type information
     /// belongs in the rendered form so the user can see at a glance which
@@ -819,7 +1834,79 @@ impl Vm {
             Value::U64(n) => format!("{n}u64"),
             // `{:?}` quotes and escapes the string, so text reads as text.
             Value::Str(id) => format!("{:?}", self.heap.str(id)),
+            // Printable ASCII as is, anything else as an escape.
+            Value::Bytes(id) => format!("b\"{}\"", self.heap.bytes(id).escape_ascii()),
+            Value::File(_) => "<file>".to_string(),
             Value::Bool(b) => if b { "true" } else { "false" }.to_string(),
+            Value::Char(c) => format!("{c:?}"),
+            Value::Fn(id) => format!("<fn {}>", self.fn_ty_of(id, 0)),
+            Value::Closure(id) => {
+                let closure = self.heap.closure(id);
+                format!(
+                    "<fn {}>",
+                    self.fn_ty_of(closure.name, closure.captured.len())
+                )
+            }
+            Value::Record(id) => {
+                let record = self.heap.record(id);
+                let fields: Vec<String> = self.structs[&*record.name]
+                    .fields
+                    .iter()
+                    .zip(record.fields.iter())
+                    .map(|((name, _), &v)| format!("{name}: {}", self.render(v)))
+                    .collect();
+                format!("{}{{{}}}", record.name, fields.join(", "))
+            }
         }
     }
+
+    /// The checker type of a runtime value. A function reference's type is
+    /// the signature of the function it names; a record's is its struct.
+    fn value_ty(&self, value: Value) -> Ty {
+        match value {
+            Value::Fn(id) => Ty::Fn(self.fn_ty_of(id, 0).into()),
+            Value::Closure(id) => {
+                let closure = self.heap.closure(id);
+                Ty::Fn(self.fn_ty_of(closure.name, closure.captured.len()).into())
+            }
+            Value::Record(id) => Ty::Struct(Rc::clone(&self.structs[&*self.heap.record(id).name])),
+            scalar => Ty::of_scalar(scalar).expect("every other value is a scalar"),
+        }
+    }
+
+    /// The type of a reference to the function named by `id` that supplies
+    /// `captured` of its inputs itself. The name is always in the
+    /// dictionary: quotations are defined before their references can be
+    /// pushed, and nothing removes a definition.
+    fn fn_ty_of(&self, id: StrId, captured: usize) -> FnTy {
+        self.functions
+            .get(self.heap.str(id))
+            .map(|f| f.sig.closure_ty(captured))
+            .expect("a function reference names a defined function")
+    }
+}
+
+/// The integer `wide` at the width `target`, truncated the way Rust's `as`
+/// truncates. `None` if `target` is not an integer type.
+fn int_value(target: &Ty, wide: i128) -> Option<Value> {
+    Some(match target {
+        Ty::I8 => Value::I8(wide as i8),
+        Ty::I16 => Value::I16(wide as i16),
+        Ty::I32 => Value::I32(wide as i32),
+        Ty::I64 => Value::I64(wide as i64),
+        Ty::U8 => Value::U8(wide as u8),
+        Ty::U16 => Value::U16(wide as u16),
+        Ty::U32 => Value::U32(wide as u32),
+        Ty::U64 => Value::U64(wide as u64),
+        Ty::Str
+        | Ty::Bytes
+        | Ty::File
+        | Ty::Bool
+        | Ty::Char
+        | Ty::Fn(_)
+        | Ty::Struct(_)
+        | Ty::Var(_)
+        | Ty::Row(_)
+        | Ty::Hole(_) => return None,
+    })
 }
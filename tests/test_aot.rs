@@ -10,7 +10,7 @@
 //! The tests are skipped automatically when a C compiler isn't on
 //! `PATH`; CI environments without `cc` shouldn't break the build.
 
-use std::process::Command;
+use std::process::{Command, Output};
 
 fn plenty_bin() -> &'static str {
     env!("CARGO_BIN_EXE_plenty")
@@ -264,8 +264,8 @@ aot_matches_interpreter!(
 aot_matches_interpreter!(
     multi_return_function,
     "fn-multi-return",
-    r#": split { x i64 -> i64 i64 } "Push x and x+1." x x 1 + ;
-       5 :split ."#,
+    r#": pair { x i64 -> i64 i64 } "Push x and x+1." x x 1 + ;
+       5 :pair ."#,
 );
 
 aot_matches_interpreter!(
@@ -633,3 +633,567 @@ aot_failure_matches_interpreter!(
     // `"integer overflow"` message the interpreter emits.
     "-2147483648 :as-i32 -1 :as-i32 / .",
 );
+
+aot_failure_matches_interpreter!(rem_by_zero, "trap-rem-zero", "7 0 rem .");
+
+aot_failure_matches_interpreter!(unsigned_mod_by_zero, "trap-umod-zero", "7u16 0u16 mod .",);
+
+aot_failure_matches_interpreter!(divmod_by_zero, "trap-divmod-zero", "7u8 0u8 divmod .",);
+
+aot_failure_matches_interpreter!(
+    signed_rem_int_min_by_neg_one,
+    "trap-srem-intmin",
+    // `checked_rem` refuses INT_MIN % -1 just as `checked_div` refuses
+    // INT_MIN / -1, although the remainder itself would be 0.
+    "-128i8 -1i8 rem .",
+);
+
+aot_failure_matches_interpreter!(
+    signed_mod_int_min_by_neg_one,
+    "trap-smod-intmin",
+    "-9223372036854775808 -1 mod .",
+);
+
+aot_failure_matches_interpreter!(
+    abs_of_int_min_overflows,
+    "trap-abs-intmin",
+    "-32768i16 abs .",
+);
+
+aot_failure_matches_interpreter!(
+    neg_of_nonzero_unsigned_overflows,
+    "trap-neg-unsigned",
+    "0u32 neg 1u32 neg .",
+);
+
+aot_failure_matches_interpreter!(
+    pow_overflows_at_the_target_width,
+    "trap-pow-overflow",
+    "2u8 8u8 pow .",
+);
+
+aot_failure_matches_interpreter!(
+    pow_rejects_a_negative_exponent,
+    "trap-pow-negative",
+    "2 -1 pow .",
+);
+
+#[test]
+fn imported_definitions_are_compiled_into_the_executable() {
+    if !cc_available() {
+        eprintln!(
+            "skipping imported_definitions_are_compiled_into_the_executable: no `cc` on PATH"
+        );
+        return;
+    }
+    // The library sits next to the generated main source (both land in
+    // the tempdir), so a bare relative path resolves against the base.
+    let lib_name = format!("plenty-aot-import-lib-{}.plenty", nonce());
+    let lib_path = std::env::temp_dir().join(&lib_name);
+    std::fs::write(
+        &lib_path,
+        ": cube { x i64 -> i64 } x x * x * ;\n: greet { -> } \"Say hi.\" \"hi\" :println ;\n",
+    )
+    .expect("write library");
+    let source = format!("import \"{lib_name}\"\n:greet 3 :cube .\n");
+    let interp = run_interpreter(&source, "import");
+    let aot = run_aot(&source, "import");
+    let _ = std::fs::remove_file(&lib_path);
+    assert_eq!(aot, interp);
+    assert_eq!(interp, "hi\n[27i64]\n");
+}
+
+aot_matches_interpreter!(
+    module_functions_use_qualified_symbols,
+    "modules",
+    "module m\n  : twice { x i64 -> i64 } x :add-self ;\n  \
+     private : add-self { x i64 -> i64 } x x + ;\nend\n21 :m.twice .\n",
+);
+
+aot_matches_interpreter!(
+    constants_lower_as_literals,
+    "consts",
+    "const LIMIT 7u8 ;\nconst GREETING \"hey\" ;\n\
+     : shout { -> } GREETING :println ;\n:shout LIMIT LIMIT + .\n",
+);
+
+aot_matches_interpreter!(
+    quotations_lower_to_indirect_calls,
+    "quotations",
+    ": twice { f ( i64 -> i64 ) x i64 -> i64 } x f :call f :call ;\n\
+     { n i64 -> i64 } [ n n * ] dup . 3 :twice .\n\
+     \"hello\" { s Str -> Bool } [ s \"ll\" :contains ] :call .\n",
+);
+
+aot_matches_interpreter!(
+    closures_store_captures_in_an_environment,
+    "closures",
+    ": has { needle Str -> ( Str -> Bool ) } { s Str -> Bool } with needle [ s needle :contains ] ;\n\
+     : scaled { unused i64 k u8 -> ( u8 -> u8 ) } { x u8 -> u8 } with k [ x k * ] ;\n\
+     \"hello\" \"ell\" :has :call .\n\
+     0 3u8 :scaled 5u8 swap :call .\n\
+     \"x\" :has .\n",
+);
+
+aot_matches_interpreter!(
+    generic_functions_are_instantiated_per_call,
+    "generics",
+    ": swap-under { a 'T b 'U -> 'U 'T } b a ;\n\
+     : dip { ..R x 'T f ( ..R -> ..S ) -> ..S 'T } f :call x ;\n\
+     : countdown { ..R n i64 -> ..R } n 0 = match true [ ] false [ n 1 - :countdown ] end ;\n\
+     1 \"a\" :swap-under .\n\
+     true 2u8 :swap-under .\n\
+     5 { n i64 -> i64 } [ n 10 * ] 7 swap :dip .\n\
+     3 :countdown .\n",
+);
+
+aot_matches_interpreter!(
+    tail_calls_keep_values_beneath_their_arguments,
+    "tail-keep",
+    ": inc { y i64 -> i64 } y 1 + ;\n\
+     : pair { x i64 -> i64 i64 } 1 x :inc ;\n\
+     5 :pair .\n",
+);
+
+aot_matches_interpreter!(
+    inferred_signatures_compile_like_written_ones,
+    "inferred",
+    ": inc { x ? -> ? } x 1 + ;\n\
+     : ident { x ? -> ? } x ;\n\
+     : five { ? } 5 ;\n\
+     41 :inc . \"s\" :ident . :five .\n",
+);
+
+aot_matches_interpreter!(
+    structs_are_built_read_updated_and_printed,
+    "structs",
+    "struct point { x i32 y i32 }\n\
+     struct tagged { label Str at point ok Bool }\n\
+     : shift { p point -> point } \"Move right.\" p p :point.x 1i32 + :point.with-x ;\n\
+     1i32 2i32 :point :shift . \"p\" swap true :tagged dup :tagged.at :point.y . drop :print\n",
+);
+
+aot_matches_interpreter!(
+    match2_dispatches_on_two_values,
+    "match2",
+    ": classify { n i64 s Str -> i64 } \"Pick by both.\"\n\
+       n s match2 0 \"a\" [ 1 ] 0 _ [ 2 ] _ \"a\" [ 3 ] _ _ [ 4 ] end ;\n\
+     : xor { a Bool b Bool -> Bool } \"Exclusive or.\"\n\
+       a b match2 true false [ true ] false true [ true ] _ _ [ false ] end ;\n\
+     0 \"a\" :classify 0 \"b\" :classify 7 \"a\" :classify 7 \"b\" :classify .\n\
+     true true :xor true false :xor .\n",
+);
+
+aot_matches_interpreter!(
+    ranges_alternatives_and_guards_dispatch,
+    "ranges",
+    ": classify { n u8 -> Str } \"Bucket a byte.\"\n\
+       n match 0 [ \"zero\" ] 1 | 2 | 3 [ \"few\" ] 4..=99 [ \"some\" ] 100.. [ \"many\" ] end ;\n\
+     : sign { n i8 -> i64 } \"Sign by range.\"\n\
+       n match ..=-1 [ -1 ] 0 [ 0 ] 1i8.. [ 1 ] end ;\n\
+     : parity { n i64 -> Str } \"Even or odd, by guard.\"\n\
+       n match _ when [ 2 / 2 * n = ] [ \"even\" ] _ [ \"odd\" ] end ;\n\
+     0u8 :classify 3u8 :classify 42u8 :classify 200u8 :classify .\n\
+     -5i8 :sign 0i8 :sign 127i8 :sign .\n\
+     4 :parity 7 :parity \"b\" match \"a\" | \"b\" [ 1 ] _ [ 0 ] end .\n",
+);
+
+aot_matches_interpreter!(
+    binding_patterns_name_the_matched_value,
+    "bindings",
+    ": describe { n i64 -> i64 } \"Scale by bucket.\"\n\
+       n 2 * match 0 [ -1 ] 1..=9 as d [ d 100 + ] _ as big when [ 50 > ] [ big 1000 * ] _ as other [ other n + ] end ;\n\
+     : pair { a i64 b Bool -> i64 } \"Sign by flag.\"\n\
+       a b match2 _ as x true [ x ] _ as y false [ y -1 * ] end ;\n\
+     : count { n i64 acc i64 -> i64 } \"Sum 1..=n.\"\n\
+       n match 0 [ acc ] _ as k [ k 1 - acc k + :count ] end ;\n\
+     0 :describe 2 :describe 30 :describe 10 :describe .\n\
+     3 true :pair 4 false :pair 1000 0 :count .\n",
+);
+
+aot_matches_interpreter!(
+    escapes_raw_literals_and_interior_nuls,
+    "escapes",
+    "\"tab\\there\\r\\n\" r#\"raw \\d \"q\"\"# .\n\
+     \"a\\0b\" \"a\\0c\" = \"a\\0b\" \"a\\0b\" = \"x\\0yz\" \"\\0y\" :contains .\n\
+     \"caf\\u{e9} \\x7f\\u{85}\" \"\\0\" + .\n\
+     \"a\\0b\" :println\n",
+);
+
+aot_matches_interpreter!(
+    prefixed_and_grouped_literals,
+    "radix",
+    "0xFFu8 0b1010 0o17 1_000_000 -0x80i8 0xFFFF_FFFF_FFFF_FFFFu64 .\n\
+     0x41u8 match 0x00..=0x1F [ 0 ] 0x41 | 0x61 [ 1 ] _ [ 2 ] end .\n",
+);
+
+aot_matches_interpreter!(
+    bitwise_words_and_out_of_range_shifts,
+    "bits",
+    ": bits { a u8 b u8 -> u8 u8 u8 u8 } \"Every bitwise op.\" a b band a b bor a b bxor a bnot ;\n\
+     : shifts { a i16 n i16 -> i16 i16 i16 i16 } \"Shifts and rotates.\" a n shl a n shr a n rotl a n rotr ;\n\
+     0b1100u8 0b1010u8 :bits .\n\
+     -300i16 3i16 :shifts .\n\
+     -300i16 16i16 :shifts .\n\
+     -300i16 -1i16 :shifts .\n\
+     0xF0F0u16 20u16 shl 0xF0F0u16 20u16 shr 0x8000_0000_0000_0000u64 63u64 shr .\n",
+);
+
+aot_matches_interpreter!(
+    integer_math_words,
+    "intmath",
+    "7 3 rem -7 3 rem 7 -3 rem -7 -3 rem .\n\
+     7 3 mod -7 3 mod 7 -3 mod -7 -3 mod -7i8 -128i8 mod 200u8 7u8 mod .\n\
+     -7 2 divmod 17u32 5u32 divmod .\n\
+     -5i8 abs 5i8 abs 200u8 abs -127i8 abs 9 neg 0u8 neg -127i8 neg .\n\
+     3 -4 min 3 -4 max 200u8 7u8 min 200u8 7u8 max .\n\
+     2 10 pow 3u8 5u8 pow -2i8 7i8 pow 7 0 pow 0 0 pow -1 1000001 pow 1u64 18446744073709551615u64 pow .\n",
+);
+
+aot_matches_interpreter!(
+    wrapping_and_saturating_words,
+    "wrapsat",
+    "200u8 100u8 +% 16u8 17u8 *% 9223372036854775807 1 +% -128i8 -1i8 *% .\n\
+     200u8 100u8 +| 5u8 9u8 -| 18446744073709551615u64 1u64 +| 0u64 1u64 -| .\n\
+     100i8 100i8 +| -100i8 -100i8 +| 100i8 -100i8 -| -100i8 100i8 -| 3i8 4i8 -| .\n\
+     2147483647i32 1i32 +| -2147483648i32 1i32 -| -9223372036854775808 -1 -| .\n",
+);
+
+aot_matches_interpreter!(
+    string_manipulation_words,
+    "strings",
+    "\"a-\\u{e9}-b\" dup :len swap 1 4 :slice \"\" :len \"abc\" 0 0 :slice \"abc\" 0 3 :slice .\n\
+     \"k=v=w\" \"=\" :split \"abc\" \"x\" :split \"a::b\" \"::\" :split .\n\
+     \" \\t hi there\\n\\r \" :trim \"   \" :trim \"x\" :trim .\n\
+     \"hello\" \"he\" :starts-with \"hello\" \"lo\" :ends-with \"lo\" \"hello\" :ends-with \"\" \"\" :starts-with .\n\
+     \"h\\u{e9}llo\" \"l\" :find \"hello\" \"z\" :find \"hello\" \"\" :find .\n\
+     \"aaa\" \"aa\" \"b\" :replace \"a.b.c\" \".\" \"\" :replace \"x\" \"x\" \"\\u{e9}\\u{e9}\" :replace .\n\
+     \"Mix\\u{e9}d 42\" dup :upper swap :lower \"ab\" 3 :repeat \"ab\" 0 :repeat \"\" 9 :repeat .\n",
+);
+
+aot_failure_matches_interpreter!(
+    slice_rejects_an_out_of_bounds_range,
+    "trap-slice",
+    "\"h\\u{e9}\" 1 3 :slice .",
+);
+
+aot_failure_matches_interpreter!(
+    split_rejects_an_empty_separator,
+    "trap-split",
+    "\"abc\" \"\" :split .",
+);
+
+aot_failure_matches_interpreter!(
+    replace_rejects_an_empty_pattern,
+    "trap-replace",
+    "\"abc\" \"\" \"x\" :replace .",
+);
+
+aot_failure_matches_interpreter!(
+    repeat_rejects_a_negative_count,
+    "trap-repeat",
+    "\"abc\" -2 :repeat .",
+);
+
+aot_matches_interpreter!(
+    parsing_and_rendering_integers,
+    "parse-render",
+    "\"42\" :parse-i64 \"-128\" :parse-i8 \"-129\" :parse-i8 \"+255\" :parse-u8 \"-0\" :parse-u8 .\n\
+     \"18446744073709551615\" :parse-u64 \"-9223372036854775808\" :parse-i64 \"\" :parse-i32 \" 1\" :parse-i32 \"-\" :parse-i16 .\n\
+     -5i8 :to-str 200u8 :to-str \"a\\tb\" :to-str true :to-str -5i8 :to-digits 18446744073709551615u64 :to-digits .\n\
+     struct p { x i16 name Str } 3i16 \"n\" :p :to-str { n i64 -> i64 } [ n ] :to-str .\n",
+);
+
+aot_matches_interpreter!(
+    format_fills_placeholders_in_order,
+    "format",
+    "\"alice\" 3 \"user={} count={}\" :format :println\n\
+     -1i8 255u8 -2 \"q\" \"{:x} {:x} {:x} {{literal}} {:?}\" :format .\n\
+     struct p { x i16 } 3i16 :p true \"a\\tb\" 18446744073709551615u64 \"{} {} {:?} {} {}!\" :format :println\n\
+     : greet { name Str n u8 -> Str } name n \"hi {}, you are {}\" :format ;\n\
+     \"bo\" 9u8 :greet :println \"no placeholders\" :format \"\" :format .\n",
+);
+
+aot_matches_interpreter!(
+    format_debug_escapes_what_rust_debug_escapes,
+    "format-debug",
+    "'\\u{301}' \"e\\u{301}\" '\\u{10FFFF}' '\\u{301}' \"{:?} {:?} {:?} {}\" :format dup :len .\n",
+);
+
+aot_matches_interpreter!(
+    chars_cast_print_and_match,
+    "chars",
+    "'a' '\\u{e9}' '\\n' '\\'' '\"' '\\u{7f}' '\\u{1F600}' .\n\
+     \"h\\u{e9}llo\" 1 :next-char \"\" 0 :next-char \"h\\u{e9}llo\" 1 :char-at '\\u{e9}' :from-char .\n\
+     : count-from { s Str i i64 n i64 -> i64 } s i :next-char match false [ drop drop n ] true [ swap drop s swap n 1 + :count-from ] end ;\n\
+     \"abcdefghij\" 25600 :repeat 0 0 :count-from \"h\\u{e9}\\u{1F600}\" 0 0 :count-from .\n\
+     233 :as-char 128512u32 :as-char 'A' :as-u32 '\\u{e9}' :as-i8 'z' 'z' = 'a' 'b' != .\n\
+     'q' :to-str 'x' 'y' \"{}{:?}\" :format .\n\
+     : kind { c Char -> Str } c match 'a'..='z' [ \"lower\" ] '0'..='9' | '_' [ \"word\" ] ..='\\u{7f}' [ \"ascii\" ] _ [ \"other\" ] end ;\n\
+     'q' :kind '7' :kind '_' :kind '!' :kind '\\u{e9}' :kind .\n",
+);
+
+aot_matches_interpreter!(
+    renderings_escape_what_rust_debug_escapes,
+    "debug-escapes",
+    "\"a\\u{301}\" dup :to-str :len '\\u{301}' dup :to-str :len '\\u{10FFFF}' dup :to-str :len .\n\
+     \"\\u{a0}\\u{ad}\\u{200b}\\u{e000}x\\u{1F600}\" '\\u{ad}' '\\u{200d}' .\n",
+);
+
+aot_failure_matches_interpreter!(
+    as_char_rejects_a_surrogate,
+    "trap-as-char-surrogate",
+    "55296 :as-char .",
+);
+
+aot_failure_matches_interpreter!(
+    as_char_rejects_a_negative_value,
+    "trap-as-char-negative",
+    "-1i8 :as-char .",
+);
+
+aot_failure_matches_interpreter!(
+    char_at_rejects_an_out_of_bounds_index,
+    "trap-char-at",
+    "\"h\\u{e9}\" 2 :char-at .",
+);
+
+aot_failure_matches_interpreter!(
+    next_char_rejects_an_offset_inside_a_char,
+    "trap-next-char",
+    "\"h\\u{e9}\" 2 :next-char .",
+);
+
+aot_matches_interpreter!(
+    byte_strings_encode_compare_and_render,
+    "bytes",
+    "\"h\\u{e9}\\n\" :utf8-encode dup :utf8-decode .\n\
+     \"ab\" :utf8-encode \"ab\" :utf8-encode = \"a\" :utf8-encode \"b\" :utf8-encode != .\n\
+     \"q\\\"\\\\\" :utf8-encode :to-str :println\n",
+);
+
+aot_matches_interpreter!(
+    byte_strings_measure_cut_join_and_decode_lossily,
+    "bytes-cut",
+    "\"h\\u{e9}\\u{20ac}\" :utf8-encode dup :bytes-len . :clear\n\
+     \"h\\u{e9}\" :utf8-encode 0 2 :bytes-slice \"\\u{20ac}\" :utf8-encode 0 2 :bytes-slice +\n\
+     dup :utf8-decode . :clear\n\
+     \"h\\u{e9}\" :utf8-encode 0 2 :bytes-slice \"\\u{20ac}\" :utf8-encode 0 2 :bytes-slice +\n\
+     \"x\" :utf8-encode + :utf8-decode-lossy :println\n",
+);
+
+aot_failure_matches_interpreter!(
+    bytes_slice_rejects_an_out_of_bounds_range,
+    "trap-bytes-slice",
+    "\"abc\" :utf8-encode 1 4 :bytes-slice .",
+);
+
+/// Run `cmd` with `input` on its stdin and return what it left behind.
+fn output_with_stdin(cmd: &mut Command, input: &[u8]) -> Output {
+    use std::io::Write;
+    use std::process::Stdio;
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn");
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(input)
+        .expect("write stdin");
+    child.wait_with_output().expect("wait")
+}
+
+/// Run `cmd` with `input` on its stdin and return its stdout as raw
+/// bytes, for programs whose output need not be UTF-8.
+fn run_with_stdin(cmd: &mut Command, input: &[u8]) -> Vec<u8> {
+    let out = output_with_stdin(cmd, input);
+    assert!(out.status.success(), "exit was {:?}", out.status);
+    out.stdout
+}
+
+#[test]
+fn byte_input_and_output_agree_with_the_interpreter() {
+    if !cc_available() {
+        eprintln!("skipping byte_input_and_output_agree_with_the_interpreter: no `cc` on PATH");
+        return;
+    }
+    let tmp = std::env::temp_dir();
+    let n = nonce();
+    let src_path = tmp.join(format!("plenty-aot-bytes-{n}.plenty"));
+    let exe_path = tmp.join(format!("plenty-aot-bytes-{n}.exe"));
+    std::fs::write(
+        &src_path,
+        ":readline-bytes drop :utf8-decode .\n\
+         :readline-bytes drop dup :utf8-decode .\n\
+         :read-bytes dup :write-bytes :readline-bytes .\n",
+    )
+    .expect("write source");
+    let compile = Command::new(plenty_bin())
+        .arg("--compile")
+        .arg(&src_path)
+        .arg("-o")
+        .arg(&exe_path)
+        .output()
+        .expect("spawn plenty --compile");
+    assert!(compile.status.success(), "compile failed");
+
+    // A valid line, a line holding an encoded surrogate, then a tail
+    // with a nul and a stray continuation byte and no final newline.
+    let input = b"h\xc3\xa9\r\n\xed\xa0\x80\t\"\n\x00\xffrest";
+    let interp = run_with_stdin(Command::new(plenty_bin()).arg(&src_path), input);
+    let aot = run_with_stdin(&mut Command::new(&exe_path), input);
+    let _ = std::fs::remove_file(&src_path);
+    let _ = std::fs::remove_file(&exe_path);
+    assert_eq!(aot, interp);
+    assert_eq!(
+        interp,
+        b"[\"h\xc3\xa9\" true]\n\
+          [\"h\xc3\xa9\" true b\"\\xed\\xa0\\x80\\t\\\"\" \"\" false]\n\
+          \x00\xffrest[\"h\xc3\xa9\" true b\"\\xed\\xa0\\x80\\t\\\"\" \"\" false \
+          b\"\\x00\\xffrest\" b\"\" false]\n"
+    );
+}
+
+#[test]
+fn readline_fails_on_invalid_utf8_like_the_interpreter() {
+    if !cc_available() {
+        eprintln!("skipping readline_fails_on_invalid_utf8_like_the_interpreter: no `cc` on PATH");
+        return;
+    }
+    let tmp = std::env::temp_dir();
+    let n = nonce();
+    let src_path = tmp.join(format!("plenty-aot-bad-line-{n}.plenty"));
+    let exe_path = tmp.join(format!("plenty-aot-bad-line-{n}.exe"));
+    std::fs::write(&src_path, ":readline . :readline drop 0 :next-char .\n").expect("write source");
+    let compile = Command::new(plenty_bin())
+        .arg("--compile")
+        .arg(&src_path)
+        .arg("-o")
+        .arg(&exe_path)
+        .output()
+        .expect("spawn plenty --compile");
+    assert!(compile.status.success(), "compile failed");
+
+    // A cut-off four-byte sequence must not become a string.
+    let input = b"ok\n\xf0\n";
+    let interp = output_with_stdin(Command::new(plenty_bin()).arg(&src_path), input);
+    let aot = output_with_stdin(&mut Command::new(&exe_path), input);
+    let _ = std::fs::remove_file(&src_path);
+    let _ = std::fs::remove_file(&exe_path);
+    assert_eq!(aot.status.code(), Some(1));
+    assert_eq!(aot.status.code(), interp.status.code());
+    assert_eq!(aot.stdout, interp.stdout);
+    assert_eq!(aot.stderr, interp.stderr);
+    assert_eq!(
+        interp.stderr,
+        b"error: stream did not contain valid UTF-8\n"
+    );
+}
+
+#[test]
+fn file_words_agree_with_the_interpreter() {
+    if !cc_available() {
+        eprintln!("skipping file_words_agree_with_the_interpreter: no `cc` on PATH");
+        return;
+    }
+    let dir = std::env::temp_dir().join(format!("plenty-aot-files-{}", nonce()));
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    std::fs::write(dir.join("c.bin"), b"\xff\r\nok").expect("write binary file");
+    let dir_name = dir.display();
+    let source = format!(
+        ": lines {{ f File -> i64 }} f :file-readline match false [ drop 0 ] true [ :println f :lines 1 + ] end ;\n\
+         : closed-early {{ f File -> Str Bool Str Bool }} f :file-readline f :close-file f :file-readline ;\n\
+         \"{dir_name}/a.txt\" \"one\\ntwo\\r\\n\" :write-file \"{dir_name}/a.txt\" \"three\" :append-file .\n\
+         \"{dir_name}/a.txt\" :read-file \"{dir_name}/a.txt\" :file-exists \"{dir_name}/b.txt\" :file-exists .\n\
+         \"{dir_name}/none/b.txt\" :read-file \"{dir_name}/none/b.txt\" \"x\" :write-file . :clear\n\
+         \"{dir_name}/a.txt\" :open-file . drop :lines . :clear\n\
+         \"{dir_name}/a.txt\" :open-file drop :closed-early \"{dir_name}/b.txt\" :open-file drop :closed-early . :clear\n\
+         : three-bytes {{ f File -> Bytes Bool Bytes Bool Bytes Bool }} \
+         f :file-readline-bytes f :file-readline-bytes f :file-readline-bytes ;\n\
+         \"{dir_name}/c.bin\" :open-file drop :file-readline \"{dir_name}/c.bin\" :open-file drop :three-bytes .\n"
+    );
+    let interp = run_interpreter(&source, "files");
+    let aot = run_aot(&source, "files");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(aot, interp);
+    assert!(
+        interp.contains("[<file> true]\none\ntwo\nthree\n[3i64]\n"),
+        "{interp}"
+    );
+    assert!(
+        interp.ends_with("[\"\" false b\"\\xff\" true b\"ok\" true b\"\" false]\n"),
+        "{interp}"
+    );
+}
+
+#[test]
+fn arguments_and_environment_agree_with_the_interpreter() {
+    if !cc_available() {
+        eprintln!("skipping arguments_and_environment_agree_with_the_interpreter: no `cc` on PATH");
+        return;
+    }
+    let tmp = std::env::temp_dir();
+    let n = nonce();
+    let src_path = tmp.join(format!("plenty-aot-args-{n}.plenty"));
+    let exe_path = tmp.join(format!("plenty-aot-args-{n}.exe"));
+    std::fs::write(
+        &src_path,
+        ":argc 0 :arg 2 :arg 3 :arg -1 :arg . :clear\n\
+         \"PLENTY_TEST_VAR\" :env \"PLENTY_TEST_UNSET\" :env \"\" :env \"A=B\" :env .\n",
+    )
+    .expect("write source");
+    let compile = Command::new(plenty_bin())
+        .arg("--compile")
+        .arg(&src_path)
+        .arg("-o")
+        .arg(&exe_path)
+        .output()
+        .expect("spawn plenty --compile");
+    assert!(compile.status.success(), "compile failed");
+
+    let args = ["a", "-b", "c d"];
+    let interp = run_with_stdin(
+        Command::new(plenty_bin())
+            .arg(&src_path)
+            .args(args)
+            .env("PLENTY_TEST_VAR", "h\u{e9}")
+            .env_remove("PLENTY_TEST_UNSET"),
+        b"",
+    );
+    let aot = run_with_stdin(
+        Command::new(&exe_path)
+            .args(args)
+            .env("PLENTY_TEST_VAR", "h\u{e9}")
+            .env_remove("PLENTY_TEST_UNSET"),
+        b"",
+    );
+    let bare = run_with_stdin(&mut Command::new(&exe_path), b"");
+    let _ = std::fs::remove_file(&src_path);
+    let _ = std::fs::remove_file(&exe_path);
+    assert_eq!(aot, interp);
+    assert_eq!(
+        String::from_utf8(interp).unwrap(),
+        "[3i64 \"a\" true \"c d\" true \"\" false \"\" false]\n\
+         [\"h\u{e9}\" true \"\" false \"\" false \"\" false]\n"
+    );
+    assert!(String::from_utf8(bare)
+        .unwrap()
+        .starts_with("[0i64 \"\" false"));
+}
+
+aot_matches_interpreter!(
+    exit_zero_ends_the_program_early,
+    "exit-zero",
+    ": done { n i64 -> } n . :clear 0i32 :exit ;\n\"before\" :println 5 :done \"after\" :println\n",
+);
+
+aot_failure_matches_interpreter!(
+    exit_status_and_stderr_match,
+    "exit-status",
+    ": fail { why Str -> } why :eprintln 'x' :eprint 300 :as-i32 :exit ;\n\
+     \"ok\" :println \"bad input\" :fail\n",
+);
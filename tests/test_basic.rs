@@ -22,6 +22,13 @@ fn arithmetic_leaves_expected_stack(#[case] program: &str, #[case] expected: &st
 #[case(r#""hello world""#, r#"["hello world"]"#)]
 #[case(r#""a\"b""#, r#"["a\"b"]"#)]
 #[case(r#""a\\b""#, r#"["a\\b"]"#)]
+#[case(r#""one\ttwo\r\n""#, r#"["one\ttwo\r\n"]"#)]
+#[case(r#""nul\0inside""#, r#"["nul\0inside"]"#)]
+#[case(r#""\x41\x7f""#, r#"["A\u{7f}"]"#)]
+#[case(r#""caf\u{e9} \u{1F600}""#, "[\"caf\u{e9} \u{1F600}\"]")]
+#[case(r#"r"C:\dir\n""#, r#"["C:\\dir\\n"]"#)]
+#[case(r##"r#"say "hi" \"#"##, r#"["say \"hi\" \\"]"#)]
+#[case(r#"r"" "rx""#, r#"["" "rx"]"#)]
 fn a_quoted_string_pushes_text(#[case] program: &str, #[case] expected: &str) {
     let mut vm = Vm::new();
     vm.run(program).unwrap();
@@ -31,6 +38,18 @@ fn a_quoted_string_pushes_text(#[case] program: &str, #[case] expected: &str) {
 #[rstest]
 #[case(r#""hello"#)] // unterminated string literal
 #[case(r#""bad \z escape""#)] // unrecognised escape sequence
+#[case(r#""\x4""#)] // \x needs two hex digits
+#[case(r#""\xff""#)] // \x is ASCII only
+#[case(r#""\u{110000}""#)] // not a Unicode scalar value
+#[case(r#""\u{d800}""#)] // a surrogate
+#[case(r#""\u41""#)] // \u needs braces
+#[case(r#""\u{}""#)] // \u needs at least one digit
+#[case(r#""\u{41""#)] // \u needs its closing brace
+#[case(r"'\u{41'")] // in a char literal too
+#[case(r#""\u{+41}""#)] // hex digits only, no sign
+#[case(r#""\x+4""#)] // likewise for \x
+#[case(r##"r#"open"##)] // unterminated raw string literal
+#[case(r##"r#"x""##)] // a bare quote does not close a hash-fenced literal
 fn malformed_string_literals_are_rejected(#[case] program: &str) {
     let mut vm = Vm::new();
     assert!(vm.run(program).is_err());
@@ -138,8 +157,8 @@ fn function_sig_returns_the_captured_signature() {
 #[case(r#": zero { -> i64 } "Push zero." 0 ;"#)] // no inputs, one output
 #[case(r#": consume { x i64 -> } "Discard an int." ;"#)]
 // input, no outputs
-// named outputs — placeholder body that produces two Ints (no `mod` op yet).
-#[case(r#": divmod { a i64 b i64 -> q i64 r i64 } "Quot and rem (placeholder)." a b / a b / ;"#)]
+// named outputs — a body that produces two Ints.
+#[case(r#": divmod { a i64 b i64 -> q i64 r i64 } "Quot and rem." a b divmod ;"#)]
 // Bool type — identity body.
 #[case(r#": flip { x Bool -> Bool } "Identity, until ops exist." x ;"#)]
 #[case(r#": echo { s Str -> Str } "Identity for strings." s ;"#)] // Str type
@@ -170,6 +189,16 @@ fn malformed_type_headers_are_rejected(#[case] program: &str) {
     assert!(vm.run(program).is_err());
 }
 
+#[rstest]
+#[case("`Char`")]
+#[case("`Bytes`")]
+#[case("`File`")]
+fn an_unknown_type_error_lists_every_builtin_type(#[case] name: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(": f { a Floob -> } ;").unwrap_err().to_string();
+    assert!(err.contains(name), "error was {err:?}");
+}
+
 #[rstest]
 #[case(": double { x i64 -> i64 } x 2 * ; 21 :double", "[42i64]")] // no docstring
 #[case(": discard { x i64 -> } ; 7 :discard", "[]")] // no body either
@@ -254,6 +283,43 @@ fn suffixed_integer_literals_preserve_their_width_and_range() {
     assert!(vm.run("-1u8").is_err());
 }
 
+#[rstest]
+#[case("0xFF", "[255i64]")]
+#[case("0xffu8", "[255u8]")]
+#[case("0b1010", "[10i64]")]
+#[case("0o17", "[15i64]")]
+#[case("1_000_000", "[1000000i64]")]
+#[case("0xFFFF_FFFFu32", "[4294967295u32]")]
+#[case("0b1000_0000u8", "[128u8]")]
+#[case("-0x80i8", "[-128i8]")]
+#[case("0xFFFF_FFFF_FFFF_FFFFu64", "[18446744073709551615u64]")]
+#[case("1_i16", "[1i16]")]
+fn prefixed_and_grouped_integer_literals(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("0x100u8", "does not fit u8")]
+#[case("0x80i8", "does not fit i8")]
+#[case("-0b1u16", "does not fit u16")]
+#[case("0x8000_0000_0000_0000", "does not fit i64")]
+#[case("99999999999999999999", "does not fit i64")]
+#[case("0xZZ", "invalid hexadecimal literal `0xZZ`")]
+#[case("0b102", "invalid binary literal")]
+#[case("0o8", "invalid octal literal")]
+#[case("0x", "invalid hexadecimal literal")]
+#[case("0xFFu7", "unknown suffix `u7`")]
+fn out_of_range_or_malformed_prefixed_literals_are_rejected(
+    #[case] program: &str,
+    #[case] needle: &str,
+) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
 #[rstest]
 // One input loaded twice in the body.
 #[case(
@@ -432,6 +498,437 @@ fn mixed_widths_are_rejected_at_check_time(#[case] program: &str) {
     assert!(!err.to_string().is_empty());
 }
 
+#[rstest]
+#[case("0b1100u8 0b1010u8 band", "[8u8]")]
+#[case("0b1100u8 0b1010u8 bor", "[14u8]")]
+#[case("0b1100u8 0b1010u8 bxor", "[6u8]")]
+#[case("0u8 bnot 0 bnot", "[255u8 -1i64]")]
+#[case("1 4 shl 0xF0u8 4 :as-u8 shl", "[16i64 0u8]")]
+#[case("-16i8 2i8 shr 0xF0u8 4u8 shr", "[-4i8 15u8]")]
+#[case("0x81u8 1u8 rotl 0x81u8 1u8 rotr", "[3u8 192u8]")]
+#[case("0x81u8 9u8 rotl 1i8 -1i8 rotl", "[3u8 -128i8]")]
+fn bitwise_words_work_at_every_width(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+// Shifting by the width or more shifts every bit out; `shr` on a negative
+// signed value leaves the sign fill.
+#[case("1u8 8u8 shl 0xFFu8 200u8 shr", "[0u8 0u8]")]
+#[case("-5i8 8i8 shr 5i8 100i8 shr", "[-1i8 0i8]")]
+#[case("1 64 shl -1 64 shr", "[0i64 -1i64]")]
+// A negative count is read as unsigned, so it too is past the width.
+#[case("1i32 -1i32 shl -8i32 -1i32 shr", "[0i32 -1i32]")]
+fn shift_counts_at_or_past_the_width_are_defined(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("1u8 1u16 band", "`band` requires same-width integers, got (u8 u16)")]
+#[case("1 1u8 shl", "`shl` requires same-width integers, got (i64 u8)")]
+#[case("true false bor", "`bor` requires same-width integers")]
+#[case("\"a\" bnot", "`bnot` requires an integer, got Str")]
+fn bitwise_words_require_same_width_integers(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case("7 3 rem -7 3 rem 7 -3 rem", "[1i64 -1i64 1i64]")]
+#[case("7 3 mod -7 3 mod 7 -3 mod -7 -3 mod", "[1i64 2i64 1i64 2i64]")]
+#[case("200u8 7u8 rem 200u8 7u8 mod", "[4u8 4u8]")]
+#[case("-7 2 divmod", "[-3i64 -1i64]")]
+#[case("-5i8 abs 200u8 abs 9 neg 0u8 neg", "[5i8 200u8 -9i64 0u8]")]
+#[case("3 -4 min 3 -4 max 200u8 7u8 min", "[-4i64 3i64 7u8]")]
+#[case("2 10 pow 7 0 pow -2i8 7i8 pow", "[1024i64 1i64 -128i8]")]
+#[case("-1 1000001 pow 1u64 18446744073709551615u64 pow", "[-1i64 1u64]")]
+fn integer_math_words_work_at_every_width(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("7 0 rem", "division by zero")]
+#[case("7u8 0u8 mod", "division by zero")]
+#[case("7 0 divmod", "division by zero")]
+#[case("-128i8 -1i8 rem", "integer overflow")]
+#[case("-128i8 -1i8 divmod", "integer overflow")]
+#[case("-128i8 abs", "integer overflow")]
+#[case("1u8 neg", "integer overflow")]
+#[case("2u8 8u8 pow", "integer overflow")]
+#[case("2 -1 pow", "negative exponent")]
+#[case("2 3u8 pow", "`pow` requires same-width integers, got (i64 u8)")]
+#[case("true abs", "`abs` requires an integer, got Bool")]
+fn integer_math_words_trap_like_the_arithmetic_they_extend(
+    #[case] program: &str,
+    #[case] needle: &str,
+) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case("200u8 100u8 +%", "[44u8]")]
+#[case("16u8 17u8 *%", "[16u8]")]
+#[case("9223372036854775807 1 +%", "[-9223372036854775808i64]")]
+#[case("-128i8 -1i8 *%", "[-128i8]")]
+#[case("200u8 100u8 +| 5u8 9u8 -|", "[255u8 0u8]")]
+#[case("100i8 100i8 +| -100i8 -100i8 +|", "[127i8 -128i8]")]
+#[case("100i8 -100i8 -| -100i8 100i8 -| 3i8 4i8 -|", "[127i8 -128i8 -1i8]")]
+fn wrapping_and_saturating_words_never_overflow(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("1u8 1u16 +%", "`+%` requires same-width integers, got (u8 u16)")]
+#[case("\"a\" \"b\" +|", "`+|` requires same-width integers, got (Str Str)")]
+fn wrapping_and_saturating_words_are_integer_only(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(r#""h\u{e9}llo" :len "" :len"#, "[5i64 0i64]")]
+#[case(r#""h\u{e9}llo" 1 3 :slice "abc" 3 3 :slice"#, r#"["él" ""]"#)]
+#[case(r#""k=v=w" "=" :split"#, r#"["k" "v=w" true]"#)]
+#[case(r#""abc" ", " :split"#, r#"["abc" "" false]"#)]
+#[case(r#"" \t hi \r\n" :trim"#, r#"["hi"]"#)]
+#[case(r#""hello" "he" :starts-with "hello" "he" :ends-with"#, "[true false]")]
+#[case(
+    r#""h\u{e9}llo" "l" :find "hello" "z" :find"#,
+    "[2i64 true -1i64 false]"
+)]
+#[case(r#""aaa" "aa" "b" :replace"#, r#"["ba"]"#)]
+#[case(
+    r#""Stra\u{df}e 9" dup :upper swap :lower"#,
+    r#"["STRAßE 9" "straße 9"]"#
+)]
+#[case(r#""ab" 3 :repeat "ab" 0 :repeat"#, r#"["ababab" ""]"#)]
+fn string_words_index_by_char(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case(
+    r#""h\u{e9}" 1 3 :slice"#,
+    "`:slice` range 1..3 is out of bounds for a string of 2 chars"
+)]
+#[case(r#""abc" 2 1 :slice"#, "`:slice` range 2..1 is out of bounds")]
+#[case(r#""abc" "" :split"#, "`:split` separator must not be empty")]
+#[case(r#""abc" "" "x" :replace"#, "`:replace` pattern must not be empty")]
+#[case(r#""abc" -1 :repeat"#, "`:repeat` count must not be negative, got -1")]
+#[case(
+    r#""abc" 1u8 2 :slice"#,
+    "`:slice` requires (Str i64 i64), got (Str u8 i64)"
+)]
+#[case("5 :len", "`:len` requires (Str), got (i64)")]
+#[case(r#""a" :find"#, "stack underflow on `:find` (need 2 values, have 1)")]
+fn string_words_reject_bad_arguments(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(r#""42" :parse-i64 "-7" :parse-i8"#, "[42i64 true -7i8 true]")]
+#[case(r#""+255" :parse-u8 "256" :parse-u8"#, "[255u8 true 0u8 false]")]
+#[case(
+    r#""-1" :parse-u32 "" :parse-i64 "1 " :parse-i64"#,
+    "[0u32 false 0i64 false 0i64 false]"
+)]
+#[case(r#""0x10" :parse-i64 "1_000" :parse-i64"#, "[0i64 false 0i64 false]")]
+fn parse_words_accept_plain_decimal_text(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("-5i8 :to-str 7u64 :to-str", r#"["-5i8" "7u64"]"#)]
+#[case(r#""q\"" :to-str false :to-str"#, r#"["\"q\\\"\"" "false"]"#)]
+#[case("-5i8 :to-digits 255u8 :to-digits", r#"["-5" "255"]"#)]
+#[case(r#""12" :parse-i64 drop 1 + :to-digits"#, r#"["13"]"#)]
+fn to_str_renders_like_display(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("5 :parse-i64", "`:parse-i64` requires Str, got i64")]
+#[case(r#""5" :to-digits"#, "`:to-digits` requires an integer, got Str")]
+#[case(":to-str", "stack underflow on `:to-str`")]
+fn conversion_words_check_their_operand(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(r#""alice" 3 "user={} count={}" :format"#, r#"["user=alice count=3"]"#)]
+#[case(r#"-1i8 "q" true "{:x} {:?} {}" :format"#, r#"["ff \"q\" true"]"#)]
+#[case(r#"7 "{{{}}}" :format "" :format"#, r#"["{7}" ""]"#)]
+#[case(r##": tag { n u8 -> Str } n "#{}" :format ; 9u8 :tag"##, r##"["#9"]"##)]
+fn format_fills_placeholders_in_order(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case(r#"1 "{} {}" :format"#, "stack underflow on `:format` (need 2 values")]
+#[case(
+    r#""x" "{:x}" :format"#,
+    "`:format` placeholder 1 is `{:x}`, which requires an integer, got Str"
+)]
+#[case(r#"1 "{:y}" :format"#, "unknown `:format` placeholder `{:y}`")]
+#[case(r#"1 "{" :format"#, "unclosed `{` in `:format` template")]
+#[case(r#"1 "}" :format"#, "unmatched `}` in `:format` template")]
+#[case(
+    r#"1 "{}" dup drop :format"#,
+    "`:format` must directly follow its template, a `\"...\"` or `r\"...\"` string literal"
+)]
+#[case("1 hello :format", "`:format` must directly follow its template")]
+#[case(
+    r#"const T "{}" ; 1 T :format"#,
+    "`:format` must directly follow its template"
+)]
+fn format_templates_are_checked_at_compile_time(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+    assert_eq!(vm.stack_repr(), "[]");
+}
+
+#[rstest]
+#[case(r"'a' '\u{e9}' '\'' '\\' '\n'", r"['a' 'é' '\'' '\\' '\n']")]
+#[case(r#"'"' ';' '#' ' '"#, r#"['"' ';' '#' ' ']"#)]
+#[case(r#""h\u{e9}!" 0 :next-char"#, "['h' 1i64 true]")]
+#[case(r#""h\u{e9}!" 1 :next-char"#, "['é' 3i64 true]")]
+#[case(r#""h\u{e9}!" 4 :next-char"#, r"['\0' 4i64 false]")]
+#[case(r#""" 0 :next-char"#, r"['\0' 0i64 false]")]
+#[case(r#""h\u{e9}llo" 1 :char-at 'x' :from-char"#, r#"['é' "x"]"#)]
+#[case("'a' 'a' = 'a' 'b' !=", "[true true]")]
+#[case(r#"'x' 'y' "{}{:?}" :format 'q' :to-str"#, r#"["x'y'" "'q'"]"#)]
+fn chars_are_scalar_values(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+/// Counts the chars from byte offset `i` on, one `:next-char` step per
+/// call; each step reads the string in place.
+const COUNT_CHARS: &str = r#": count-from { s Str i i64 n i64 -> i64 }
+    s i :next-char match
+      false [ drop drop n ]
+      true  [ swap drop s swap n 1 + :count-from ]
+    end ;"#;
+
+#[test]
+fn next_char_walks_a_large_string_in_linear_time() {
+    let mut vm = Vm::new();
+    vm.run(COUNT_CHARS).unwrap();
+    vm.run(r#""abcdefghij" 25600 :repeat 0 0 :count-from "h\u{e9}\u{1F600}" 0 0 :count-from"#)
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[256000i64 3i64]");
+}
+
+#[rstest]
+#[case("233 :as-char 65u8 :as-char", "['é' 'A']")]
+#[case(
+    r"'A' :as-u32 '\u{e9}' :as-i8 '\u{1F600}' :as-u64",
+    "[65u32 -23i8 128512u64]"
+)]
+fn chars_cast_to_and_from_integers(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("55296 :as-char", "`:as-char` 55296 is not a Unicode scalar value")]
+#[case("-1 :as-char", "`:as-char` -1 is not a Unicode scalar value")]
+#[case(
+    "1114112u32 :as-char",
+    "`:as-char` 1114112 is not a Unicode scalar value"
+)]
+#[case("'a' :as-char", "cast `:as-char` requires an integer source, got Char")]
+#[case(
+    r#""a" :as-u32"#,
+    "cast `:as-u32` requires an integer or Char source, got Str"
+)]
+#[case(
+    r#""h\u{e9}" 2 :char-at"#,
+    "`:char-at` index 2 is out of bounds for a string of 2 chars"
+)]
+#[case(
+    r#""h\u{e9}" 2 :next-char"#,
+    "`:next-char` offset 2 is not a char boundary of a string of 3 bytes"
+)]
+#[case(
+    r#""h" -1 :next-char"#,
+    "`:next-char` offset -1 is not a char boundary of a string of 1 bytes"
+)]
+#[case(
+    r#""h" 'a' :next-char"#,
+    "`:next-char` requires (Str i64), got (Str Char)"
+)]
+#[case(
+    "'a' 1 =",
+    "`=` requires both operands of the same type, got (Char i64)"
+)]
+#[case("'a' 'b' <", "`<` requires same-width integers, got (Char Char)")]
+#[case("5 :from-char", "`:from-char` requires (Char), got (i64)")]
+#[case(r"'\x'", r"invalid escape: \x")]
+fn char_words_reject_bad_arguments(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(r#""h\u{e9}\n" :utf8-encode"#, r#"[b"h\xc3\xa9\n"]"#)]
+#[case(r#""h\u{e9}" :utf8-encode :utf8-decode"#, r#"["hé" true]"#)]
+#[case(r#""" :utf8-encode :utf8-decode"#, r#"["" true]"#)]
+#[case(
+    r#""ab" :utf8-encode "ab" :utf8-encode = "a" :utf8-encode "b" :utf8-encode ="#,
+    "[true false]"
+)]
+#[case(r#""q\"" :utf8-encode :to-str"#, r#"["b\"q\\\"\""]"#)]
+fn byte_strings_are_separate_from_text(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case(r#""h\u{e9}" :utf8-encode :bytes-len "h\u{e9}" :len"#, "[3i64 2i64]")]
+#[case(r#""h\u{e9}" :utf8-encode 0 2 :bytes-slice"#, r#"[b"h\xc3"]"#)]
+#[case(r#""h\u{e9}" :utf8-encode 3 3 :bytes-slice"#, r#"[b""]"#)]
+#[case(
+    r#""h\u{e9}" :utf8-encode dup 0 2 :bytes-slice swap 2 3 :bytes-slice + :utf8-decode"#,
+    r#"["hé" true]"#
+)]
+#[case(
+    r#""h\u{e9}" :utf8-encode 0 2 :bytes-slice :utf8-decode-lossy"#,
+    "[\"h\u{fffd}\"]"
+)]
+// A cut-off sequence is one replacement, not one per byte; a stray
+// continuation byte is its own.
+#[case(
+    r#""\u{20ac}" :utf8-encode dup 0 2 :bytes-slice "a" :utf8-encode + swap 2 3 :bytes-slice +
+       :utf8-decode-lossy"#,
+    "[\"\u{fffd}a\u{fffd}\"]"
+)]
+#[case(r#""ok" :utf8-encode :utf8-decode-lossy"#, r#"["ok"]"#)]
+fn byte_strings_are_measured_and_cut_in_bytes(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case(
+    r#""h\u{e9}" :utf8-encode 2 4 :bytes-slice"#,
+    "`:bytes-slice` range 2..4 is out of bounds for 3 bytes"
+)]
+#[case(
+    r#""h\u{e9}" :utf8-encode 2 1 :bytes-slice"#,
+    "`:bytes-slice` range 2..1 is out of bounds for 3 bytes"
+)]
+#[case(r#""a" :bytes-len"#, "`:bytes-len` requires (Bytes), got (Str)")]
+#[case(
+    r#""a" :utf8-encode "b" +"#,
+    "`+` requires same-width integers, (Str Str) or (Bytes Bytes), got (Bytes Str)"
+)]
+fn byte_string_words_reject_bad_arguments(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(r#""a" :write-bytes"#, "`:write-bytes` requires (Bytes), got (Str)")]
+#[case(r#""a" :utf8-decode"#, "`:utf8-decode` requires (Bytes), got (Str)")]
+#[case(
+    r#""a" :utf8-encode :utf8-encode"#,
+    "`:utf8-encode` requires (Str), got (Bytes)"
+)]
+#[case(
+    r#""a" :utf8-encode "a" ="#,
+    "`=` requires both operands of the same type, got (Bytes Str)"
+)]
+#[case(r#""a" :utf8-encode :len"#, "got (Bytes)")]
+fn byte_strings_do_not_mix_with_text(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+    assert_eq!(vm.stack_repr(), "[]");
+}
+
+#[rstest]
+#[case(":argc", "[2i64]")]
+#[case("0 :arg 1 :arg", r#"["first" true "second" true]"#)]
+#[case("2 :arg -1 :arg", r#"["" false "" false]"#)]
+#[case(r#""" :env "A=B" :env"#, r#"["" false "" false]"#)]
+fn arguments_are_read_by_index(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.set_args(vec!["first".into(), "second".into()]);
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_vm_starts_with_no_arguments() {
+    let mut vm = Vm::new();
+    vm.run(":argc 0 :arg").unwrap();
+    assert_eq!(vm.stack_repr(), r#"[0i64 "" false]"#);
+}
+
+#[cfg(unix)]
+#[test]
+fn an_argument_that_is_not_utf8_counts_but_reads_as_missing() {
+    use std::os::unix::ffi::OsStringExt;
+    let mut vm = Vm::new();
+    vm.set_args(vec![std::ffi::OsString::from_vec(b"\xff".to_vec())]);
+    vm.run(":argc 0 :arg").unwrap();
+    assert_eq!(vm.stack_repr(), r#"[1i64 "" false]"#);
+}
+
+#[test]
+fn exit_stops_the_run_without_an_error() {
+    let mut vm = Vm::new();
+    vm.run(": stop { -> } 7i32 :exit ; 1 :stop 2").unwrap();
+    assert_eq!(vm.exit_code(), Some(7));
+    assert_eq!(vm.stack_repr(), "[1i64]");
+    vm.run("3").unwrap();
+    assert_eq!(vm.exit_code(), None);
+    assert_eq!(vm.stack_repr(), "[1i64 3i64]");
+}
+
+#[rstest]
+#[case("3 :exit", "`:exit` requires (i32), got (i64)")]
+#[case("1 :eprintln", "`:eprintln` requires (Str), got (i64)")]
+#[case(":eprint", "stack underflow")]
+fn exit_and_stderr_words_are_type_checked(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+    assert_eq!(vm.exit_code(), None);
+}
+
 #[test]
 fn function_signatures_can_use_any_integer_width() {
     use plenty::Ty;
@@ -457,6 +954,59 @@ fn match_against_a_narrow_int_requires_in_range_patterns() {
     assert!(err.to_string().contains("out of range"));
 }
 
+#[rstest]
+#[case("const LIMIT 100 ; LIMIT LIMIT +", "[200i64]")]
+#[case("const MASK 255u8 ; MASK", "[255u8]")]
+#[case(r#"const SEP ", " ; SEP"#, r#"[", "]"#)]
+#[case("const DEBUG false ; DEBUG not", "[true]")]
+#[case("const SEP ',' ; SEP SEP =", "[true]")]
+#[case(
+    "const LIMIT 10 ; : over? { n i64 -> Bool } n LIMIT > ; 11 :over?",
+    "[true]"
+)]
+#[case("const x 1 ; : f { x i64 -> i64 } x ; 5 :f", "[5i64]")] // an input shadows a constant
+fn constants_compile_to_their_literal(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("LIMIT", "[100i64]")]
+#[case(": over? { n i64 -> Bool } n LIMIT > ; 101 :over?", "[true]")]
+#[case("const LIMIT 5 ; LIMIT", "[5i64]")] // a later input may declare it again
+fn constants_carry_over_to_later_inputs(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run("const LIMIT 100 ;").unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_constant_from_an_earlier_input_is_not_a_pattern() {
+    let mut vm = Vm::new();
+    vm.run("const LIMIT 100 ;").unwrap();
+    let err = vm
+        .run("5 match LIMIT [ 1 ] _ [ 0 ] end")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("constant `LIMIT` cannot be a match-arm pattern"),
+        "error was {err:?}"
+    );
+}
+
+#[rstest]
+#[case("const N 1 ; const N 2 ;")] // redefinition
+#[case("const N dup ;")] // not a literal
+#[case("const N 1 2 ;")] // more than one value
+#[case("const 5 1 ;")] // a literal is not a name
+#[case(": f { -> } const N 1 ; ;")] // not at the top level
+#[case("const N 1 ; N \"x\" +")] // typed as the literal it stands for
+fn malformed_or_mistyped_constants_are_rejected(#[case] program: &str) {
+    assert!(Vm::new().run(program).is_err());
+}
+
 #[test]
 fn a_stack_slot_stays_small() {
     // The point of the memory model: a stack slot never grows past 16 bytes,
@@ -0,0 +1,71 @@
+//! Closures: quotations with a `with` list that copy named inputs of the
+//! enclosing function into the function reference they build.
+
+use plenty::Vm;
+use rstest::rstest;
+
+const HELPERS: &str = r#"
+    : keep-if { s Str p ( Str -> Bool ) -> Bool }
+        "Apply the predicate `p` to `s`."
+        s p :call ;
+    : has { needle Str -> ( Str -> Bool ) }
+        "A predicate accepting text that contains `needle`."
+        { s Str -> Bool } with needle [ s needle :contains ] ;
+    : scaled { unused i64 k u8 -> ( u8 -> u8 ) }
+        "Multiply by `k`; captures the second input, not the first."
+        { x u8 -> u8 } with k [ x k * ] ;
+"#;
+
+#[rstest]
+#[case(r#""hello" "ell" :has :keep-if"#, "[true]")]
+#[case(r#""hello" "xyz" :has :keep-if"#, "[false]")]
+#[case("0 3u8 :scaled 5u8 swap :call", "[15u8]")]
+#[case(r#""m" :has"#, "[<fn ( Str -> Bool )>]")]
+fn closures_carry_their_captured_inputs(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(HELPERS).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn each_closure_keeps_the_values_it_was_built_with() {
+    let mut vm = Vm::new();
+    vm.run(HELPERS).unwrap();
+    vm.run(r#""a" :has "b" :has"#).unwrap();
+    vm.run(r#""b" swap :call swap "b" swap :call"#).unwrap();
+    assert_eq!(vm.stack_repr(), "[true false]");
+}
+
+#[test]
+fn a_closure_may_capture_several_inputs_in_list_order() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": between { lo i64 hi i64 -> ( i64 -> Bool ) }
+             "A range test."
+             { x i64 -> Bool } with hi lo [ x lo >= x hi <= and ] ;
+           5 1 10 :between :call"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[true]");
+}
+
+#[rstest]
+#[case("{ x i64 -> i64 } with y [ x ]", "not an input")] // top level has no inputs
+#[case(": f { a i64 -> } { -> } with b [ ] drop ;", "`b`")] // not an input of `f`
+#[case(": f { a i64 -> } { -> } with a a [ ] drop ;", "twice")]
+#[case(": f { a i64 -> } { a i64 -> } with a [ ] drop ;", "also declares")]
+#[case(": f { a i64 -> } { -> } with [ ] drop ;", "at least one")]
+fn malformed_capture_lists_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[test]
+fn a_quotation_without_with_still_cannot_see_enclosing_inputs() {
+    let err = Vm::new()
+        .run(r#": f { needle Str -> } { s Str -> Bool } [ s needle :contains ] drop ;"#)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("needle"), "error was {err:?}");
+}
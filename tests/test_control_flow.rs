@@ -426,6 +426,346 @@ fn nested_match_branch_join_works_independently_of_outer() {
     assert!(err.to_string().contains("same stack effect"));
 }
 
+// --- Matching two values together ----------------------------------------
+
+const XOR: &str = r#": xor { a Bool b Bool -> Bool }
+    "Exclusive or."
+    a b match2
+      true  false [ true ]
+      false true  [ true ]
+      _     _     [ false ]
+    end ;"#;
+
+#[rstest]
+#[case("true false :xor", "[true]")]
+#[case("false true :xor", "[true]")]
+#[case("true true :xor", "[false]")]
+#[case("false false :xor", "[false]")]
+fn match2_matches_two_values_together(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(XOR).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case(r#"0 "a""#, "[1i64]")]
+#[case(r#"0 "b""#, "[2i64]")]
+#[case(r#"5 "a""#, "[3i64]")]
+fn match2_mixes_pattern_kinds_per_column(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(&format!(
+        r#"{program} match2 0 "a" [ 1 ] 0 _ [ 2 ] _ _ [ 3 ] end"#
+    ))
+    .unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn match2_is_exhaustive_over_bool_pairs_without_a_wildcard() {
+    let mut vm = Vm::new();
+    vm.run(
+        "true false match2
+           true true [ 1 ] true false [ 2 ] false _ [ 3 ]
+         end",
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[2i64]");
+}
+
+#[rstest]
+#[case(
+    "true true match2 true true [ 1 ] false _ [ 2 ] end",
+    "no arm matches `true false`"
+)]
+#[case("1 true match2 0 _ [ 1 ] end", "no arm matches `..=-1 true`")]
+#[case("1 true match2 1 [ 1 ] end", "needs 2 patterns")]
+#[case("true match2 _ _ [ 1 ] end", "need 2 values")]
+#[case(r#"1 true match2 _ "x" [ 1 ] _ _ [ 2 ] end"#, "incompatible")]
+fn ill_formed_match2_is_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+// --- Ranges, or-patterns and guards --------------------------------------
+
+const CLASSIFY: &str = r#": classify { n u8 -> Str }
+    "Bucket a byte."
+    n match
+      0         [ "zero" ]
+      1 | 2 | 3 [ "few" ]
+      4..=99    [ "some" ]
+      100..     [ "many" ]
+    end ;"#;
+
+#[rstest]
+#[case("0u8", r#"["zero"]"#)]
+#[case("2u8", r#"["few"]"#)]
+#[case("4u8", r#"["some"]"#)]
+#[case("99u8", r#"["some"]"#)]
+#[case("255u8", r#"["many"]"#)]
+fn ranges_and_alternatives_cover_a_type_without_a_wildcard(
+    #[case] program: &str,
+    #[case] expected: &str,
+) {
+    let mut vm = Vm::new();
+    vm.run(CLASSIFY).unwrap();
+    vm.run(&format!("{program} :classify")).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("-7", "[-1i64]")]
+#[case("0", "[0i64]")]
+#[case("7", "[1i64]")]
+fn open_ended_ranges_reach_the_types_limits(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(&format!(
+        "{program} match ..=-1 [ -1 ] 0 [ 0 ] 1.. [ 1 ] end"
+    ))
+    .unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn or_patterns_mix_with_strings_and_suffixed_bounds() {
+    let mut vm = Vm::new();
+    vm.run(r#""b" match "a" | "b" [ 1 ] _ [ 0 ] end"#).unwrap();
+    vm.run("-3i8 match -128i8..=-1i8 [ 2 ] 0i8.. [ 3 ] end")
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[1i64 2i64]");
+}
+
+#[test]
+fn patterns_accept_prefixed_and_grouped_literals() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": kind { b u8 -> Str } "Classify a byte."
+          b match 0x00..=0x1F | 0x7F [ "control" ] 0b0010_0000 [ "space" ] 0o60..=0o71 [ "digit" ] _ [ "other" ] end ;
+        0x09u8 :kind 32u8 :kind 0x35u8 :kind 0x7Fu8 :kind 0x41u8 :kind"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vm.stack_repr(),
+        r#"["control" "space" "digit" "control" "other"]"#
+    );
+}
+
+#[rstest]
+#[case("4", r#"["even"]"#)]
+#[case("5", r#"["odd"]"#)]
+fn a_guard_sees_a_copy_of_the_matched_value(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(&format!(
+        r#"{program} match _ when [ 2 / 2 * {program} = ] [ "even" ] _ [ "odd" ] end"#
+    ))
+    .unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_guard_reads_locals_and_leaves_the_stack_below_untouched() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": above { n i64 limit i64 -> Str }
+             "Compare against a limit."
+             "x" n match
+               _ when [ limit > ] [ drop "above" ]
+               _                  [ drop "within" ]
+             end ;
+           5 3 :above 2 3 :above"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), r#"["above" "within"]"#);
+}
+
+#[test]
+fn match2_guards_see_both_values() {
+    let mut vm = Vm::new();
+    vm.run("3 2 match2 _ _ when [ > ] [ 1 ] _ _ [ 0 ] end")
+        .unwrap();
+    vm.run("2 3 match2 _ _ when [ > ] [ 1 ] _ _ [ 0 ] end")
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[1i64 0i64]");
+}
+
+#[rstest]
+#[case(
+    ": g { n i64 -> Bool } n match 0 [ true ] _ when [ 1 - :g ] [ true ] _ [ false ] end ;",
+    "[true]"
+)]
+// Every guard but the innermost says no, so each level resumes its arm walk.
+#[case(
+    ": g { n i64 -> i64 } n match 0 [ 0 ] _ when [ 1 - :g 0 < ] [ 1 ] _ [ 2 ] end ;",
+    "[2i64]"
+)]
+fn deep_recursion_through_a_guard_does_not_overflow_the_call_stack(
+    #[case] definition: &str,
+    #[case] expected: &str,
+) {
+    // A guard runs as a frame of the main loop, like an arm body, so a call
+    // inside one grows the explicit frames vec rather than the host stack.
+    let mut vm = Vm::new();
+    vm.run(&format!("{definition} 100000 :g")).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("3u8 match 0..=9 [ 1 ] 20.. [ 2 ] end", "no arm matches `10..=19`")]
+#[case("3u8 match 0..=254 [ 1 ] end", "no arm matches `255`")]
+#[case("3 match 0.. [ 1 ] end", "no arm matches `..=-1`")]
+#[case(
+    "true match true when [ ] [ 1 ] false [ 0 ] end",
+    "no arm matches `true`"
+)]
+#[case("3 match 5..=1 [ 1 ] _ [ 2 ] end", "is empty")]
+#[case("3u8 match 0..=300 [ 1 ] _ [ 2 ] end", "out of range for u8")]
+#[case("3 match 1..5 [ 1 ] _ [ 2 ] end", "must be `LO..=HI`")]
+#[case("3 match 1 | [ 1 ] _ [ 2 ] end", "followed by another pattern")]
+#[case("3 match 1 | true [ 1 ] _ [ 2 ] end", "incompatible")]
+#[case(
+    "3 match _ when [ drop 1 ] [ 1 ] _ [ 2 ] end",
+    "must leave exactly [Bool]"
+)]
+#[case("3 match _ when 1 [ 1 ] _ [ 2 ] end", "followed by a `[ ... ]` guard")]
+#[case("3 match when [ true ] [ 1 ] end", "missing a pattern before `when`")]
+fn ill_formed_ranges_and_guards_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case("'q'", r#"["lower"]"#)]
+#[case("'Q'", r#"["upper"]"#)]
+#[case("'\\0'", r#"["low"]"#)]
+#[case("'~'", r#"["low"]"#)]
+#[case("'\\u{e9}'", r#"["high"]"#)]
+fn char_patterns_and_ranges_cover_every_scalar_value(
+    #[case] program: &str,
+    #[case] expected: &str,
+) {
+    let mut vm = Vm::new();
+    vm.run(&format!(
+        r#"{program} match 'a'..='z' [ "lower" ] 'A'..='Z' [ "upper" ] ..='\u{{7f}}' [ "low" ] '\u{{80}}'.. [ "high" ] end"#
+    ))
+    .unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[rstest]
+#[case("'a' match 'a'..='z' [ 1 ] end", "no arm matches `..='`'`")]
+#[case("'a' match ..='\\u{d7ff}' [ 1 ] end", "no arm matches `'\\u{e000}'..`")]
+#[case(
+    "'a' match 'z'..='a' [ 1 ] _ [ 2 ] end",
+    "range pattern 'z'..='a' is empty"
+)]
+#[case(
+    "'a' match 'a'..=9 [ 1 ] _ [ 2 ] end",
+    "bound `9` is not a char literal"
+)]
+#[case(
+    "'a' match 1 [ 1 ] _ [ 2 ] end",
+    "incompatible with the matched type Char"
+)]
+#[case("'\\n\\t' match _ [ 1 ] end", "must hold exactly one char")]
+fn ill_formed_or_partial_char_matches_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[test]
+fn when_is_not_an_input_name() {
+    assert!(Vm::new().run(": f { when i64 -> i64 } when ;").is_err());
+}
+
+// --- Binding patterns ----------------------------------------------------
+
+const DESCRIBE: &str = r#": describe { n i64 -> i64 }
+    "Scale by bucket."
+    n 2 * match
+      0                      [ -1 ]
+      1..=9 as d             [ d 100 + ]
+      _ as big when [ 50 > ] [ big 1000 * ]
+      _ as other             [ other n + ]
+    end ;"#;
+
+#[rstest]
+#[case("0", "[-1i64]")]
+#[case("2", "[104i64]")]
+#[case("30", "[60000i64]")]
+#[case("10", "[30i64]")]
+fn a_binding_names_the_matched_value_for_its_arm(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(DESCRIBE).unwrap();
+    vm.run(&format!("{program} :describe")).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn match2_binds_either_column_and_nested_arms_keep_outer_bindings() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": pair { a i64 b Bool -> i64 }
+             "Sign by flag."
+             a b match2 _ as x true [ x ] _ as y false [ y -1 * ] end ;
+           : nest { n i64 -> i64 }
+             "Both bindings in scope."
+             n match _ as a [ a 1 + match _ as b [ a b + ] end ] end ;
+           3 true :pair 4 false :pair 5 :nest"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[3i64 -4i64 11i64]");
+}
+
+#[test]
+fn a_binding_arm_tail_calls_without_growing_the_locals() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": count { n i64 acc i64 -> i64 }
+             "Sum 1..=n."
+             n match 0 [ acc ] _ as k [ k 1 - acc k + :count ] end ;
+           100000 0 :count"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[5000050000i64]");
+}
+
+#[test]
+fn a_binding_shadows_an_input_and_types_an_inferred_one() {
+    let mut vm = Vm::new();
+    vm.run(": inc { a ? -> ? } a match _ as a [ a 1 + ] end ; 1 :inc")
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[2i64]");
+}
+
+#[rstest]
+#[case("3 match _ as n [ n ] end", "only valid inside a function body")]
+#[case(
+    ": f { a i64 b i64 -> i64 } a b match2 _ as x _ as x [ x ] end ;",
+    "binds `x` twice"
+)]
+#[case(
+    "const MAX 5 ; : f { a i64 -> i64 } a match MAX [ 1 ] _ [ 2 ] end ;",
+    "constant `MAX` cannot be a match-arm pattern"
+)]
+#[case(
+    ": f { a i64 -> i64 } a match 1 as [ 1 ] _ [ 2 ] end ;",
+    "followed by a name"
+)]
+#[case(
+    ": f { a i64 -> ( -> i64 ) } a match _ as n [ { -> i64 } with n [ n ] ] end ;",
+    "comes from a match pattern"
+)]
+#[case(": f { as i64 -> i64 } as ;", "not a valid input name")]
+#[case(
+    ": f { b Bool -> i64 } b match tru [ 1 ] _ [ 2 ] end ;",
+    "pattern `tru` is not a recognised literal; to name the matched value, write `_ as tru`"
+)]
+fn ill_formed_bindings_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
 // --- Recursion + tail-call optimisation -----------------------------------
 
 #[test]
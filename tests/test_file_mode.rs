@@ -89,6 +89,48 @@ fn print_consumes_and_renders_one_value_without_a_newline() {
     assert_eq!(String::from_utf8_lossy(&out.stdout), "1i64\"x\"true");
 }
 
+#[test]
+fn arguments_after_the_file_belong_to_the_program() {
+    let path = write_tempfile(
+        r#":argc 0 :arg 2 :arg 3 :arg "PLENTY_TEST_GREETING" :env ."#,
+        "args",
+    );
+    let out = Command::new(plenty_bin())
+        .arg(&path)
+        .args(["one", "--two", "three four"])
+        .env("PLENTY_TEST_GREETING", "hello")
+        .output()
+        .expect("spawn");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(
+        out.status.success(),
+        "stderr: {:?}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout).trim(),
+        r#"[3i64 "one" true "three four" true "" false "hello" true]"#
+    );
+}
+
+#[test]
+fn exit_sets_the_status_and_eprintln_writes_to_stderr() {
+    let path = write_tempfile(
+        r#""kept" :println "bad input" :eprintln 'x' :eprint 4i32 :exit "skipped" :println"#,
+        "exit",
+    );
+    let out = Command::new(plenty_bin())
+        .arg(&path)
+        .output()
+        .expect("spawn");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(out.status.code(), Some(4));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "kept\n");
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "bad input\n'x'");
+}
+
 #[test]
 fn a_type_error_exits_nonzero_with_a_diagnostic() {
     // `+` on mixed Int and Str is rejected by the type checker before any
@@ -139,10 +181,40 @@ fn the_help_flag_prints_usage_and_exits_zero() {
 #[test]
 fn unrecognised_arguments_exit_nonzero() {
     let out = Command::new(plenty_bin())
-        .args(["foo.plenty", "bar.plenty"])
+        .args(["--bogus", "foo.plenty"])
         .output()
         .expect("spawn");
-    assert!(!out.status.success(), "multiple files should be rejected");
+    assert!(!out.status.success(), "an unknown flag should be rejected");
     let stderr = String::from_utf8_lossy(&out.stderr);
     assert!(stderr.contains("unrecognised"), "stderr was {stderr:?}");
 }
+
+#[test]
+fn lint_prints_warnings_and_exits_nonzero_only_when_there_are_some() {
+    let dirty = write_tempfile(
+        ": first { a i64 b i64 -> i64 } a ; 1 2 :first\n",
+        "lint-dirty",
+    );
+    let out = Command::new(plenty_bin())
+        .arg("lint")
+        .arg(&dirty)
+        .output()
+        .expect("spawn");
+    let _ = std::fs::remove_file(&dirty);
+    assert!(!out.status.success(), "warnings should exit non-zero");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("warning: in `first`: input `b` is never used"),
+        "stdout was {stdout:?}"
+    );
+
+    let clean = write_tempfile(": first { a i64 -> i64 } a ; 1 :first\n", "lint-clean");
+    let out = Command::new(plenty_bin())
+        .arg("lint")
+        .arg(&clean)
+        .output()
+        .expect("spawn");
+    let _ = std::fs::remove_file(&clean);
+    assert!(out.status.success(), "a clean file should exit zero");
+    assert!(String::from_utf8_lossy(&out.stdout).is_empty());
+}
@@ -0,0 +1,177 @@
+//! File I/O words — §8 Files.
+//!
+//! Each test works in its own scratch directory under the system temp
+//! dir, so parallel tests never share a path. Failures are values, not
+//! errors: the assertions check the flags the words push, and that the
+//! capability switch turns every word into an error instead.
+
+use std::path::PathBuf;
+
+use plenty::Vm;
+use rstest::rstest;
+
+/// A fresh, empty directory for one test. The caller removes it.
+fn scratch_dir(label: &str) -> PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "plenty-files-{label}-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
+
+/// Run `program` with every `DIR` replaced by the scratch directory and
+/// return the final stack.
+fn run_in_scratch(label: &str, program: &str) -> String {
+    let dir = scratch_dir(label);
+    let mut vm = Vm::new();
+    let result = vm.run(&program.replace("DIR", &dir.display().to_string()));
+    let _ = std::fs::remove_dir_all(&dir);
+    result.unwrap();
+    vm.stack_repr()
+}
+
+#[rstest]
+#[case(
+    r#""DIR/a.txt" "one\n" :write-file "DIR/a.txt" "two" :append-file "DIR/a.txt" :read-file"#,
+    r#"[true true "one\ntwo" true]"#
+)]
+#[case(
+    r#""DIR/a.txt" "old" :write-file drop "DIR/a.txt" "new" :write-file "DIR/a.txt" :read-file"#,
+    r#"[true "new" true]"#
+)]
+#[case(
+    r#""DIR/a.txt" :file-exists "DIR/a.txt" "" :write-file "DIR/a.txt" :file-exists "DIR" :file-exists"#,
+    "[false true true true]"
+)]
+#[case(
+    r#""DIR/missing.txt" :read-file "DIR/no/such/dir.txt" "x" :write-file"#,
+    r#"["" false false]"#
+)]
+#[case(r#""DIR/missing.txt" "x" :append-file"#, "[true]")]
+fn whole_files_read_and_write_as_text(#[case] program: &str, #[case] expected: &str) {
+    assert_eq!(run_in_scratch("whole", program), expected);
+}
+
+#[test]
+fn a_file_that_is_not_utf8_reads_as_a_failure() {
+    let dir = scratch_dir("binary");
+    std::fs::write(dir.join("b.bin"), b"ok\n\xff\nafter\n").unwrap();
+    let mut vm = Vm::new();
+    let path = dir.join("b.bin").display().to_string();
+    let result = vm.run(&format!(
+        r#": three {{ f File -> Str Bool Str Bool Str Bool }}
+             f :file-readline f :file-readline f :file-readline ;
+           "{path}" :read-file "{path}" :open-file drop :three"#
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    result.unwrap();
+    assert_eq!(
+        vm.stack_repr(),
+        r#"["" false "ok" true "" false "after" true]"#
+    );
+}
+
+#[test]
+fn reading_lines_as_bytes_reports_false_only_at_the_end() {
+    let dir = scratch_dir("binary-bytes");
+    std::fs::write(dir.join("b.bin"), b"ok\r\n\xff\n\nafter").unwrap();
+    let mut vm = Vm::new();
+    let path = dir.join("b.bin").display().to_string();
+    let result = vm.run(&format!(
+        r#": two {{ f File -> Bytes Bool Bytes Bool }} f :file-readline-bytes f :file-readline-bytes ;
+           : lines {{ f File -> i64 }}
+             f :file-readline-bytes match false [ drop 0 ] true [ drop f :lines 1 + ] end ;
+           "{path}" :open-file drop :two "{path}" :open-file drop :lines"#
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    result.unwrap();
+    assert_eq!(vm.stack_repr(), r#"[b"ok" true b"\xff" true 4i64]"#);
+}
+
+#[rstest]
+#[case(
+    r#": count { f File -> i64 } f :file-readline match false [ drop 0 ] true [ drop f :count 1 + ] end ;
+       "DIR/l.txt" "a\r\nb\n\nc" :write-file drop
+       "DIR/l.txt" :open-file drop :count"#,
+    "[4i64]"
+)]
+#[case(
+    r#": closed-early { f File -> Str Bool Str Bool } f :file-readline f :close-file f :file-readline ;
+       "DIR/l.txt" "a\nb\n" :write-file drop
+       "DIR/l.txt" :open-file drop :closed-early"#,
+    r#"["a" true "" false]"#
+)]
+#[case(
+    r#": closed-early { f File -> Str Bool Str Bool } f :file-readline f :close-file f :file-readline ;
+       "DIR/missing.txt" :open-file drop :closed-early"#,
+    r#"["" false "" false]"#
+)]
+#[case(r#""DIR/missing.txt" :open-file"#, "[<file> false]")]
+fn file_handles_read_line_by_line(#[case] program: &str, #[case] expected: &str) {
+    assert_eq!(run_in_scratch("lines", program), expected);
+}
+
+#[rstest]
+#[case(
+    r#""x" :read-file"#,
+    "`:read-file` needs filesystem access, which is disabled"
+)]
+#[case(r#""x" "y" :write-file"#, "`:write-file` needs filesystem access")]
+#[case(r#""x" "y" :append-file"#, "`:append-file` needs filesystem access")]
+#[case(r#""x" :file-exists"#, "`:file-exists` needs filesystem access")]
+#[case(r#""x" :open-file"#, "`:open-file` needs filesystem access")]
+fn embedders_can_turn_filesystem_access_off(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    vm.set_filesystem_access(false);
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[test]
+fn turning_filesystem_access_off_refuses_imports() {
+    let dir = scratch_dir("import");
+    std::fs::write(dir.join("lib.plenty"), ": one { -> i64 } 1 ;").expect("write lib");
+    let mut vm = Vm::new();
+    vm.set_filesystem_access(false);
+    let program = format!(r#"import "{}/lib.plenty" :one"#, dir.display());
+    let err = vm.run(&program).unwrap_err().to_string();
+    vm.set_filesystem_access(true);
+    vm.run(&program).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        err.contains("`import` needs filesystem access, which is disabled"),
+        "error was {err:?}"
+    );
+    assert_eq!(vm.stack_repr(), "[1i64]");
+}
+
+#[test]
+fn filesystem_access_can_be_turned_back_on() {
+    let mut vm = Vm::new();
+    vm.set_filesystem_access(false);
+    vm.set_filesystem_access(true);
+    vm.run(r#""/definitely/not/here" :file-exists"#).unwrap();
+    assert_eq!(vm.stack_repr(), "[false]");
+}
+
+#[rstest]
+#[case(r#""x" :file-readline"#, "`:file-readline` requires (File), got (Str)")]
+#[case(r#""x" :close-file"#, "`:close-file` requires (File), got (Str)")]
+#[case(
+    r#""x" 1 :write-file"#,
+    "`:write-file` requires (Str Str), got (Str i64)"
+)]
+#[case(
+    r#""x" :open-file drop dup ="#,
+    "`=` cannot compare file handles (File)"
+)]
+fn file_words_are_type_checked(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+    assert_eq!(vm.stack_repr(), "[]");
+}
@@ -0,0 +1,60 @@
+//! Generic signatures: type variables (`'T`) instantiated per call, and
+//! row variables (`..R`) standing for the rest of the caller's stack.
+
+use plenty::Vm;
+use rstest::rstest;
+
+const HELPERS: &str = r#"
+    : swap-under { a 'T b 'U -> 'U 'T } "Exchange two values of any types." b a ;
+    : apply { x 'T f ( 'T -> 'U ) -> 'U } "Apply `f` to `x`." x f :call ;
+    : dip { ..R x 'T f ( ..R -> ..S ) -> ..S 'T }
+        "Run `f` on the stack beneath `x`, then put `x` back."
+        f :call x ;
+    : nip-row { ..R a 'T b 'T -> ..R 'T } "Keep the second of two values." b ;
+"#;
+
+#[rstest]
+#[case(r#"1 "a" :swap-under"#, r#"["a" 1i64]"#)]
+#[case("true 2u8 :swap-under", "[2u8 true]")]
+#[case(r#"5 { n i64 -> Str } [ "five" ] :apply"#, r#"["five"]"#)]
+#[case("1 2 { a i64 -> i64 } [ a 10 + ] :dip", "[11i64 2i64]")]
+#[case(r#"1 2 "x" { a i64 b i64 -> i64 } [ a b + ] :dip"#, r#"[3i64 "x"]"#)]
+#[case(r#""s" 1 2 :nip-row"#, r#"["s" 2i64]"#)]
+fn generic_functions_take_the_types_of_their_arguments(
+    #[case] program: &str,
+    #[case] expected: &str,
+) {
+    let mut vm = Vm::new();
+    vm.run(HELPERS).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_generic_function_may_call_another() {
+    let mut vm = Vm::new();
+    vm.run(HELPERS).unwrap();
+    vm.run(
+        r#": swap-twice { a 'A b 'B -> 'A 'B } "Round trip." a b :swap-under :swap-under ;
+           3 "z" :swap-twice"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), r#"[3i64 "z"]"#);
+}
+
+#[rstest]
+#[case(r#"1 "x" :nip-row"#, "expects i64, got Str")] // both `'T` must agree
+#[case("1 { a Str -> } [ ] :dip", "`..R` is [Str]")] // row disagrees with the stack
+#[case(": f { a 'T -> i64 } a ;", "leaves ['T]")] // `'T` is not i64 in the body
+#[case(": f { a 'T b 'T -> Bool } a b = ;", "type variable")] // no equality on `'T`
+#[case(": f { ..R -> ..R } drop ;", "`..R`")] // the row is not a value
+#[case(": f { a 'T -> 'U } a ;", "`'U` appears in the outputs")]
+#[case(": f { ..R -> ..S } ;", "`..S` appears in the outputs")]
+#[case(": f { a i64 -> ..R } ;", "both the inputs and the outputs")]
+#[case("{ a 'T -> } [ ]", "type or row variables")] // quotations are monomorphic
+fn ill_typed_generics_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    vm.run(HELPERS).unwrap();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
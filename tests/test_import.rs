@@ -0,0 +1,184 @@
+//! `import "path"`: resolution order, once-per-compilation loading, cycle
+//! detection, and the definitions-only rule for imported files.
+//!
+//! Each test builds its own directory tree under the system tempdir so the
+//! relative paths being resolved are real files on disk.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use plenty::{ImportPaths, Vm};
+
+fn plenty_bin() -> &'static str {
+    env!("CARGO_BIN_EXE_plenty")
+}
+
+/// A fresh, empty directory unique to this test process and `label`.
+fn tempdir(label: &str) -> PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "plenty-import-{label}-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).expect("create tempdir");
+    dir
+}
+
+fn write(dir: &Path, name: &str, source: &str) -> PathBuf {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).expect("create parent dir");
+    }
+    std::fs::write(&path, source).expect("write source");
+    path
+}
+
+fn vm_in(dir: &Path) -> Vm {
+    let mut vm = Vm::new();
+    vm.set_import_paths(ImportPaths {
+        base: Some(dir.to_path_buf()),
+        search: Vec::new(),
+        refused: false,
+    });
+    vm
+}
+
+#[test]
+fn imported_definitions_are_callable() {
+    let dir = tempdir("basic");
+    write(&dir, "lib/math.plenty", ": double { x i64 -> i64 } x 2 * ;");
+    let mut vm = vm_in(&dir);
+    vm.run(r#"import "lib/math.plenty" 21 :double"#).unwrap();
+    assert_eq!(vm.stack_repr(), "[42i64]");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn nested_imports_resolve_relative_to_the_importing_file() {
+    let dir = tempdir("nested");
+    write(&dir, "lib/inc.plenty", ": inc { x i64 -> i64 } x 1 + ;");
+    write(
+        &dir,
+        "lib/twice.plenty",
+        r#"import "inc.plenty" : inc2 { x i64 -> i64 } x :inc :inc ;"#,
+    );
+    let mut vm = vm_in(&dir);
+    vm.run(r#"import "lib/twice.plenty" 40 :inc2"#).unwrap();
+    assert_eq!(vm.stack_repr(), "[42i64]");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_file_imported_twice_is_compiled_once() {
+    let dir = tempdir("diamond");
+    write(&dir, "base.plenty", ": one { -> i64 } 1 ;");
+    write(
+        &dir,
+        "a.plenty",
+        r#"import "base.plenty" : a { -> i64 } :one ;"#,
+    );
+    write(
+        &dir,
+        "b.plenty",
+        r#"import "./base.plenty" : b { -> i64 } :one ;"#,
+    );
+    let mut vm = vm_in(&dir);
+    vm.run(r#"import "a.plenty" import "b.plenty" :a :b +"#)
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[2i64]");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn search_directories_are_tried_after_the_base() {
+    let dir = tempdir("search");
+    let std_dir = dir.join("std");
+    write(
+        &std_dir,
+        "greet.plenty",
+        r#": hi { -> Str } "Greeting." "from std" ;"#,
+    );
+    let mut vm = Vm::new();
+    vm.set_import_paths(ImportPaths {
+        base: Some(dir.join("project")),
+        search: vec![std_dir],
+        refused: false,
+    });
+    vm.run(r#"import "greet.plenty" :hi"#).unwrap();
+    assert_eq!(vm.stack_repr(), r#"["from std"]"#);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn an_import_cycle_is_a_compile_error_naming_the_chain() {
+    let dir = tempdir("cycle");
+    write(&dir, "a.plenty", r#"import "b.plenty" : fa { -> i64 } 1 ;"#);
+    write(&dir, "b.plenty", r#"import "a.plenty" : fb { -> i64 } 2 ;"#);
+    let mut vm = vm_in(&dir);
+    let err = vm.run(r#"import "a.plenty""#).unwrap_err().to_string();
+    assert!(err.contains("import cycle"), "error was {err:?}");
+    assert!(err.contains("a.plenty -> "), "error was {err:?}");
+    assert_eq!(vm.function_names().len(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn an_imported_file_may_only_define() {
+    let dir = tempdir("effects");
+    write(&dir, "noisy.plenty", ": f { -> i64 } 1 ; 99");
+    let mut vm = vm_in(&dir);
+    let err = vm.run(r#"import "noisy.plenty""#).unwrap_err().to_string();
+    assert!(err.contains("in import `"), "error was {err:?}");
+    assert!(
+        err.contains("only definitions and imports, found `99`"),
+        "error was {err:?}"
+    );
+    assert_eq!(vm.stack_repr(), "[]");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_missing_file_lists_where_it_looked() {
+    let dir = tempdir("missing");
+    let mut vm = vm_in(&dir);
+    let err = vm.run(r#"import "nope.plenty""#).unwrap_err().to_string();
+    assert!(err.contains("file not found"), "error was {err:?}");
+    assert!(err.contains("nope.plenty"), "error was {err:?}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn import_is_only_valid_at_the_top_level() {
+    let mut vm = Vm::new();
+    let err = vm
+        .run(r#": f { -> } import "x.plenty" ;"#)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("top level"), "error was {err:?}");
+}
+
+#[test]
+fn the_binary_resolves_imports_next_to_the_file_and_on_plenty_path() {
+    let dir = tempdir("bin");
+    write(&dir, "lib/sq.plenty", ": sq { x i64 -> i64 } x x * ;");
+    let main = write(
+        &dir,
+        "app/main.plenty",
+        r#"import "sq.plenty" import "local.plenty" 3 :sq :plus1 ."#,
+    );
+    write(&dir, "app/local.plenty", ": plus1 { x i64 -> i64 } x 1 + ;");
+    let out = Command::new(plenty_bin())
+        .arg(&main)
+        .env("PLENTY_PATH", dir.join("lib"))
+        .output()
+        .expect("spawn");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        out.status.success(),
+        "stderr was {:?}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "[10i64]\n");
+}
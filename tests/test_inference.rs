@@ -0,0 +1,90 @@
+//! Signature inference: `?` in a top-level definition's header, filled in
+//! from the body and stored in the dictionary like a written signature.
+
+use plenty::Vm;
+use rstest::rstest;
+
+/// Define `source` and render the signature stored for `name`.
+fn inferred(source: &str, name: &str) -> String {
+    let mut vm = Vm::new();
+    vm.run(source).unwrap();
+    vm.function_sig(name).expect("defined").to_string()
+}
+
+#[rstest]
+#[case(": inc { x ? -> ? } x 1 + ;", "{ x i64 -> i64 }")]
+#[case(": five { ? } 5 ;", "{ -> i64 }")]
+#[case(
+    r#": greet { name ? -> ? } "Greet." "hi " name + ;"#,
+    "{ name Str -> Str }"
+)]
+#[case(": same { a ? b ? -> ? } a b = ;", "{ a i64 b i64 -> Bool }")]
+#[case(": wide { a ? b ? -> ? } a b 1u16 + + ;", "{ a u16 b u16 -> u16 }")]
+#[case(
+    ": small { n ? -> ? } n match 0u8 [ true ] _ [ false ] end ;",
+    "{ n u8 -> Bool }"
+)]
+#[case(": pos { n ? -> Bool } n 0 > ;", "{ n i64 -> Bool }")]
+#[case(": ident { x ? -> ? } x ;", "{ x 'A -> 'A }")]
+#[case(": both { x ? y ? -> ? } y x ;", "{ x 'A y 'B -> 'B 'A }")]
+#[case(": twice { x u8 -> ? } x x * ;", "{ x u8 -> u8 }")]
+#[case(
+    ": cut { s ? a ? b ? -> ? } s a b :slice :len ;",
+    "{ s Str a i64 b i64 -> i64 }"
+)]
+#[case(": num { s ? -> ? } s :parse-u8 drop ;", "{ s Str -> u8 }")]
+#[case(": initial { s ? -> ? } s 0 :char-at ;", "{ s Str -> Char }")]
+#[case(": text { b ? -> ? } b :utf8-decode drop ;", "{ b Bytes -> Str }")]
+#[case(
+    ": apply-to { x ? -> ? } { n i64 -> i64 } [ n 1 + ] x :ap ;
+     : ap { f ( 'T -> 'T ) x 'T -> 'T } x f :call ;",
+    "{ x i64 -> i64 }"
+)]
+#[case(
+    ": keep { x ? -> ? } x { n u8 -> u8 } [ n ] :hold ;
+     : hold { x 'T f ( 'T -> 'T ) -> 'T ( 'T -> 'T ) } x f ;",
+    "{ x u8 -> u8 ( u8 -> u8 ) }"
+)]
+fn signatures_are_inferred_from_the_body(#[case] source: &str, #[case] expected: &str) {
+    assert_eq!(
+        inferred(source, source.split_whitespace().nth(1).unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn an_inferred_function_is_called_like_any_other() {
+    let mut vm = Vm::new();
+    vm.run(": inc { x ? -> ? } x 1 + ; : inc2 { x ? -> ? } x :inc :inc ; 40 :inc2")
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[42i64]");
+    assert_eq!(
+        vm.function_sig("inc2").unwrap().to_string(),
+        "{ x i64 -> i64 }"
+    );
+}
+
+#[test]
+fn an_unconstrained_input_makes_the_function_generic() {
+    let mut vm = Vm::new();
+    vm.run(r#": ident { x ? -> ? } x ; 1 :ident "s" :ident"#)
+        .unwrap();
+    assert_eq!(vm.stack_repr(), r#"[1i64 "s"]"#);
+}
+
+#[rstest]
+#[case(
+    ": f { n ? -> ? } n 0 = match true [ 0 ] _ [ n 1 - :f ] end ;",
+    "cannot call itself"
+)]
+#[case(": g { ? } 1 :h ; : h { x ? -> ? } x ;", "defined before")]
+#[case(": o { -> } : inner { x ? -> ? } x ; ;", "only top-level")]
+#[case("{ x ? -> i64 } [ 1 ]", "quotation")]
+#[case(r#": f { x ? -> ? } x "a" + 1 + ;"#, "`+` requires")]
+#[case(": f { ..R x ? -> ..R } ;", "type or row variables")]
+#[case(": f { x ? -> ? i64 } x ;", "`-> ? }`")]
+#[case(": f { ? -> } ;", "not a valid input name")]
+fn uninferable_signatures_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let err = Vm::new().run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
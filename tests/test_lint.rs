@@ -0,0 +1,113 @@
+//! Linter tests: each rule fires on the mistake it targets and stays quiet
+//! on the idiomatic version of the same program.
+//!
+//! Assertions are made against `WarningKind`s, not message wording, except
+//! where the message is the point (naming the function or the suggestion).
+
+use plenty::{lint_source, ImportPaths, WarningKind};
+use rstest::rstest;
+
+fn kinds(source: &str) -> Vec<WarningKind> {
+    lint_source(source, &ImportPaths::default())
+        .expect("source should compile and type-check")
+        .into_iter()
+        .map(|w| w.kind)
+        .collect()
+}
+
+#[test]
+fn a_clean_program_has_no_warnings() {
+    let source = r#"
+        : sum-to { n i64 acc i64 -> i64 }
+            "Tail-recursive accumulator."
+            n 0 = match
+              true  [ acc ]
+              false [ n 1 - acc n + :sum-to ]
+            end ;
+        10 0 :sum-to .
+    "#;
+    assert_eq!(kinds(source), vec![]);
+}
+
+#[rstest]
+#[case(
+    r#": first { a i64 b i64 -> i64 } a ; 1 2 :first"#,
+    vec![WarningKind::UnusedInput]
+)]
+#[case(r#": helper { -> i64 } 1 ; 2"#, vec![WarningKind::UnusedFunction])]
+#[case(
+    "1 match 0 [ 10 ] _ [ 20 ] 5 [ 30 ] end",
+    vec![WarningKind::UnreachableArm]
+)]
+#[case(
+    r#""a" match "x" [ "same" ] "y" [ "same" ] _ [ "other" ] end"#,
+    vec![WarningKind::DuplicateArm]
+)]
+#[case(
+    r#": fib { n i64 -> i64 }
+         n 2 < match
+           true  [ n ]
+           false [ n 1 - :fib n 2 - :fib + ]
+         end ;
+       10 :fib"#,
+    vec![WarningKind::NonTailRecursion]
+)]
+#[case(
+    r#": double { x i64 -> i64 } x 2 * ; 21 :double doubel"#,
+    vec![WarningKind::LikelyTypo]
+)]
+fn each_rule_fires_on_its_mistake(#[case] source: &str, #[case] expected: Vec<WarningKind>) {
+    assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn self_recursion_alone_does_not_count_as_a_use() {
+    let source = r#": spin { n i64 -> i64 }
+                      n 0 = match true [ n ] false [ n 1 - :spin ] end ;"#;
+    assert_eq!(kinds(source), vec![WarningKind::UnusedFunction]);
+}
+
+#[test]
+fn a_nested_definition_does_not_use_its_parents_inputs() {
+    // The inner body's `LoadLocal(0)` is its own `y`, not the outer `x`.
+    let source = r#": outer { x i64 -> i64 }
+                      : inner { y i64 -> i64 } y 1 + ;
+                      1 :inner ;
+                    2 :outer"#;
+    let warnings = lint_source(source, &ImportPaths::default()).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::UnusedInput);
+    assert!(warnings[0].message.contains("`outer`"));
+    assert!(warnings[0].message.contains("`x`"));
+}
+
+#[test]
+fn a_quotation_is_named_as_a_quotation_not_by_its_internal_name() {
+    let warnings = lint_source("{ x i64 -> i64 } [ 1 ] drop", &ImportPaths::default()).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "in a quotation: input `x` is never used"
+    );
+}
+
+#[test]
+fn quoted_text_is_never_reported_as_a_typo() {
+    let source = r#": double { x i64 -> i64 } x 2 * ; 21 :double "doubel" double"#;
+    let warnings = lint_source(source, &ImportPaths::default()).unwrap();
+    assert_eq!(warnings.len(), 1, "only the bare `double` is suspect");
+    assert!(warnings[0].message.contains("did you mean `:double`"));
+}
+
+#[test]
+fn a_type_error_is_an_error_not_a_warning() {
+    assert!(lint_source("1 hello +", &ImportPaths::default()).is_err());
+}
+
+#[test]
+fn a_guarded_wildcard_leaves_later_arms_reachable() {
+    let source = r#": sign { n i64 -> i64 }
+                      n match _ when [ 0 < ] [ -1 ] _ [ 1 ] end ;
+                    3 :sign"#;
+    assert_eq!(kinds(source), vec![]);
+}
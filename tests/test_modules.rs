@@ -0,0 +1,107 @@
+//! `module NAME ... end`: qualified names, unqualified calls inside a
+//! module, private helpers, and per-namespace reserved names.
+
+use plenty::Vm;
+use rstest::rstest;
+
+const TEXT: &str = r#"
+    module text
+      : split { s Str -> Str Str } "Halve (placeholder)." s :dup-str ;
+      private : dup-str { s Str -> Str Str } "Helper." s s ;
+      : print { s Str -> } "Shadows the builtin inside `text`." s :println ;
+    end
+"#;
+
+#[rstest]
+#[case(r#""ab" :text.split ."#, r#"["ab" "ab"]"#)]
+#[case(r#""hi" :text.print ."#, "[]")]
+fn qualified_calls_reach_module_functions(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(TEXT).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn module_functions_are_stored_under_qualified_names() {
+    let mut vm = Vm::new();
+    vm.run(TEXT).unwrap();
+    assert_eq!(
+        vm.function_names(),
+        vec!["text.dup-str", "text.print", "text.split"]
+    );
+}
+
+#[test]
+fn two_modules_may_define_the_same_name() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#"module a : parse { -> i64 } 1 ; end
+           module b : parse { -> i64 } 2 ; end
+           :a.parse :b.parse"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[1i64 2i64]");
+}
+
+#[rstest]
+#[case(
+    r#"module text : twice { s Str -> Str Str Str } s :split s ; end "ab" :text.twice"#,
+    r#"["ab" "ab" "ab"]"#
+)] // later input
+#[case(
+    r#"module text : both { s Str -> Str Str } s :dup-str ; end "ab" :text.both"#,
+    r#"["ab" "ab"]"#
+)] // its private helpers too
+fn a_reopened_module_calls_its_earlier_functions_unqualified(
+    #[case] program: &str,
+    #[case] expected: &str,
+) {
+    let mut vm = Vm::new();
+    vm.run(TEXT).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_module_reopened_in_the_same_source_calls_its_earlier_functions() {
+    let mut vm = Vm::new();
+    vm.run("module m : f { -> i64 } 1 ; end module m : g { -> i64 } :f 1 + ; end :m.g")
+        .unwrap();
+    assert_eq!(vm.stack_repr(), "[2i64]");
+}
+
+#[rstest]
+#[case(r#""x" :text.dup-str"#)] // later input
+#[case(r#"module other : f { s Str -> Str Str } s :text.dup-str ; end"#)] // another module
+fn private_helpers_are_invisible_outside_their_module(#[case] program: &str) {
+    let mut vm = Vm::new();
+    vm.run(TEXT).unwrap();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(
+        err.contains("`text.dup-str` is private to module `text`"),
+        "error was {err:?}"
+    );
+}
+
+#[test]
+fn a_private_call_in_the_same_source_names_the_module() {
+    let mut vm = Vm::new();
+    let source = format!("{TEXT} \"x\" :text.dup-str");
+    let err = vm.run(&source).unwrap_err().to_string();
+    assert!(
+        err.contains("`text.dup-str` is private to module `text`"),
+        "error was {err:?}"
+    );
+}
+
+#[rstest]
+#[case(": print { -> } ;")] // reserved in the root namespace
+#[case(": a.b { -> } ;")] // dots come only from `module`
+#[case("module m 1 end")] // only definitions inside a module
+#[case("module m : f { -> } ;")] // no closing `end`
+#[case(": f { -> } module m end ;")] // not at the top level
+#[case("module m module n end end")] // modules do not nest
+fn malformed_modules_are_rejected(#[case] program: &str) {
+    assert!(Vm::new().run(program).is_err());
+}
@@ -0,0 +1,94 @@
+//! Quotations (`{ sig } [ body ]`), function-reference types such as
+//! `( Str -> Bool )`, and `:call`.
+
+use plenty::Vm;
+use rstest::rstest;
+
+const KEEP_IF: &str = r#"
+    : keep-if { s Str p ( Str -> Bool ) -> Bool }
+        "Apply the predicate `p` to `s`."
+        s p :call ;
+"#;
+
+#[rstest]
+#[case("{ x i64 -> i64 } [ x 1 + ] 41 swap :call", "[42i64]")]
+#[case("{ -> } [ ] :call", "[]")]
+#[case("1 2 { a i64 b i64 -> i64 i64 } [ b a ] :call", "[2i64 1i64]")]
+#[case("{ x i64 -> i64 } [ x ]", "[<fn ( i64 -> i64 )>]")]
+#[case(
+    r#""hello" { s Str -> Bool } [ s "ell" :contains ] :keep-if"#,
+    "[true]"
+)]
+#[case(
+    r#""hello" { s Str -> Bool } [ s "xyz" :contains ] :keep-if"#,
+    "[false]"
+)]
+fn quotations_are_values_that_call_can_apply(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(KEEP_IF).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn a_named_function_may_call_the_same_quotation_twice() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": twice { f ( i64 -> i64 ) x i64 -> i64 } "Apply `f` twice." x f :call f :call ;
+           { n i64 -> i64 } [ n n * ] 3 :twice"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[81i64]");
+}
+
+#[test]
+fn a_quotation_may_recurse_through_a_named_function() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#": fact { n i64 -> i64 } n 1 <= match true [ 1 ] false [ n 1 - :fact n * ] end ;
+           5 { n i64 -> i64 } [ n :fact ] :call"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), "[120i64]");
+}
+
+#[test]
+fn quotation_bodies_are_not_listed_as_functions() {
+    let mut vm = Vm::new();
+    vm.run(KEEP_IF).unwrap();
+    vm.run(r#""x" { s Str -> Bool } [ true ] :keep-if"#)
+        .unwrap();
+    assert_eq!(vm.function_names(), vec!["keep-if"]);
+}
+
+#[test]
+fn a_reference_left_on_the_stack_is_typed_on_the_next_line() {
+    let mut vm = Vm::new();
+    vm.run("{ x i64 -> Bool } [ x 0 > ]").unwrap();
+    vm.run("5 swap :call").unwrap();
+    assert_eq!(vm.stack_repr(), "[true]");
+}
+
+#[rstest]
+#[case(r#"1 { s Str -> Bool } [ true ] :keep-if"#, "keep-if")] // wrong value input
+#[case(r#""x" { n i64 -> Bool } [ true ] :keep-if"#, "( Str -> Bool )")] // wrong fn type
+#[case("{ x i64 -> i64 } [ x ] :call", "needs 1 value")] // missing argument
+#[case("1 :call", "function reference")] // not a function
+#[case("{ -> } [ ] { -> } [ ] =", "function references")] // no equality
+#[case("{ x i64 -> Str } [ x ]", "a quotation body leaves")] // body disagrees with its signature
+fn ill_typed_quotations_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    vm.run(KEEP_IF).unwrap();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+    assert_eq!(vm.stack_repr(), "[]");
+}
+
+#[rstest]
+#[case("{ x i64 -> i64 }")] // no body
+#[case("{ x i64 -> i64 } x")] // body not bracketed
+#[case("{ x i64 -> i64 } [ x")] // unterminated body
+#[case(": f { p ( i64 -> i64 -> } ;")] // unterminated function type
+fn malformed_quotations_are_rejected(#[case] program: &str) {
+    assert!(Vm::new().run(program).is_err());
+}
@@ -0,0 +1,108 @@
+//! Structs: `struct NAME { field Type ... }` declares a record type with a
+//! constructor (`:NAME`), accessors (`:NAME.field`), and functional update
+//! (`:NAME.with-field`).
+
+use plenty::Vm;
+use rstest::rstest;
+
+const USER: &str = "struct user { name Str id i64 }";
+
+#[rstest]
+#[case(r#""bob" 1 :user"#, r#"[user{name: "bob", id: 1i64}]"#)]
+#[case(r#""bob" 1 :user :user.name"#, r#"["bob"]"#)]
+#[case(r#""bob" 1 :user :user.id"#, "[1i64]")]
+#[case(
+    r#""bob" 1 :user dup 2 :user.with-id"#,
+    r#"[user{name: "bob", id: 1i64} user{name: "bob", id: 2i64}]"#
+)]
+#[case(r#""bob" 1 :user "al" :user.with-name :user.name"#, r#"["al"]"#)]
+fn struct_words_build_read_and_update(#[case] program: &str, #[case] expected: &str) {
+    let mut vm = Vm::new();
+    vm.run(USER).unwrap();
+    vm.run(program).unwrap();
+    assert_eq!(vm.stack_repr(), expected);
+}
+
+#[test]
+fn structs_are_types_in_signatures_and_nest() {
+    let mut vm = Vm::new();
+    vm.run(
+        r#"struct user { name Str id i64 }
+           struct team { lead user size u8 }
+           : rename { u user n Str -> user } "Change the name." u n :user.with-name ;
+           : lead-name { t team -> Str } "The lead's name." t :team.lead :user.name ;
+           "bob" 1 :user 3u8 :team dup :team.lead "al" :rename :team.with-lead :lead-name"#,
+    )
+    .unwrap();
+    assert_eq!(vm.stack_repr(), r#"["al"]"#);
+}
+
+#[test]
+fn a_struct_declared_on_one_line_is_usable_on_the_next() {
+    let mut vm = Vm::new();
+    vm.run(USER).unwrap();
+    vm.run(r#""bob" 1 :user"#).unwrap();
+    vm.run(USER).unwrap();
+    vm.run(":user.id").unwrap();
+    assert_eq!(vm.stack_repr(), "[1i64]");
+}
+
+#[test]
+fn inference_sees_through_struct_words() {
+    let mut vm = Vm::new();
+    vm.run(USER).unwrap();
+    vm.run(": id-of { u ? -> ? } u :user.id ;").unwrap();
+    assert_eq!(
+        vm.function_sig("id-of").unwrap().to_string(),
+        "{ u user -> i64 }"
+    );
+}
+
+#[rstest]
+#[case(r#"1 "bob" :user"#, "field `name` expects Str, got i64")]
+#[case("1 :user.name", "requires a user, got i64")]
+#[case(
+    r#""bob" 1 :user "x" :user.with-id"#,
+    "`:user.with-id` expects i64, got Str"
+)]
+#[case(":user.email", "has no field `email`")]
+#[case(r#""b" 1 :user dup ="#, "cannot compare structs")]
+#[case("struct user { name Str }", "already declared with different fields")]
+#[case("struct pair { a i64 a i64 }", "field `a` twice")]
+#[case("struct empty { }", "at least one field")]
+#[case("struct box { v 'T }", "type or row variables")]
+#[case("struct rec { next rec }", "not a known type")]
+#[case("struct b { with-x i64 }", "not a valid field name")]
+#[case(": user { -> } ;", "constructor of struct `user`")]
+#[case(": f { -> } struct p { x i64 } ;", "only valid at the top level")]
+fn ill_formed_structs_are_rejected(#[case] program: &str, #[case] needle: &str) {
+    let mut vm = Vm::new();
+    vm.run(USER).unwrap();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(err.contains(needle), "error was {err:?}");
+}
+
+#[rstest]
+#[case(": pt { -> i64 } 7 ; struct pt { x i64 } 1 :pt .")] // same source
+#[case(": m { -> } : pt { -> i64 } 7 ; ; struct pt { x i64 }")] // nested definition
+fn a_struct_may_not_take_a_functions_name(#[case] program: &str) {
+    let mut vm = Vm::new();
+    let err = vm.run(program).unwrap_err().to_string();
+    assert!(
+        err.contains("struct name `pt` is taken by function `pt`"),
+        "error was {err:?}"
+    );
+}
+
+#[test]
+fn a_struct_may_not_take_the_name_of_an_earlier_inputs_function() {
+    let mut vm = Vm::new();
+    vm.run(": pt { -> i64 } 7 ;").unwrap();
+    let err = vm.run("struct pt { x i64 }").unwrap_err().to_string();
+    assert!(
+        err.contains("struct name `pt` is taken by function `pt`"),
+        "error was {err:?}"
+    );
+    vm.run(":pt").unwrap();
+    assert_eq!(vm.stack_repr(), "[7i64]");
+}